@@ -116,6 +116,8 @@ fn parse_delete_query(pair: Pair) -> Delete {
     if optional_token(Rule::KwWhere, &mut pairs).is_some() {
         let expr = parse_expr(pairs.next().unwrap());
         query = query.with(expr);
+    } else {
+        query = query.all();
     }
     expect_done(&mut pairs);
     query
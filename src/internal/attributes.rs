@@ -0,0 +1,254 @@
+use crate::internal::value::Value;
+use std::ops::{BitAnd, BitOr};
+
+// ========================================================================= //
+
+/// Defines a set of bit flags stored in an integer column, in the same style
+/// as [`TransformFlags`](crate::TransformFlags).
+macro_rules! attribute_flags {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$flag_meta:meta])*
+                const $flag:ident = $value:expr;
+            )+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+        pub struct $name(i32);
+
+        impl $name {
+            $(
+                $(#[$flag_meta])*
+                pub const $flag: $name = $name($value);
+            )+
+
+            const ALL_BITS: i32 = 0 $(| $value)+;
+
+            /// Returns the flags with the given bitmask, or `None` if the
+            /// bitmask has any bits set that don't correspond to a flag.
+            #[must_use]
+            pub fn from_bits(bits: i32) -> Option<$name> {
+                if bits & !$name::ALL_BITS == 0 {
+                    Some($name(bits))
+                } else {
+                    None
+                }
+            }
+
+            /// Returns the flags held in a column value, or `None` if the
+            /// value isn't an integer or isn't a valid bitmask for these
+            /// flags.
+            #[must_use]
+            pub fn from_value(value: &Value) -> Option<$name> {
+                value.as_int().and_then($name::from_bits)
+            }
+
+            /// Returns the flags as a bitmask, as stored in the table.
+            #[must_use]
+            pub fn bits(self) -> i32 {
+                self.0
+            }
+
+            /// Returns true if all of the given flags are set.
+            #[must_use]
+            pub fn contains(self, other: $name) -> bool {
+                (self.0 & other.0) == other.0
+            }
+        }
+
+        impl BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, rhs: $name) -> $name {
+                $name(self.0 | rhs.0)
+            }
+        }
+
+        impl BitAnd for $name {
+            type Output = $name;
+
+            fn bitand(self, rhs: $name) -> $name {
+                $name(self.0 & rhs.0)
+            }
+        }
+
+        impl From<$name> for Value {
+            fn from(flags: $name) -> Value {
+                Value::Int(flags.0)
+            }
+        }
+    };
+}
+
+// ========================================================================= //
+
+attribute_flags! {
+    /// The bit flags stored in the `Attributes` column of the `Component`
+    /// table.  Flags can be combined with the `|` operator.
+    pub struct ComponentAttributes {
+        /// The component is installed to run locally (this is the default,
+        /// with no bits set).
+        const LOCAL_ONLY = 0x0000;
+        /// The component is run from the source media.
+        const SOURCE_ONLY = 0x0001;
+        /// The component can run either locally or from source.
+        const OPTIONAL = 0x0002;
+        /// The `KeyPath` column refers to a row of the `Registry` table
+        /// rather than the `File` table.
+        const REGISTRY_KEY_PATH = 0x0004;
+        /// The component's key file is reference counted in the shared DLL
+        /// registry.
+        const SHARED_DLL_REF_COUNT = 0x0008;
+        /// The component is never uninstalled.
+        const PERMANENT = 0x0010;
+        /// The `KeyPath` column refers to a row of the `ODBCDataSource`
+        /// table.
+        const ODBC_DATA_SOURCE = 0x0020;
+        /// The component's condition is reevaluated on reinstall.
+        const TRANSITIVE = 0x0040;
+        /// The component isn't installed if its key file or key path
+        /// already exists.
+        const NEVER_OVERWRITE = 0x0080;
+        /// The component is a 64-bit component.
+        const SIXTY_FOUR_BIT = 0x0100;
+        /// Registry reflection is disabled for the component.
+        const DISABLE_REGISTRY_REFLECTION = 0x0200;
+        /// The component is uninstalled when a patch that supersedes it is
+        /// installed.
+        const UNINSTALL_ON_SUPERSEDENCE = 0x0400;
+        /// The component is shared between products, and patches to it are
+        /// applied to all of them.
+        const SHARED = 0x0800;
+    }
+}
+
+attribute_flags! {
+    /// The bit flags stored in the `Attributes` column of the `Feature`
+    /// table.  Flags can be combined with the `|` operator.
+    pub struct FeatureAttributes {
+        /// The feature's components are installed locally by default (this
+        /// is the default, with no bits set).
+        const FAVOR_LOCAL = 0x0000;
+        /// The feature's components are run from source by default.
+        const FAVOR_SOURCE = 0x0001;
+        /// The feature's install state follows its parent's.
+        const FOLLOW_PARENT = 0x0002;
+        /// The feature is advertised by default.
+        const FAVOR_ADVERTISE = 0x0004;
+        /// The feature can't be advertised.
+        const DISALLOW_ADVERTISE = 0x0008;
+        /// The user interface doesn't offer to make the feature absent.
+        const UI_DISALLOW_ABSENT = 0x0010;
+        /// The feature is only advertised on platforms that support it.
+        const NO_UNSUPPORTED_ADVERTISE = 0x0020;
+    }
+}
+
+attribute_flags! {
+    /// The bit flags stored in the `Event` column of the `ServiceControl`
+    /// table.  Flags can be combined with the `|` operator.
+    pub struct ServiceControlEvent {
+        /// Starts the service when the component is installed.
+        const INSTALL_START = 0x0001;
+        /// Stops the service when the component is installed.
+        const INSTALL_STOP = 0x0002;
+        /// Deletes the service when the component is installed.
+        const INSTALL_DELETE = 0x0008;
+        /// Starts the service when the component is uninstalled.
+        const UNINSTALL_START = 0x0010;
+        /// Stops the service when the component is uninstalled.
+        const UNINSTALL_STOP = 0x0020;
+        /// Deletes the service when the component is uninstalled.
+        const UNINSTALL_DELETE = 0x0080;
+    }
+}
+
+attribute_flags! {
+    /// The bit flags stored in the `InstallMode` column of the `RemoveFile`
+    /// table.  Flags can be combined with the `|` operator.
+    pub struct RemoveFileInstallMode {
+        /// Removes the file when the component is installed.
+        const ON_INSTALL = 0x0001;
+        /// Removes the file when the component is uninstalled.
+        const ON_REMOVE = 0x0002;
+        /// Removes the file when the component is installed or uninstalled.
+        const ON_BOTH = 0x0003;
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ComponentAttributes, FeatureAttributes, RemoveFileInstallMode,
+        ServiceControlEvent,
+    };
+    use crate::internal::value::Value;
+
+    #[test]
+    fn decode_component_attributes() {
+        let attrs = ComponentAttributes::from_bits(0x0104).unwrap();
+        assert!(attrs.contains(ComponentAttributes::SIXTY_FOUR_BIT));
+        assert!(attrs.contains(ComponentAttributes::REGISTRY_KEY_PATH));
+        assert!(!attrs.contains(ComponentAttributes::PERMANENT));
+        assert_eq!(
+            attrs,
+            ComponentAttributes::SIXTY_FOUR_BIT
+                | ComponentAttributes::REGISTRY_KEY_PATH
+        );
+        assert_eq!(
+            ComponentAttributes::LOCAL_ONLY
+                | ComponentAttributes::NEVER_OVERWRITE,
+            ComponentAttributes::from_bits(0x0080).unwrap()
+        );
+        assert_eq!(ComponentAttributes::from_bits(0x1000), None);
+    }
+
+    #[test]
+    fn decode_feature_attributes() {
+        let attrs = FeatureAttributes::from_bits(0x0012).unwrap();
+        assert_eq!(
+            attrs,
+            FeatureAttributes::FOLLOW_PARENT
+                | FeatureAttributes::UI_DISALLOW_ABSENT
+        );
+        assert_eq!(
+            attrs & FeatureAttributes::UI_DISALLOW_ABSENT,
+            FeatureAttributes::UI_DISALLOW_ABSENT
+        );
+        assert_eq!(FeatureAttributes::from_bits(-1), None);
+    }
+
+    #[test]
+    fn service_control_event_value_round_trip() {
+        // The value WiX uses for a service that is started on install, and
+        // stopped and deleted on uninstall.
+        let value = Value::Int(161);
+        let event = ServiceControlEvent::from_value(&value).unwrap();
+        assert_eq!(
+            event,
+            ServiceControlEvent::INSTALL_START
+                | ServiceControlEvent::UNINSTALL_STOP
+                | ServiceControlEvent::UNINSTALL_DELETE
+        );
+        assert_eq!(Value::from(event), value);
+        assert_eq!(ServiceControlEvent::from_value(&Value::Null), None);
+        assert_eq!(ServiceControlEvent::from_value(&Value::from("1")), None);
+    }
+
+    #[test]
+    fn remove_file_install_mode() {
+        let mode = RemoveFileInstallMode::from_bits(3).unwrap();
+        assert_eq!(mode, RemoveFileInstallMode::ON_BOTH);
+        assert!(mode.contains(RemoveFileInstallMode::ON_INSTALL));
+        assert!(mode.contains(RemoveFileInstallMode::ON_REMOVE));
+        assert_eq!(mode.bits(), 3);
+        assert_eq!(RemoveFileInstallMode::from_bits(4), None);
+    }
+}
+
+// ========================================================================= //
@@ -0,0 +1,150 @@
+use crate::internal::codepage::CodePage;
+use crate::internal::error::Result;
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::io::{self, Read, Seek, SeekFrom};
+
+// ========================================================================= //
+
+const SIGNATURE: &[u8; 4] = b"MSCF";
+
+const FLAG_PREV_CABINET: u16 = 0x0001;
+const FLAG_NEXT_CABINET: u16 = 0x0002;
+const FLAG_RESERVE_PRESENT: u16 = 0x0004;
+
+const ATTR_NAME_IS_UTF: u16 = 0x0080;
+
+// ========================================================================= //
+
+/// A reader for a cabinet (.cab) archive, such as one embedded in a package.
+///
+/// Currently this only supports listing the files in the cabinet, not
+/// extracting them.
+pub struct CabinetReader<R> {
+    reader: R,
+    files: Vec<CabinetFile>,
+}
+
+impl<R: Read + Seek> CabinetReader<R> {
+    /// Parses the header of a cabinet archive from the given reader.
+    pub fn new(mut reader: R) -> Result<CabinetReader<R>> {
+        reader.seek(SeekFrom::Start(0))?;
+        let mut signature = [0u8; 4];
+        reader.read_exact(&mut signature)?;
+        if &signature != SIGNATURE {
+            invalid_data!("Not a cabinet file (signature {:?})", signature);
+        }
+        let _reserved1 = reader.read_u32::<LittleEndian>()?;
+        let _cabinet_size = reader.read_u32::<LittleEndian>()?;
+        let _reserved2 = reader.read_u32::<LittleEndian>()?;
+        let files_offset = reader.read_u32::<LittleEndian>()?;
+        let _reserved3 = reader.read_u32::<LittleEndian>()?;
+        let _version_minor = reader.read_u8()?;
+        let version_major = reader.read_u8()?;
+        if version_major != 1 {
+            invalid_data!("Unsupported cabinet version {}", version_major);
+        }
+        let num_folders = reader.read_u16::<LittleEndian>()?;
+        let num_files = reader.read_u16::<LittleEndian>()?;
+        let flags = reader.read_u16::<LittleEndian>()?;
+        let _set_id = reader.read_u16::<LittleEndian>()?;
+        let _cabinet_index = reader.read_u16::<LittleEndian>()?;
+        if (flags & FLAG_RESERVE_PRESENT) != 0 {
+            let header_reserve = reader.read_u16::<LittleEndian>()?;
+            let _folder_reserve = reader.read_u8()?;
+            let _data_reserve = reader.read_u8()?;
+            reader.seek(SeekFrom::Current(header_reserve as i64))?;
+        }
+        if (flags & FLAG_PREV_CABINET) != 0 || (flags & FLAG_NEXT_CABINET) != 0
+        {
+            // TODO: Support cabinets that span multiple files.
+            invalid_data!("Multi-cabinet archives are not supported");
+        }
+        reader.seek(SeekFrom::Start(files_offset as u64))?;
+        let mut files = Vec::with_capacity(num_files as usize);
+        for _ in 0..num_files {
+            let size = reader.read_u32::<LittleEndian>()?;
+            let _offset_in_folder = reader.read_u32::<LittleEndian>()?;
+            let folder_index = reader.read_u16::<LittleEndian>()?;
+            let _date = reader.read_u16::<LittleEndian>()?;
+            let _time = reader.read_u16::<LittleEndian>()?;
+            let attributes = reader.read_u16::<LittleEndian>()?;
+            let name = read_null_terminated(&mut reader)?;
+            // Names that aren't flagged as UTF-8 are in an unspecified
+            // codepage; assume Windows-1252, which is correct for ASCII.
+            let name = if (attributes & ATTR_NAME_IS_UTF) != 0 {
+                CodePage::Utf8.decode(&name).into_owned()
+            } else {
+                CodePage::Windows1252.decode(&name).into_owned()
+            };
+            if (folder_index as u32) >= (num_folders as u32)
+                && folder_index < 0xfffd
+            {
+                invalid_data!(
+                    "File {:?} refers to folder {}, but cabinet only has {} \
+                     folders",
+                    name,
+                    folder_index,
+                    num_folders
+                );
+            }
+            files.push(CabinetFile { name, size, attributes });
+        }
+        Ok(CabinetReader { reader, files })
+    }
+
+    /// Returns an iterator over the entries for the files in the cabinet.
+    pub fn files(&self) -> std::slice::Iter<'_, CabinetFile> {
+        self.files.iter()
+    }
+
+    /// Consumes the `CabinetReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn read_null_terminated<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    loop {
+        let byte = reader.read_u8()?;
+        if byte == 0 {
+            break;
+        }
+        bytes.push(byte);
+    }
+    Ok(bytes)
+}
+
+// ========================================================================= //
+
+/// Metadata about one file stored in a cabinet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CabinetFile {
+    name: String,
+    size: u32,
+    attributes: u16,
+}
+
+impl CabinetFile {
+    /// Returns the name of the file within the cabinet.  For cabinets
+    /// embedded in a package, this is the file's key in the `File` table.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the uncompressed size of the file, in bytes.
+    #[must_use]
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Returns the file's attribute bits (read-only, hidden, etc.), as stored
+    /// in the cabinet.
+    #[must_use]
+    pub fn attributes(&self) -> u16 {
+        self.attributes
+    }
+}
+
+// ========================================================================= //
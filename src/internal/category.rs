@@ -1,5 +1,7 @@
+use crate::internal::error::{Error, Result};
+use crate::internal::filename::is_valid_filename;
+use crate::internal::version::Version;
 use std::fmt;
-use std::io;
 use std::str;
 use uuid::Uuid;
 
@@ -135,18 +137,48 @@ pub enum Category {
     /// For more details, see the [MSI
     /// docs](https://docs.microsoft.com/en-us/windows/win32/msi/filename) for
     /// this data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Valid:
+    /// assert!(msi::Category::Filename.validate("README.TXT"));
+    /// assert!(msi::Category::Filename.validate("README~1.TXT|Read Me.txt"));
+    /// // Invalid:
+    /// assert!(!msi::Category::Filename.validate("LONGFILENAME.TXT"));
+    /// assert!(!msi::Category::Filename.validate("FOO.TXT|foo?.txt"));
+    /// ```
     Filename,
     /// A filename that can contain shell glob wildcards.
     ///
     /// For more details, see the [MSI
     /// docs](https://docs.microsoft.com/en-us/windows/win32/msi/wildcardfilename)
     /// for this data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Valid:
+    /// assert!(msi::Category::WildCardFilename.validate("*.TXT"));
+    /// // Invalid:
+    /// assert!(!msi::Category::WildCardFilename.validate("LONGNAME*.TXT"));
+    /// ```
     WildCardFilename,
     /// A string containing an absolute filepath.
     ///
     /// For more details, see the [MSI
     /// docs](https://docs.microsoft.com/en-us/windows/win32/msi/path) for this
     /// data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Valid:
+    /// assert!(msi::Category::Path.validate("C:\\Program Files"));
+    /// assert!(msi::Category::Path.validate("\\\\server\\share"));
+    /// // Invalid:
+    /// assert!(!msi::Category::Path.validate("Program Files"));
+    /// ```
     Path,
     /// A string containing a semicolon-separated list of absolute filepaths.
     ///
@@ -165,6 +197,17 @@ pub enum Category {
     /// For more details, see the [MSI
     /// docs](https://docs.microsoft.com/en-us/windows/win32/msi/defaultdir)
     /// for this data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Valid:
+    /// assert!(msi::Category::DefaultDir.validate("SourceDir"));
+    /// assert!(msi::Category::DefaultDir.validate("PROGRA~1|Program Files"));
+    /// assert!(msi::Category::DefaultDir.validate(".:SRC"));
+    /// // Invalid:
+    /// assert!(!msi::Category::DefaultDir.validate("Program Files"));
+    /// ```
     DefaultDir,
     /// A string containing a registry path.
     ///
@@ -178,6 +221,16 @@ pub enum Category {
     /// For more details, see the [MSI
     /// docs](https://docs.microsoft.com/en-us/windows/win32/msi/formatted) for
     /// this data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Valid:
+    /// assert!(msi::Category::Formatted.validate("[ProductName] Setup"));
+    /// assert!(msi::Category::Formatted.validate("[\\[]literal[\\]]"));
+    /// // Invalid:
+    /// assert!(!msi::Category::Formatted.validate("[ProductName Setup"));
+    /// ```
     Formatted,
     /// A security descriptor definition language (SDDL) text string written in
     /// valid [Security Descriptor String
@@ -198,6 +251,15 @@ pub enum Category {
     /// For more details, see the [MSI
     /// docs](https://docs.microsoft.com/en-us/windows/win32/msi/condition) for
     /// this data type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// // Valid:
+    /// assert!(msi::Category::Condition.validate("VersionNT >= 600"));
+    /// // Invalid:
+    /// assert!(!msi::Category::Condition.validate("  "));
+    /// ```
     Condition,
     /// A hyphenated, uppercase GUID string, enclosed in curly braces.
     ///
@@ -404,11 +466,7 @@ impl Category {
                     && !string.chars().any(|chr| chr.is_ascii_lowercase())
                     && Uuid::parse_str(&string[1..37]).is_ok()
             }
-            Category::Version => {
-                let mut parts = string.split('.');
-                parts.clone().count() <= 4
-                    && parts.all(|part| part.parse::<u16>().is_ok())
-            }
+            Category::Version => string.parse::<Version>().is_ok(),
             Category::Language => {
                 let mut parts = string.split(',');
                 parts.all(|part| part.parse::<u16>().is_ok())
@@ -426,8 +484,39 @@ impl Category {
                         && (parts.len() < 2 || parts[1].len() <= 3)
                 }
             }
-            // TODO: Validate other categories.
-            _ => true,
+            Category::Filename => is_valid_filename(string, false),
+            Category::WildCardFilename => is_valid_filename(string, true),
+            Category::Path => is_valid_absolute_path(string),
+            Category::Paths => {
+                !string.is_empty()
+                    && string.split(';').all(is_valid_absolute_path)
+            }
+            Category::AnyPath => {
+                !string.is_empty() && !string.contains(INVALID_PATH_CHARS)
+            }
+            Category::DefaultDir => {
+                Category::Identifier.validate(string)
+                    || string.split(':').count() <= 2
+                        && string.split(':').all(|name| {
+                            name == "." || is_valid_filename(name, false)
+                        })
+            }
+            Category::RegPath => {
+                !string.is_empty() && !string.starts_with('\\')
+            }
+            Category::Formatted
+            | Category::FormattedSddlText
+            | Category::Template => has_balanced_brackets(string),
+            Category::Condition => !string.trim().is_empty(),
+            Category::CustomSource => Category::Identifier.validate(string),
+            Category::Shortcut => {
+                if string.contains('[') {
+                    has_balanced_brackets(string)
+                } else {
+                    Category::Identifier.validate(string)
+                }
+            }
+            Category::TimeDate | Category::Binary => true,
         }
     }
 }
@@ -439,9 +528,9 @@ impl fmt::Display for Category {
 }
 
 impl str::FromStr for Category {
-    type Err = io::Error;
+    type Err = Error;
 
-    fn from_str(string: &str) -> io::Result<Category> {
+    fn from_str(string: &str) -> Result<Category> {
         match string {
             "AnyPath" => Ok(Category::AnyPath),
             "Binary" => Ok(Category::Binary),
@@ -478,10 +567,151 @@ impl str::FromStr for Category {
 
 // ========================================================================= //
 
+/// Characters that may not appear anywhere in a path.
+const INVALID_PATH_CHARS: &[char] = &['?', '|', '>', '<', '*', '"'];
+
+/// Returns true if the string is an absolute path, starting either with a
+/// drive letter (e.g. `C:\`) or with a UNC server/share prefix (`\\`).
+fn is_valid_absolute_path(string: &str) -> bool {
+    let rest = if let Some(rest) = string.strip_prefix("\\\\") {
+        rest
+    } else {
+        let mut chars = string.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(drive), Some(':'), Some('\\'))
+                if drive.is_ascii_alphabetic() =>
+            {
+                &string[3..]
+            }
+            _ => return false,
+        }
+    };
+    !rest.contains(INVALID_PATH_CHARS) && !rest.contains(':')
+}
+
+/// Returns true if every `[` in the string has a matching `]`, treating
+/// `[\x]` as an escape for the literal character `x`.
+fn has_balanced_brackets(string: &str) -> bool {
+    let mut depth: usize = 0;
+    let mut chars = string.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '[' => {
+                if chars.as_str().starts_with('\\') {
+                    let mut escape = chars.clone();
+                    escape.next();
+                    if escape.next().is_some() && escape.next() == Some(']') {
+                        chars = escape;
+                        continue;
+                    }
+                }
+                depth += 1;
+            }
+            ']' => {
+                if depth == 0 {
+                    return false;
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+    depth == 0
+}
+
+// ========================================================================= //
+
 #[cfg(test)]
 mod tests {
     use super::Category;
 
+    #[test]
+    fn validate_categories() {
+        let cases: &[(Category, &[&str], &[&str])] = &[
+            (Category::Text, &["", "Hello, World!"], &[]),
+            (Category::UpperCase, &["HELLO 123"], &["Hello"]),
+            (Category::LowerCase, &["hello 123"], &["Hello"]),
+            (Category::Integer, &["0", "-32767"], &["32768", "1.5", ""]),
+            (Category::DoubleInteger, &["-2147483647"], &["2147483648"]),
+            (
+                Category::Identifier,
+                &["Foo", "_foo.Bar_9"],
+                &["", "9Foo", "Foo-Bar", "Foo Bar"],
+            ),
+            (Category::Property, &["Foo", "%Foo"], &["%", "Foo%"]),
+            (
+                Category::Filename,
+                &["FOO.TXT", "FOO", "FOO~1.TXT|Foo Bar.txt"],
+                &["", "FOOBARBAZ.TXT", "FOO.TEXT", "FOO BAR", "A|", "A.B.C"],
+            ),
+            (
+                Category::WildCardFilename,
+                &["*.TXT", "FOO?.*|foo bar?.*"],
+                &["FOO/BAR", "LONGFILENAME.*"],
+            ),
+            (
+                Category::Path,
+                &["C:\\", "C:\\Program Files\\Foo", "\\\\server\\share"],
+                &["", "Program Files", "C:Foo", "C:\\Foo?"],
+            ),
+            (
+                Category::Paths,
+                &["C:\\Foo", "C:\\Foo;D:\\Bar"],
+                &["", "C:\\Foo;", "C:\\Foo;Bar"],
+            ),
+            (Category::AnyPath, &["Foo\\Bar.txt", "C:\\Foo"], &["", "Foo*"]),
+            (
+                Category::DefaultDir,
+                &["SourceDir", ".", "FOO|Foo Bar", "FOO:BAR", ".:SRC|Source"],
+                &["", "Foo Bar", "A:B:C", "FOO:Bar Baz"],
+            ),
+            (
+                Category::RegPath,
+                &["Software\\Foo", "Software\\[ProductName]"],
+                &["", "\\Software"],
+            ),
+            (
+                Category::Formatted,
+                &["", "[Foo] and [Bar]", "[[Foo]]", "[\\[]", "[\\]]"],
+                &["[Foo", "Foo]", "[Foo]]"],
+            ),
+            (Category::Template, &["[1] [2]"], &["[1"]),
+            (Category::Condition, &["Installed", "NOT REMOVE"], &["", "  "]),
+            (
+                Category::Guid,
+                &["{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}"],
+                &["{34ab5c53-9b30-4e14-aef0-2c1c7ba826c0}", ""],
+            ),
+            (Category::Version, &["1", "1.2.3.4"], &["1.2.3.4.5", "1.a"]),
+            (Category::Language, &["0", "1033,1031"], &["", "1033,"]),
+            (Category::CustomSource, &["MyDll"], &["", "My Dll"]),
+            (Category::Cabinet, &["FOO.CAB", "#Foo"], &["", "#1"]),
+            (
+                Category::Shortcut,
+                &["MainFeature", "[INSTALLDIR]Foo.exe"],
+                &["", "Main Feature", "[INSTALLDIR"],
+            ),
+        ];
+        for &(category, valid, invalid) in cases {
+            for string in valid {
+                assert!(
+                    category.validate(string),
+                    "{:?} should be a valid {}",
+                    string,
+                    category
+                );
+            }
+            for string in invalid {
+                assert!(
+                    !category.validate(string),
+                    "{:?} should not be a valid {}",
+                    string,
+                    category
+                );
+            }
+        }
+    }
+
     #[test]
     fn category_string_round_trip() {
         for category in Category::all() {
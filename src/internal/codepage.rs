@@ -1,4 +1,5 @@
 use encoding_rs::{EncoderResult, Encoding};
+use std::borrow::Cow;
 
 // ========================================================================= //
 
@@ -12,6 +13,11 @@ use encoding_rs::{EncoderResult, Encoding};
 /// list of valid code pages.
 #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub enum CodePage {
+    /// The language-neutral code page (ID 0), used by databases that don't
+    /// specify a code page.  Such databases are expected to contain only ASCII
+    /// strings; any other bytes are decoded (and characters encoded) as
+    /// Windows-1252, the ANSI code page of a US English system.
+    Neutral,
     /// [Windows-932 (Japanese Shift JIS)](https://en.wikipedia.org/wiki/Code_page_932_(Microsoft_Windows))
     Windows932,
     /// [Windows-936 (Chinese (simplified) GBK)](https://en.wikipedia.org/wiki/Code_page_936_(Microsoft_Windows))
@@ -69,11 +75,18 @@ pub enum CodePage {
 }
 
 impl CodePage {
-    /// Returns the code page (if any) with the given ID number.
+    /// Returns all code pages supported by this library.
+    #[must_use]
+    pub fn all() -> &'static [CodePage] {
+        &ALL_CODE_PAGES
+    }
+
+    /// Returns the code page (if any) with the given ID number.  An ID of zero
+    /// indicates the language-neutral code page, `CodePage::Neutral`.
     #[must_use]
     pub fn from_id(id: i32) -> Option<CodePage> {
         match id {
-            0 => Some(CodePage::default()),
+            0 => Some(CodePage::Neutral),
             932 => Some(CodePage::Windows932),
             936 => Some(CodePage::Windows936),
             949 => Some(CodePage::Windows949),
@@ -106,8 +119,9 @@ impl CodePage {
 
     /// Returns the ID number used within Windows to represent this code page.
     #[must_use]
-    pub fn id(&self) -> i32 {
+    pub fn id(&self) -> u16 {
         match *self {
+            CodePage::Neutral => 0,
             CodePage::Windows932 => 932,
             CodePage::Windows936 => 936,
             CodePage::Windows949 => 949,
@@ -141,6 +155,7 @@ impl CodePage {
     #[must_use]
     pub fn name(&self) -> &str {
         match *self {
+            CodePage::Neutral => "Language neutral",
             CodePage::Windows932 => "Windows Japanese Shift JIS",
             CodePage::Windows936 => "Windows Chinese (simplified) GBK",
             CodePage::Windows949 => "Windows Korean Unified Hangul Code",
@@ -173,12 +188,16 @@ impl CodePage {
     /// Decodes a byte array into a string, using this code page.  Invalid
     /// characters will be replaced with a Unicode replacement character
     /// (U+FFFD).
+    ///
+    /// When the bytes are already valid in the output encoding (for example,
+    /// ASCII text or UTF-8), the returned string borrows from them instead of
+    /// allocating.
     #[must_use]
-    pub fn decode(&self, bytes: &[u8]) -> String {
+    pub fn decode<'a>(&self, bytes: &'a [u8]) -> Cow<'a, str> {
         if *self == CodePage::UsAscii {
             ascii_decode(bytes)
         } else {
-            self.encoding().decode(bytes).0.into_owned()
+            self.encoding().decode_without_bom_handling(bytes).0
         }
     }
 
@@ -191,44 +210,72 @@ impl CodePage {
         if *self == CodePage::UsAscii {
             ascii_encode(string)
         } else {
-            let mut encoder = self.encoding().new_encoder();
-            let mut bytes = Vec::new();
-            let mut buffer = [0; 1024];
-            let mut total_read = 0;
-            loop {
-                let (result, read, written) = encoder
-                    .encode_from_utf8_without_replacement(
-                        &string[total_read..],
-                        &mut buffer[..],
-                        true,
-                    );
-                total_read += read;
-                bytes.extend_from_slice(&buffer[..written]);
-                match result {
-                    EncoderResult::InputEmpty => {
-                        break;
-                    }
-                    EncoderResult::OutputFull => {
-                        continue;
-                    }
-                    EncoderResult::Unmappable(_) => {
-                        bytes.push(b'?');
+            self.encode_with_replacement(string, true)
+                .expect("encoding with replacement cannot fail")
+        }
+    }
+
+    /// Returns true if every character in the string can be represented in
+    /// this code page, i.e. if [`encode`](CodePage::encode) would not need
+    /// to substitute any replacement characters.
+    #[must_use]
+    pub fn can_encode(&self, string: &str) -> bool {
+        if string.is_ascii() || *self == CodePage::Utf8 {
+            true
+        } else if *self == CodePage::UsAscii {
+            false
+        } else {
+            self.encode_with_replacement(string, false).is_some()
+        }
+    }
+
+    fn encode_with_replacement(
+        self,
+        string: &str,
+        replace: bool,
+    ) -> Option<Vec<u8>> {
+        let mut encoder = self.encoding().new_encoder();
+        let mut bytes = Vec::new();
+        let mut buffer = [0; 1024];
+        let mut total_read = 0;
+        loop {
+            let (result, read, written) = encoder
+                .encode_from_utf8_without_replacement(
+                    &string[total_read..],
+                    &mut buffer[..],
+                    true,
+                );
+            total_read += read;
+            bytes.extend_from_slice(&buffer[..written]);
+            match result {
+                EncoderResult::InputEmpty => {
+                    break;
+                }
+                EncoderResult::OutputFull => {
+                    continue;
+                }
+                EncoderResult::Unmappable(_) => {
+                    if !replace {
+                        return None;
                     }
+                    bytes.push(b'?');
                 }
             }
-            bytes
         }
+        Some(bytes)
     }
 
     fn encoding(self) -> &'static Encoding {
         match self {
-            CodePage::Windows932 => encoding_rs::EUC_JP,
-            CodePage::Windows936 => encoding_rs::BIG5,
+            CodePage::Windows932 => encoding_rs::SHIFT_JIS,
+            CodePage::Windows936 => encoding_rs::GBK,
             CodePage::Windows949 => encoding_rs::EUC_KR,
             CodePage::Windows950 | CodePage::Windows951 => encoding_rs::BIG5,
             CodePage::Windows1250 => encoding_rs::WINDOWS_1250,
             CodePage::Windows1251 => encoding_rs::WINDOWS_1251,
-            CodePage::Windows1252 => encoding_rs::WINDOWS_1252,
+            CodePage::Neutral | CodePage::Windows1252 => {
+                encoding_rs::WINDOWS_1252
+            }
             CodePage::Windows1253 => encoding_rs::WINDOWS_1253,
             CodePage::Windows1254 => encoding_rs::WINDOWS_1254,
             CodePage::Windows1255 => encoding_rs::WINDOWS_1255,
@@ -251,6 +298,36 @@ impl CodePage {
     }
 }
 
+const ALL_CODE_PAGES: [CodePage; 27] = [
+    CodePage::Neutral,
+    CodePage::Windows932,
+    CodePage::Windows936,
+    CodePage::Windows949,
+    CodePage::Windows950,
+    CodePage::Windows951,
+    CodePage::Windows1250,
+    CodePage::Windows1251,
+    CodePage::Windows1252,
+    CodePage::Windows1253,
+    CodePage::Windows1254,
+    CodePage::Windows1255,
+    CodePage::Windows1256,
+    CodePage::Windows1257,
+    CodePage::Windows1258,
+    CodePage::MacintoshRoman,
+    CodePage::MacintoshCyrillic,
+    CodePage::UsAscii,
+    CodePage::Iso88591,
+    CodePage::Iso88592,
+    CodePage::Iso88593,
+    CodePage::Iso88594,
+    CodePage::Iso88595,
+    CodePage::Iso88596,
+    CodePage::Iso88597,
+    CodePage::Iso88598,
+    CodePage::Utf8,
+];
+
 fn ascii_encode(string: &str) -> Vec<u8> {
     let mut bytes = Vec::with_capacity(string.len());
     for ch in string.chars() {
@@ -263,7 +340,10 @@ fn ascii_encode(string: &str) -> Vec<u8> {
     bytes
 }
 
-fn ascii_decode(bytes: &[u8]) -> String {
+fn ascii_decode(bytes: &[u8]) -> Cow<'_, str> {
+    if bytes.is_ascii() {
+        return Cow::Borrowed(std::str::from_utf8(bytes).unwrap());
+    }
     let mut chars = Vec::new();
     for ch in bytes {
         if ch.is_ascii() {
@@ -272,7 +352,7 @@ fn ascii_decode(bytes: &[u8]) -> String {
             chars.push('\u{FFFD}');
         }
     }
-    chars.into_iter().collect()
+    Cow::Owned(chars.into_iter().collect())
 }
 
 // ========================================================================= //
@@ -283,35 +363,14 @@ mod tests {
 
     #[test]
     fn id_round_trip() {
-        let codepages = &[
-            CodePage::Windows932,
-            CodePage::Windows936,
-            CodePage::Windows949,
-            CodePage::Windows950,
-            CodePage::Windows951,
-            CodePage::Windows1250,
-            CodePage::Windows1251,
-            CodePage::Windows1252,
-            CodePage::Windows1253,
-            CodePage::Windows1254,
-            CodePage::Windows1255,
-            CodePage::Windows1256,
-            CodePage::Windows1257,
-            CodePage::Windows1258,
-            CodePage::MacintoshRoman,
-            CodePage::MacintoshCyrillic,
-            CodePage::Iso88591,
-            CodePage::Iso88592,
-            CodePage::Iso88593,
-            CodePage::Iso88594,
-            CodePage::Iso88595,
-            CodePage::Iso88596,
-            CodePage::Iso88597,
-            CodePage::Iso88598,
-            CodePage::Utf8,
-        ];
-        for &codepage in codepages {
-            assert_eq!(CodePage::from_id(codepage.id()), Some(codepage));
+        assert_eq!(CodePage::from_id(1252), Some(CodePage::Windows1252));
+        assert_eq!(CodePage::from_id(0), Some(CodePage::Neutral));
+        assert_eq!(CodePage::from_id(1), None);
+        for &codepage in CodePage::all() {
+            assert_eq!(
+                CodePage::from_id(i32::from(codepage.id())),
+                Some(codepage)
+            );
         }
     }
 
@@ -331,6 +390,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn decode_double_byte_string() {
+        assert_eq!(
+            &CodePage::Windows932.decode(b"\x93\xfa\x96\x7b\x8c\xea"),
+            "日本語"
+        );
+        assert_eq!(&CodePage::Windows936.decode(b"\xd6\xd0\xce\xc4"), "中文");
+        assert_eq!(
+            &CodePage::Windows949.decode(b"\xc7\xd1\xb1\xb9\xbe\xee"),
+            "한국어"
+        );
+        assert_eq!(&CodePage::Windows950.decode(b"\xa4\xa4\xa4\xe5"), "中文");
+    }
+
+    #[test]
+    fn double_byte_round_trip() {
+        let bytes =
+            b"\x83\x74\x83\x40\x83\x43\x83\x8b \x93\xfa\x96\x7b\x8c\xea";
+        let string = CodePage::Windows932.decode(bytes);
+        assert_eq!(string, "ファイル 日本語");
+        assert_eq!(&CodePage::Windows932.encode(&string) as &[u8], bytes);
+        for codepage in
+            [CodePage::Windows936, CodePage::Windows949, CodePage::Windows950]
+        {
+            let bytes = codepage.encode("中文 ABC");
+            assert_eq!(codepage.decode(&bytes), "中文 ABC");
+        }
+    }
+
     #[test]
     fn decoding_error() {
         assert_eq!(
@@ -339,6 +427,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_encode_string() {
+        assert!(CodePage::UsAscii.can_encode("What's up?"));
+        assert!(!CodePage::UsAscii.can_encode("¿Qué pasa?"));
+        assert!(CodePage::Windows1252.can_encode("¿Qué pasa?"));
+        assert!(!CodePage::Windows1252.can_encode("Что происходит?"));
+        assert!(CodePage::Windows1251.can_encode("Что происходит?"));
+        assert!(CodePage::Utf8.can_encode("Что происходит?"));
+    }
+
     #[test]
     fn encode_string() {
         assert_eq!(
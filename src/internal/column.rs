@@ -56,6 +56,18 @@ impl ColumnType {
         }
     }
 
+    /// Returns true if the value is of the kind stored in columns of this
+    /// type (an integer or a string), or is null.  Other constraints, such
+    /// as nullability and ranges, are not checked.
+    pub(crate) fn accepts_kind(&self, value: &Value) -> bool {
+        matches!(
+            (value, self),
+            (Value::Null, _)
+                | (Value::Int(_), ColumnType::Int16 | ColumnType::Int32)
+                | (Value::Str(_), ColumnType::Str(_))
+        )
+    }
+
     fn bitfield(&self) -> i32 {
         match *self {
             ColumnType::Int16 => 0x2,
@@ -94,8 +106,20 @@ impl ColumnType {
         long_string_refs: bool,
     ) -> io::Result<()> {
         match *self {
+            // The minimum value of each integer type is reserved, since it
+            // encodes as zero, which means null.
             ColumnType::Int16 => match value_ref {
                 ValueRef::Null => writer.write_i16::<LittleEndian>(0)?,
+                ValueRef::Int(number)
+                    if number <= i16::MIN as i32
+                        || number > i16::MAX as i32 =>
+                {
+                    invalid_input!(
+                        "Cannot write {:?} to {} column",
+                        value_ref,
+                        self
+                    )
+                }
                 ValueRef::Int(number) => {
                     let number = (number as i16) ^ -0x8000;
                     writer.write_i16::<LittleEndian>(number)?
@@ -108,6 +132,11 @@ impl ColumnType {
             },
             ColumnType::Int32 => match value_ref {
                 ValueRef::Null => writer.write_i32::<LittleEndian>(0)?,
+                ValueRef::Int(i32::MIN) => invalid_input!(
+                    "Cannot write {:?} to {} column",
+                    value_ref,
+                    self
+                ),
                 ValueRef::Int(number) => {
                     let number = number ^ -0x8000_0000;
                     writer.write_i32::<LittleEndian>(number)?
@@ -178,6 +207,7 @@ pub struct Column {
     foreign_key: Option<(String, i32)>,
     category: Option<Category>,
     enum_values: Vec<String>,
+    default_value: Option<Value>,
 }
 
 impl Column {
@@ -209,6 +239,7 @@ impl Column {
                 foreign_key: self.foreign_key.clone(),
                 category: self.category,
                 enum_values: self.enum_values.clone(),
+                default_value: self.default_value.clone(),
             }
         }
     }
@@ -258,6 +289,16 @@ impl Column {
         self.coltype
     }
 
+    /// Returns the maximum length of strings in this column, or `None` if
+    /// this is an integer column or a string column with no maximum length.
+    #[must_use]
+    pub fn max_len(&self) -> Option<usize> {
+        match self.coltype {
+            ColumnType::Str(max_len) if max_len > 0 => Some(max_len),
+            _ => None,
+        }
+    }
+
     /// Returns true if values in this column can be localized.
     #[must_use]
     pub fn is_localizable(&self) -> bool {
@@ -304,9 +345,69 @@ impl Column {
         }
     }
 
+    /// Returns the value that is used for this column when an inserted row
+    /// omits it, if the column has a default.  Defaults are part of the
+    /// in-memory schema only; they are not stored in the package.
+    #[must_use]
+    pub fn default_value(&self) -> Option<&Value> {
+        self.default_value.as_ref()
+    }
+
     /// Returns true if the given value is valid for this column.
     #[must_use]
     pub fn is_valid_value(&self, value: &Value) -> bool {
+        self.is_valid_value_type(value) && self.matches_category(value)
+    }
+
+    /// Returns an error describing why the given value cannot be stored in
+    /// this column of the named table, if it can't.  If `check_category` is
+    /// false, string values are not checked against the column's category
+    /// or enum values.
+    pub(crate) fn check_value(
+        &self,
+        table_name: &str,
+        value: &Value,
+        check_category: bool,
+    ) -> io::Result<()> {
+        if !self.is_valid_value_type(value) {
+            invalid_input!(
+                "{} is not a valid value for column {:?} of table {:?}",
+                value,
+                self.name,
+                table_name
+            );
+        }
+        if check_category && !self.matches_category(value) {
+            match self.category {
+                Some(category)
+                    if !category.validate(value.as_str().unwrap()) =>
+                {
+                    invalid_input!(
+                        "{} is not a valid {} value for column {:?} of table \
+                         {:?}",
+                        value,
+                        category,
+                        self.name,
+                        table_name
+                    );
+                }
+                _ => {
+                    invalid_input!(
+                        "{} is not one of the allowed values for column {:?} \
+                         of table {:?}",
+                        value,
+                        self.name,
+                        table_name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the value has the right type for this column, and is
+    /// within the column's range or length limits.
+    fn is_valid_value_type(&self, value: &Value) -> bool {
         match *value {
             Value::Null => self.is_nullable,
             Value::Int(number) => {
@@ -327,21 +428,26 @@ impl Column {
             Value::Str(ref string) => match self.coltype {
                 ColumnType::Int16 | ColumnType::Int32 => false,
                 ColumnType::Str(max_len) => {
-                    if let Some(category) = self.category {
-                        if !category.validate(string) {
-                            return false;
-                        }
-                    }
-                    if !self.enum_values.is_empty()
-                        && !self.enum_values.contains(string)
-                    {
-                        return false;
-                    }
                     max_len == 0 || string.chars().count() <= max_len
                 }
             },
         }
     }
+
+    /// Returns true if the value (if it is a string) is valid for this
+    /// column's category and is one of its enum values (if any).
+    fn matches_category(&self, value: &Value) -> bool {
+        let string = match *value {
+            Value::Str(ref string) => string,
+            _ => return true,
+        };
+        if let Some(category) = self.category {
+            if !category.validate(string) {
+                return false;
+            }
+        }
+        self.enum_values.is_empty() || self.enum_values.contains(string)
+    }
 }
 
 // ========================================================================= //
@@ -356,6 +462,7 @@ pub struct ColumnBuilder {
     foreign_key: Option<(String, i32)>,
     category: Option<Category>,
     enum_values: Vec<String>,
+    default_value: Option<Value>,
 }
 
 impl ColumnBuilder {
@@ -369,6 +476,7 @@ impl ColumnBuilder {
             foreign_key: None,
             category: None,
             enum_values: Vec::new(),
+            default_value: None,
         }
     }
 
@@ -425,6 +533,17 @@ impl ColumnBuilder {
         self
     }
 
+    /// Gives the column a default value, which is used when a row inserted
+    /// with [`Insert`](crate::Insert) omits this column.  Only trailing
+    /// columns can be omitted, so every column after this one should also
+    /// have a default.  The value must be valid for the column; this is
+    /// checked when the table is created.
+    #[must_use]
+    pub fn default(mut self, value: Value) -> ColumnBuilder {
+        self.default_value = Some(value);
+        self
+    }
+
     /// Builds a column that stores a 16-bit integer.
     #[must_use]
     pub fn int16(self) -> Column {
@@ -483,6 +602,7 @@ impl ColumnBuilder {
             foreign_key: self.foreign_key,
             category: self.category,
             enum_values: self.enum_values,
+            default_value: self.default_value,
         }
     }
 
@@ -498,6 +618,7 @@ impl ColumnBuilder {
             foreign_key: self.foreign_key,
             category: self.category,
             enum_values: self.enum_values,
+            default_value: self.default_value,
         })
     }
 }
@@ -646,6 +767,38 @@ mod tests {
         assert_eq!(&output as &[u8], b"\x01\x00\x00");
     }
 
+    #[test]
+    fn integer_extremes_are_distinct_from_null() {
+        for (coltype, min, max) in [
+            (ColumnType::Int16, -0x7fff, 0x7fff),
+            (ColumnType::Int32, -0x7fff_ffff, 0x7fff_ffff),
+        ] {
+            for value_ref in [
+                ValueRef::Null,
+                ValueRef::Int(0),
+                ValueRef::Int(min),
+                ValueRef::Int(max),
+            ] {
+                let mut output = Vec::<u8>::new();
+                coltype.write_value(&mut output, value_ref, false).unwrap();
+                assert_eq!(
+                    output.iter().all(|&byte| byte == 0),
+                    value_ref == ValueRef::Null
+                );
+                let mut input: &[u8] = &output;
+                assert_eq!(
+                    coltype.read_value(&mut input, false).unwrap(),
+                    value_ref
+                );
+            }
+            let mut output = Vec::<u8>::new();
+            let value_ref = ValueRef::Int(min - 1);
+            assert!(coltype
+                .write_value(&mut output, value_ref, false)
+                .is_err());
+        }
+    }
+
     #[test]
     fn valid_column_value() {
         let column = Column::build("Foo").nullable().int16();
@@ -0,0 +1,559 @@
+use crate::internal::error::Result;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+// ========================================================================= //
+
+/// A parsed MSI conditional expression, such as those stored in
+/// `Category::Condition` columns (e.g. in the `LaunchCondition` table).
+///
+/// Conditions can refer to properties by name, and can compare properties and
+/// literals using `=`, `<>`, `<`, `<=`, `>`, `>=`, `><` (substring, or
+/// bitwise-and for integers), `<<` (starts with), and `>>` (ends with), each
+/// of which may be prefixed with `~` for a case-insensitive comparison.
+/// Comparisons can be combined with `NOT`, `AND`, `OR`, `XOR`, `EQV`, and
+/// `IMP`, and grouped with parentheses.  Environment variables (`%NAME`) are
+/// looked up in the property map under their `%`-prefixed name.  Component
+/// and feature states (`$Comp`, `?Comp`, `&Feature`, `!Feature`) depend on
+/// the state of an installation in progress, and are not supported.
+///
+/// For more details, see the [MSI
+/// docs](https://docs.microsoft.com/en-us/windows/win32/msi/conditional-statement-syntax)
+/// for conditional statement syntax.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+/// let condition = msi::Condition::parse("VersionNT >= 600").unwrap();
+/// let mut properties = HashMap::new();
+/// properties.insert("VersionNT".to_string(), "601".to_string());
+/// assert!(condition.evaluate(&properties));
+/// properties.insert("VersionNT".to_string(), "501".to_string());
+/// assert!(!condition.evaluate(&properties));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Condition {
+    ast: Option<Ast>,
+}
+
+impl Condition {
+    /// Parses a conditional expression.  Returns an error if the expression
+    /// is malformed, or uses syntax that isn't supported.  An empty (or
+    /// all-whitespace) expression is valid, and always evaluates to true.
+    pub fn parse(string: &str) -> Result<Condition> {
+        let tokens = tokenize(string)?;
+        if tokens.is_empty() {
+            return Ok(Condition { ast: None });
+        }
+        let mut parser = Parser { tokens, index: 0 };
+        let ast = parser.parse_imp()?;
+        if parser.index < parser.tokens.len() {
+            invalid_input!(
+                "Unexpected {} in condition {:?}",
+                parser.tokens[parser.index],
+                string
+            );
+        }
+        Ok(Condition { ast: Some(ast) })
+    }
+
+    /// Evaluates the condition, using the given map of property values.
+    /// Properties that aren't in the map are treated as unset.
+    #[must_use]
+    pub fn evaluate(&self, properties: &HashMap<String, String>) -> bool {
+        match self.ast {
+            Some(ref ast) => ast.eval(properties),
+            None => true,
+        }
+    }
+}
+
+// ========================================================================= //
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Ast {
+    Term(Term),
+    Compare(Term, CmpOp, bool, Term),
+    Not(Box<Ast>),
+    Logic(Box<Ast>, LogicOp, Box<Ast>),
+}
+
+impl Ast {
+    fn eval(&self, properties: &HashMap<String, String>) -> bool {
+        match *self {
+            Ast::Term(ref term) => match term.value(properties) {
+                Operand::Int(number) => number != 0,
+                Operand::Str(string) => !string.is_empty(),
+            },
+            Ast::Compare(ref lhs, op, ignore_case, ref rhs) => op.eval(
+                lhs.value(properties),
+                rhs.value(properties),
+                ignore_case,
+            ),
+            Ast::Not(ref ast) => !ast.eval(properties),
+            Ast::Logic(ref lhs, op, ref rhs) => {
+                let lhs = lhs.eval(properties);
+                let rhs = rhs.eval(properties);
+                match op {
+                    LogicOp::And => lhs && rhs,
+                    LogicOp::Or => lhs || rhs,
+                    LogicOp::Xor => lhs != rhs,
+                    LogicOp::Eqv => lhs == rhs,
+                    LogicOp::Imp => !lhs || rhs,
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Term {
+    Property(String),
+    Int(i32),
+    Str(String),
+}
+
+impl Term {
+    fn value(&self, properties: &HashMap<String, String>) -> Operand {
+        match *self {
+            Term::Property(ref name) => match properties.get(name) {
+                Some(value) => match value.parse::<i32>() {
+                    Ok(number) => Operand::Int(number),
+                    Err(_) => Operand::Str(value.clone()),
+                },
+                None => Operand::Str(String::new()),
+            },
+            Term::Int(number) => Operand::Int(number),
+            Term::Str(ref string) => Operand::Str(string.clone()),
+        }
+    }
+}
+
+enum Operand {
+    Int(i32),
+    Str(String),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+impl CmpOp {
+    fn eval(self, lhs: Operand, rhs: Operand, ignore_case: bool) -> bool {
+        match (lhs, rhs) {
+            (Operand::Int(lhs), Operand::Int(rhs)) => match self {
+                CmpOp::Contains => (lhs & rhs) != 0,
+                CmpOp::StartsWith => ((lhs >> 16) & 0xffff) == rhs,
+                CmpOp::EndsWith => (lhs & 0xffff) == rhs,
+                _ => self.eval_ordering(lhs.cmp(&rhs)),
+            },
+            (Operand::Str(mut lhs), Operand::Str(mut rhs)) => {
+                if ignore_case {
+                    lhs = lhs.to_lowercase();
+                    rhs = rhs.to_lowercase();
+                }
+                match self {
+                    CmpOp::Contains => lhs.contains(&rhs),
+                    CmpOp::StartsWith => lhs.starts_with(&rhs),
+                    CmpOp::EndsWith => lhs.ends_with(&rhs),
+                    _ => {
+                        let ordering =
+                            match (parse_version(&lhs), parse_version(&rhs)) {
+                                (Some(lhs), Some(rhs)) => lhs.cmp(&rhs),
+                                _ => lhs.cmp(&rhs),
+                            };
+                        self.eval_ordering(ordering)
+                    }
+                }
+            }
+            // Comparing a string with an integer is always false, except for
+            // the "not equal" operator.
+            _ => self == CmpOp::Ne,
+        }
+    }
+
+    fn eval_ordering(self, ordering: Ordering) -> bool {
+        match self {
+            CmpOp::Eq => ordering == Ordering::Equal,
+            CmpOp::Ne => ordering != Ordering::Equal,
+            CmpOp::Lt => ordering == Ordering::Less,
+            CmpOp::Le => ordering != Ordering::Greater,
+            CmpOp::Gt => ordering == Ordering::Greater,
+            CmpOp::Ge => ordering != Ordering::Less,
+            CmpOp::Contains | CmpOp::StartsWith | CmpOp::EndsWith => false,
+        }
+    }
+}
+
+/// Parses a string of two to four period-separated numbers (e.g. `"1.2.3"`)
+/// into a version that compares numerically, component by component.
+fn parse_version(string: &str) -> Option<[u16; 4]> {
+    let mut version = [0u16; 4];
+    let mut count = 0;
+    for part in string.split('.') {
+        if count >= 4 {
+            return None;
+        }
+        version[count] = part.parse().ok()?;
+        count += 1;
+    }
+    if count < 2 {
+        return None;
+    }
+    Some(version)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum LogicOp {
+    And,
+    Or,
+    Xor,
+    Eqv,
+    Imp,
+}
+
+// ========================================================================= //
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Token {
+    Identifier(String),
+    Int(i32),
+    Str(String),
+    Compare(CmpOp, bool),
+    Logic(LogicOp),
+    Not,
+    OpenParen,
+    CloseParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Token::Identifier(ref name) => write!(formatter, "{name:?}"),
+            Token::Int(number) => write!(formatter, "{number}"),
+            Token::Str(ref string) => write!(formatter, "\"{string}\""),
+            Token::Compare(..) => formatter.write_str("comparison operator"),
+            Token::Logic(op) => {
+                write!(formatter, "{}", format!("{op:?}").to_uppercase())
+            }
+            Token::Not => formatter.write_str("NOT"),
+            Token::OpenParen => formatter.write_str("'('"),
+            Token::CloseParen => formatter.write_str("')'"),
+        }
+    }
+}
+
+fn tokenize(string: &str) -> io::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars: Peekable<CharIndices> = string.char_indices().peekable();
+    while let Some((start, chr)) = chars.next() {
+        match chr {
+            _ if chr.is_whitespace() => {}
+            '(' => tokens.push(Token::OpenParen),
+            ')' => tokens.push(Token::CloseParen),
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, chr)) => value.push(chr),
+                        None => invalid_input!(
+                            "Unterminated string in condition {:?}",
+                            string
+                        ),
+                    }
+                }
+                tokens.push(Token::Str(value));
+            }
+            '~' | '=' | '<' | '>' => {
+                let ignore_case = chr == '~';
+                let first = if ignore_case {
+                    match chars.next() {
+                        Some((_, chr)) => chr,
+                        None => invalid_input!(
+                            "Expected operator after '~' in condition {:?}",
+                            string
+                        ),
+                    }
+                } else {
+                    chr
+                };
+                let second = chars.peek().map(|&(_, chr)| chr);
+                let (op, consume) = match (first, second) {
+                    ('=', _) => (CmpOp::Eq, false),
+                    ('<', Some('>')) => (CmpOp::Ne, true),
+                    ('<', Some('=')) => (CmpOp::Le, true),
+                    ('<', Some('<')) => (CmpOp::StartsWith, true),
+                    ('<', _) => (CmpOp::Lt, false),
+                    ('>', Some('=')) => (CmpOp::Ge, true),
+                    ('>', Some('<')) => (CmpOp::Contains, true),
+                    ('>', Some('>')) => (CmpOp::EndsWith, true),
+                    ('>', _) => (CmpOp::Gt, false),
+                    _ => invalid_input!(
+                        "Expected operator after '~' in condition {:?}",
+                        string
+                    ),
+                };
+                if consume {
+                    chars.next();
+                }
+                tokens.push(Token::Compare(op, ignore_case));
+            }
+            '-' | '0'..='9' => {
+                let mut end = start + chr.len_utf8();
+                while let Some(&(index, chr)) = chars.peek() {
+                    if !chr.is_ascii_digit() {
+                        break;
+                    }
+                    end = index + chr.len_utf8();
+                    chars.next();
+                }
+                match string[start..end].parse::<i32>() {
+                    Ok(number) => tokens.push(Token::Int(number)),
+                    Err(_) => invalid_input!(
+                        "Invalid integer {:?} in condition {:?}",
+                        &string[start..end],
+                        string
+                    ),
+                }
+            }
+            '%' | '_' | 'A'..='Z' | 'a'..='z' => {
+                let mut end = start + chr.len_utf8();
+                while let Some(&(index, chr)) = chars.peek() {
+                    if !(chr.is_ascii_alphanumeric()
+                        || chr == '_'
+                        || chr == '.')
+                    {
+                        break;
+                    }
+                    end = index + chr.len_utf8();
+                    chars.next();
+                }
+                let word = &string[start..end];
+                if word == "%" {
+                    invalid_input!(
+                        "Expected environment variable name after '%' in \
+                         condition {:?}",
+                        string
+                    );
+                }
+                tokens.push(match word.to_ascii_uppercase().as_str() {
+                    "NOT" => Token::Not,
+                    "AND" => Token::Logic(LogicOp::And),
+                    "OR" => Token::Logic(LogicOp::Or),
+                    "XOR" => Token::Logic(LogicOp::Xor),
+                    "EQV" => Token::Logic(LogicOp::Eqv),
+                    "IMP" => Token::Logic(LogicOp::Imp),
+                    _ => Token::Identifier(word.to_string()),
+                });
+            }
+            '$' | '?' | '&' | '!' => {
+                invalid_input!(
+                    "Component and feature states ('{}') are not supported \
+                     in condition {:?}",
+                    chr,
+                    string
+                );
+            }
+            _ => {
+                invalid_input!(
+                    "Unexpected character {:?} in condition {:?}",
+                    chr,
+                    string
+                );
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// ========================================================================= //
+
+struct Parser {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.index).cloned();
+        self.index += 1;
+        token
+    }
+
+    fn parse_logic(
+        &mut self,
+        op: LogicOp,
+        operand: fn(&mut Parser) -> io::Result<Ast>,
+    ) -> io::Result<Ast> {
+        let mut ast = operand(self)?;
+        while self.peek() == Some(&Token::Logic(op)) {
+            self.index += 1;
+            let rhs = operand(self)?;
+            ast = Ast::Logic(Box::new(ast), op, Box::new(rhs));
+        }
+        Ok(ast)
+    }
+
+    // Logical operators, from lowest to highest precedence: IMP, EQV, XOR,
+    // OR, AND, NOT.
+    fn parse_imp(&mut self) -> io::Result<Ast> {
+        self.parse_logic(LogicOp::Imp, Parser::parse_eqv)
+    }
+
+    fn parse_eqv(&mut self) -> io::Result<Ast> {
+        self.parse_logic(LogicOp::Eqv, Parser::parse_xor)
+    }
+
+    fn parse_xor(&mut self) -> io::Result<Ast> {
+        self.parse_logic(LogicOp::Xor, Parser::parse_or)
+    }
+
+    fn parse_or(&mut self) -> io::Result<Ast> {
+        self.parse_logic(LogicOp::Or, Parser::parse_and)
+    }
+
+    fn parse_and(&mut self) -> io::Result<Ast> {
+        self.parse_logic(LogicOp::And, Parser::parse_not)
+    }
+
+    fn parse_not(&mut self) -> io::Result<Ast> {
+        if self.peek() == Some(&Token::Not) {
+            self.index += 1;
+            Ok(Ast::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> io::Result<Ast> {
+        if self.peek() == Some(&Token::OpenParen) {
+            self.index += 1;
+            let ast = self.parse_imp()?;
+            match self.next() {
+                Some(Token::CloseParen) => return Ok(ast),
+                Some(token) => invalid_input!("Expected ')', found {}", token),
+                None => invalid_input!("Expected ')', found end of condition"),
+            }
+        }
+        let lhs = self.parse_term()?;
+        if let Some(&Token::Compare(op, ignore_case)) = self.peek() {
+            self.index += 1;
+            let rhs = self.parse_term()?;
+            Ok(Ast::Compare(lhs, op, ignore_case, rhs))
+        } else {
+            Ok(Ast::Term(lhs))
+        }
+    }
+
+    fn parse_term(&mut self) -> io::Result<Term> {
+        match self.next() {
+            Some(Token::Identifier(name)) => Ok(Term::Property(name)),
+            Some(Token::Int(number)) => Ok(Term::Int(number)),
+            Some(Token::Str(string)) => Ok(Term::Str(string)),
+            Some(token) => invalid_input!("Expected a value, found {}", token),
+            None => invalid_input!("Expected a value, found end of condition"),
+        }
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::Condition;
+    use std::collections::HashMap;
+
+    fn props(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|&(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn eval(condition: &str, properties: &HashMap<String, String>) -> bool {
+        Condition::parse(condition).unwrap().evaluate(properties)
+    }
+
+    #[test]
+    fn version_nt() {
+        let condition = "VersionNT >= 600";
+        assert!(eval(condition, &props(&[("VersionNT", "600")])));
+        assert!(eval(condition, &props(&[("VersionNT", "603")])));
+        assert!(!eval(condition, &props(&[("VersionNT", "501")])));
+        assert!(!eval(condition, &props(&[])));
+    }
+
+    #[test]
+    fn installed_and_not_remove() {
+        let condition = "Installed AND NOT REMOVE";
+        assert!(!eval(condition, &props(&[])));
+        assert!(eval(condition, &props(&[("Installed", "1")])));
+        assert!(!eval(
+            condition,
+            &props(&[("Installed", "1"), ("REMOVE", "ALL")])
+        ));
+        assert!(!eval(condition, &props(&[("REMOVE", "ALL")])));
+    }
+
+    #[test]
+    fn operators() {
+        let properties = props(&[
+            ("Name", "Hello World"),
+            ("Flags", "6"),
+            ("Version", "1.10.0"),
+        ]);
+        assert!(eval("Name = \"Hello World\"", &properties));
+        assert!(eval("Name <> \"hello world\"", &properties));
+        assert!(eval("Name ~= \"hello world\"", &properties));
+        assert!(eval("Name >< \"lo W\"", &properties));
+        assert!(eval("Name << \"Hello\"", &properties));
+        assert!(eval("Name >> \"World\"", &properties));
+        assert!(!eval("Name >> \"world\"", &properties));
+        assert!(eval("Name ~>> \"world\"", &properties));
+        assert!(eval("Flags >< 2", &properties));
+        assert!(!eval("Flags >< 1", &properties));
+        assert!(eval("Flags < 7 and Flags > -1", &properties));
+        assert!(eval("Version > \"1.9.5\"", &properties));
+        assert!(!eval("Version <= \"1.9\"", &properties));
+        assert!(eval("Name = 5 OR Name <> 5", &properties));
+        assert!(eval("(Flags = 1 OR Flags = 6) AND NOT Missing", &properties));
+        assert!(eval("Missing XOR Name", &properties));
+        assert!(eval("Missing IMP Nonsense", &properties));
+        assert!(eval("Missing EQV Nonsense", &properties));
+        assert!(eval("", &properties));
+        assert!(eval("%TEMP", &props(&[("%TEMP", "C:\\Temp")])));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Condition::parse("Foo AND").is_err());
+        assert!(Condition::parse("(Foo").is_err());
+        assert!(Condition::parse("Foo Bar").is_err());
+        assert!(Condition::parse("Foo = \"bar").is_err());
+        assert!(Condition::parse("$Component = 3").is_err());
+        assert!(Condition::parse("&Feature = 3").is_err());
+        assert!(Condition::parse("Foo ~ Bar").is_err());
+        assert!(Condition::parse("Foo = 99999999999").is_err());
+    }
+}
+
+// ========================================================================= //
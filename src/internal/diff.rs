@@ -0,0 +1,235 @@
+//! Utilities for comparing the contents of two packages.
+//!
+//! # Examples
+//!
+//! ```
+//! use msi::{Package, PackageType};
+//! use std::io::Cursor;
+//!
+//! let mut packages = Vec::new();
+//! for version in ["1.0.0", "1.1.0"] {
+//!     let cursor = Cursor::new(Vec::new());
+//!     let mut package = Package::create(PackageType::Installer, cursor)?;
+//!     package.set_product_version(version)?;
+//!     packages.push(package);
+//! }
+//! let (base, target) = packages.split_at_mut(1);
+//! let (base, target) = (&mut base[0], &mut target[0]);
+//! let diff = msi::diff::table_diff(base, target, "Property")?;
+//! assert_eq!(diff.modified().len(), 1);
+//! let change = &diff.modified()[0].changes()[0];
+//! assert_eq!(change.new_value(), &msi::Value::from("1.1.0"));
+//! # Ok::<(), msi::Error>(())
+//! ```
+
+use crate::internal::error::Result;
+use crate::internal::package::Package;
+use crate::internal::query::Select;
+use crate::internal::table::Table;
+use crate::internal::value::Value;
+use std::collections::BTreeMap;
+use std::io::{self, Read, Seek};
+
+// ========================================================================= //
+
+/// The differences between the rows of a table in two packages, as returned
+/// by [`table_diff`].  Rows are matched up by their primary key values, and
+/// each list is ordered by primary key.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TableDiff {
+    added: Vec<Vec<Value>>,
+    removed: Vec<Vec<Value>>,
+    modified: Vec<RowDiff>,
+}
+
+impl TableDiff {
+    /// Returns the rows (with values for all columns) that exist only in the
+    /// second package.
+    #[must_use]
+    pub fn added(&self) -> &[Vec<Value>] {
+        &self.added
+    }
+
+    /// Returns the rows (with values for all columns) that exist only in the
+    /// first package.
+    #[must_use]
+    pub fn removed(&self) -> &[Vec<Value>] {
+        &self.removed
+    }
+
+    /// Returns the rows that exist in both packages, but with different
+    /// values in some columns.
+    #[must_use]
+    pub fn modified(&self) -> &[RowDiff] {
+        &self.modified
+    }
+
+    /// Returns true if the table's rows are the same in both packages.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+    }
+}
+
+/// A row that exists in both packages being compared, but with different
+/// values in some of its columns.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RowDiff {
+    key: Vec<Value>,
+    changes: Vec<ColumnChange>,
+}
+
+impl RowDiff {
+    /// Returns the values of the row's primary key columns.
+    #[must_use]
+    pub fn key(&self) -> &[Value] {
+        &self.key
+    }
+
+    /// Returns the columns whose values differ, in column order.
+    #[must_use]
+    pub fn changes(&self) -> &[ColumnChange] {
+        &self.changes
+    }
+}
+
+/// A change to the value of one column of a [`RowDiff`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ColumnChange {
+    column: String,
+    old_value: Value,
+    new_value: Value,
+}
+
+impl ColumnChange {
+    /// Returns the name of the column.
+    #[must_use]
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    /// Returns the column's value in the first package.
+    #[must_use]
+    pub fn old_value(&self) -> &Value {
+        &self.old_value
+    }
+
+    /// Returns the column's value in the second package.
+    #[must_use]
+    pub fn new_value(&self) -> &Value {
+        &self.new_value
+    }
+}
+
+// ========================================================================= //
+
+/// Compares the rows of the named table in two packages, returning the rows
+/// that were added, removed, or modified going from `a` to `b`.
+///
+/// If the table exists in only one of the packages, it is treated as empty
+/// in the other, so that all of its rows are reported as added or removed.
+/// Returns an error if the table exists in neither package, or if it has
+/// different columns in the two packages.
+pub fn table_diff<F, G>(
+    a: &mut Package<F>,
+    b: &mut Package<G>,
+    table_name: &str,
+) -> Result<TableDiff>
+where
+    F: Read + Seek,
+    G: Read + Seek,
+{
+    let table = match (a.get_table(table_name), b.get_table(table_name)) {
+        (Some(table_a), Some(table_b)) => {
+            if !same_columns(table_a, table_b) {
+                schema_mismatch!(
+                    "Table {:?} has different columns in the two packages",
+                    table_name
+                );
+            }
+            table_a.clone()
+        }
+        (Some(table), None) | (None, Some(table)) => table.clone(),
+        (None, None) => {
+            not_found!(
+                "Table {:?} does not exist in either package",
+                table_name
+            )
+        }
+    };
+    let rows_a = keyed_rows(a, &table)?;
+    let rows_b = keyed_rows(b, &table)?;
+    let mut diff = TableDiff::default();
+    for (key, values_a) in rows_a.iter() {
+        let values_b = match rows_b.get(key) {
+            Some(values_b) => values_b,
+            None => {
+                diff.removed.push(values_a.clone());
+                continue;
+            }
+        };
+        let changes: Vec<ColumnChange> = table
+            .columns()
+            .iter()
+            .zip(values_a.iter().zip(values_b.iter()))
+            .filter(|(_, (value_a, value_b))| value_a != value_b)
+            .map(|(column, (value_a, value_b))| ColumnChange {
+                column: column.name().to_string(),
+                old_value: value_a.clone(),
+                new_value: value_b.clone(),
+            })
+            .collect();
+        if !changes.is_empty() {
+            diff.modified.push(RowDiff { key: key.clone(), changes });
+        }
+    }
+    for (key, values_b) in rows_b.iter() {
+        if !rows_a.contains_key(key) {
+            diff.added.push(values_b.clone());
+        }
+    }
+    Ok(diff)
+}
+
+// ========================================================================= //
+
+/// Returns true if the two tables have the same column names and types, and
+/// the same primary key columns.
+pub(crate) fn same_columns(table_a: &Table, table_b: &Table) -> bool {
+    table_a.columns().len() == table_b.columns().len()
+        && table_a.columns().iter().zip(table_b.columns()).all(
+            |(column_a, column_b)| {
+                column_a.name() == column_b.name()
+                    && column_a.coltype() == column_b.coltype()
+                    && column_a.is_primary_key() == column_b.is_primary_key()
+            },
+        )
+}
+
+/// Reads all rows of the table, keyed by their primary key values.  If the
+/// table doesn't exist in the package, returns no rows.
+pub(crate) fn keyed_rows<F: Read + Seek>(
+    package: &mut Package<F>,
+    table: &Table,
+) -> io::Result<BTreeMap<Vec<Value>, Vec<Value>>> {
+    let mut rows = BTreeMap::new();
+    if !package.has_table(table.name()) {
+        return Ok(rows);
+    }
+    let key_indices = table.primary_key_indices();
+    for row in package.select_rows(Select::table(table.name()))? {
+        let values: Vec<Value> =
+            (0..row.len()).map(|index| row[index].clone()).collect();
+        let key = if key_indices.is_empty() {
+            values.clone()
+        } else {
+            key_indices.iter().map(|&index| values[index].clone()).collect()
+        };
+        rows.insert(key, values);
+    }
+    Ok(rows)
+}
+
+// ========================================================================= //
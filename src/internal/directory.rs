@@ -0,0 +1,310 @@
+use crate::internal::error::{Error, Result};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str;
+
+// ========================================================================= //
+
+/// Directory table keys that name standard system folders.  The installer
+/// sets these properties itself, so their paths don't depend on their
+/// parents in the `Directory` table.
+const SYSTEM_FOLDERS: &[&str] = &[
+    "AdminToolsFolder",
+    "AppDataFolder",
+    "CommonAppDataFolder",
+    "CommonFiles64Folder",
+    "CommonFiles6432Folder",
+    "CommonFilesFolder",
+    "DesktopFolder",
+    "FavoritesFolder",
+    "FontsFolder",
+    "LocalAppDataFolder",
+    "MyPicturesFolder",
+    "NetHoodFolder",
+    "PersonalFolder",
+    "PrintHoodFolder",
+    "ProgramFiles64Folder",
+    "ProgramFiles6432Folder",
+    "ProgramFilesFolder",
+    "ProgramMenuFolder",
+    "RecentFolder",
+    "SendToFolder",
+    "StartMenuFolder",
+    "StartupFolder",
+    "System16Folder",
+    "System64Folder",
+    "System6432Folder",
+    "SystemFolder",
+    "TempFolder",
+    "TemplateFolder",
+    "WindowsFolder",
+    "WindowsVolume",
+];
+
+// ========================================================================= //
+
+/// A parsed value of a column with the `DefaultDir` category (such as
+/// `Directory.DefaultDir`), which names a directory on the target machine
+/// and, optionally, a different name for it in the source image.
+///
+/// The value has the form `target[:source]`, where each of `target` and
+/// `source` is either a single name or a `short|long` pair.  When no source
+/// name is given, the source directory has the same name as the target.
+///
+/// # Examples
+///
+/// ```
+/// let dir: msi::DefaultDir = "PFiles|Program Files".parse().unwrap();
+/// assert_eq!(dir.target_short(), "PFiles");
+/// assert_eq!(dir.target_long(), "Program Files");
+/// assert_eq!(dir.source_long(), "Program Files");
+/// assert_eq!(dir.to_string(), "PFiles|Program Files");
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct DefaultDir {
+    target: DirName,
+    source: Option<DirName>,
+}
+
+impl DefaultDir {
+    /// Parses a `DefaultDir` value.  Returns an error if the string has more
+    /// than one `:` separator, if either side has more than one `|`
+    /// separator, or if any of the names is empty.
+    pub fn parse(string: &str) -> Result<DefaultDir> {
+        string.parse()
+    }
+
+    /// Returns the short name of the directory on the target machine.
+    #[must_use]
+    pub fn target_short(&self) -> &str {
+        &self.target.short
+    }
+
+    /// Returns the long name of the directory on the target machine, or the
+    /// short name if no long name was given.
+    #[must_use]
+    pub fn target_long(&self) -> &str {
+        self.target.long()
+    }
+
+    /// Returns the short name of the directory in the source image.  This is
+    /// the same as the target short name if no source name was given.
+    #[must_use]
+    pub fn source_short(&self) -> &str {
+        &self.source.as_ref().unwrap_or(&self.target).short
+    }
+
+    /// Returns the long name of the directory in the source image.  This is
+    /// the same as the target long name if no source name was given.
+    #[must_use]
+    pub fn source_long(&self) -> &str {
+        self.source.as_ref().unwrap_or(&self.target).long()
+    }
+
+    /// Returns true if the value explicitly names a source directory.
+    #[must_use]
+    pub fn has_source(&self) -> bool {
+        self.source.is_some()
+    }
+}
+
+impl fmt::Display for DefaultDir {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.target.fmt(formatter)?;
+        if let Some(ref source) = self.source {
+            write!(formatter, ":{source}")?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for DefaultDir {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<DefaultDir> {
+        let (target, source) = match string.split_once(':') {
+            Some((target, source)) => (target, Some(source)),
+            None => (string, None),
+        };
+        let invalid = || {
+            Error::Validation(format!("Invalid DefaultDir value: {string:?}"))
+        };
+        let target = DirName::parse(target).ok_or_else(invalid)?;
+        let source = match source {
+            Some(source) => Some(DirName::parse(source).ok_or_else(invalid)?),
+            None => None,
+        };
+        Ok(DefaultDir { target, source })
+    }
+}
+
+// ========================================================================= //
+
+/// One side of a `DefaultDir` value: a short name, and optionally a long
+/// name.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct DirName {
+    short: String,
+    long: Option<String>,
+}
+
+impl DirName {
+    fn parse(string: &str) -> Option<DirName> {
+        let (short, long) = match string.split_once('|') {
+            Some((short, long)) => (short, Some(long)),
+            None => (string, None),
+        };
+        let is_valid =
+            |name: &str| !name.is_empty() && !name.contains([':', '|']);
+        if !is_valid(short) || !long.is_none_or(is_valid) {
+            return None;
+        }
+        Some(DirName {
+            short: short.to_string(),
+            long: long.map(str::to_string),
+        })
+    }
+
+    fn long(&self) -> &str {
+        self.long.as_deref().unwrap_or(&self.short)
+    }
+}
+
+impl fmt::Display for DirName {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(&self.short)?;
+        if let Some(ref long) = self.long {
+            write!(formatter, "|{long}")?;
+        }
+        Ok(())
+    }
+}
+
+// ========================================================================= //
+
+/// Resolves each directory, given as a `(key, parent, default_dir)` triple
+/// from the `Directory` table, to a `/`-separated path relative to the root
+/// directory or system folder that contains it.
+///
+/// Root directories (those with no parent, or that are their own parent) and
+/// standard system folders resolve to their own key.  Every other directory
+/// resolves to its parent's path joined with the long target name from its
+/// `DefaultDir` value, except that a target name of `.` resolves to the
+/// parent's path itself.
+pub(crate) fn resolve_paths(
+    directories: &[(String, Option<String>, String)],
+) -> Result<HashMap<String, String>> {
+    let mut entries = HashMap::<&str, (Option<&str>, &str)>::new();
+    for (key, parent, default_dir) in directories {
+        let parent = parent.as_deref().filter(|parent| parent != key);
+        entries.insert(key.as_str(), (parent, default_dir.as_str()));
+    }
+    let mut paths = HashMap::<String, String>::new();
+    for &start in entries.keys() {
+        // Walk up the tree until reaching an already-resolved directory or a
+        // root, then resolve the directories on the way back down.
+        let mut chain = Vec::<&str>::new();
+        let mut visited = HashSet::<&str>::new();
+        let mut key = start;
+        let mut path = loop {
+            if let Some(path) = paths.get(key) {
+                break path.clone();
+            }
+            if !visited.insert(key) {
+                invalid_data!("Directory {:?} is its own ancestor", key);
+            }
+            let (parent, _) = entries[key];
+            match parent {
+                Some(parent) if !SYSTEM_FOLDERS.contains(&key) => {
+                    if !entries.contains_key(parent) {
+                        invalid_data!(
+                            "Directory {:?} has nonexistent parent {:?}",
+                            key,
+                            parent
+                        );
+                    }
+                    chain.push(key);
+                    key = parent;
+                }
+                _ => {
+                    paths.insert(key.to_string(), key.to_string());
+                    break key.to_string();
+                }
+            }
+        };
+        while let Some(key) = chain.pop() {
+            let default_dir = match DefaultDir::parse(entries[key].1) {
+                Ok(default_dir) => default_dir,
+                Err(_) => invalid_data!(
+                    "Directory {:?} has invalid DefaultDir value {:?}",
+                    key,
+                    entries[key].1
+                ),
+            };
+            let name = default_dir.target_long();
+            if name != "." {
+                path.push('/');
+                path.push_str(name);
+            }
+            paths.insert(key.to_string(), path.clone());
+        }
+    }
+    Ok(paths)
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::DefaultDir;
+
+    #[test]
+    fn parse_short_and_long_target() {
+        let dir = DefaultDir::parse("PFiles|Program Files").unwrap();
+        assert_eq!(dir.target_short(), "PFiles");
+        assert_eq!(dir.target_long(), "Program Files");
+        assert_eq!(dir.source_short(), "PFiles");
+        assert_eq!(dir.source_long(), "Program Files");
+        assert!(!dir.has_source());
+        assert_eq!(dir.to_string(), "PFiles|Program Files");
+    }
+
+    #[test]
+    fn parse_single_name() {
+        let dir = DefaultDir::parse("TARGETDIR").unwrap();
+        assert_eq!(dir.target_short(), "TARGETDIR");
+        assert_eq!(dir.target_long(), "TARGETDIR");
+        assert_eq!(dir.source_short(), "TARGETDIR");
+        assert_eq!(dir.source_long(), "TARGETDIR");
+        assert_eq!(dir.to_string(), "TARGETDIR");
+    }
+
+    #[test]
+    fn parse_target_and_source() {
+        let string = "twoshort|Two Long:srcshort|Source Long";
+        let dir = DefaultDir::parse(string).unwrap();
+        assert_eq!(dir.target_short(), "twoshort");
+        assert_eq!(dir.target_long(), "Two Long");
+        assert_eq!(dir.source_short(), "srcshort");
+        assert_eq!(dir.source_long(), "Source Long");
+        assert!(dir.has_source());
+        assert_eq!(dir.to_string(), string);
+
+        let dir = DefaultDir::parse(".:SRC").unwrap();
+        assert_eq!(dir.target_long(), ".");
+        assert_eq!(dir.source_long(), "SRC");
+        assert_eq!(dir.to_string(), ".:SRC");
+    }
+
+    #[test]
+    fn parse_invalid_default_dirs() {
+        for string in ["", ":", "A:", ":B", "A|", "|B", "A|B|C", "A:B:C"] {
+            assert_eq!(
+                DefaultDir::parse(string).unwrap_err().to_string(),
+                format!("Invalid DefaultDir value: {string:?}")
+            );
+        }
+    }
+}
+
+// ========================================================================= //
@@ -0,0 +1,145 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+// ========================================================================= //
+
+/// A specialized `Result` type for operations on MSI packages.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An error from an operation on an MSI package.
+///
+/// Each variant other than `Io` carries a human-readable description of the
+/// problem.  Errors convert to and from `io::Error` without losing their
+/// variant, so code that uses `io::Result` and the `?` operator keeps
+/// working, and [`kind`](Error::kind) reports the `io::ErrorKind` that this
+/// library reported for the same error before `Error` existed.
+///
+/// # Examples
+///
+/// ```
+/// use msi::{Error, Package, PackageType, Select};
+/// use std::io::Cursor;
+///
+/// let cursor = Cursor::new(Vec::new());
+/// let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+/// match package.select_rows(Select::table("Property")) {
+///     Err(Error::NotFound(message)) => {
+///         assert_eq!(message, "Table \"Property\" does not exist");
+///     }
+///     _ => panic!("expected a NotFound error"),
+/// }
+/// ```
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O error from the underlying file.
+    Io(io::Error),
+    /// A table, column, row, stream, or other named item doesn't exist.
+    NotFound(String),
+    /// A table, row, stream, or other named item already exists.
+    AlreadyExists(String),
+    /// A query or row doesn't match the schema of the table it refers to
+    /// (for example, it names a nonexistent column or has the wrong number
+    /// of values).
+    SchemaMismatch(String),
+    /// An argument is invalid (for example, a value isn't allowed in the
+    /// column it's being stored in, or a name isn't a valid identifier).
+    Validation(String),
+    /// The package's contents are malformed or inconsistent.
+    Corrupt(String),
+}
+
+impl Error {
+    /// Returns the `io::ErrorKind` corresponding to this error.
+    #[must_use]
+    pub fn kind(&self) -> io::ErrorKind {
+        match self {
+            Error::Io(error) => error.kind(),
+            Error::NotFound(_) => io::ErrorKind::NotFound,
+            Error::AlreadyExists(_) => io::ErrorKind::AlreadyExists,
+            Error::SchemaMismatch(_) | Error::Validation(_) => {
+                io::ErrorKind::InvalidInput
+            }
+            Error::Corrupt(_) => io::ErrorKind::InvalidData,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => error.fmt(formatter),
+            Error::NotFound(message)
+            | Error::AlreadyExists(message)
+            | Error::SchemaMismatch(message)
+            | Error::Validation(message)
+            | Error::Corrupt(message) => formatter.write_str(message),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        // An `io::Error` that was converted from an `Error` holds the
+        // original as its payload, so unwrap it to recover the variant.
+        if error.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = error.into_inner().unwrap();
+            return *inner.downcast::<Error>().unwrap();
+        }
+        Error::Io(error)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> io::Error {
+        match error {
+            Error::Io(error) => error,
+            error => io::Error::new(error.kind(), error),
+        }
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use std::io;
+
+    #[test]
+    fn round_trip_through_io_error() {
+        let error = Error::SchemaMismatch("Wrong columns".to_string());
+        let io_error = io::Error::from(error);
+        assert_eq!(io_error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(io_error.to_string(), "Wrong columns");
+        match Error::from(io_error) {
+            Error::SchemaMismatch(message) => {
+                assert_eq!(message, "Wrong columns")
+            }
+            error => panic!("Unexpected error: {error:?}"),
+        }
+    }
+
+    #[test]
+    fn plain_io_errors_stay_io() {
+        let io_error = io::Error::new(io::ErrorKind::NotFound, "No file");
+        let error = Error::from(io_error);
+        assert!(matches!(error, Error::Io(_)));
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "No file");
+        let io_error = io::Error::from(error);
+        assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+        assert!(io_error.get_ref().unwrap().downcast_ref::<Error>().is_none());
+    }
+}
+
+// ========================================================================= //
@@ -2,6 +2,7 @@ use crate::internal::table::Row;
 use crate::internal::value::Value;
 use std::collections::HashSet;
 use std::fmt;
+use std::io;
 use std::ops;
 
 // ========================================================================= //
@@ -62,6 +63,20 @@ impl Expr {
         Expr { ast: Ast::Literal(Value::Str(string.into())) }
     }
 
+    /// Returns an expression that evaluates to the given value.
+    pub(crate) fn literal(value: Value) -> Expr {
+        Expr { ast: Ast::Literal(value) }
+    }
+
+    /// Returns the value of this expression if it is a constant, without
+    /// needing a row to evaluate it against.
+    pub(crate) fn as_literal(&self) -> Option<&Value> {
+        match self.ast {
+            Ast::Literal(ref value) => Some(value),
+            _ => None,
+        }
+    }
+
     /// Returns an expression that evaluates to true if the two subexpressions
     /// evaluate to equal values.
     #[must_use]
@@ -143,6 +158,76 @@ impl Expr {
         Expr::unop(UnOp::BoolNot, self.ast)
     }
 
+    /// Returns an expression that evaluates to true if the subexpression
+    /// evaluates to a value that appears in the given column of the given
+    /// table, like SQL's `IN (SELECT column FROM table)`.  The column's
+    /// values are read once per query, rather than once per row.  Null
+    /// values never match.
+    ///
+    /// Expressions using this method can only be evaluated as part of a
+    /// query; calling [`eval`](Expr::eval) on one directly will always
+    /// return a null value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use msi::{Expr, Select};
+    /// // Select the components that belong to at least one feature.
+    /// let query = Select::table("Component").with(
+    ///     Expr::col("Component").exists_in("FeatureComponents", "Component_"),
+    /// );
+    /// assert_eq!(
+    ///     query.to_string(),
+    ///     "SELECT * FROM Component WHERE Component IN \
+    ///      (SELECT Component_ FROM FeatureComponents)"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn exists_in<S, T>(self, table_name: S, column_name: T) -> Expr
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        Expr::subquery(self.ast, table_name.into(), column_name.into(), false)
+    }
+
+    /// Returns an expression that evaluates to true if the subexpression
+    /// evaluates to a value that does *not* appear in the given column of
+    /// the given table.  This is the negation of
+    /// [`exists_in`](Expr::exists_in), and the same restrictions apply; in
+    /// particular, a null value is never found in the column, so this
+    /// evaluates to true for it.
+    #[must_use]
+    pub fn not_exists_in<S, T>(self, table_name: S, column_name: T) -> Expr
+    where
+        S: Into<String>,
+        T: Into<String>,
+    {
+        Expr::subquery(self.ast, table_name.into(), column_name.into(), true)
+    }
+
+    fn subquery(
+        ast: Ast,
+        table_name: String,
+        column_name: String,
+        negated: bool,
+    ) -> Expr {
+        let subquery =
+            Subquery { table_name, column_name, negated, values: None };
+        Expr { ast: Ast::In(Box::new(ast), Box::new(subquery)) }
+    }
+
+    /// Loads the values of the columns referenced by any
+    /// [`exists_in`](Expr::exists_in) or
+    /// [`not_exists_in`](Expr::not_exists_in) subexpressions, using the
+    /// given function, so that the expression can be evaluated.
+    pub(crate) fn resolve_subqueries(
+        &mut self,
+        load: &mut dyn FnMut(&str, &str) -> io::Result<HashSet<Value>>,
+    ) -> io::Result<()> {
+        self.ast.resolve_subqueries(load)
+    }
+
     /// Evaluates the expression against the given row.  Any errors in the
     /// expression (such as dividing a number by zero, or applying a bitwise
     /// operator to a string) will result in a null value.
@@ -289,6 +374,16 @@ enum Ast {
     BinOp(BinOp, Box<Ast>, Box<Ast>),
     And(Box<Ast>, Box<Ast>),
     Or(Box<Ast>, Box<Ast>),
+    In(Box<Ast>, Box<Subquery>),
+}
+
+/// The right-hand side of an `IN` expression: a column of another table, and
+/// that column's values, once the enclosing query has loaded them.
+struct Subquery {
+    table_name: String,
+    column_name: String,
+    negated: bool,
+    values: Option<HashSet<Value>>,
 }
 
 impl Ast {
@@ -314,16 +409,52 @@ impl Ast {
                     Value::from_bool(arg2.eval(row).to_bool())
                 }
             }
+            Ast::In(ref arg, ref subquery) => match subquery.values {
+                Some(ref values) => {
+                    let value = arg.eval(row);
+                    let found = !value.is_null() && values.contains(&value);
+                    Value::from_bool(found != subquery.negated)
+                }
+                None => Value::Null,
+            },
         }
     }
 
+    fn resolve_subqueries(
+        &mut self,
+        load: &mut dyn FnMut(&str, &str) -> io::Result<HashSet<Value>>,
+    ) -> io::Result<()> {
+        match *self {
+            Ast::Literal(_) | Ast::Column(_) => {}
+            Ast::UnOp(_, ref mut arg) => arg.resolve_subqueries(load)?,
+            Ast::BinOp(_, ref mut arg1, ref mut arg2)
+            | Ast::And(ref mut arg1, ref mut arg2)
+            | Ast::Or(ref mut arg1, ref mut arg2) => {
+                arg1.resolve_subqueries(load)?;
+                arg2.resolve_subqueries(load)?;
+            }
+            Ast::In(ref mut arg, ref mut subquery) => {
+                arg.resolve_subqueries(load)?;
+                if subquery.values.is_none() {
+                    subquery.values = Some(load(
+                        &subquery.table_name,
+                        &subquery.column_name,
+                    )?);
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn populate_column_names<'a>(&'a self, names: &mut HashSet<&'a str>) {
         match *self {
             Ast::Literal(_) => {}
             Ast::Column(ref name) => {
                 names.insert(name.as_str());
             }
-            Ast::UnOp(_, ref arg) => arg.populate_column_names(names),
+            Ast::UnOp(_, ref arg) | Ast::In(ref arg, _) => {
+                arg.populate_column_names(names)
+            }
             Ast::BinOp(_, ref arg1, ref arg2)
             | Ast::And(ref arg1, ref arg2)
             | Ast::Or(ref arg1, ref arg2) => {
@@ -404,6 +535,25 @@ impl Ast {
                 }
                 Ok(())
             }
+            Ast::In(ref arg, ref subquery) => {
+                let op_prec = 3;
+                if op_prec < parent_prec {
+                    formatter.write_str("(")?;
+                }
+                arg.format_with_precedence(formatter, op_prec + 1)?;
+                if subquery.negated {
+                    formatter.write_str(" NOT")?;
+                }
+                write!(
+                    formatter,
+                    " IN (SELECT {} FROM {})",
+                    subquery.column_name, subquery.table_name
+                )?;
+                if op_prec < parent_prec {
+                    formatter.write_str(")")?;
+                }
+                Ok(())
+            }
         }
     }
 }
@@ -658,6 +808,16 @@ mod tests {
         let expr = Expr::col("Foo") - (Expr::col("Bar") - Expr::col("Baz"));
         assert_eq!(expr.to_string(), "Foo - (Bar - Baz)".to_string());
 
+        let expr = Expr::col("Foo")
+            .exists_in("Table", "Bar")
+            .and(Expr::col("Baz").not_exists_in("Table", "Quux"));
+        assert_eq!(
+            expr.to_string(),
+            "Foo IN (SELECT Bar FROM Table) AND \
+             Baz NOT IN (SELECT Quux FROM Table)"
+                .to_string()
+        );
+
         let expr = Expr::col("Foo").or(Expr::col("Bar")).or(Expr::col("Baz"));
         assert_eq!(expr.to_string(), "Foo OR Bar OR Baz".to_string());
 
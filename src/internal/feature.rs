@@ -0,0 +1,118 @@
+use crate::internal::error::Result;
+use std::collections::{HashMap, HashSet};
+
+// ========================================================================= //
+
+/// A feature from a package's `Feature` table, along with its child
+/// features, as returned by `Package::feature_tree`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureNode {
+    name: String,
+    title: Option<String>,
+    level: i32,
+    children: Vec<FeatureNode>,
+}
+
+impl FeatureNode {
+    /// Returns the feature's key in the `Feature` table.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the feature's user-visible title, if it has one.
+    #[must_use]
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the feature's install level.  A level of zero means the
+    /// feature is disabled by default.
+    #[must_use]
+    pub fn level(&self) -> i32 {
+        self.level
+    }
+
+    /// Returns the feature's child features, ordered by key.
+    #[must_use]
+    pub fn children(&self) -> &[FeatureNode] {
+        &self.children
+    }
+}
+
+// ========================================================================= //
+
+/// One row of the `Feature` table: the feature's key, its parent's key (if
+/// any), its title (if any), and its install level.
+pub(crate) type FeatureRow = (String, Option<String>, Option<String>, i32);
+
+/// Arranges rows of the `Feature` table into a forest of `FeatureNode`s, with
+/// the root features (those with no parent, or that are their own parent)
+/// at the top level.  Returns an error if a feature's parent doesn't exist,
+/// or if the parent links form a cycle.
+pub(crate) fn build_tree(rows: Vec<FeatureRow>) -> Result<Vec<FeatureNode>> {
+    let mut parents = HashMap::<String, Option<String>>::new();
+    for (name, parent, _, _) in &rows {
+        let parent = parent.clone().filter(|parent| parent != name);
+        parents.insert(name.clone(), parent);
+    }
+    for (name, _, _, _) in &rows {
+        let mut visited = HashSet::<&str>::new();
+        let mut current = (name.as_str(), parents[name].as_deref());
+        while let (child, Some(parent)) = current {
+            if !visited.insert(child) {
+                invalid_data!("Feature {:?} is its own ancestor", name);
+            }
+            match parents.get(parent) {
+                Some(grandparent) => {
+                    current = (parent, grandparent.as_deref());
+                }
+                None => invalid_data!(
+                    "Feature {:?} has nonexistent parent {:?}",
+                    child,
+                    parent
+                ),
+            }
+        }
+    }
+    let mut nodes = HashMap::<String, FeatureNode>::new();
+    let mut children = HashMap::<String, Vec<String>>::new();
+    let mut roots = Vec::<String>::new();
+    for (name, _, title, level) in rows {
+        match parents.remove(&name).unwrap() {
+            Some(parent) => {
+                children.entry(parent).or_default().push(name.clone());
+            }
+            None => roots.push(name.clone()),
+        }
+        let node = FeatureNode {
+            name: name.clone(),
+            title,
+            level,
+            children: Vec::new(),
+        };
+        nodes.insert(name, node);
+    }
+    roots.sort();
+    Ok(roots
+        .into_iter()
+        .map(|root| take_subtree(&root, &mut nodes, &mut children))
+        .collect())
+}
+
+fn take_subtree(
+    name: &str,
+    nodes: &mut HashMap<String, FeatureNode>,
+    children: &mut HashMap<String, Vec<String>>,
+) -> FeatureNode {
+    let mut node = nodes.remove(name).unwrap();
+    let mut child_names = children.remove(name).unwrap_or_default();
+    child_names.sort();
+    node.children = child_names
+        .iter()
+        .map(|child| take_subtree(child, nodes, children))
+        .collect();
+    node
+}
+
+// ========================================================================= //
@@ -0,0 +1,238 @@
+//! Utilities for the filenames stored in columns with the `Filename`
+//! category.
+//!
+//! Such a column holds either a short (8.3) filename, or a short filename and
+//! a long filename separated by a `|` character, such as
+//! `"MYDOCU~1.TXT|My Document.txt"`.
+
+use std::collections::HashSet;
+
+// ========================================================================= //
+
+/// Characters that may not appear in a long filename.
+const INVALID_LONG_FILENAME_CHARS: &[char] =
+    &['\\', '?', '|', '>', '<', ':', '/', '*', '"'];
+
+/// Characters (in addition to the above) that may not appear in a short
+/// filename.
+const INVALID_SHORT_FILENAME_CHARS: &[char] =
+    &['+', ',', ';', '=', '[', ']', ' '];
+
+/// The most `~N` suffixes to try before giving up on finding a unique short
+/// filename.
+const MAX_SHORT_FILENAME_SUFFIX: usize = 999_999;
+
+// ========================================================================= //
+
+/// Returns true if the string is a valid short (8.3) filename: a base name of
+/// one to eight characters, optionally followed by a period and an extension
+/// of at most three characters, with no spaces or other characters that
+/// short filenames forbid.
+///
+/// # Examples
+///
+/// ```
+/// use msi::filename::is_valid_short;
+/// assert!(is_valid_short("README.TXT"));
+/// assert!(is_valid_short("MYDOCU~1.TXT"));
+/// assert!(!is_valid_short("My Document.txt"));
+/// assert!(!is_valid_short("LONGFILENAME.TXT"));
+/// ```
+#[must_use]
+pub fn is_valid_short(name: &str) -> bool {
+    is_valid_short_filename(name, false)
+}
+
+/// Generates a short (8.3) filename for the given long filename, in the
+/// style Windows uses, that isn't already in the `existing` set.
+///
+/// If the long name is already a valid, all-ASCII short filename (ignoring
+/// case), its uppercase form is used as-is.  Otherwise, characters that aren't
+/// allowed in short filenames are dropped or replaced with underscores, and
+/// the base name is truncated and given a `~N` suffix, choosing the smallest
+/// `N` that makes the name unique.  The returned name is always uppercase, so
+/// the `existing` set should hold uppercase names (such as earlier results of
+/// this function).
+///
+/// # Panics
+///
+/// Panics if no unique name can be found (which requires `existing` to hold
+/// about a million names sharing the same prefix).
+///
+/// # Examples
+///
+/// ```
+/// use msi::filename::generate_short;
+/// use std::collections::HashSet;
+/// let mut existing = HashSet::new();
+/// let short = generate_short("My Document.txt", &existing);
+/// assert_eq!(short, "MYDOCU~1.TXT");
+/// existing.insert(short);
+/// assert_eq!(generate_short("My Documents.txt", &existing), "MYDOCU~2.TXT");
+/// ```
+#[must_use]
+pub fn generate_short(long: &str, existing: &HashSet<String>) -> String {
+    let uppercase = long.to_ascii_uppercase();
+    if uppercase.is_ascii()
+        && is_valid_short(&uppercase)
+        && !existing.contains(&uppercase)
+    {
+        return uppercase;
+    }
+    let trimmed = uppercase.trim_start_matches('.');
+    let (base, ext) = match trimmed.rsplit_once('.') {
+        Some((base, ext)) => (base, ext),
+        None => (trimmed, ""),
+    };
+    let mut base = short_filename_chars(base);
+    if base.is_empty() {
+        base.push('_');
+    }
+    let ext: String = short_filename_chars(ext).chars().take(3).collect();
+    for number in 1..=MAX_SHORT_FILENAME_SUFFIX {
+        let suffix = format!("~{number}");
+        let prefix: String =
+            base.chars().take(8 - suffix.len().min(7)).collect();
+        let mut candidate = prefix + &suffix;
+        if !ext.is_empty() {
+            candidate.push('.');
+            candidate.push_str(&ext);
+        }
+        if !existing.contains(&candidate) {
+            return candidate;
+        }
+    }
+    panic!("Could not generate a unique short filename for {long:?}");
+}
+
+/// Returns the characters of the string that can appear in a short filename,
+/// dropping spaces and periods and replacing other disallowed characters with
+/// underscores.
+fn short_filename_chars(string: &str) -> String {
+    string
+        .chars()
+        .filter(|&chr| chr != ' ' && chr != '.')
+        .map(|chr| {
+            if !chr.is_ascii()
+                || chr.is_ascii_control()
+                || INVALID_LONG_FILENAME_CHARS.contains(&chr)
+                || INVALID_SHORT_FILENAME_CHARS.contains(&chr)
+            {
+                '_'
+            } else {
+                chr
+            }
+        })
+        .collect()
+}
+
+// ========================================================================= //
+
+/// Returns true if the string is a valid value for a `Filename` column; that
+/// is, either a short (8.3) filename, or a short filename and a long filename
+/// separated by a `|` character.  If `wildcards` is true, the `?` and `*`
+/// characters are also permitted.
+pub(crate) fn is_valid_filename(string: &str, wildcards: bool) -> bool {
+    match string.split_once('|') {
+        Some((short, long)) => {
+            is_valid_short_filename(short, wildcards)
+                && is_valid_long_filename(long, wildcards)
+        }
+        None => is_valid_short_filename(string, wildcards),
+    }
+}
+
+fn is_valid_short_filename(name: &str, wildcards: bool) -> bool {
+    if name.contains(|chr| is_invalid_filename_char(chr, wildcards))
+        || name.contains(INVALID_SHORT_FILENAME_CHARS)
+    {
+        return false;
+    }
+    let (base, ext) = match name.split_once('.') {
+        Some((base, ext)) => (base, ext),
+        None => (name, ""),
+    };
+    !base.is_empty()
+        && base.chars().count() <= 8
+        && ext.chars().count() <= 3
+        && !ext.contains('.')
+}
+
+fn is_valid_long_filename(name: &str, wildcards: bool) -> bool {
+    !name.is_empty()
+        && name.chars().count() <= 255
+        && !name.contains(|chr| is_invalid_filename_char(chr, wildcards))
+}
+
+fn is_invalid_filename_char(chr: char, wildcards: bool) -> bool {
+    if wildcards && (chr == '?' || chr == '*') {
+        return false;
+    }
+    INVALID_LONG_FILENAME_CHARS.contains(&chr)
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_short, is_valid_short};
+    use std::collections::HashSet;
+
+    #[test]
+    fn valid_short_filenames() {
+        assert!(is_valid_short("README"));
+        assert!(is_valid_short("README.TXT"));
+        assert!(is_valid_short("A~1.B"));
+        assert!(!is_valid_short(""));
+        assert!(!is_valid_short(".TXT"));
+        assert!(!is_valid_short("README.TEXT"));
+        assert!(!is_valid_short("NINECHARS.TXT"));
+        assert!(!is_valid_short("A.B.C"));
+        assert!(!is_valid_short("A B.TXT"));
+        assert!(!is_valid_short("A+B.TXT"));
+        assert!(!is_valid_short("A*.TXT"));
+    }
+
+    #[test]
+    fn generate_short_filenames() {
+        let none = HashSet::new();
+        assert_eq!(generate_short("My Document.txt", &none), "MYDOCU~1.TXT");
+        assert_eq!(generate_short("readme.txt", &none), "README.TXT");
+        assert_eq!(generate_short("Makefile", &none), "MAKEFILE");
+        assert_eq!(generate_short("archive.tar.gz", &none), "ARCHIV~1.GZ");
+        assert_eq!(generate_short("page.html", &none), "PAGE~1.HTM");
+        assert_eq!(generate_short(".gitignore", &none), "GITIGN~1");
+        assert_eq!(generate_short("a+b=c.txt", &none), "A_B_C~1.TXT");
+        assert_eq!(generate_short("Café.txt", &none), "CAF_~1.TXT");
+        assert_eq!(generate_short("...", &none), "_~1");
+        for long in ["My Document.txt", "a+b=c.txt", "...", "Café.txt"] {
+            assert!(is_valid_short(&generate_short(long, &none)), "{long:?}");
+        }
+    }
+
+    #[test]
+    fn generated_short_filenames_are_unique() {
+        let mut existing: HashSet<String> =
+            ["README.TXT".to_string(), "MYDOCU~1.TXT".to_string()].into();
+        assert_eq!(generate_short("readme.txt", &existing), "README~1.TXT");
+        assert_eq!(
+            generate_short("My Document.txt", &existing),
+            "MYDOCU~2.TXT"
+        );
+        for number in 2..10 {
+            existing.insert(format!("MYDOCU~{number}.TXT"));
+        }
+        assert_eq!(
+            generate_short("My Document.txt", &existing),
+            "MYDOC~10.TXT"
+        );
+        let mut existing = HashSet::new();
+        for _ in 0..20 {
+            let short = generate_short("My Document.txt", &existing);
+            assert!(is_valid_short(&short));
+            assert!(existing.insert(short));
+        }
+    }
+}
+
+// ========================================================================= //
@@ -0,0 +1,93 @@
+// ========================================================================= //
+
+/// Expands a `Formatted` string, replacing each `[PropertyName]` reference
+/// with the value returned by `lookup` (or with the empty string, if `lookup`
+/// returns `None`).  References may be nested (e.g. `[[Foo]]` expands to the
+/// value of the property named by the value of `Foo`), and `[\x]` expands to
+/// the literal character `x`.
+///
+/// References that can only be resolved at install time (environment
+/// variables like `[%PATH]`, file and component paths like `[#File]`,
+/// `[!File]`, and `[$Component]`, and the `[~]` null character) are left in
+/// the output unchanged.  An unmatched `[` or `]` is also copied through
+/// literally.
+pub(crate) fn expand<L>(input: &str, lookup: L) -> String
+where
+    L: Fn(&str) -> Option<String>,
+{
+    // The bottom of the stack is the output; each other element holds the
+    // contents of a not-yet-closed `[` reference.
+    let mut stack: Vec<String> = vec![String::new()];
+    let mut chars = input.chars();
+    while let Some(chr) = chars.next() {
+        match chr {
+            '[' => {
+                let mut escape = chars.clone();
+                if let (Some('\\'), Some(literal), Some(']')) =
+                    (escape.next(), escape.next(), escape.next())
+                {
+                    stack.last_mut().unwrap().push(literal);
+                    chars = escape;
+                } else {
+                    stack.push(String::new());
+                }
+            }
+            ']' if stack.len() > 1 => {
+                let name = stack.pop().unwrap();
+                let expanded = resolve(&name, &lookup);
+                stack.last_mut().unwrap().push_str(&expanded);
+            }
+            _ => stack.last_mut().unwrap().push(chr),
+        }
+    }
+    // Copy any unclosed references through literally.
+    while stack.len() > 1 {
+        let unclosed = stack.pop().unwrap();
+        let output = stack.last_mut().unwrap();
+        output.push('[');
+        output.push_str(&unclosed);
+    }
+    stack.pop().unwrap()
+}
+
+fn resolve<L>(name: &str, lookup: &L) -> String
+where
+    L: Fn(&str) -> Option<String>,
+{
+    if name.starts_with(['%', '#', '!', '$', '~']) {
+        format!("[{name}]")
+    } else {
+        lookup(name).unwrap_or_default()
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "Foo" => Some("foo".to_string()),
+            "Bar" => Some("Foo".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expand_formatted_strings() {
+        assert_eq!(expand("", lookup), "");
+        assert_eq!(expand("no references", lookup), "no references");
+        assert_eq!(expand("[Foo] and [Foo]", lookup), "foo and foo");
+        assert_eq!(expand("x[Unknown]y", lookup), "xy");
+        assert_eq!(expand("[[Bar]]", lookup), "foo");
+        assert_eq!(expand("[\\[]Foo[\\]]", lookup), "[Foo]");
+        assert_eq!(expand("[%PATH];[#File]", lookup), "[%PATH];[#File]");
+        assert_eq!(expand("[!File][$Comp][~]", lookup), "[!File][$Comp][~]");
+        assert_eq!(expand("a]b", lookup), "a]b");
+        assert_eq!(expand("[Foo] [Foo", lookup), "foo [Foo");
+    }
+}
+
+// ========================================================================= //
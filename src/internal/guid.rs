@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+use uuid::Uuid;
+
+// ========================================================================= //
+
+/// A GUID, formatted the way MSI databases expect: hyphenated, uppercase,
+/// and enclosed in curly braces (as required by the `Guid` column category).
+///
+/// `Guid` values can be passed to `Value::from()` to produce a column value
+/// that can be stored in a column with the `Guid` category.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Guid {
+    uuid: Uuid,
+}
+
+impl Guid {
+    /// Generates a new random GUID.
+    #[must_use]
+    pub fn new_v4() -> Guid {
+        Guid { uuid: Uuid::new_v4() }
+    }
+
+    /// Generates a new random GUID, and returns it in braced, uppercase
+    /// form, ready to be used as (for example) a `ComponentId`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let guid = msi::Guid::new_v4_braced();
+    /// assert!(msi::Category::Guid.validate(&guid));
+    /// ```
+    #[must_use]
+    pub fn new_v4_braced() -> String {
+        Guid::new_v4().to_string()
+    }
+
+    /// Parses any common spelling of a UUID (with or without braces or
+    /// hyphens, in any case) and returns it in the braced, uppercase form
+    /// that MSI databases expect, or `None` if the string isn't a UUID.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(
+    ///     msi::Guid::normalize("34ab5c53-9b30-4e14-aef0-2c1c7ba826c0"),
+    ///     Some("{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}".to_string())
+    /// );
+    /// assert_eq!(msi::Guid::normalize("{34AB5C53-9B30}"), None);
+    /// ```
+    #[must_use]
+    pub fn normalize(string: &str) -> Option<String> {
+        string.parse::<Guid>().ok().map(|guid| guid.to_string())
+    }
+
+    /// Returns the GUID as a `Uuid`.
+    #[must_use]
+    pub fn uuid(&self) -> Uuid {
+        self.uuid
+    }
+}
+
+impl From<Uuid> for Guid {
+    fn from(uuid: Uuid) -> Guid {
+        Guid { uuid }
+    }
+}
+
+impl From<Guid> for Uuid {
+    fn from(guid: Guid) -> Uuid {
+        guid.uuid
+    }
+}
+
+impl fmt::Display for Guid {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut buffer = Uuid::encode_buffer();
+        let string = self.uuid.braced().encode_upper(&mut buffer);
+        formatter.write_str(string)
+    }
+}
+
+impl FromStr for Guid {
+    type Err = uuid::Error;
+
+    fn from_str(string: &str) -> Result<Guid, uuid::Error> {
+        Uuid::parse_str(string.trim()).map(Guid::from)
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::Guid;
+    use crate::internal::category::Category;
+
+    #[test]
+    fn normalize_guid_spellings() {
+        let canonical = "{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}";
+        for input in [
+            "{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}",
+            "{34ab5c53-9b30-4e14-aef0-2c1c7ba826c0}",
+            "34ab5c53-9b30-4e14-aef0-2c1c7ba826c0",
+            "34AB5C539B304E14AEF02C1C7BA826C0",
+            "urn:uuid:34ab5c53-9b30-4e14-aef0-2c1c7ba826c0",
+            " 34ab5c53-9b30-4e14-aef0-2c1c7ba826c0\n",
+        ] {
+            assert_eq!(
+                Guid::normalize(input).as_deref(),
+                Some(canonical),
+                "{input:?}"
+            );
+        }
+        assert!(Category::Guid.validate(canonical));
+    }
+
+    #[test]
+    fn normalize_invalid_guids() {
+        assert_eq!(Guid::normalize(""), None);
+        assert_eq!(Guid::normalize("{}"), None);
+        assert_eq!(
+            Guid::normalize("{HELLOWO-RLDH-ELLO-WORL-DHELLOWORLD0}"),
+            None
+        );
+        assert_eq!(
+            Guid::normalize("34ab5c53-9b30-4e14-aef0-2c1c7ba826"),
+            None
+        );
+    }
+
+    #[test]
+    fn new_guids_are_valid_and_distinct() {
+        let first = Guid::new_v4_braced();
+        let second = Guid::new_v4_braced();
+        assert!(Category::Guid.validate(&first));
+        assert!(Category::Guid.validate(&second));
+        assert_ne!(first, second);
+    }
+}
+
+// ========================================================================= //
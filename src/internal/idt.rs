@@ -0,0 +1,346 @@
+use crate::internal::category::Category;
+use crate::internal::codepage::CodePage;
+use crate::internal::column::{Column, ColumnType};
+use crate::internal::table::Table;
+use crate::internal::value::Value;
+use std::io::{self, Read, Write};
+
+// ========================================================================= //
+
+/// The pseudo-table name used in the header of an IDT file that sets the
+/// database codepage instead of holding table data.
+pub(crate) const FORCE_CODEPAGE_TABLE_NAME: &str = "_ForceCodepage";
+
+/// The contents of an IDT archive file (as produced by `msidb -e` or
+/// msitools' `msiexport`).
+pub(crate) struct IdtFile {
+    pub(crate) table_name: String,
+    pub(crate) codepage: Option<CodePage>,
+    pub(crate) columns: Vec<Column>,
+    pub(crate) rows: Vec<Vec<Value>>,
+}
+
+/// Parses an IDT file.  The file's first three lines hold the column names,
+/// the column type specifiers, and the table name followed by the names of
+/// the primary key columns (or, for a `_ForceCodepage` file, the codepage
+/// followed by `_ForceCodepage`).  Each following line holds one row, with
+/// tab-separated values.  Strings are decoded using `default_codepage`,
+/// unless the header specifies a codepage.
+pub(crate) fn parse<R: Read>(
+    mut reader: R,
+    default_codepage: CodePage,
+) -> io::Result<IdtFile> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let mut lines = data
+        .split(|&byte| byte == b'\n')
+        .map(|line| line.strip_suffix(b"\r").unwrap_or(line));
+    let mut header = Vec::with_capacity(3);
+    for _ in 0..3 {
+        match lines.next() {
+            Some(line) => {
+                header.push(CodePage::UsAscii.decode(line).into_owned())
+            }
+            None => invalid_data!("IDT file is missing its header lines"),
+        }
+    }
+    let table_fields: Vec<&str> = header[2].split('\t').collect();
+    if table_fields.len() == 2 && table_fields[1] == FORCE_CODEPAGE_TABLE_NAME
+    {
+        let codepage = match table_fields[0].parse().ok() {
+            Some(id) => match CodePage::from_id(id) {
+                Some(codepage) => codepage,
+                None => invalid_data!("Unsupported codepage: {}", id),
+            },
+            None => invalid_data!(
+                "Invalid codepage in IDT header: {:?}",
+                table_fields[0]
+            ),
+        };
+        return Ok(IdtFile {
+            table_name: FORCE_CODEPAGE_TABLE_NAME.to_string(),
+            codepage: Some(codepage),
+            columns: Vec::new(),
+            rows: Vec::new(),
+        });
+    }
+    let (table_name, key_names) = table_fields.split_first().unwrap();
+    if table_name.is_empty() {
+        invalid_data!("IDT header is missing the table name");
+    }
+    let column_names: Vec<&str> = header[0].split('\t').collect();
+    let type_specs: Vec<&str> = header[1].split('\t').collect();
+    if column_names.len() != type_specs.len() {
+        invalid_data!(
+            "IDT header has {} column names but {} column types",
+            column_names.len(),
+            type_specs.len()
+        );
+    }
+    for key_name in key_names {
+        if !column_names.contains(key_name) {
+            invalid_data!(
+                "IDT primary key {:?} is not a column of table {:?}",
+                key_name,
+                table_name
+            );
+        }
+    }
+    let mut columns = Vec::with_capacity(column_names.len());
+    for (&name, &spec) in column_names.iter().zip(type_specs.iter()) {
+        columns.push(parse_column(name, spec, key_names.contains(&name))?);
+    }
+    let mut rows = Vec::new();
+    for line in lines {
+        if line.is_empty() {
+            continue;
+        }
+        let line = default_codepage.decode(line);
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() != columns.len() {
+            invalid_data!(
+                "IDT row for table {:?} has {} fields, but the table has {} \
+                 columns",
+                table_name,
+                fields.len(),
+                columns.len()
+            );
+        }
+        let mut values = Vec::with_capacity(columns.len());
+        for (field, column) in fields.into_iter().zip(columns.iter()) {
+            values.push(parse_value(field, column)?);
+        }
+        rows.push(values);
+    }
+    Ok(IdtFile {
+        table_name: table_name.to_string(),
+        codepage: None,
+        columns,
+        rows,
+    })
+}
+
+/// Builds a column from an IDT type specifier, such as `s72` (a string of up
+/// to 72 characters), `L0` (a nullable, localizable string of any length),
+/// `i2` (a 16-bit integer), `I4` (a nullable 32-bit integer), or `v0` (a
+/// binary stream).  Uppercase letters indicate nullable columns.
+fn parse_column(name: &str, spec: &str, is_key: bool) -> io::Result<Column> {
+    let mut chars = spec.chars();
+    let code = match chars.next() {
+        Some(code) => code,
+        None => invalid_data!("Missing IDT type for column {:?}", name),
+    };
+    let size: usize = match chars.as_str().parse() {
+        Ok(size) => size,
+        Err(_) => {
+            invalid_data!("Invalid IDT type {:?} for column {:?}", spec, name)
+        }
+    };
+    let mut builder = Column::build(name);
+    if is_key {
+        builder = builder.primary_key();
+    }
+    if code.is_ascii_uppercase() {
+        builder = builder.nullable();
+    }
+    Ok(match (code.to_ascii_lowercase(), size) {
+        ('s', _) => builder.string(size),
+        ('l', _) => builder.localizable().string(size),
+        ('v', 0) => builder.binary(),
+        ('i', 2) => builder.int16(),
+        ('i', 4) => builder.int32(),
+        _ => invalid_data!(
+            "Unsupported IDT type {:?} for column {:?}",
+            spec,
+            name
+        ),
+    })
+}
+
+fn parse_value(field: &str, column: &Column) -> io::Result<Value> {
+    if field.is_empty() {
+        return Ok(Value::Null);
+    }
+    match column.coltype() {
+        ColumnType::Int16 | ColumnType::Int32 => match field.parse() {
+            Ok(number) => Ok(Value::Int(number)),
+            Err(_) => invalid_data!(
+                "Invalid integer {:?} for column {:?}",
+                field,
+                column.name()
+            ),
+        },
+        ColumnType::Str(_) => Ok(Value::Str(unescape(field))),
+    }
+}
+
+/// Reverses the substitutions that IDT files use for characters that would
+/// otherwise be interpreted as field or row separators: tab is stored as
+/// ASCII 21, carriage return as ASCII 17, and line feed as ASCII 25.
+fn unescape(field: &str) -> String {
+    field
+        .chars()
+        .map(|chr| match chr {
+            '\x15' => '\t',
+            '\x11' => '\r',
+            '\x19' => '\n',
+            _ => chr,
+        })
+        .collect()
+}
+
+/// Writes the header lines of an IDT file for the given table.
+pub(crate) fn write_header<W: Write>(
+    out: &mut W,
+    table: &Table,
+) -> io::Result<()> {
+    let names: Vec<&str> = table.columns().iter().map(Column::name).collect();
+    let specs: Vec<String> = table.columns().iter().map(column_spec).collect();
+    let mut table_line = vec![table.name()];
+    for column in table.columns() {
+        if column.is_primary_key() {
+            table_line.push(column.name());
+        }
+    }
+    write!(out, "{}\r\n", names.join("\t"))?;
+    write!(out, "{}\r\n", specs.join("\t"))?;
+    write!(out, "{}\r\n", table_line.join("\t"))?;
+    Ok(())
+}
+
+/// Writes one row of an IDT file, encoding strings with the given codepage.
+pub(crate) fn write_row<W: Write>(
+    out: &mut W,
+    values: &[Value],
+    codepage: CodePage,
+) -> io::Result<()> {
+    let fields: Vec<String> = values
+        .iter()
+        .map(|value| match *value {
+            Value::Null => String::new(),
+            Value::Int(number) => number.to_string(),
+            Value::Str(ref string) => escape(string),
+        })
+        .collect();
+    let mut line = fields.join("\t");
+    line.push_str("\r\n");
+    out.write_all(&codepage.encode(&line))
+}
+
+/// Returns the IDT type specifier for the given column (the inverse of
+/// `parse_column`).
+fn column_spec(column: &Column) -> String {
+    let spec = match column.coltype() {
+        ColumnType::Int16 => "i2".to_string(),
+        ColumnType::Int32 => "i4".to_string(),
+        ColumnType::Str(0) if column.category() == Some(Category::Binary) => {
+            "v0".to_string()
+        }
+        ColumnType::Str(max_len) if column.is_localizable() => {
+            format!("l{max_len}")
+        }
+        ColumnType::Str(max_len) => format!("s{max_len}"),
+    };
+    if column.is_nullable() {
+        spec.to_ascii_uppercase()
+    } else {
+        spec
+    }
+}
+
+/// Substitutes characters that IDT files can't store directly; see
+/// `unescape`.
+fn escape(field: &str) -> String {
+    field
+        .chars()
+        .map(|chr| match chr {
+            '\t' => '\x15',
+            '\r' => '\x11',
+            '\n' => '\x19',
+            _ => chr,
+        })
+        .collect()
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::{column_spec, escape, parse, unescape};
+    use crate::internal::codepage::CodePage;
+    use crate::internal::column::Column;
+    use crate::internal::column::ColumnType;
+    use crate::internal::value::Value;
+
+    #[test]
+    fn parse_column_types() {
+        let idt = "A\tB\tC\tD\tE\tF\r\n\
+                   s72\tS0\tl255\tI2\ti4\tV0\r\n\
+                   Foo\tA\tE\r\n";
+        let file = parse(idt.as_bytes(), CodePage::Utf8).unwrap();
+        assert_eq!(file.table_name, "Foo");
+        let columns = &file.columns;
+        assert_eq!(columns[0].coltype(), ColumnType::Str(72));
+        assert!(columns[0].is_primary_key() && !columns[0].is_nullable());
+        assert_eq!(columns[1].coltype(), ColumnType::Str(0));
+        assert!(columns[1].is_nullable() && !columns[1].is_localizable());
+        assert!(columns[2].is_localizable() && !columns[2].is_nullable());
+        assert_eq!(columns[3].coltype(), ColumnType::Int16);
+        assert!(columns[3].is_nullable());
+        assert_eq!(columns[4].coltype(), ColumnType::Int32);
+        assert!(columns[4].is_primary_key());
+        assert!(columns[5].is_nullable());
+        assert!(file.rows.is_empty());
+    }
+
+    #[test]
+    fn parse_force_codepage() {
+        let idt = "\r\n\r\n1252\t_ForceCodepage\r\n";
+        let file = parse(idt.as_bytes(), CodePage::Utf8).unwrap();
+        assert_eq!(file.codepage, Some(CodePage::Windows1252));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("A\ns72\n".as_bytes(), CodePage::Utf8).is_err());
+        assert!(
+            parse("A\tB\ns72\nFoo\tA\n".as_bytes(), CodePage::Utf8).is_err()
+        );
+        assert!(parse("A\nx72\nFoo\tA\n".as_bytes(), CodePage::Utf8).is_err());
+        assert!(parse("A\ni3\nFoo\tA\n".as_bytes(), CodePage::Utf8).is_err());
+        assert!(parse("A\ns72\nFoo\tB\n".as_bytes(), CodePage::Utf8).is_err());
+        assert!(
+            parse("A\ni2\nFoo\tA\nbar\n".as_bytes(), CodePage::Utf8).is_err()
+        );
+        assert_eq!(
+            parse("A\ni2\nFoo\tA\n-5\n\n".as_bytes(), CodePage::Utf8)
+                .unwrap()
+                .rows,
+            vec![vec![Value::Int(-5)]]
+        );
+    }
+
+    #[test]
+    fn column_specs() {
+        assert_eq!(column_spec(&Column::build("A").id_string(72)), "s72");
+        assert_eq!(
+            column_spec(&Column::build("A").nullable().string(0)),
+            "S0"
+        );
+        assert_eq!(
+            column_spec(&Column::build("A").localizable().string(255)),
+            "l255"
+        );
+        assert_eq!(column_spec(&Column::build("A").int16()), "i2");
+        assert_eq!(column_spec(&Column::build("A").nullable().int32()), "I4");
+        assert_eq!(column_spec(&Column::build("A").nullable().binary()), "V0");
+    }
+
+    #[test]
+    fn escape_special_chars() {
+        assert_eq!(unescape("a\x15b\x11\x19c"), "a\tb\r\nc");
+        assert_eq!(escape("a\tb\r\nc"), "a\x15b\x11\x19c");
+    }
+}
+
+// ========================================================================= //
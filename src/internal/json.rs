@@ -0,0 +1,108 @@
+use crate::internal::column::Column;
+use crate::internal::error::Result;
+use crate::internal::package::Package;
+use crate::internal::query::Select;
+use crate::internal::summary::SummaryInfo;
+use serde_json::{json, Map, Value as JsonValue};
+use std::io::{Read, Seek};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ========================================================================= //
+
+impl<F: Read + Seek> Package<F> {
+    /// Serializes the entire package as JSON (requires the `serde` feature).
+    ///
+    /// The result is an object with three fields:
+    ///
+    /// * `tables`: an object mapping each table name to an object with a
+    ///   `columns` array (describing the table's schema) and a `rows` array
+    ///   (each row being an array of values).
+    /// * `streams`: an array of objects giving the `name` and `length` of each
+    ///   embedded binary stream.  The stream data itself is not included.
+    /// * `summary_info`: an object holding the summary information
+    ///   properties.  Timestamps are given in seconds since the Unix epoch.
+    pub fn to_json(&mut self) -> Result<JsonValue> {
+        let table_names: Vec<String> =
+            self.tables().map(|table| table.name().to_string()).collect();
+        let mut tables = Map::new();
+        for table_name in table_names {
+            let columns: Vec<JsonValue> = self
+                .get_table(&table_name)
+                .unwrap()
+                .columns()
+                .iter()
+                .map(column_to_json)
+                .collect();
+            let rows: Vec<JsonValue> = self
+                .select_rows(Select::table(table_name.as_str()))?
+                .map(|row| {
+                    (0..row.len())
+                        .map(|index| json!(row[index]))
+                        .collect::<JsonValue>()
+                })
+                .collect();
+            tables.insert(
+                table_name,
+                json!({ "columns": columns, "rows": rows }),
+            );
+        }
+        let stream_names: Vec<String> = self.streams().collect();
+        let mut streams = Vec::with_capacity(stream_names.len());
+        for name in stream_names {
+            let length = self.stream_len(&name)?;
+            streams.push(json!({ "name": name, "length": length }));
+        }
+        Ok(json!({
+            "tables": tables,
+            "streams": streams,
+            "summary_info": summary_info_to_json(self.summary_info()),
+        }))
+    }
+}
+
+fn column_to_json(column: &Column) -> JsonValue {
+    json!({
+        "name": column.name(),
+        "type": column.coltype().to_string(),
+        "nullable": column.is_nullable(),
+        "localizable": column.is_localizable(),
+        "primary_key": column.is_primary_key(),
+        "category": column.category().map(|category| category.to_string()),
+        "range": column.value_range().map(|(min, max)| [min, max]),
+        "enum_values": column.enum_values(),
+    })
+}
+
+fn summary_info_to_json(summary_info: &SummaryInfo) -> JsonValue {
+    fn timestamp(time: Option<SystemTime>) -> Option<u64> {
+        time.and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+    }
+    let languages: Vec<String> = summary_info
+        .languages()
+        .iter()
+        .map(|lang| lang.tag().to_string())
+        .collect();
+    json!({
+        "arch": summary_info.arch(),
+        "author": summary_info.author(),
+        "character_count": summary_info.character_count(),
+        "codepage": summary_info.codepage().id(),
+        "comments": summary_info.comments(),
+        "creating_application": summary_info.creating_application(),
+        "creation_time": timestamp(summary_info.creation_time()),
+        "doc_security": summary_info.doc_security(),
+        "keywords": summary_info.keywords(),
+        "languages": languages,
+        "last_printed": timestamp(summary_info.last_printed()),
+        "last_saved_by": summary_info.last_saved_by(),
+        "last_saved_time": timestamp(summary_info.last_saved_time()),
+        "page_count": summary_info.page_count(),
+        "subject": summary_info.subject(),
+        "title": summary_info.title(),
+        "uuid": summary_info.uuid().map(|uuid| uuid.hyphenated().to_string()),
+        "word_count": summary_info.word_count(),
+    })
+}
+
+// ========================================================================= //
@@ -39,7 +39,9 @@ impl Language {
         Language { code }
     }
 
-    /// Returns a `Language` value for the given RFC 5646 language tag.
+    /// Returns a `Language` value for the given RFC 5646 language tag.  Tags
+    /// are matched case-insensitively.  A tag whose primary language is not
+    /// recognized yields the language-neutral `Language` (code 0).
     ///
     /// # Examples
     ///
@@ -47,15 +49,17 @@ impl Language {
     /// assert_eq!(msi::Language::from_tag("en").tag(), "en");
     /// assert_eq!(msi::Language::from_tag("en-US").tag(), "en-US");
     /// assert_eq!(msi::Language::from_tag("fr-CA").tag(), "fr-CA");
+    /// assert_eq!(msi::Language::from_tag("zh-cn").tag(), "zh-CN");
+    /// assert_eq!(msi::Language::from_tag("xx-YY").code(), 0);
     /// ```
     #[must_use]
     pub fn from_tag(tag: &str) -> Language {
         let parts: Vec<&str> = tag.splitn(2, '-').collect();
         for &(lang_code, lang_tag, sublangs) in LANGUAGES {
-            if lang_tag == parts[0] {
+            if lang_tag.eq_ignore_ascii_case(parts[0]) {
                 if parts.len() > 1 {
                     for &(sublang_code, sublang_tag) in sublangs {
-                        if sublang_tag == tag {
+                        if sublang_tag.eq_ignore_ascii_case(tag) {
                             return Language::new(lang_code, sublang_code);
                         }
                     }
@@ -85,6 +89,20 @@ impl Language {
         self.code
     }
 
+    /// Returns the primary language identifier for this language (the low 10
+    /// bits of the language code), without any sublanguage.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(msi::Language::from_tag("en-US").primary(), 9);
+    /// assert_eq!(msi::Language::from_tag("fr-CA").primary(), 12);
+    /// ```
+    #[must_use]
+    pub fn primary(&self) -> u16 {
+        self.code & LANG_MASK
+    }
+
     /// Returns the RFC 5646 language tag for this language.  Returns "und"
     /// (the language tag for "undetermined") if the `Language` value is not
     /// recognized.
@@ -99,7 +117,7 @@ impl Language {
     /// ```
     #[must_use]
     pub fn tag(&self) -> &str {
-        let lang_code = self.code & LANG_MASK;
+        let lang_code = self.primary();
         match LANGUAGES.binary_search_by_key(&lang_code, |t| t.0) {
             Ok(index) => {
                 let (_, lang_tag, sublangs) = LANGUAGES[index];
@@ -117,7 +135,8 @@ impl Language {
 // ========================================================================= //
 
 type SubLanguage = (u16, &'static str);
-// TODO: This table is incomplete.
+// TODO: This table is still missing languages whose sublanguages use different
+// primary tags (e.g. Croatian/Serbian/Bosnian and the Sami languages).
 const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
     (
         0x01,
@@ -142,8 +161,18 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
         ],
     ),
     (0x02, "bg", &[(0x01, "bg-BG")]),
-    (0x03, "ca", &[(0x01, "ca-ES")]),
-    (0x04, "zh", &[(0x03, "zh-HK"), (0x04, "zh-SG"), (0x05, "zh-MO")]),
+    (0x03, "ca", &[(0x01, "ca-ES"), (0x02, "ca-ES-valencia")]),
+    (
+        0x04,
+        "zh",
+        &[
+            (0x01, "zh-TW"),
+            (0x02, "zh-CN"),
+            (0x03, "zh-HK"),
+            (0x04, "zh-SG"),
+            (0x05, "zh-MO"),
+        ],
+    ),
     (0x05, "cs", &[(0x01, "cs-CZ")]),
     (0x06, "da", &[(0x01, "da-DK")]),
     (
@@ -175,9 +204,11 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
             (0x0b, "en-TT"),
             (0x0c, "en-ZW"),
             (0x0d, "en-PH"),
+            (0x0f, "en-HK"),
             (0x10, "en-IN"),
             (0x11, "en-MY"),
             (0x12, "en-SG"),
+            (0x13, "en-AE"),
         ],
     ),
     (
@@ -204,6 +235,8 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
             (0x13, "es-NI"),
             (0x14, "es-PR"),
             (0x15, "es-US"),
+            (0x16, "es-419"),
+            (0x17, "es-CU"),
         ],
     ),
     (0x0b, "fi", &[(0x01, "fi-FI")]),
@@ -217,6 +250,14 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
             (0x04, "fr-CH"),
             (0x05, "fr-LU"),
             (0x06, "fr-MC"),
+            (0x08, "fr-RE"),
+            (0x09, "fr-CD"),
+            (0x0a, "fr-SN"),
+            (0x0b, "fr-CM"),
+            (0x0c, "fr-CI"),
+            (0x0d, "fr-ML"),
+            (0x0e, "fr-MA"),
+            (0x0f, "fr-HT"),
         ],
     ),
     (0x0d, "he", &[(0x01, "he-IL")]),
@@ -230,15 +271,15 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
     (0x15, "pl", &[(0x01, "pl-PL")]),
     (0x16, "pt", &[(0x01, "pt-BR"), (0x02, "pt-PT")]),
     (0x17, "rm", &[(0x01, "rm-CH")]),
-    (0x18, "ro", &[(0x01, "ro-RO")]),
-    (0x19, "ru", &[(0x01, "ru-RU")]),
+    (0x18, "ro", &[(0x01, "ro-RO"), (0x02, "ro-MD")]),
+    (0x19, "ru", &[(0x01, "ru-RU"), (0x02, "ru-MD")]),
     (0x1a, "bs", &[(0x05, "bs-BA")]),
     (0x1b, "sk", &[(0x01, "sk-SK")]),
     (0x1c, "sq", &[(0x01, "sq-AL")]),
     (0x1d, "sv", &[(0x01, "sv-SE"), (0x02, "sv-FI")]),
     (0x1e, "th", &[(0x01, "th-TH")]),
     (0x1f, "tr", &[(0x01, "tr-TR")]),
-    (0x20, "ur", &[(0x01, "ur-PK")]),
+    (0x20, "ur", &[(0x01, "ur-PK"), (0x02, "ur-IN")]),
     (0x21, "id", &[(0x01, "id-ID")]),
     (0x22, "uk", &[(0x01, "uk-UA")]),
     (0x23, "be", &[(0x01, "be-BY")]),
@@ -252,8 +293,12 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
     (0x2b, "hy", &[(0x01, "hy-AM")]),
     (0x2c, "az", &[(0x01, "az-AZ")]),
     (0x2d, "eu", &[(0x01, "eu-ES")]),
+    (0x2e, "hsb", &[(0x01, "hsb-DE")]),
     (0x2f, "mk", &[(0x01, "mk-MK")]),
+    (0x30, "st", &[(0x01, "st-ZA")]),
+    (0x31, "ts", &[(0x01, "ts-ZA")]),
     (0x32, "tn", &[(0x01, "tn-ZA"), (0x02, "tn-BW")]),
+    (0x33, "ve", &[(0x01, "ve-ZA")]),
     (0x34, "xh", &[(0x01, "xh-ZA")]),
     (0x35, "zu", &[(0x01, "zu-ZA")]),
     (0x36, "af", &[(0x01, "af-ZA")]),
@@ -281,17 +326,20 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
     (0x4d, "as", &[(0x01, "as-IN")]),
     (0x4e, "mr", &[(0x01, "mr-IN")]),
     (0x4f, "sa", &[(0x01, "sa-IN")]),
-    (0x50, "mn", &[(0x01, "mn-MN")]),
+    (0x50, "mn", &[(0x01, "mn-MN"), (0x02, "mn-Mong-CN")]),
     (0x51, "bo", &[(0x01, "bo-CN")]),
     (0x52, "cy", &[(0x01, "cy-GB")]),
-    (0x53, "kh", &[(0x01, "kh-KH")]),
+    (0x53, "km", &[(0x01, "km-KH")]),
     (0x54, "lo", &[(0x01, "lo-LA")]),
+    (0x55, "my", &[(0x01, "my-MM")]),
     (0x56, "gl", &[(0x01, "gl-ES")]),
     (0x57, "kok", &[(0x01, "kok-IN")]),
+    (0x58, "mni", &[(0x01, "mni-IN")]),
+    (0x59, "sd", &[(0x02, "sd-Arab-PK")]),
     (0x5a, "syr", &[(0x01, "syr-SY")]),
     (0x5b, "si", &[(0x01, "si-LK")]),
-    (0x5c, "chr", &[(0x01, "chr-CHR")]),
-    (0x5d, "iu", &[(0x01, "iu-CA")]),
+    (0x5c, "chr", &[(0x01, "chr-Cher-US")]),
+    (0x5d, "iu", &[(0x01, "iu-CA"), (0x02, "iu-Latn-CA")]),
     (0x5e, "am", &[(0x01, "am-ET")]),
     (0x5f, "tzm", &[(0x02, "tzm-DZ")]),
     (0x61, "ne", &[(0x01, "ne-NP"), (0x02, "ne-IN")]),
@@ -299,16 +347,20 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
     (0x63, "ps", &[(0x01, "ps-AF")]),
     (0x64, "tl", &[(0x01, "tl-PH")]),
     (0x65, "dv", &[(0x01, "dv-MV")]),
-    (0x67, "ff", &[(0x02, "ff-SN")]),
+    (0x67, "ff", &[(0x01, "ff-NG"), (0x02, "ff-SN")]),
     (0x68, "ha", &[(0x01, "ha-NG")]),
+    (0x6a, "yo", &[(0x01, "yo-NG")]),
     (0x6b, "qu", &[(0x01, "qu-BO"), (0x02, "qu-EC"), (0x03, "qu-PE")]),
     (0x6c, "nso", &[(0x01, "nso-ZA")]),
     (0x6d, "ba", &[(0x01, "ba-RU")]),
     (0x6e, "lb", &[(0x01, "lb-LU")]),
     (0x6f, "kl", &[(0x01, "kl-GL")]),
     (0x70, "ig", &[(0x01, "ig-NG")]),
+    (0x72, "om", &[(0x01, "om-ET")]),
     (0x73, "ti", &[(0x01, "ti-ET"), (0x02, "ti-ER")]),
+    (0x74, "gn", &[(0x01, "gn-PY")]),
     (0x75, "haw", &[(0x01, "haw-US")]),
+    (0x77, "so", &[(0x01, "so-SO")]),
     (0x78, "ii", &[(0x01, "ii-CN")]),
     (0x7a, "arn", &[(0x01, "arn-CL")]),
     (0x7c, "moh", &[(0x01, "moh-CA")]),
@@ -323,6 +375,7 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
     (0x87, "rw", &[(0x01, "rw-RW")]),
     (0x88, "wo", &[(0x01, "wo-SN")]),
     (0x8c, "prs", &[(0x01, "prs-AF")]),
+    (0x91, "gd", &[(0x01, "gd-GB")]),
     (0x92, "ku", &[(0x01, "ku-IQ")]),
 ];
 
@@ -330,9 +383,60 @@ const LANGUAGES: &[(u16, &str, &[SubLanguage])] = &[
 
 #[cfg(test)]
 mod tests {
-    use super::LANGUAGES;
+    use super::{Language, LANGUAGES, SUBLANG_SHIFT};
     use std::collections::HashSet;
 
+    #[test]
+    fn tags_round_trip() {
+        for &(lang_code, lang_tag, sublangs) in LANGUAGES {
+            let language = Language::from_tag(lang_tag);
+            assert_eq!(language.code(), lang_code);
+            assert_eq!(language.primary(), lang_code);
+            assert_eq!(Language::from_code(language.code()).tag(), lang_tag);
+            for &(sublang_code, sublang_tag) in sublangs {
+                let language = Language::from_tag(sublang_tag);
+                let code = lang_code | (sublang_code << SUBLANG_SHIFT);
+                assert_eq!(language.code(), code);
+                assert_eq!(language.primary(), lang_code);
+                assert_eq!(Language::from_code(code).tag(), sublang_tag);
+            }
+        }
+    }
+
+    #[test]
+    fn well_known_codes() {
+        let expected = [
+            ("en-US", 0x0409),
+            ("en-GB", 0x0809),
+            ("de-DE", 0x0407),
+            ("fr-FR", 0x040c),
+            ("es-ES", 0x040a),
+            ("it-IT", 0x0410),
+            ("ja-JP", 0x0411),
+            ("ko-KR", 0x0412),
+            ("zh-TW", 0x0404),
+            ("zh-CN", 0x0804),
+            ("pt-BR", 0x0416),
+            ("ru-RU", 0x0419),
+            ("km-KH", 0x0453),
+            ("gd-GB", 0x0491),
+        ];
+        for (tag, code) in expected {
+            assert_eq!(Language::from_tag(tag).code(), code, "{tag}");
+            assert_eq!(Language::from_code(code).tag(), tag);
+        }
+    }
+
+    #[test]
+    fn unknown_tags() {
+        assert_eq!(Language::from_tag("").code(), 0);
+        assert_eq!(Language::from_tag("xx").code(), 0);
+        assert_eq!(Language::from_tag("xx-YY").code(), 0);
+        assert_eq!(Language::from_tag("-").code(), 0);
+        assert_eq!(Language::from_tag("en-XX").primary(), 9);
+        assert_eq!(Language::from_code(0xffff).tag(), "und");
+    }
+
     #[test]
     fn lang_codes_are_unique() {
         let mut codes = HashSet::<u16>::new();
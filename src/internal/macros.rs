@@ -1,45 +1,81 @@
 // ========================================================================= //
 
+// Each of these macros returns early with an `Error` of the corresponding
+// variant, converted with `into()` so that they can be used in functions
+// returning either `io::Result` or `msi::Result`.
+
 macro_rules! already_exists {
     ($e:expr) => {
-        return Err(::std::io::Error::new(::std::io::ErrorKind::AlreadyExists,
-                                         $e))
+        return Err($crate::internal::error::Error::AlreadyExists(
+            ::std::string::String::from($e),
+        )
+        .into())
     };
     ($fmt:expr, $($arg:tt)+) => {
-        return Err(::std::io::Error::new(::std::io::ErrorKind::AlreadyExists,
-                                         format!($fmt, $($arg)+)))
+        return Err($crate::internal::error::Error::AlreadyExists(
+            format!($fmt, $($arg)+),
+        )
+        .into())
     };
 }
 
 macro_rules! invalid_data {
     ($e:expr) => {
-        return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidData,
-                                         $e))
+        return Err($crate::internal::error::Error::Corrupt(
+            ::std::string::String::from($e),
+        )
+        .into())
     };
     ($fmt:expr, $($arg:tt)+) => {
-        return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidData,
-                                         format!($fmt, $($arg)+)))
+        return Err($crate::internal::error::Error::Corrupt(
+            format!($fmt, $($arg)+),
+        )
+        .into())
     };
 }
 
 macro_rules! invalid_input {
     ($e:expr) => {
-        return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput,
-                                         $e))
+        return Err($crate::internal::error::Error::Validation(
+            ::std::string::String::from($e),
+        )
+        .into())
     };
     ($fmt:expr, $($arg:tt)+) => {
-        return Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput,
-                                         format!($fmt, $($arg)+)))
+        return Err($crate::internal::error::Error::Validation(
+            format!($fmt, $($arg)+),
+        )
+        .into())
     };
 }
 
 macro_rules! not_found {
     ($e:expr) => {
-        return Err(::std::io::Error::new(::std::io::ErrorKind::NotFound, $e))
+        return Err($crate::internal::error::Error::NotFound(
+            ::std::string::String::from($e),
+        )
+        .into())
+    };
+    ($fmt:expr, $($arg:tt)+) => {
+        return Err($crate::internal::error::Error::NotFound(
+            format!($fmt, $($arg)+),
+        )
+        .into())
+    };
+}
+
+macro_rules! schema_mismatch {
+    ($e:expr) => {
+        return Err($crate::internal::error::Error::SchemaMismatch(
+            ::std::string::String::from($e),
+        )
+        .into())
     };
     ($fmt:expr, $($arg:tt)+) => {
-        return Err(::std::io::Error::new(::std::io::ErrorKind::NotFound,
-                                         format!($fmt, $($arg)+)))
+        return Err($crate::internal::error::Error::SchemaMismatch(
+            format!($fmt, $($arg)+),
+        )
+        .into())
     };
 }
 
@@ -0,0 +1,273 @@
+use crate::internal::column::Column;
+use crate::internal::diff::same_columns;
+use crate::internal::error::Result;
+use crate::internal::expr::Expr;
+use crate::internal::package::{
+    Package, COMPONENT_TABLE_NAME, DIRECTORY_TABLE_NAME,
+    FEATURE_COMPONENTS_TABLE_NAME, FEATURE_TABLE_NAME, FILE_TABLE_NAME,
+};
+use crate::internal::query::{Agg, Insert, Select};
+use crate::internal::table::Table;
+use crate::internal::value::Value;
+use std::io::{Read, Seek, Write};
+
+// ========================================================================= //
+
+const MODULE_SIGNATURE_TABLE_NAME: &str = "ModuleSignature";
+
+/// The key of the root directory of a merge module's directory tree.
+const TARGETDIR: &str = "TARGETDIR";
+
+/// How the values in a column of a merged table refer to other rows.
+#[derive(Clone, Copy)]
+enum KeyKind {
+    /// A key of a row from the merge module.
+    Key,
+    /// A key of a row in the module's `Directory` table, which may be the
+    /// module's root directory.
+    Directory,
+}
+
+/// The tables copied from a merge module, in the order they're merged, and
+/// the columns of each that hold keys of rows from the module.
+const MERGED_TABLES: &[(&str, &[(&str, KeyKind)])] = &[
+    (
+        DIRECTORY_TABLE_NAME,
+        &[
+            ("Directory", KeyKind::Directory),
+            ("Directory_Parent", KeyKind::Directory),
+        ],
+    ),
+    (
+        COMPONENT_TABLE_NAME,
+        &[
+            ("Component", KeyKind::Key),
+            ("Directory_", KeyKind::Directory),
+            ("KeyPath", KeyKind::Key),
+        ],
+    ),
+    (FILE_TABLE_NAME, &[("File", KeyKind::Key), ("Component_", KeyKind::Key)]),
+];
+
+// ========================================================================= //
+
+impl<F: Read + Write + Seek> Package<F> {
+    /// Merges a merge module (.msm) into this package, installing the
+    /// module's components as part of the given feature, with the module's
+    /// root directory (`TARGETDIR`) redirected to the given directory of
+    /// this package.
+    ///
+    /// The module's `Directory`, `Component`, and `File` tables are copied
+    /// into this package (creating them if necessary), and a
+    /// `FeatureComponents` row is added for each of the module's components.
+    /// Keys of rows from the module are given the module's GUID as a suffix
+    /// (e.g. `Comp.0A3C2E1F_...`), as merge module databases require, unless
+    /// they already have it.  File sequence numbers are offset to follow
+    /// those of the files already in this package.  The module's embedded
+    /// streams (such as `MergeModule.CABinet`) are copied under the same
+    /// names; other tables, and `Media` entries for the module's files, are
+    /// not yet merged.
+    ///
+    /// Returns an error without modifying this package if `msm` isn't a
+    /// merge module, if the feature or directory doesn't exist, if a merged
+    /// table's columns differ from this package's, or if a row or stream
+    /// from the module already exists in this package.
+    pub fn merge_module<R: Read + Seek>(
+        &mut self,
+        msm: R,
+        feature: &str,
+        redirect_dir: &str,
+    ) -> Result<()> {
+        let mut module = Package::open(msm)?;
+        let suffix = module_suffix(&mut module)?;
+        let condition = Expr::col("Feature").eq(Expr::string(feature));
+        if self.count_rows(FEATURE_TABLE_NAME, Some(condition))? == 0 {
+            not_found!("Feature {:?} does not exist", feature);
+        }
+        if !self.has_table(DIRECTORY_TABLE_NAME)
+            || self
+                .get_row(DIRECTORY_TABLE_NAME, &[Value::from(redirect_dir)])?
+                .is_none()
+        {
+            not_found!("Directory {:?} does not exist", redirect_dir);
+        }
+        let keys = KeyMap { suffix, redirect_dir };
+
+        // Read and rekey the module's rows, checking that they fit into this
+        // package before changing anything.
+        let mut merged = Vec::<(Table, Vec<Vec<Value>>)>::new();
+        for &(table_name, key_columns) in MERGED_TABLES {
+            let table = match module.get_table(table_name) {
+                Some(table) => table.clone(),
+                None => continue,
+            };
+            if let Some(existing) = self.get_table(table_name) {
+                if !same_columns(existing, &table) {
+                    schema_mismatch!(
+                        "Table {:?} has different columns in the merge module",
+                        table_name
+                    );
+                }
+            }
+            let mut rows = Vec::new();
+            for row in module.select_rows(Select::table(table_name))? {
+                let mut values: Vec<Value> =
+                    (0..row.len()).map(|index| row[index].clone()).collect();
+                for &(column_name, kind) in key_columns {
+                    if let Some(index) =
+                        table.index_for_column_name(column_name)
+                    {
+                        values[index] = keys.map(&values[index], kind);
+                    }
+                }
+                rows.push(values);
+            }
+            if let Some(index) = table.index_for_column_name("Directory") {
+                // The module's root directory is replaced by redirect_dir.
+                rows.retain(|values| {
+                    values[index].as_str() != Some(redirect_dir)
+                });
+            }
+            merged.push((table, rows));
+        }
+        for (table, rows) in &merged {
+            if !self.has_table(table.name()) {
+                continue;
+            }
+            let key_indices = table.primary_key_indices();
+            for values in rows {
+                let key: Vec<Value> =
+                    key_indices.iter().map(|&i| values[i].clone()).collect();
+                if self.get_row(table.name(), &key)?.is_some() {
+                    already_exists!(
+                        "Table {:?} already contains a row with key {:?}",
+                        table.name(),
+                        key
+                    );
+                }
+            }
+        }
+        let stream_names: Vec<String> = module.streams().collect();
+        for stream_name in &stream_names {
+            if self.has_stream(stream_name) {
+                already_exists!("Stream {:?} already exists", stream_name);
+            }
+        }
+
+        // Renumber the module's files to follow this package's files.
+        let sequence_offset = if self.has_table(FILE_TABLE_NAME) {
+            match self.aggregate(FILE_TABLE_NAME, "Sequence", Agg::Max)? {
+                Value::Int(max) => max,
+                _ => 0,
+            }
+        } else {
+            0
+        };
+        let mut components = Vec::<Value>::new();
+        for (table, rows) in &mut merged {
+            if table.name() == FILE_TABLE_NAME {
+                if let Some(index) = table.index_for_column_name("Sequence") {
+                    for values in rows.iter_mut() {
+                        if let Value::Int(sequence) = values[index] {
+                            values[index] =
+                                Value::Int(sequence + sequence_offset);
+                        }
+                    }
+                }
+            } else if table.name() == COMPONENT_TABLE_NAME {
+                if let Some(index) = table.index_for_column_name("Component") {
+                    components = rows
+                        .iter()
+                        .map(|values| values[index].clone())
+                        .collect();
+                }
+            }
+        }
+
+        // Copy everything into this package.
+        for (table, rows) in merged {
+            if !self.has_table(table.name()) {
+                self.create_table(table.name(), table.columns().to_vec())?;
+            }
+            if !rows.is_empty() {
+                self.insert_rows(Insert::into(table.name()).rows(rows))?;
+            }
+        }
+        if !components.is_empty() {
+            if !self.has_table(FEATURE_COMPONENTS_TABLE_NAME) {
+                let columns = vec![
+                    Column::build("Feature_").primary_key().id_string(38),
+                    Column::build("Component_").primary_key().id_string(72),
+                ];
+                self.create_table(FEATURE_COMPONENTS_TABLE_NAME, columns)?;
+            }
+            let rows = components
+                .into_iter()
+                .map(|component| vec![Value::from(feature), component])
+                .collect();
+            self.insert_rows(
+                Insert::into(FEATURE_COMPONENTS_TABLE_NAME).rows(rows),
+            )?;
+        }
+        for stream_name in stream_names {
+            self.copy_stream_from(&mut module, &stream_name)?;
+        }
+        Ok(())
+    }
+}
+
+// ========================================================================= //
+
+/// Returns the GUID part of a merge module's `ModuleID` (the part after the
+/// last period, with underscores in place of hyphens), which is used as the
+/// suffix of the module's keys.
+fn module_suffix<R: Read + Seek>(module: &mut Package<R>) -> Result<String> {
+    if !module.has_table(MODULE_SIGNATURE_TABLE_NAME) {
+        invalid_input!(
+            "Package is not a merge module (it has no {} table)",
+            MODULE_SIGNATURE_TABLE_NAME
+        );
+    }
+    let query =
+        Select::table(MODULE_SIGNATURE_TABLE_NAME).columns(&["ModuleID"]);
+    let module_id = match module.select_rows(query)?.next() {
+        Some(row) => row[0].as_str().unwrap_or_default().to_string(),
+        None => invalid_data!(
+            "Merge module has an empty {} table",
+            MODULE_SIGNATURE_TABLE_NAME
+        ),
+    };
+    match module_id.rsplit_once('.') {
+        Some((name, guid)) if !name.is_empty() && !guid.is_empty() => {
+            Ok(guid.to_string())
+        }
+        _ => invalid_data!("Invalid merge module ModuleID {:?}", module_id),
+    }
+}
+
+/// Maps the keys of rows from a merge module to the keys they will have once
+/// merged.
+struct KeyMap<'a> {
+    suffix: String,
+    redirect_dir: &'a str,
+}
+
+impl KeyMap<'_> {
+    fn map(&self, value: &Value, kind: KeyKind) -> Value {
+        let key = match value.as_str() {
+            Some(key) => key,
+            None => return value.clone(),
+        };
+        if let KeyKind::Directory = kind {
+            if key == TARGETDIR {
+                return Value::from(self.redirect_dir);
+            }
+        }
+        match key.strip_suffix(self.suffix.as_str()) {
+            Some(name) if name.ends_with('.') => value.clone(),
+            _ => Value::Str(format!("{}.{}", key, self.suffix)),
+        }
+    }
+}
+
+// ========================================================================= //
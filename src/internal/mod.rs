@@ -1,11 +1,25 @@
 #[macro_use]
 mod macros;
 
+pub mod attributes;
+pub mod cabinet;
 pub mod category;
 pub mod codepage;
 pub mod column;
+pub mod condition;
+pub mod diff;
+pub mod directory;
+pub mod error;
 pub mod expr;
+pub mod feature;
+pub mod filename;
+pub mod formatted;
+pub mod guid;
+pub mod idt;
+#[cfg(feature = "serde")]
+mod json;
 pub mod language;
+mod merge;
 pub mod package;
 pub mod propset;
 pub mod query;
@@ -15,4 +29,7 @@ pub mod stringpool;
 pub mod summary;
 pub mod table;
 pub mod timestamp;
+pub mod transform;
+pub mod validation;
 pub mod value;
+pub mod version;
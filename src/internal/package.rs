@@ -1,21 +1,35 @@
+use crate::internal::cabinet::CabinetReader;
 use crate::internal::category::Category;
 use crate::internal::codepage::CodePage;
-use crate::internal::column::Column;
+use crate::internal::column::{Column, ColumnType};
+use crate::internal::directory;
+use crate::internal::error::Result;
 use crate::internal::expr::Expr;
-use crate::internal::query::{Delete, Insert, Select, Update};
+use crate::internal::feature::{self, FeatureNode};
+use crate::internal::formatted;
+use crate::internal::guid::Guid;
+use crate::internal::idt;
+use crate::internal::query::{self, Agg, Delete, Insert, Select, Update};
 use crate::internal::stream::{StreamReader, StreamWriter, Streams};
 use crate::internal::streamname::{
     self, DIGITAL_SIGNATURE_STREAM_NAME, MSI_DIGITAL_SIGNATURE_EX_STREAM_NAME,
     SUMMARY_INFO_STREAM_NAME,
 };
 use crate::internal::stringpool::{StringPool, StringPoolBuilder};
-use crate::internal::summary::SummaryInfo;
-use crate::internal::table::{Rows, Table};
-use crate::internal::value::{Value, ValueRef};
+use crate::internal::summary::{SummaryInfo, DEFAULT_CREATING_APPLICATION};
+use crate::internal::table::{find_table, Row, RowStream, Rows, Table};
+use crate::internal::transform::{RowChange, Transform, TransformFlags};
+use crate::internal::validation::{
+    ColumnRule, ForeignKey, IntegrityViolation, SequenceIssue,
+    ValidationError, SEQUENCE_DEPENDENCIES,
+};
+use crate::internal::value::{csv_field, Value, ValueRef};
 use cfb;
 use std::borrow::Borrow;
 use std::collections::{btree_map, BTreeMap, HashMap, HashSet};
+use std::fs;
 use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use uuid::Uuid;
 
@@ -25,12 +39,37 @@ const INSTALLER_PACKAGE_CLSID: &str = "000C1084-0000-0000-C000-000000000046";
 const PATCH_PACKAGE_CLSID: &str = "000C1086-0000-0000-C000-000000000046";
 const TRANSFORM_PACKAGE_CLSID: &str = "000C1082-0000-0000-C000-000000000046";
 
-const COLUMNS_TABLE_NAME: &str = "_Columns";
-const TABLES_TABLE_NAME: &str = "_Tables";
+/// The number of bytes copied at a time by `Package::rename_stream`.
+const RENAME_STREAM_CHUNK_SIZE: usize = 64 * 1024;
+
+pub(crate) const COLUMNS_TABLE_NAME: &str = "_Columns";
+pub(crate) const TABLES_TABLE_NAME: &str = "_Tables";
 const VALIDATION_TABLE_NAME: &str = "_Validation";
 
-const STRING_DATA_TABLE_NAME: &str = "_StringData";
-const STRING_POOL_TABLE_NAME: &str = "_StringPool";
+// Characters that may not appear in the file name of an extracted stream.
+const UNSAFE_FILE_NAME_CHARS: [char; 10] =
+    ['/', '\\', '\0', ':', '<', '>', '"', '|', '?', '*'];
+
+pub(crate) const COMPONENT_TABLE_NAME: &str = "Component";
+pub(crate) const DIRECTORY_TABLE_NAME: &str = "Directory";
+pub(crate) const FEATURE_TABLE_NAME: &str = "Feature";
+pub(crate) const FEATURE_COMPONENTS_TABLE_NAME: &str = "FeatureComponents";
+pub(crate) const FILE_TABLE_NAME: &str = "File";
+const MEDIA_TABLE_NAME: &str = "Media";
+const MSI_FILE_HASH_TABLE_NAME: &str = "MsiFileHash";
+const PROPERTY_TABLE_NAME: &str = "Property";
+
+/// The properties that every installer package must define.
+const REQUIRED_PROPERTIES: &[&str] = &[
+    "ProductCode",
+    "ProductLanguage",
+    "ProductName",
+    "ProductVersion",
+    "Manufacturer",
+];
+
+pub(crate) const STRING_DATA_TABLE_NAME: &str = "_StringData";
+pub(crate) const STRING_POOL_TABLE_NAME: &str = "_StringPool";
 
 const MAX_NUM_TABLE_COLUMNS: usize = 32;
 
@@ -84,12 +123,138 @@ fn make_validation_table(long_string_refs: bool) -> Rc<Table> {
     )
 }
 
+fn make_validation_row(table_name: &str, column: &Column) -> Vec<Value> {
+    let (min_value, max_value) = if let Some((min, max)) = column.value_range()
+    {
+        (Value::Int(min), Value::Int(max))
+    } else {
+        (Value::Null, Value::Null)
+    };
+    let (key_table, key_column) =
+        if let Some((table, column)) = column.foreign_key() {
+            (Value::Str(table.to_string()), Value::Int(column))
+        } else {
+            (Value::Null, Value::Null)
+        };
+    vec![
+        Value::Str(table_name.to_string()),
+        Value::Str(column.name().to_string()),
+        Value::Str(if column.is_nullable() {
+            "Y".to_string()
+        } else {
+            "N".to_string()
+        }),
+        min_value,
+        max_value,
+        key_table,
+        key_column,
+        if let Some(category) = column.category() {
+            Value::Str(category.to_string())
+        } else {
+            Value::Null
+        },
+        if let Some(values) = column.enum_values() {
+            Value::Str(values.join(";"))
+        } else {
+            Value::Null
+        },
+        Value::Null,
+    ]
+}
+
+/// Returns an expression matching the row of the table with the given
+/// primary key values.
+fn key_condition(table: &Table, key: &[Value]) -> Expr {
+    let mut condition: Option<Expr> = None;
+    for (column, value) in table.primary_key_columns().into_iter().zip(key) {
+        let expr = Expr::col(column.name()).eq(Expr::literal(value.clone()));
+        condition = Some(match condition {
+            Some(condition) => condition.and(expr),
+            None => expr,
+        });
+    }
+    condition.unwrap_or_else(|| Expr::boolean(true))
+}
+
+/// Formats a row's primary key values for use in error messages.
+fn format_key(key: &[Value]) -> String {
+    let values: Vec<String> = key.iter().map(Value::to_string).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// Checks that the compound file holds the streams that every package of
+/// the given type needs, returning an error naming the first one that's
+/// missing.  Transforms only hold the tables they change, so they aren't
+/// required to have `_Tables` or `_Columns` streams.
+fn check_required_streams<F>(
+    comp: &cfb::CompoundFile<F>,
+    package_type: PackageType,
+) -> io::Result<()> {
+    let mut required = vec![
+        (SUMMARY_INFO_STREAM_NAME.to_string(), "summary information"),
+        (
+            streamname::encode(STRING_POOL_TABLE_NAME, true),
+            STRING_POOL_TABLE_NAME,
+        ),
+        (
+            streamname::encode(STRING_DATA_TABLE_NAME, true),
+            STRING_DATA_TABLE_NAME,
+        ),
+    ];
+    if package_type != PackageType::Transform {
+        required.push((
+            streamname::encode(TABLES_TABLE_NAME, true),
+            TABLES_TABLE_NAME,
+        ));
+        required.push((
+            streamname::encode(COLUMNS_TABLE_NAME, true),
+            COLUMNS_TABLE_NAME,
+        ));
+    }
+    for (stream_name, description) in required {
+        if !comp.is_stream(&stream_name) {
+            invalid_data!(
+                "Package is corrupt: missing the {} stream",
+                description
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the summary information's "template" property is formatted
+/// the way it should be for the given package type.
+fn check_summary_template(
+    package_type: PackageType,
+    summary_info: &SummaryInfo,
+) -> io::Result<()> {
+    let template = summary_info.template().unwrap_or("");
+    if !package_type.matches_template(template) {
+        invalid_data!(
+            "Summary information template {:?} doesn't match {:?} package",
+            template,
+            package_type
+        );
+    }
+    Ok(())
+}
+
 fn is_reserved_table_name(table_name: &str) -> bool {
     table_name == COLUMNS_TABLE_NAME
         || table_name == TABLES_TABLE_NAME
         || table_name == VALIDATION_TABLE_NAME
 }
 
+/// Returns true if the given (unencoded) stream name belongs to one of the
+/// streams that hold the package's tables, string pool, and summary
+/// information, which can't be manipulated as binary streams.
+fn is_internal_stream_name(stream_name: &str) -> bool {
+    is_reserved_table_name(stream_name)
+        || stream_name == STRING_DATA_TABLE_NAME
+        || stream_name == STRING_POOL_TABLE_NAME
+        || streamname::is_metadata(stream_name)
+}
+
 // ========================================================================= //
 
 /// The type of MSI package (e.g. installer or patch).
@@ -117,7 +282,17 @@ impl PackageType {
         }
     }
 
-    fn clsid(self) -> Uuid {
+    pub(crate) fn from_title(title: &str) -> Option<PackageType> {
+        [PackageType::Installer, PackageType::Patch, PackageType::Transform]
+            .into_iter()
+            .find(|package_type| package_type.default_title() == title)
+    }
+
+    /// Returns the class ID that identifies this type of package.  It is
+    /// stored on the root storage of the package's compound file, and is
+    /// what Windows uses to tell installers, patches, and transforms apart.
+    #[must_use]
+    pub fn clsid(self) -> Uuid {
         match self {
             PackageType::Installer => {
                 Uuid::parse_str(INSTALLER_PACKAGE_CLSID).unwrap()
@@ -131,13 +306,212 @@ impl PackageType {
         }
     }
 
-    fn default_title(&self) -> &str {
+    pub(crate) fn default_title(&self) -> &str {
         match *self {
             PackageType::Installer => "Installation Database",
             PackageType::Patch => "Patch",
             PackageType::Transform => "Transform",
         }
     }
+
+    /// Returns the summary "template" property that a newly created package
+    /// of this type starts with: an empty product code list for a patch, or
+    /// an empty architecture and language list otherwise.
+    pub(crate) fn default_template(&self) -> &str {
+        match *self {
+            PackageType::Patch => "",
+            PackageType::Installer | PackageType::Transform => ";",
+        }
+    }
+
+    /// Returns true if the given summary "template" property is formatted
+    /// the way it should be for this type of package.  A patch's template is
+    /// a semicolon-separated list of product codes, while an installer's or
+    /// a transform's names an architecture and languages, which never look
+    /// like product codes.
+    pub(crate) fn matches_template(&self, template: &str) -> bool {
+        let is_product_code_list = !template.is_empty()
+            && template.split(';').all(|code| {
+                let code = code.trim().trim_start_matches('{');
+                Uuid::parse_str(code.trim_end_matches('}')).is_ok()
+            });
+        match *self {
+            PackageType::Patch => template.is_empty() || is_product_code_list,
+            PackageType::Installer | PackageType::Transform => {
+                !is_product_code_list
+            }
+        }
+    }
+}
+
+// ========================================================================= //
+
+/// The sector size of the compound file that holds a package, which is
+/// determined by the version of the compound file format it uses.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SectorSize {
+    /// 512-byte sectors (compound file version 3).  This is what Windows
+    /// Installer itself usually writes.
+    Bytes512,
+    /// 4096-byte sectors (compound file version 4).  This is what
+    /// [`Package::create`] uses.
+    Bytes4096,
+}
+
+impl SectorSize {
+    fn from_version(version: cfb::Version) -> SectorSize {
+        match version {
+            cfb::Version::V3 => SectorSize::Bytes512,
+            cfb::Version::V4 => SectorSize::Bytes4096,
+        }
+    }
+
+    fn version(self) -> cfb::Version {
+        match self {
+            SectorSize::Bytes512 => cfb::Version::V3,
+            SectorSize::Bytes4096 => cfb::Version::V4,
+        }
+    }
+
+    /// Returns the sector size in bytes.
+    #[must_use]
+    pub fn bytes(self) -> usize {
+        match self {
+            SectorSize::Bytes512 => 512,
+            SectorSize::Bytes4096 => 4096,
+        }
+    }
+}
+
+// ========================================================================= //
+
+/// Options for creating a new package with [`Package::create_with_options`].
+///
+/// # Examples
+///
+/// ```
+/// use msi::{CreateOptions, Package, PackageType, SectorSize};
+/// use std::io::Cursor;
+///
+/// let options = CreateOptions::new()
+///     .sector_size(SectorSize::Bytes512)
+///     .canonical_layout(true);
+/// let cursor = Cursor::new(Vec::new());
+/// let package =
+///     Package::create_with_options(PackageType::Installer, options, cursor)?;
+/// assert_eq!(package.sector_size(), SectorSize::Bytes512);
+/// # Ok::<(), msi::Error>(())
+/// ```
+pub struct CreateOptions {
+    summary_info: SummaryInfo,
+    sector_size: SectorSize,
+    canonical_layout: bool,
+}
+
+impl CreateOptions {
+    /// Returns the default options, as used by [`Package::create`]: empty
+    /// summary information, 4096-byte sectors, and no canonical layout.
+    #[must_use]
+    pub fn new() -> CreateOptions {
+        CreateOptions {
+            summary_info: SummaryInfo::new(),
+            sector_size: SectorSize::Bytes4096,
+            canonical_layout: false,
+        }
+    }
+
+    /// Sets the summary information for the new package (see
+    /// [`SummaryInfo::build`]).  If it has no title, the default title for
+    /// the package type is used, and if it has no creating application,
+    /// [`DEFAULT_CREATING_APPLICATION`] is used.  The database code page is
+    /// taken from the summary information's code page.
+    #[must_use]
+    pub fn summary_info(mut self, summary_info: SummaryInfo) -> CreateOptions {
+        self.summary_info = summary_info;
+        self
+    }
+
+    /// Sets the sector size of the underlying compound file.
+    #[must_use]
+    pub fn sector_size(mut self, sector_size: SectorSize) -> CreateOptions {
+        self.sector_size = sector_size;
+        self
+    }
+
+    /// Sets whether the package should be written with a canonical layout.
+    ///
+    /// Normally, the layout of the underlying compound file depends on the
+    /// order in which tables, rows, and streams were written, and string pool
+    /// entries freed by deletions are reused by later strings.  With a
+    /// canonical layout, every flush that has changes to write (including
+    /// the one made by [`Package::into_inner`] or when the package is
+    /// dropped) instead rebuilds the compound file from scratch, as
+    /// [`Package::compact_into`] does: the tables are written in order by
+    /// name, each table's rows in order by primary key, and the string pool
+    /// in the order the strings are first used.  Two packages with the same
+    /// contents, each written to an initially empty writer that the package
+    /// never shrank in, are then byte-for-byte identical however they were
+    /// built.
+    ///
+    /// Each rebuild takes time proportional to the size of the whole package,
+    /// and, like `compact_into`, drops any digital signature.  The underlying
+    /// writer can't be truncated, so if the package gets smaller between
+    /// flushes, the leftover bytes at the end of the file are zeroed rather
+    /// than removed; the file's length then depends on its history, and only
+    /// the bytes before the leftover zeroes are canonical.
+    #[must_use]
+    pub fn canonical_layout(mut self, canonical: bool) -> CreateOptions {
+        self.canonical_layout = canonical;
+        self
+    }
+}
+
+impl Default for CreateOptions {
+    fn default() -> CreateOptions {
+        CreateOptions::new()
+    }
+}
+
+/// Options for opening an existing package with
+/// [`Package::open_with_options`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use msi::{OpenOptions, Package};
+///
+/// let file = std::fs::File::open("example.msi")?;
+/// let options = OpenOptions::new().check_summary_template(false);
+/// let package = Package::open_with_options(file, options)?;
+/// # Ok::<(), msi::Error>(())
+/// ```
+pub struct OpenOptions {
+    check_summary_template: bool,
+}
+
+impl OpenOptions {
+    /// Returns the default options, as used by [`Package::open`].
+    #[must_use]
+    pub fn new() -> OpenOptions {
+        OpenOptions { check_summary_template: true }
+    }
+
+    /// Sets whether opening the package should fail if its summary
+    /// information's "template" property isn't formatted the way it should
+    /// be for the package's type (see [`Package::check_summary_template`]).
+    /// This is on by default; turning it off lets a package written by a
+    /// tool that gets the template wrong be opened anyway.
+    #[must_use]
+    pub fn check_summary_template(mut self, check: bool) -> OpenOptions {
+        self.check_summary_template = check;
+        self
+    }
+}
+
+impl Default for OpenOptions {
+    fn default() -> OpenOptions {
+        OpenOptions::new()
+    }
 }
 
 // ========================================================================= //
@@ -196,6 +570,8 @@ pub struct Package<F> {
     is_summary_info_modified: bool,
     string_pool: StringPool,
     tables: BTreeMap<String, Rc<Table>>,
+    validate_on_insert: bool,
+    canonical_layout: bool,
     finisher: Option<Box<dyn Finish<F>>>,
 }
 
@@ -206,6 +582,16 @@ impl<F> Package<F> {
         self.package_type
     }
 
+    /// Returns the class ID stored on the root storage of the package's
+    /// compound file.  This is always the [`PackageType::clsid`] of the
+    /// package's type; it is set when the package is created (or by
+    /// [`Package::set_root_clsid`]), and a package whose root class ID isn't
+    /// one of the standard MSI class IDs can't be opened.
+    #[must_use]
+    pub fn root_clsid(&self) -> Uuid {
+        *self.comp().root_entry().clsid()
+    }
+
     /// Returns summary information for this package.
     #[must_use]
     pub fn summary_info(&self) -> &SummaryInfo {
@@ -218,28 +604,77 @@ impl<F> Package<F> {
         self.string_pool.codepage()
     }
 
-    /// Returns true if the database has a table with the given name.
+    /// Returns true if inserted and updated values will be checked against
+    /// their columns' categories.  See
+    /// [`set_validate_on_insert`](Package::set_validate_on_insert).
+    #[must_use]
+    pub fn validate_on_insert(&self) -> bool {
+        self.validate_on_insert
+    }
+
+    /// Returns true if strings that can't be represented in the database
+    /// code page are stored with `'?'` substituted for the unrepresentable
+    /// characters.  See [`set_lossy_encoding`](Package::set_lossy_encoding).
+    #[must_use]
+    pub fn lossy_encoding(&self) -> bool {
+        self.string_pool.lossy_encoding()
+    }
+
+    /// Returns true if the database has a table with the given name.  Table
+    /// names are matched the same way as by [`Package::get_table`].
     #[must_use]
     pub fn has_table(&self, table_name: &str) -> bool {
-        self.tables.contains_key(table_name)
+        find_table(&self.tables, table_name).is_some()
     }
 
-    /// Returns the database table with the given name (if any).
+    /// Returns the database table with the given name (if any).  An exact
+    /// match for the name is preferred, but failing that, table names are
+    /// compared ASCII-case-insensitively.
     pub fn get_table(&self, table_name: &str) -> Option<&Table> {
-        self.tables.get(table_name).map(Rc::borrow)
+        find_table(&self.tables, table_name).map(Rc::borrow)
     }
 
-    /// Returns an iterator over the database tables in this package.
+    /// Returns an iterator over the database tables in this package, in
+    /// order by table name (including the special `_Columns`, `_Tables`, and
+    /// `_Validation` tables).
     #[must_use]
     pub fn tables(&self) -> Tables<'_> {
         Tables { iter: self.tables.values() }
     }
 
+    /// Returns an iterator over the database tables in this package that
+    /// hold package content, in order by table name, skipping system tables
+    /// such as `_Columns` and `_Tables` (see [`Table::is_system`]).
+    pub fn user_tables(&self) -> impl Iterator<Item = &Table> + '_ {
+        self.tables().filter(|table| !table.is_system())
+    }
+
+    /// Creates each of this package's [user tables](Package::user_tables) in
+    /// `dest`, with the same columns (including their validation
+    /// constraints), but without copying any rows.  Tables that already
+    /// exist in `dest` are left as they are, even if their columns differ.
+    pub fn clone_schema_into<G: Read + Write + Seek>(
+        &self,
+        dest: &mut Package<G>,
+    ) -> Result<()> {
+        for table in self.user_tables() {
+            if !dest.has_table(table.name()) {
+                dest.create_table(table.name(), table.columns().to_vec())?;
+            }
+        }
+        Ok(())
+    }
+
     /// Returns true if the package has an embedded binary stream with the
-    /// given name.
+    /// given name.  This only checks the compound file's directory, without
+    /// opening the stream, so it is a cheap way to check whether
+    /// [`read_stream`](Package::read_stream) will find the stream.  Returns
+    /// false for names that aren't valid stream names (including the names
+    /// of the streams that hold the package's tables).
     #[must_use]
     pub fn has_stream(&self, stream_name: &str) -> bool {
-        self.comp().is_stream(streamname::encode(stream_name, false))
+        streamname::is_valid(stream_name, false)
+            && self.comp().is_stream(streamname::encode(stream_name, false))
     }
 
     /// Returns an iterator over the embedded binary streams in this package.
@@ -248,6 +683,75 @@ impl<F> Package<F> {
         Streams::new(self.comp().read_root_storage())
     }
 
+    /// Returns the length, in bytes, of an embedded binary stream, without
+    /// opening the stream for reading.
+    pub fn stream_len(&self, stream_name: &str) -> Result<u64> {
+        if !streamname::is_valid(stream_name, false) {
+            invalid_input!("{:?} is not a valid stream name", stream_name);
+        }
+        let encoded_name = streamname::encode(stream_name, false);
+        if !self.comp().is_stream(&encoded_name) {
+            not_found!("Stream {:?} does not exist", stream_name);
+        }
+        Ok(self.comp().entry(&encoded_name)?.len())
+    }
+
+    /// Checks that the summary information's "template" property is
+    /// formatted the way it should be for the package's type (see
+    /// [`SummaryInfo::template`]), and returns an error describing the
+    /// mismatch if it isn't.  [`Package::open`] makes the same check, but it
+    /// can be turned off with [`OpenOptions::check_summary_template`].
+    pub fn check_summary_template(&self) -> Result<()> {
+        Ok(check_summary_template(self.package_type, &self.summary_info)?)
+    }
+
+    /// For a patch package, returns the product codes of the products that
+    /// the patch can be applied to, as listed in the "template" property of
+    /// the summary information.  Returns an empty list for other package
+    /// types.
+    #[must_use]
+    pub fn patch_target_product_codes(&self) -> Vec<Uuid> {
+        if self.package_type != PackageType::Patch {
+            return Vec::new();
+        }
+        let template = self.summary_info.template().unwrap_or("");
+        template
+            .split(';')
+            .filter_map(|code| {
+                let code = code.trim().trim_start_matches('{');
+                Uuid::parse_str(code.trim_end_matches('}')).ok()
+            })
+            .collect()
+    }
+
+    /// For a patch package, returns the names of the substorages holding
+    /// the patch's transforms (e.g. `"RTM.1"` and `"#RTM.1"`).  Returns an
+    /// empty list for other package types.
+    #[must_use]
+    pub fn patch_transforms(&self) -> Vec<String> {
+        if self.package_type != PackageType::Patch {
+            return Vec::new();
+        }
+        self.substorages().collect()
+    }
+
+    /// Returns an iterator over the names of the substorages (nested
+    /// storages) in the root of the package's compound file, such as the
+    /// transforms embedded in a patch package.  Names are returned as they
+    /// are stored, without MSI stream name decoding.
+    pub fn substorages(&self) -> impl Iterator<Item = String> + '_ {
+        self.comp()
+            .read_root_storage()
+            .filter(|entry| entry.is_storage())
+            .map(|entry| entry.name().to_string())
+    }
+
+    /// Returns the sector size of the compound file that holds the package.
+    #[must_use]
+    pub fn sector_size(&self) -> SectorSize {
+        SectorSize::from_version(self.comp().version())
+    }
+
     /// Returns true if the package has been digitally signed.  Note that this
     /// method only checks whether a signature is present; it does *not* verify
     /// that the signature is actually valid.
@@ -256,8 +760,26 @@ impl<F> Package<F> {
         self.comp().is_stream(DIGITAL_SIGNATURE_STREAM_NAME)
     }
 
+    /// Computes the 128-bit file hash that Windows Installer stores in the
+    /// `MsiFileHash` table, returned as the values of the `HashPart1` through
+    /// `HashPart4` columns.  This is the MD5 digest of the file contents,
+    /// split into four little-endian 32-bit integers.
+    #[must_use]
+    pub fn compute_file_hash(data: &[u8]) -> [i32; 4] {
+        let digest = md5::compute(data);
+        let mut parts = [0i32; 4];
+        for (part, bytes) in parts.iter_mut().zip(digest.0.chunks_exact(4)) {
+            *part =
+                i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        parts
+    }
+
     /// Consumes the `Package` object, returning the underlying reader/writer.
-    pub fn into_inner(mut self) -> io::Result<F> {
+    /// Any pending changes are flushed first (see [`Package::flush`]), so
+    /// for example a package built in a `Cursor<Vec<u8>>` can be turned
+    /// straight back into the complete bytes of the MSI file.
+    pub fn into_inner(mut self) -> Result<F> {
         if let Some(finisher) = self.finisher.take() {
             finisher.finish(&mut self)?;
         }
@@ -277,8 +799,39 @@ impl<F: Read + Seek> Package<F> {
     /// Opens an existing MSI file, using the underlying reader.  If the
     /// underlying reader also supports the `Write` trait, then the `Package`
     /// object will be writable as well.
-    pub fn open(inner: F) -> io::Result<Package<F>> {
-        let mut comp = cfb::CompoundFile::open(inner)?;
+    ///
+    /// Otherwise, the package is read-only: all query and inspection methods
+    /// are available, but the methods that modify the package don't exist
+    /// for it, so there's no need to open a file writable just to read it.
+    ///
+    /// ```compile_fail
+    /// # let bytes: Vec<u8> = Vec::new();
+    /// let cursor = std::io::Cursor::new(bytes.as_slice());
+    /// let mut package = msi::Package::open(cursor)?;
+    /// package.drop_table("Property")?; // error: package isn't writable
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    ///
+    /// Returns an error if the package is malformed, or if its summary
+    /// information's "template" property doesn't match its type (see
+    /// [`Package::check_summary_template`]).
+    pub fn open(inner: F) -> Result<Package<F>> {
+        Package::open_with_options(inner, OpenOptions::new())
+    }
+
+    /// Opens an existing MSI file, as with [`Package::open`], using the
+    /// given options.
+    pub fn open_with_options(
+        inner: F,
+        options: OpenOptions,
+    ) -> Result<Package<F>> {
+        let mut comp = match cfb::CompoundFile::open(inner) {
+            Ok(comp) => comp,
+            Err(error) if error.kind() == io::ErrorKind::InvalidData => {
+                invalid_data!("Not a valid MSI package: {}", error)
+            }
+            Err(error) => return Err(error.into()),
+        };
         let package_type = {
             let root_entry = comp.root_entry();
             let clsid = root_entry.clsid();
@@ -290,8 +843,12 @@ impl<F: Read + Seek> Package<F> {
                 ),
             }
         };
+        check_required_streams(&comp, package_type)?;
         let summary_info =
             SummaryInfo::read(comp.open_stream(SUMMARY_INFO_STREAM_NAME)?)?;
+        if options.check_summary_template {
+            check_summary_template(package_type, &summary_info)?;
+        }
         let string_pool = {
             let builder = {
                 let name = streamname::encode(STRING_POOL_TABLE_NAME, true);
@@ -476,6 +1033,8 @@ impl<F: Read + Seek> Package<F> {
             is_summary_info_modified: false,
             string_pool,
             tables: all_tables,
+            validate_on_insert: true,
+            canonical_layout: false,
             finisher: None,
         })
     }
@@ -483,102 +1042,1365 @@ impl<F: Read + Seek> Package<F> {
     /// Attempts to execute a select query.  Returns an error if the query
     /// fails (e.g. due to the column names being incorrect or the table(s) not
     /// existing).
-    pub fn select_rows(&mut self, query: Select) -> io::Result<Rows<'_>> {
-        query.exec(
+    pub fn select_rows(&mut self, query: Select) -> Result<Rows<'_>> {
+        Ok(query.exec(
             self.comp.as_mut().unwrap(),
             &self.string_pool,
             &self.tables,
-        )
+        )?)
     }
 
-    /// Opens an existing binary stream in the package for reading.
-    pub fn read_stream(
-        &mut self,
-        stream_name: &str,
-    ) -> io::Result<StreamReader<F>> {
-        if !streamname::is_valid(stream_name, false) {
-            invalid_input!("{:?} is not a valid stream name", stream_name);
-        }
-        let encoded_name = streamname::encode(stream_name, false);
-        if !self.comp().is_stream(&encoded_name) {
-            not_found!("Stream {:?} does not exist", stream_name);
+    /// Discards the decoded rows that the package keeps for each table it
+    /// has read, to speed up repeated queries.  This frees memory at the cost
+    /// of re-reading table streams on subsequent queries; it never affects
+    /// query results.
+    pub fn clear_cache(&mut self) {
+        for table in self.tables.values() {
+            table.clear_cache();
         }
-        Ok(StreamReader::new(self.comp_mut().open_stream(&encoded_name)?))
     }
 
-    // TODO: pub fn has_valid_digital_signature(&mut self) -> io::Result<bool>
-}
-
-impl<F: Read + Write + Seek> Package<F> {
-    /// Creates a new, empty package of the given type, using the underlying
-    /// reader/writer.  The reader/writer should be initially empty.
-    pub fn create(
-        package_type: PackageType,
-        inner: F,
-    ) -> io::Result<Package<F>> {
-        let mut comp = cfb::CompoundFile::create(inner)?;
-        comp.set_storage_clsid("/", package_type.clsid())?;
-        let mut summary_info = SummaryInfo::new();
-        summary_info.set_title(package_type.default_title().to_string());
-        let string_pool = StringPool::new(summary_info.codepage());
-        let tables = {
-            let mut tables = BTreeMap::<String, Rc<Table>>::new();
-            let table = make_tables_table(string_pool.long_string_refs());
-            tables.insert(table.name().to_string(), table);
-            let table = make_columns_table(string_pool.long_string_refs());
-            tables.insert(table.name().to_string(), table);
-            tables
-        };
-        let mut package = Package {
-            comp: Some(comp),
-            package_type,
-            summary_info,
-            is_summary_info_modified: true,
-            string_pool,
-            tables,
-            finisher: None,
+    /// Returns the row in the specified table whose primary key columns (in
+    /// order) have the given values, or `None` if there is no such row.
+    /// Returns an error if the table doesn't exist, or if the number of
+    /// values given doesn't match the number of primary key columns.
+    pub fn get_row(
+        &mut self,
+        table_name: &str,
+        key: &[Value],
+    ) -> Result<Option<Row>> {
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table,
+            None => not_found!("Table {:?} does not exist", table_name),
         };
-        package
-            .create_table(VALIDATION_TABLE_NAME, make_validation_columns())?;
-        package.flush()?;
-        debug_assert!(!package.is_summary_info_modified);
-        debug_assert!(!package.string_pool.is_modified());
-        Ok(package)
-    }
-
-    /// Returns a mutable reference to the summary information for this
-    /// package.  Call `flush()` or drop the `Package` object to persist any
-    /// changes made to the underlying writer.
-    pub fn summary_info_mut(&mut self) -> &mut SummaryInfo {
-        self.is_summary_info_modified = true;
-        self.set_finisher();
-        &mut self.summary_info
+        let key_columns = table.primary_key_columns();
+        if key.len() != key_columns.len() {
+            schema_mismatch!(
+                "Table {:?} has {} primary key columns, but a key with {} \
+                 values was provided",
+                table_name,
+                key_columns.len(),
+                key.len()
+            );
+        }
+        let query = Select::table(table_name).with(key_condition(table, key));
+        Ok(self.select_rows(query)?.next())
     }
 
-    /// Sets the code page used for serializing strings in the database.
-    pub fn set_database_codepage(&mut self, codepage: CodePage) {
-        self.set_finisher();
-        self.string_pool.set_codepage(codepage)
+    /// Returns the number of rows in the specified table that match the
+    /// given condition, or the total number of rows if `condition` is `None`.
+    ///
+    /// This is cheaper than counting the results of
+    /// [`select_rows`](Package::select_rows): with no condition, the count is
+    /// computed from the length of the table's data stream alone, and with a
+    /// condition, string values are only resolved for the columns that the
+    /// condition actually refers to.
+    pub fn count_rows(
+        &mut self,
+        table_name: &str,
+        condition: Option<Expr>,
+    ) -> Result<usize> {
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table.clone(),
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        let stream_name = table.stream_name();
+        if !self.comp().exists(&stream_name) {
+            return Ok(0);
+        }
+        let mut expr = match condition {
+            Some(expr) => expr,
+            None => {
+                let data_length = self.comp().entry(&stream_name)?.len();
+                return Ok(table.num_rows(data_length)?);
+            }
+        };
+        let mut is_used = vec![false; table.columns().len()];
+        for column_name in expr.column_names() {
+            match table.index_for_column_name(column_name) {
+                Some(index) => is_used[index] = true,
+                None => schema_mismatch!(
+                    "Table {:?} has no column named {:?}",
+                    table_name,
+                    column_name
+                ),
+            }
+        }
+        query::resolve_subqueries(
+            &mut expr,
+            self.comp.as_mut().unwrap(),
+            &self.string_pool,
+            &self.tables,
+        )?;
+        let rows = table.load_rows(self.comp.as_mut().unwrap())?;
+        let mut count = 0;
+        for value_refs in rows {
+            let values: Vec<Value> = value_refs
+                .iter()
+                .zip(is_used.iter())
+                .map(|(value_ref, &used)| {
+                    if used {
+                        value_ref.to_value(&self.string_pool)
+                    } else {
+                        Value::Null
+                    }
+                })
+                .collect();
+            if expr.eval(&Row::new(table.clone(), values)).to_bool() {
+                count += 1;
+            }
+        }
+        Ok(count)
     }
 
-    /// Creates a new database table.  Returns an error without modifying the
-    /// database if the table name or columns are invalid, or if a table with
-    /// that name already exists.
-    pub fn create_table<S: Into<String>>(
+    /// Computes an aggregate function over the values in one column of the
+    /// specified table, skipping null values.  `Count` always returns an
+    /// integer; `Min`, `Max`, and `Sum` return `Value::Null` if the column
+    /// has no non-null values.  Returns an error if the table or column
+    /// doesn't exist, if `Sum` is applied to a string column, or if the sum
+    /// doesn't fit in an `i32`.
+    pub fn aggregate(
         &mut self,
-        table_name: S,
-        columns: Vec<Column>,
-    ) -> io::Result<()> {
-        self.create_table_with_name(table_name.into(), columns)
+        table_name: &str,
+        column_name: &str,
+        agg: Agg,
+    ) -> Result<Value> {
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table,
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        let column = match table.get_column(column_name) {
+            Some(column) => column,
+            None => schema_mismatch!(
+                "Table {:?} has no column named {:?}",
+                table_name,
+                column_name
+            ),
+        };
+        if agg == Agg::Sum && matches!(column.coltype(), ColumnType::Str(_)) {
+            schema_mismatch!(
+                "Cannot compute {} of non-integer column {:?}",
+                agg,
+                column_name
+            );
+        }
+        let query = Select::table(table_name).columns(&[column_name]);
+        let values = self
+            .select_rows(query)?
+            .map(|row| row[0].clone())
+            .filter(|value| !value.is_null());
+        let result = match agg {
+            Agg::Count => Value::Int(values.count() as i32),
+            Agg::Min => values.min().unwrap_or(Value::Null),
+            Agg::Max => values.max().unwrap_or(Value::Null),
+            Agg::Sum => {
+                let mut sum: Option<i32> = None;
+                for value in values {
+                    let int = value.as_int().unwrap();
+                    sum = match sum.unwrap_or(0).checked_add(int) {
+                        Some(sum) => Some(sum),
+                        None => invalid_data!(
+                            "Sum of column {:?} in table {:?} overflows",
+                            column_name,
+                            table_name
+                        ),
+                    };
+                }
+                sum.map_or(Value::Null, Value::Int)
+            }
+        };
+        Ok(result)
     }
 
-    fn create_table_with_name(
+    /// Checks every row of every table against the constraints recorded in
+    /// the package's `_Validation` table (nullability, integer ranges,
+    /// categories, sets of allowed values, and references to key tables),
+    /// and returns a list of all the problems found.  Columns that have no
+    /// entry in `_Validation` are not checked.
+    ///
+    /// Unlike the checks done when inserting rows, this reads the
+    /// `_Validation` table as it currently is, so it will catch existing
+    /// rows that violate the package's own constraints.
+    pub fn validate_against_validation_table(
         &mut self,
-        table_name: String,
-        columns: Vec<Column>,
-    ) -> io::Result<()> {
-        if !Table::is_valid_name(&table_name) {
-            invalid_input!("{:?} is not a valid table name", table_name);
+    ) -> Result<Vec<ValidationError>> {
+        let mut rules = HashMap::<(String, String), ColumnRule>::new();
+        if self.has_table(VALIDATION_TABLE_NAME) {
+            for row in
+                self.select_rows(Select::table(VALIDATION_TABLE_NAME))?
+            {
+                let table_name = row[0].as_str().unwrap().to_string();
+                let column_name = row[1].as_str().unwrap().to_string();
+                rules.insert(
+                    (table_name, column_name),
+                    ColumnRule::from_row(&row),
+                );
+            }
+        }
+        // Gather the values of all columns referred to as foreign keys.
+        let mut key_values = HashMap::<(String, usize), HashSet<Value>>::new();
+        for rule in rules.values() {
+            let (key_tables, key_index) = match rule.foreign_key() {
+                Some(foreign_key) => foreign_key,
+                None => continue,
+            };
+            for key_table in key_tables {
+                let key = (key_table.clone(), key_index);
+                if key_values.contains_key(&key) {
+                    continue;
+                }
+                let column_name = match find_table(&self.tables, key_table) {
+                    Some(table) if key_index < table.columns().len() => {
+                        table.columns()[key_index].name().to_string()
+                    }
+                    _ => continue,
+                };
+                let query =
+                    Select::table(key_table.as_str()).columns(&[column_name]);
+                let values = self
+                    .select_rows(query)?
+                    .map(|row| row[0].clone())
+                    .collect();
+                key_values.insert(key, values);
+            }
+        }
+        let mut errors = Vec::new();
+        let tables: Vec<Rc<Table>> = self.tables.values().cloned().collect();
+        for table in tables {
+            let column_rules: Vec<Option<&ColumnRule>> = table
+                .columns()
+                .iter()
+                .map(|column| {
+                    let key =
+                        (table.name().to_string(), column.name().to_string());
+                    rules.get(&key)
+                })
+                .collect();
+            if column_rules.iter().all(Option::is_none) {
+                continue;
+            }
+            let key_indices = table.primary_key_indices();
+            for row in self.select_rows(Select::table(table.name()))? {
+                for (index, column) in table.columns().iter().enumerate() {
+                    let rule = match column_rules[index] {
+                        Some(rule) => rule,
+                        None => continue,
+                    };
+                    let value = &row[index];
+                    let mut reason = rule.check(column, value);
+                    if let (None, Some((key_tables, key_index))) =
+                        (&reason, rule.foreign_key())
+                    {
+                        let found = value.is_null()
+                            || key_tables.iter().any(|key_table| {
+                                key_values
+                                    .get(&(key_table.clone(), key_index))
+                                    .is_some_and(|values| {
+                                        values.contains(value)
+                                    })
+                            });
+                        if !found {
+                            reason = Some(format!(
+                                "{} does not match any row of key table {}",
+                                value,
+                                key_tables.join(";")
+                            ));
+                        }
+                    }
+                    if let Some(reason) = reason {
+                        let key = key_indices
+                            .iter()
+                            .map(|&key_index| row[key_index].clone())
+                            .collect();
+                        errors.push(ValidationError::new(
+                            table.name(),
+                            key,
+                            column.name(),
+                            reason,
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(errors)
+    }
+
+    /// Checks that every non-null value in each foreign key's child column
+    /// appears in its parent column, and returns a list of the child rows
+    /// that refer to missing parent rows.  Returns an error if any of the
+    /// named tables or columns don't exist.
+    pub fn check_referential_integrity(
+        &mut self,
+        rules: &[ForeignKey],
+    ) -> Result<Vec<IntegrityViolation>> {
+        let mut violations = Vec::new();
+        for rule in rules {
+            for (table_name, column_name) in [
+                (rule.parent_table(), rule.parent_column()),
+                (rule.child_table(), rule.child_column()),
+            ] {
+                match find_table(&self.tables, table_name) {
+                    Some(table) if !table.has_column(column_name) => {
+                        schema_mismatch!(
+                            "Table {:?} has no column named {:?}",
+                            table_name,
+                            column_name
+                        );
+                    }
+                    Some(_) => {}
+                    None => {
+                        not_found!("Table {:?} does not exist", table_name)
+                    }
+                }
+            }
+            let query = Select::table(rule.parent_table())
+                .columns(&[rule.parent_column()]);
+            let parent_values: HashSet<Value> =
+                self.select_rows(query)?.map(|row| row[0].clone()).collect();
+            let child_table =
+                find_table(&self.tables, rule.child_table()).unwrap().clone();
+            let key_indices = child_table.primary_key_indices();
+            let column_index = child_table
+                .index_for_column_name(rule.child_column())
+                .unwrap();
+            for row in self.select_rows(Select::table(rule.child_table()))? {
+                let value = &row[column_index];
+                if value.is_null() || parent_values.contains(value) {
+                    continue;
+                }
+                let key = key_indices
+                    .iter()
+                    .map(|&key_index| row[key_index].clone())
+                    .collect();
+                violations.push(IntegrityViolation::new(
+                    rule.clone(),
+                    key,
+                    value.clone(),
+                ));
+            }
+        }
+        Ok(violations)
+    }
+
+    /// Checks a sequence table (such as `InstallExecuteSequence`) for common
+    /// authoring mistakes, and returns a list of the problems found.
+    ///
+    /// Two kinds of problems are reported: actions that share a sequence
+    /// number with another action, and standard actions that are sequenced
+    /// before a standard action they are documented to depend on (e.g.
+    /// `InstallFiles` before `InstallInitialize`).  Actions with a null
+    /// sequence number are ignored, as are actions with a negative sequence
+    /// number (which are run on exit rather than in order) when checking
+    /// dependencies.  Returns an error if the table doesn't exist or doesn't
+    /// have `Action` and `Sequence` columns.
+    pub fn verify_sequence_table(
+        &mut self,
+        table_name: &str,
+    ) -> Result<Vec<SequenceIssue>> {
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table.clone(),
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        for column_name in ["Action", "Sequence"] {
+            if !table.has_column(column_name) {
+                schema_mismatch!(
+                    "Table {:?} has no column named {:?}",
+                    table_name,
+                    column_name
+                );
+            }
+        }
+        let query =
+            Select::table(table.name()).columns(&["Action", "Sequence"]);
+        let mut sequences = BTreeMap::<String, i32>::new();
+        for row in self.select_rows(query)? {
+            if let (Some(action), Some(sequence)) =
+                (row[0].as_str(), row[1].as_int())
+            {
+                sequences.insert(action.to_string(), sequence);
+            }
+        }
+        let mut issues = Vec::new();
+        let mut by_sequence = BTreeMap::<i32, Vec<String>>::new();
+        for (action, &sequence) in sequences.iter() {
+            by_sequence.entry(sequence).or_default().push(action.clone());
+        }
+        for (sequence, actions) in by_sequence {
+            if actions.len() > 1 {
+                issues.push(SequenceIssue::DuplicateSequence {
+                    sequence,
+                    actions,
+                });
+            }
+        }
+        for &(action, dependency) in SEQUENCE_DEPENDENCIES {
+            if let (Some(&sequence), Some(&dependency_sequence)) =
+                (sequences.get(action), sequences.get(dependency))
+            {
+                if sequence > 0
+                    && dependency_sequence > 0
+                    && sequence < dependency_sequence
+                {
+                    issues.push(SequenceIssue::OutOfOrder {
+                        action: action.to_string(),
+                        sequence,
+                        dependency: dependency.to_string(),
+                        dependency_sequence,
+                    });
+                }
+            }
+        }
+        Ok(issues)
+    }
+
+    /// Expands a `Formatted` string (see [`Category::Formatted`]), replacing
+    /// each `[PropertyName]` reference with that property's value from the
+    /// package's `Property` table.  References to properties that aren't in
+    /// the table expand to the empty string, and `[\x]` expands to the
+    /// literal character `x`.
+    ///
+    /// References that can only be resolved at install time, such as
+    /// environment variables (`[%PATH]`), file paths (`[#File]`, `[!File]`),
+    /// component paths (`[$Component]`), and `[~]`, are left unexpanded.
+    pub fn expand_formatted(&mut self, input: &str) -> Result<String> {
+        let mut properties = HashMap::<String, String>::new();
+        if self.has_table(PROPERTY_TABLE_NAME) {
+            let query = Select::table(PROPERTY_TABLE_NAME)
+                .columns(&["Property", "Value"]);
+            for row in self.select_rows(query)? {
+                if let (Some(name), Some(value)) =
+                    (row[0].as_str(), row[1].as_str())
+                {
+                    properties.insert(name.to_string(), value.to_string());
+                }
+            }
+        }
+        Ok(formatted::expand(input, |name| properties.get(name).cloned()))
+    }
+
+    /// Returns the value of the given property from the package's `Property`
+    /// table, or `None` if the table or the property doesn't exist.
+    pub fn property(&mut self, name: &str) -> Result<Option<String>> {
+        if !self.has_table(PROPERTY_TABLE_NAME) {
+            return Ok(None);
+        }
+        let row = self.get_row(PROPERTY_TABLE_NAME, &[Value::from(name)])?;
+        Ok(row.and_then(|row| row[1].as_str().map(str::to_string)))
+    }
+
+    /// Returns the `ProductCode` property, the GUID that identifies this
+    /// release of the product, or `None` if it isn't set.
+    pub fn product_code(&mut self) -> Result<Option<String>> {
+        self.property("ProductCode")
+    }
+
+    /// Returns the `ProductVersion` property, or `None` if it isn't set.
+    /// Windows Installer only compares the first three fields of the version
+    /// when detecting upgrades.
+    pub fn product_version(&mut self) -> Result<Option<String>> {
+        self.property("ProductVersion")
+    }
+
+    /// Returns the `ProductName` property, the name shown to users (for
+    /// example, in the list of installed programs), or `None` if it isn't set.
+    pub fn product_name(&mut self) -> Result<Option<String>> {
+        self.property("ProductName")
+    }
+
+    /// Returns the `Manufacturer` property, or `None` if it isn't set.
+    pub fn manufacturer(&mut self) -> Result<Option<String>> {
+        self.property("Manufacturer")
+    }
+
+    /// Returns the `UpgradeCode` property, which is shared by every version of
+    /// a product so that newer versions can find older ones, or `None` if it
+    /// isn't set.
+    pub fn upgrade_code(&mut self) -> Result<Option<String>> {
+        self.property("UpgradeCode")
+    }
+
+    /// Checks the properties that every installer package must define, and
+    /// returns a list of human-readable descriptions of any problems found
+    /// (an empty list means that the properties look valid).
+    ///
+    /// The `ProductCode`, `ProductLanguage`, `ProductName`, `ProductVersion`,
+    /// and `Manufacturer` properties must all be set.  `ProductCode` and (if
+    /// it's set) `UpgradeCode` must be braced, uppercase GUIDs, and
+    /// `ProductLanguage` must be a numeric language identifier.
+    pub fn validate_required_properties(&mut self) -> Result<Vec<String>> {
+        let mut problems = Vec::new();
+        for name in REQUIRED_PROPERTIES {
+            if self.property(name)?.is_none() {
+                problems.push(format!("Required property {name} is missing"));
+            }
+        }
+        for name in ["ProductCode", "UpgradeCode"] {
+            if let Some(value) = self.property(name)? {
+                if !Category::Guid.validate(&value) {
+                    problems.push(format!(
+                        "Property {name} is {value:?}, which is not a braced \
+                         uppercase GUID"
+                    ));
+                }
+            }
+        }
+        if let Some(value) = self.property("ProductLanguage")? {
+            if value.parse::<u16>().is_err() {
+                problems.push(format!(
+                    "Property ProductLanguage is {value:?}, which is not a \
+                     language identifier"
+                ));
+            }
+        }
+        Ok(problems)
+    }
+
+    /// Resolves every directory in the package's `Directory` table to its
+    /// install path, and returns a map from each `Directory` key to its path.
+    ///
+    /// Paths are `/`-separated and relative to the root directory or standard
+    /// system folder that contains them, since the actual locations of those
+    /// are only known at install time; for example, a directory nested two
+    /// levels beneath `ProgramFilesFolder` might resolve to
+    /// `"ProgramFilesFolder/MyCompany/MyApp"`.  Root directories and system
+    /// folders resolve to their own key, long names are preferred over short
+    /// names, and a `DefaultDir` of `.` resolves to the parent's path.
+    ///
+    /// Returns an empty map if the package has no `Directory` table, or an
+    /// error if a directory's parent doesn't exist, if the parent links form
+    /// a cycle, or if a `DefaultDir` value is malformed.
+    pub fn resolve_directories(&mut self) -> Result<HashMap<String, String>> {
+        if !self.has_table(DIRECTORY_TABLE_NAME) {
+            return Ok(HashMap::new());
+        }
+        let query = Select::table(DIRECTORY_TABLE_NAME).columns(&[
+            "Directory",
+            "Directory_Parent",
+            "DefaultDir",
+        ]);
+        let mut directories = Vec::new();
+        for row in self.select_rows(query)? {
+            let key = match row[0].as_str() {
+                Some(key) => key.to_string(),
+                None => continue,
+            };
+            let parent = row[1].as_str().map(str::to_string);
+            let default_dir = row[2].as_str().unwrap_or(".").to_string();
+            directories.push((key, parent, default_dir));
+        }
+        directory::resolve_paths(&directories)
+    }
+
+    /// Determines where each file in the package's `File` table will be
+    /// installed, by following its `Component_` to the component's
+    /// `Directory_` and resolving that directory with
+    /// [`resolve_directories`](Package::resolve_directories).  Returns a list
+    /// of `(file_key, path)` pairs, ordered by file key, where `path` is the
+    /// resolved directory path joined with the file's long name (or its short
+    /// name, if it has no long name).
+    ///
+    /// Returns an empty list if the package has no `File` table, or an error
+    /// if a file refers to a component that doesn't exist, or a component
+    /// refers to a directory that doesn't exist.
+    pub fn file_install_paths(&mut self) -> Result<Vec<(String, String)>> {
+        if !self.has_table(FILE_TABLE_NAME) {
+            return Ok(Vec::new());
+        }
+        let directory_paths = self.resolve_directories()?;
+        let query = Select::table(COMPONENT_TABLE_NAME)
+            .columns(&["Component", "Directory_"]);
+        let mut component_paths = HashMap::<String, &str>::new();
+        for row in self.select_rows(query)? {
+            if let (Some(component), Some(directory)) =
+                (row[0].as_str(), row[1].as_str())
+            {
+                let path = match directory_paths.get(directory) {
+                    Some(path) => path.as_str(),
+                    None => invalid_data!(
+                        "Component {:?} refers to nonexistent directory {:?}",
+                        component,
+                        directory
+                    ),
+                };
+                component_paths.insert(component.to_string(), path);
+            }
+        }
+        let query = Select::table(FILE_TABLE_NAME).columns(&[
+            "File",
+            "Component_",
+            "FileName",
+        ]);
+        let mut paths = Vec::new();
+        for row in self.select_rows(query)? {
+            let (file, component, filename) =
+                match (row[0].as_str(), row[1].as_str(), row[2].as_str()) {
+                    (Some(file), Some(component), Some(filename)) => {
+                        (file, component, filename)
+                    }
+                    _ => continue,
+                };
+            let directory_path = match component_paths.get(component) {
+                Some(&path) => path,
+                None => invalid_data!(
+                    "File {:?} refers to nonexistent component {:?}",
+                    file,
+                    component
+                ),
+            };
+            let name = match filename.split_once('|') {
+                Some((_, long)) => long,
+                None => filename,
+            };
+            paths.push((file.to_string(), format!("{directory_path}/{name}")));
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Reads the package's `Feature` table and arranges its features into a
+    /// tree, following each feature's `Feature_Parent` link.  Returns the
+    /// root features (ordered by key), each holding its child features.
+    ///
+    /// Returns an empty list if the package has no `Feature` table, or an
+    /// error if a feature's parent doesn't exist, or if the parent links
+    /// form a cycle.
+    pub fn feature_tree(&mut self) -> Result<Vec<FeatureNode>> {
+        if !self.has_table(FEATURE_TABLE_NAME) {
+            return Ok(Vec::new());
+        }
+        let query = Select::table(FEATURE_TABLE_NAME).columns(&[
+            "Feature",
+            "Feature_Parent",
+            "Title",
+            "Level",
+        ]);
+        let mut rows = Vec::new();
+        for row in self.select_rows(query)? {
+            let name = match row[0].as_str() {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let parent = row[1]
+                .as_str()
+                .filter(|parent| !parent.is_empty())
+                .map(str::to_string);
+            let title = row[2].as_str().map(str::to_string);
+            let level = row[3].as_int().unwrap_or(0);
+            rows.push((name, parent, title, level));
+        }
+        feature::build_tree(rows)
+    }
+
+    /// Returns the keys of the components that belong directly to the given
+    /// feature, according to the `FeatureComponents` table, ordered by key.
+    /// Components of the feature's child features are not included.
+    ///
+    /// Returns an error if the package has no `Feature` table or the feature
+    /// doesn't exist in it.
+    pub fn components_for_feature(
+        &mut self,
+        feature: &str,
+    ) -> Result<Vec<String>> {
+        let condition = Expr::col("Feature").eq(Expr::string(feature));
+        if self.count_rows(FEATURE_TABLE_NAME, Some(condition))? == 0 {
+            not_found!("Feature {:?} does not exist", feature);
+        }
+        if !self.has_table(FEATURE_COMPONENTS_TABLE_NAME) {
+            return Ok(Vec::new());
+        }
+        let query = Select::table(FEATURE_COMPONENTS_TABLE_NAME)
+            .columns(&["Component_"])
+            .with(Expr::col("Feature_").eq(Expr::string(feature)));
+        let mut components: Vec<String> = self
+            .select_rows(query)?
+            .filter_map(|row| row[0].as_str().map(str::to_string))
+            .collect();
+        components.sort();
+        Ok(components)
+    }
+
+    /// Writes the contents of the specified table as CSV, with a header row
+    /// of column names followed by one line per row.  Null values are written
+    /// as empty fields, and fields are quoted as per RFC 4180 when necessary.
+    /// Values in binary columns are written as a `<stream:Name>` placeholder
+    /// naming the stream that holds the data, rather than the data itself.
+    pub fn export_table_csv<W: Write>(
+        &mut self,
+        table_name: &str,
+        mut out: W,
+    ) -> Result<()> {
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table.clone(),
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        let rows = self.select_rows(Select::table(table_name))?;
+        let header: Vec<String> = table
+            .columns()
+            .iter()
+            .map(|column| csv_field(column.name()))
+            .collect();
+        write!(out, "{}\r\n", header.join(","))?;
+        let key_indices = table.primary_key_indices();
+        for row in rows {
+            let mut fields = Vec::with_capacity(row.len());
+            for (index, column) in table.columns().iter().enumerate() {
+                let field = match row[index] {
+                    Value::Str(_)
+                        if column.category() == Some(Category::Binary) =>
+                    {
+                        let keys: Vec<String> = key_indices
+                            .iter()
+                            .map(|&key| match row[key] {
+                                Value::Str(ref string) => string.clone(),
+                                ref value => value.to_string(),
+                            })
+                            .collect();
+                        let name =
+                            format!("{}.{}", table_name, keys.join("."));
+                        csv_field(&format!("<stream:{name}>"))
+                    }
+                    ref value => value.display_for_csv().to_string(),
+                };
+                fields.push(field);
+            }
+            write!(out, "{}\r\n", fields.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the contents of the specified table as an IDT archive file, in
+    /// the format produced by `msidb -e` or msitools' `msiexport`.  Strings
+    /// are encoded using the database codepage.  Binary columns are written
+    /// as they are stored in the table; the stream data is not exported.
+    pub fn export_idt<W: Write>(
+        &mut self,
+        table_name: &str,
+        mut out: W,
+    ) -> Result<()> {
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table.clone(),
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        let codepage = self.database_codepage();
+        idt::write_header(&mut out, &table)?;
+        for row in self.select_rows(Select::table(table_name))? {
+            let values: Vec<Value> =
+                (0..row.len()).map(|index| row[index].clone()).collect();
+            idt::write_row(&mut out, &values, codepage)?;
+        }
+        Ok(())
+    }
+
+    /// Like [`select_rows`](Package::select_rows), but reads rows from the
+    /// package only as the returned iterator advances, rather than reading
+    /// the whole table up front.  Rows that don't match the query's condition
+    /// are skipped without resolving any strings beyond those the condition
+    /// refers to.  This keeps memory use low when scanning large tables.
+    pub fn stream_rows(&mut self, query: Select) -> Result<RowStream<'_, F>> {
+        Ok(query.stream(
+            self.comp.as_mut().unwrap(),
+            &self.string_pool,
+            &self.tables,
+        )?)
+    }
+
+    /// Opens an existing binary stream in the package for reading.
+    pub fn read_stream(
+        &mut self,
+        stream_name: &str,
+    ) -> Result<StreamReader<F>> {
+        if !streamname::is_valid(stream_name, false) {
+            invalid_input!("{:?} is not a valid stream name", stream_name);
+        }
+        let encoded_name = streamname::encode(stream_name, false);
+        if !self.comp().is_stream(&encoded_name) {
+            not_found!("Stream {:?} does not exist", stream_name);
+        }
+        Ok(StreamReader::new(self.comp_mut().open_stream(&encoded_name)?))
+    }
+
+    /// Opens a stream within one of the package's
+    /// [substorages](Package::substorages) for reading.  Unlike
+    /// [`read_stream`](Package::read_stream), the stream name is used as it
+    /// is stored in the compound file, without MSI stream name encoding,
+    /// since a substorage (such as a patch's transform) may hold streams of
+    /// any kind, including encoded table streams.
+    pub fn read_substorage_stream(
+        &mut self,
+        storage: &str,
+        stream: &str,
+    ) -> Result<StreamReader<F>> {
+        for name in [storage, stream] {
+            if name.is_empty() || name.contains('/') {
+                invalid_input!("{:?} is not a valid storage entry name", name);
+            }
+        }
+        if !self.comp().is_storage(format!("/{storage}")) {
+            not_found!("Substorage {:?} does not exist", storage);
+        }
+        let path = format!("/{storage}/{stream}");
+        if !self.comp().is_stream(&path) {
+            not_found!(
+                "Stream {:?} does not exist in substorage {:?}",
+                stream,
+                storage
+            );
+        }
+        Ok(StreamReader::new(self.comp_mut().open_stream(&path)?))
+    }
+
+    /// Writes each embedded binary stream in the package to a file in `dir`
+    /// named after the stream, and returns the paths of the files written,
+    /// in order by stream name.  Internal metadata streams (such as the
+    /// summary information) are not extracted.  Returns an error without
+    /// writing any files if a stream's name can't be used as a file name.
+    pub fn extract_all_streams(&mut self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let targets = self.stream_extraction_targets(dir)?;
+        for (stream_name, path) in targets.iter() {
+            let mut stream = self.read_stream(stream_name)?;
+            io::copy(&mut stream, &mut fs::File::create(path)?)?;
+        }
+        Ok(targets.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// Like [`Package::extract_all_streams`], but extracts the streams
+    /// concurrently.  Since a package's underlying file can only be read
+    /// from one place at a time, each worker thread calls `open` to get its
+    /// own handle to the same file (e.g. by reopening it from disk), so any
+    /// changes made to this package must already have been flushed.
+    #[cfg(feature = "rayon")]
+    pub fn extract_all_streams_parallel<G>(
+        &self,
+        dir: &Path,
+        open: G,
+    ) -> Result<Vec<PathBuf>>
+    where
+        G: Fn() -> io::Result<F> + Sync,
+    {
+        use rayon::prelude::*;
+        let targets = self.stream_extraction_targets(dir)?;
+        targets.par_iter().try_for_each(|(stream_name, path)| {
+            let mut comp = cfb::CompoundFile::open(open()?)?;
+            let encoded_name = streamname::encode(stream_name, false);
+            let mut stream = comp.open_stream(encoded_name)?;
+            io::copy(&mut stream, &mut fs::File::create(path)?)?;
+            Ok::<(), io::Error>(())
+        })?;
+        Ok(targets.into_iter().map(|(_, path)| path).collect())
+    }
+
+    fn stream_extraction_targets(
+        &self,
+        dir: &Path,
+    ) -> io::Result<Vec<(String, PathBuf)>> {
+        let mut stream_names: Vec<String> = self.streams().collect();
+        stream_names.sort();
+        let mut targets = Vec::with_capacity(stream_names.len());
+        for stream_name in stream_names {
+            // Besides path separators, reject the characters Windows reserves
+            // in file names, since a name like `C:foo` would otherwise let
+            // `dir.join` escape the target directory.
+            let path = dir.join(&stream_name);
+            if stream_name == "."
+                || stream_name == ".."
+                || stream_name.contains(UNSAFE_FILE_NAME_CHARS)
+                || path.parent() != Some(dir)
+            {
+                invalid_data!(
+                    "Stream {:?} cannot be extracted to a file",
+                    stream_name
+                );
+            }
+            targets.push((stream_name, path));
+        }
+        Ok(targets)
+    }
+
+    /// Opens a cabinet that is embedded in the package as a binary stream.
+    /// The `cabinet_name` is the value of the `Cabinet` column of a row in
+    /// the `Media` table, such as `"#Data1"`; the leading `#` (which marks
+    /// the cabinet as embedded rather than external) may be omitted.
+    pub fn open_embedded_cabinet(
+        &mut self,
+        cabinet_name: &str,
+    ) -> Result<CabinetReader<StreamReader<F>>> {
+        let stream_name =
+            cabinet_name.strip_prefix('#').unwrap_or(cabinet_name);
+        let cabinet = format!("#{stream_name}");
+        if !self.has_table(MEDIA_TABLE_NAME) {
+            not_found!("Package has no {} table", MEDIA_TABLE_NAME);
+        }
+        let query = Select::table(MEDIA_TABLE_NAME)
+            .columns(&["Cabinet"])
+            .with(Expr::col("Cabinet").eq(Expr::string(&cabinet)));
+        if self.select_rows(query)?.len() == 0 {
+            not_found!(
+                "No {} table row has an embedded cabinet named {:?}",
+                MEDIA_TABLE_NAME,
+                stream_name
+            );
+        }
+        CabinetReader::new(self.read_stream(stream_name)?)
+    }
+
+    /// Returns the raw contents of the package's digital signature stream
+    /// (a DER-encoded PKCS#7 `SignedData` blob), or `None` if the package
+    /// isn't signed.  The signature is not verified; this is intended for
+    /// passing to external verification tools.
+    pub fn digital_signature(&mut self) -> Result<Option<Vec<u8>>> {
+        if !self.has_digital_signature() {
+            return Ok(None);
+        }
+        let mut stream =
+            self.comp_mut().open_stream(DIGITAL_SIGNATURE_STREAM_NAME)?;
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data)?;
+        Ok(Some(data))
+    }
+
+    /// Returns the strings in the package's string pool that aren't used by
+    /// any cell of any table (including the table and column names stored in
+    /// the `_Tables` and `_Columns` tables), in string pool order.
+    ///
+    /// Deleting or updating rows releases the strings they used, so strings
+    /// are normally only left over in packages whose string pool refcounts
+    /// have drifted from the tables, or after a table is dropped with
+    /// [`drop_table`](Package::drop_table), which doesn't release the strings
+    /// used by the table's rows.  Such strings are harmless but take up
+    /// space; [`compact_into`](Package::compact_into) leaves them out.
+    pub fn unreferenced_strings(&mut self) -> Result<Vec<String>> {
+        let mut referenced = HashSet::<i32>::new();
+        for table in self.tables.values() {
+            for row in table.load_rows(self.comp.as_mut().unwrap())? {
+                for value_ref in row {
+                    if let ValueRef::Str(string_ref) = value_ref {
+                        referenced.insert(string_ref.number());
+                    }
+                }
+            }
+        }
+        Ok(self
+            .string_pool
+            .live_entries()
+            .filter(|(string_ref, _)| {
+                !referenced.contains(&string_ref.number())
+            })
+            .map(|(_, string)| string.to_string())
+            .collect())
+    }
+
+    /// Writes a compacted copy of this package to `dest` (which should be
+    /// initially empty), and returns the new package.
+    ///
+    /// Deleting rows, dropping tables, and removing streams leaves freed
+    /// sectors in the compound file, and strings that are no longer used in
+    /// the string pool; neither is reclaimed by later writes.  The copy is
+    /// built from scratch, with the same package type, sector size, summary
+    /// information, and code page, so it holds only the tables, rows, and
+    /// streams (including those in substorages) that are still in use.  The
+    /// copy's `_Validation` table holds this package's `_Validation` rows, if
+    /// it has that table.  Tables are written in order by name, and each
+    /// table's rows in order by primary key.  Any digital signature is left
+    /// out, since re-encoding the tables would invalidate it.  As with
+    /// [`Package::create`], the copy's summary information gets a default
+    /// title and creating application if this package's has none.
+    ///
+    /// To compact a package without making a copy, use
+    /// [`Package::compact`].
+    pub fn compact_into<G: Read + Write + Seek>(
+        &mut self,
+        dest: G,
+    ) -> Result<Package<G>> {
+        let options = CreateOptions::new()
+            .summary_info(self.summary_info.clone())
+            .sector_size(self.sector_size());
+        let mut package =
+            Package::create_impl(self.package_type, options, dest)?;
+        package.set_database_codepage(self.database_codepage())?;
+        package.set_lossy_encoding(self.lossy_encoding());
+        package.set_validate_on_insert(false);
+        let tables: Vec<Table> = self
+            .tables
+            .values()
+            .filter(|table| !is_reserved_table_name(table.name()))
+            .map(|table| table.as_ref().clone())
+            .collect();
+        for table in &tables {
+            package.create_table(table.name(), table.columns().to_vec())?;
+        }
+        if let Some(validation) = self.get_table(VALIDATION_TABLE_NAME) {
+            let validation = validation.clone();
+            package.delete_rows(Delete::from(VALIDATION_TABLE_NAME).all())?;
+            let rows = self.select_table_values(&validation)?;
+            package
+                .insert_rows(Insert::into(VALIDATION_TABLE_NAME).rows(rows))?;
+        }
+        for table in &tables {
+            let rows = self.select_table_values(table)?;
+            if !rows.is_empty() {
+                package.insert_rows(Insert::into(table.name()).rows(rows))?;
+            }
+        }
+
+        // Copy everything else in the compound file, other than the streams
+        // that hold the tables, string pool, and summary information, which
+        // were rewritten above, and the digital signature.
+        let entries: Vec<cfb::Entry> =
+            self.comp().walk().filter(|entry| !entry.is_root()).collect();
+        for entry in entries {
+            let path = entry.path();
+            if !entry.is_stream() {
+                let comp = package.comp_mut();
+                comp.create_storage(path)?;
+                comp.set_storage_clsid(path, *entry.clsid())?;
+                comp.set_created_time(path, entry.created())?;
+                comp.set_modified_time(path, entry.modified())?;
+                continue;
+            }
+            if path.parent() == Some(Path::new("/")) {
+                let name = path.file_name().unwrap_or_default();
+                let name = name.to_string_lossy();
+                if name.starts_with('\u{4840}')
+                    || name == SUMMARY_INFO_STREAM_NAME
+                    || name == DIGITAL_SIGNATURE_STREAM_NAME
+                    || name == MSI_DIGITAL_SIGNATURE_EX_STREAM_NAME
+                {
+                    continue;
+                }
+            }
+            let mut reader = self.comp_mut().open_stream(path)?;
+            let mut writer = package.comp_mut().create_stream(path)?;
+            io::copy(&mut reader, &mut writer)?;
+        }
+        package.set_validate_on_insert(self.validate_on_insert);
+        package.flush()?;
+        Ok(package)
+    }
+
+    /// Reads the values of all rows of the given table, in order by primary
+    /// key.
+    fn select_table_values(
+        &mut self,
+        table: &Table,
+    ) -> Result<Vec<Vec<Value>>> {
+        let mut rows: Vec<Vec<Value>> = self
+            .select_rows(Select::table(table.name()))?
+            .map(|row| {
+                (0..row.len()).map(|index| row[index].clone()).collect()
+            })
+            .collect();
+        let key_indices = table.primary_key_indices();
+        rows.sort_by_cached_key(|values| {
+            key_indices
+                .iter()
+                .map(|&index| values[index].clone())
+                .collect::<Vec<Value>>()
+        });
+        Ok(rows)
+    }
+
+    // TODO: pub fn has_valid_digital_signature(&mut self) -> io::Result<bool>
+}
+
+impl<F: Read + Write + Seek> Package<F> {
+    /// Creates a new, empty package of the given type, using the underlying
+    /// reader/writer.  The reader/writer should be initially empty.  The
+    /// package's summary information gets the default title for the package
+    /// type, a "template" property in the format that the package type uses
+    /// (see [`SummaryInfo::template`]), and [`DEFAULT_CREATING_APPLICATION`]
+    /// as its creating application; any of these can be changed with
+    /// [`summary_info_mut`](Package::summary_info_mut).
+    ///
+    /// The output is reproducible: performing the same sequence of operations
+    /// on two new packages produces byte-for-byte identical files.  No
+    /// timestamps or random UUIDs are written unless requested (for example,
+    /// with [`SummaryInfo::set_creation_time_to_now`]), and table metadata
+    /// and the string pool are always written in a fixed order.  (New strings
+    /// are added to the string pool in the order they're first used, filling
+    /// in the lowest-numbered entry freed by earlier deletions, if any.)
+    /// However, the layout of the underlying compound file does depend on the
+    /// order in which tables and streams are written, so two packages with
+    /// the same contents built in different orders may not be byte-for-byte
+    /// identical, unless they are created with
+    /// [`CreateOptions::canonical_layout`].
+    pub fn create(package_type: PackageType, inner: F) -> Result<Package<F>> {
+        Package::create_with_options(package_type, CreateOptions::new(), inner)
+    }
+
+    /// Creates a new, empty package of the given type with the given options,
+    /// using the underlying reader/writer.  The reader/writer should be
+    /// initially empty.
+    ///
+    /// Returns an error if the summary information's title is the standard
+    /// title of a different package type.
+    pub fn create_with_options(
+        package_type: PackageType,
+        options: CreateOptions,
+        inner: F,
+    ) -> Result<Package<F>> {
+        Package::create_impl(package_type, options, inner)
+    }
+
+    /// Creates a new, empty package of the given type, like
+    /// [`Package::create`], but with the given sector size for the
+    /// underlying compound file.  Some tools expect the 512-byte sectors
+    /// that Windows Installer usually writes, rather than the 4096-byte
+    /// sectors used by `create`.
+    pub fn create_with_sector_size(
+        package_type: PackageType,
+        inner: F,
+        sector_size: SectorSize,
+    ) -> Result<Package<F>> {
+        let options = CreateOptions::new().sector_size(sector_size);
+        Package::create_impl(package_type, options, inner)
+    }
+
+    /// Creates a new, empty package of the given type with the given summary
+    /// information (see `SummaryInfo::build`), using the underlying
+    /// reader/writer.  If the summary information has no title, template, or
+    /// creating application, they are filled in as for
+    /// [`create`](Package::create).  The database code page is taken from
+    /// the summary information's code page.
+    ///
+    /// Returns an error if the summary information's title is the standard
+    /// title of a different package type.
+    pub fn create_with_summary_info(
+        package_type: PackageType,
+        summary_info: SummaryInfo,
+        inner: F,
+    ) -> Result<Package<F>> {
+        let options = CreateOptions::new().summary_info(summary_info);
+        Package::create_impl(package_type, options, inner)
+    }
+
+    fn create_impl(
+        package_type: PackageType,
+        options: CreateOptions,
+        inner: F,
+    ) -> Result<Package<F>> {
+        let CreateOptions { mut summary_info, sector_size, canonical_layout } =
+            options;
+        match summary_info.title().and_then(PackageType::from_title) {
+            Some(title_type) if title_type != package_type => {
+                invalid_input!(
+                    "Cannot create {:?} package with summary information \
+                     title {:?}",
+                    package_type,
+                    summary_info.title().unwrap_or_default()
+                );
+            }
+            Some(_) => {}
+            None => {
+                if summary_info.title().is_none() {
+                    summary_info
+                        .set_title(package_type.default_title().to_string());
+                }
+            }
+        }
+        if summary_info.creating_application().is_none() {
+            summary_info
+                .set_creating_application(DEFAULT_CREATING_APPLICATION);
+        }
+        if summary_info.template().is_none() {
+            summary_info.set_template_string(package_type.default_template());
+        }
+        let mut comp = cfb::CompoundFile::create_with_version(
+            sector_size.version(),
+            inner,
+        )?;
+        comp.set_storage_clsid("/", package_type.clsid())?;
+        let string_pool = StringPool::new(summary_info.codepage());
+        let tables = {
+            let mut tables = BTreeMap::<String, Rc<Table>>::new();
+            let table = make_tables_table(string_pool.long_string_refs());
+            tables.insert(table.name().to_string(), table);
+            let table = make_columns_table(string_pool.long_string_refs());
+            tables.insert(table.name().to_string(), table);
+            tables
+        };
+        let mut package = Package {
+            comp: Some(comp),
+            package_type,
+            summary_info,
+            is_summary_info_modified: true,
+            string_pool,
+            tables,
+            validate_on_insert: true,
+            canonical_layout,
+            finisher: None,
+        };
+        package
+            .create_table(VALIDATION_TABLE_NAME, make_validation_columns())?;
+        package.flush()?;
+        debug_assert!(!package.is_summary_info_modified);
+        debug_assert!(!package.string_pool.is_modified());
+        Ok(package)
+    }
+
+    /// Returns a mutable reference to the summary information for this
+    /// package.  Call `flush()` or drop the `Package` object to persist any
+    /// changes made to the underlying writer.
+    pub fn summary_info_mut(&mut self) -> &mut SummaryInfo {
+        self.is_summary_info_modified = true;
+        self.set_finisher();
+        &mut self.summary_info
+    }
+
+    /// Sets the class ID stored on the root storage of the package's compound
+    /// file, and changes the package's type to the one that the class ID
+    /// identifies.  The summary information is left as it is, so if the new
+    /// type formats its "template" property differently (see
+    /// [`Package::check_summary_template`]), update that too, or
+    /// [`Package::open`] will reject the package.  Returns an error if
+    /// `clsid` isn't the [`PackageType::clsid`] of any package type.
+    pub fn set_root_clsid(&mut self, clsid: Uuid) -> Result<()> {
+        let package_type = match PackageType::from_clsid(&clsid) {
+            Some(package_type) => package_type,
+            None => invalid_input!(
+                "Unrecognized package CLSID ({})",
+                clsid.hyphenated()
+            ),
+        };
+        self.comp_mut().set_storage_clsid("/", clsid)?;
+        self.package_type = package_type;
+        self.set_finisher();
+        Ok(())
+    }
+
+    /// Sets the product codes of the products that this patch package can be
+    /// applied to.  Returns an error if this isn't a patch package.
+    pub fn set_patch_target_product_codes(
+        &mut self,
+        product_codes: &[Uuid],
+    ) -> Result<()> {
+        if self.package_type != PackageType::Patch {
+            invalid_input!(
+                "Cannot set target product codes for {:?} package",
+                self.package_type
+            );
+        }
+        let codes: Vec<String> = product_codes
+            .iter()
+            .map(|&code| Guid::from(code).to_string())
+            .collect();
+        self.summary_info_mut().set_template_string(codes.join(";"));
+        Ok(())
+    }
+
+    /// Sets the code page used for serializing strings in the database.
+    ///
+    /// Strings are held in memory as Unicode, so the existing string pool is
+    /// re-encoded into the new code page the next time the package is
+    /// flushed.  Returns an error, leaving the code page unchanged, if any
+    /// string already in the database cannot be represented in the new code
+    /// page.
+    ///
+    /// If [lossy encoding](Package::set_lossy_encoding) is enabled, the code
+    /// page is always changed, and any unrepresentable characters are
+    /// replaced with `'?'`.
+    ///
+    /// Code page 0 ([`CodePage::Neutral`]) marks the database as language
+    /// neutral.  Windows Installer expects such databases to contain only
+    /// ASCII strings; this library encodes them as Windows-1252.
+    pub fn set_database_codepage(&mut self, codepage: CodePage) -> Result<()> {
+        let unencodable = if self.string_pool.lossy_encoding() {
+            None
+        } else {
+            self.string_pool.find_unencodable(codepage)
+        };
+        if let Some(string) = unencodable {
+            invalid_input!(
+                "String {:?} cannot be represented in code page {} ({})",
+                string,
+                codepage.id(),
+                codepage.name()
+            );
+        }
+        self.set_finisher();
+        self.string_pool.set_codepage(codepage);
+        Ok(())
+    }
+
+    /// Sets whether `insert_rows` and `update_rows` should check string values
+    /// against their columns' categories (e.g. that a `Guid` column contains a
+    /// well-formed GUID) and enum values.  This is enabled by default; values
+    /// are always checked against their columns' types, lengths, and ranges
+    /// regardless.
+    pub fn set_validate_on_insert(&mut self, validate: bool) {
+        self.validate_on_insert = validate;
+    }
+
+    /// Sets whether strings containing characters that can't be represented
+    /// in the database code page may be stored anyway, with `'?'`
+    /// substituted for those characters.  This is disabled by default, so
+    /// that `insert_rows` and `update_rows` return an error for such strings
+    /// rather than silently losing data.
+    pub fn set_lossy_encoding(&mut self, lossy: bool) {
+        self.string_pool.set_lossy_encoding(lossy);
+    }
+
+    /// Sets the value of the given property in the package's `Property`
+    /// table, replacing any existing value.  If the package has no
+    /// `Property` table, one is created with the standard columns.
+    pub fn set_property(&mut self, name: &str, value: &str) -> Result<()> {
+        if !self.has_table(PROPERTY_TABLE_NAME) {
+            let columns = vec![
+                Column::build("Property").primary_key().id_string(72),
+                Column::build("Value").localizable().text_string(0),
+            ];
+            self.create_table(PROPERTY_TABLE_NAME, columns)?;
+        }
+        let query = Insert::into(PROPERTY_TABLE_NAME)
+            .row(vec![Value::from(name), Value::from(value)])
+            .replace();
+        self.insert_rows(query)
+    }
+
+    /// Sets the `ProductCode` property, which should be an uppercase GUID in
+    /// braces.
+    pub fn set_product_code(&mut self, value: &str) -> Result<()> {
+        self.set_property("ProductCode", value)
+    }
+
+    /// Sets the `ProductVersion` property (e.g. `"1.2.3"`).
+    pub fn set_product_version(&mut self, value: &str) -> Result<()> {
+        self.set_property("ProductVersion", value)
+    }
+
+    /// Sets the `ProductName` property.
+    pub fn set_product_name(&mut self, value: &str) -> Result<()> {
+        self.set_property("ProductName", value)
+    }
+
+    /// Sets the `Manufacturer` property.
+    pub fn set_manufacturer(&mut self, value: &str) -> Result<()> {
+        self.set_property("Manufacturer", value)
+    }
+
+    /// Sets the `UpgradeCode` property.  Unlike the product code, this should
+    /// stay the same across versions of the product.
+    pub fn set_upgrade_code(&mut self, value: &str) -> Result<()> {
+        self.set_property("UpgradeCode", value)
+    }
+
+    /// Creates a new database table.  Returns an error without modifying the
+    /// database if the table name or columns are invalid, or if a table with
+    /// that name already exists.
+    pub fn create_table<S: Into<String>>(
+        &mut self,
+        table_name: S,
+        columns: Vec<Column>,
+    ) -> Result<()> {
+        self.create_table_with_name(table_name.into(), columns)
+    }
+
+    fn create_table_with_name(
+        &mut self,
+        table_name: String,
+        columns: Vec<Column>,
+    ) -> Result<()> {
+        if !Table::is_valid_name(&table_name) {
+            invalid_input!("{:?} is not a valid table name", table_name);
         }
         if columns.is_empty() {
             invalid_input!("Cannot create a table with no columns");
@@ -608,11 +2430,27 @@ impl<F: Read + Write + Seek> Package<F> {
                         name
                     );
                 }
+                if let Some(value) = column.default_value() {
+                    if !column.is_valid_value(value) {
+                        invalid_input!(
+                            "{} is not a valid default value for column {:?}",
+                            value,
+                            name
+                        );
+                    }
+                }
                 column_names.insert(name);
             }
         }
-        if self.tables.contains_key(&table_name) {
-            already_exists!("Table {:?} already exists", table_name);
+        if let Some(existing) = find_table(&self.tables, &table_name) {
+            if existing.name() == table_name {
+                already_exists!("Table {:?} already exists", table_name);
+            }
+            already_exists!(
+                "Table {:?} already exists (as {:?})",
+                table_name,
+                existing.name()
+            );
         }
         self.insert_rows(
             Insert::into(COLUMNS_TABLE_NAME).rows(
@@ -636,44 +2474,7 @@ impl<F: Read + Write + Seek> Package<F> {
         )?;
         let validation_rows: Vec<Vec<Value>> = columns
             .iter()
-            .map(|column| {
-                let (min_value, max_value) =
-                    if let Some((min, max)) = column.value_range() {
-                        (Value::Int(min), Value::Int(max))
-                    } else {
-                        (Value::Null, Value::Null)
-                    };
-                let (key_table, key_column) =
-                    if let Some((table, column)) = column.foreign_key() {
-                        (Value::Str(table.to_string()), Value::Int(column))
-                    } else {
-                        (Value::Null, Value::Null)
-                    };
-                vec![
-                    Value::Str(table_name.clone()),
-                    Value::Str(column.name().to_string()),
-                    Value::Str(if column.is_nullable() {
-                        "Y".to_string()
-                    } else {
-                        "N".to_string()
-                    }),
-                    min_value,
-                    max_value,
-                    key_table,
-                    key_column,
-                    if let Some(category) = column.category() {
-                        Value::Str(category.to_string())
-                    } else {
-                        Value::Null
-                    },
-                    if let Some(values) = column.enum_values() {
-                        Value::Str(values.join(";"))
-                    } else {
-                        Value::Null
-                    },
-                    Value::Null,
-                ]
-            })
+            .map(|column| make_validation_row(&table_name, column))
             .collect();
         let long_string_refs = self.string_pool.long_string_refs();
         let table = Table::new(table_name.clone(), columns, long_string_refs);
@@ -686,17 +2487,19 @@ impl<F: Read + Write + Seek> Package<F> {
 
     /// Removes an existing database table.  Returns an error without modifying
     /// the database if the table name is invalid, or if no such table exists.
-    pub fn drop_table(&mut self, table_name: &str) -> io::Result<()> {
+    pub fn drop_table(&mut self, table_name: &str) -> Result<()> {
         if is_reserved_table_name(table_name) {
             invalid_input!("Cannot drop special {:?} table", table_name);
         }
         if !Table::is_valid_name(table_name) {
             invalid_input!("{:?} is not a valid table name", table_name);
         }
-        if !self.tables.contains_key(table_name) {
-            not_found!("Table {:?} does not exist", table_name);
-        }
-        let stream_name = self.tables.get(table_name).unwrap().stream_name();
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table.clone(),
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        let table_name = table.name();
+        let stream_name = table.stream_name();
         if self.comp().exists(&stream_name) {
             self.comp_mut().remove_stream(&stream_name)?;
         }
@@ -716,63 +2519,566 @@ impl<F: Read + Write + Seek> Package<F> {
         Ok(())
     }
 
-    /// Attempts to execute a delete query.  Returns an error without modifying
-    /// the database if the query fails (e.g. due to the table not existing).
-    pub fn delete_rows(&mut self, query: Delete) -> io::Result<()> {
+    /// Appends a new column to an existing database table.  Existing rows
+    /// are given the column's default value, or null if it has no default.
+    /// Returns an error without modifying the database if the column is
+    /// invalid (including if its `_Validation` row can't be stored), if it's
+    /// a primary key column, or if it can't be null and has no default but
+    /// the table already has rows.
+    pub fn add_column(
+        &mut self,
+        table_name: &str,
+        column: Column,
+    ) -> Result<()> {
+        if is_reserved_table_name(table_name) {
+            invalid_input!("Cannot alter special {:?} table", table_name);
+        }
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table.clone(),
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        let table_name = table.name();
+        if !Column::is_valid_name(column.name()) {
+            invalid_input!("{:?} is not a valid column name", column.name());
+        }
+        if table.has_column(column.name()) {
+            already_exists!(
+                "Table {:?} already has a column named {:?}",
+                table_name,
+                column.name()
+            );
+        }
+        if table.columns().len() >= MAX_NUM_TABLE_COLUMNS {
+            invalid_input!(
+                "Cannot have more than {} columns in a table",
+                MAX_NUM_TABLE_COLUMNS
+            );
+        }
+        if column.is_primary_key() {
+            invalid_input!(
+                "Cannot add primary key column {:?} to existing table {:?}",
+                column.name(),
+                table_name
+            );
+        }
+        if let Some(value) = column.default_value() {
+            if !column.is_valid_value(value) {
+                invalid_input!(
+                    "{} is not a valid default value for column {:?}",
+                    value,
+                    column.name()
+                );
+            }
+        }
+        let mut rows = table.load_rows(self.comp.as_mut().unwrap())?;
+        let fill_value =
+            column.default_value().cloned().unwrap_or(Value::Null);
+        if !rows.is_empty() && !column.is_valid_value(&fill_value) {
+            invalid_input!(
+                "Cannot add non-nullable column {:?} with no default value to \
+                 non-empty table {:?}",
+                column.name(),
+                table_name
+            );
+        }
+        if let Some(string) = fill_value.as_str() {
+            self.string_pool.check_encodable(string)?;
+        }
+        let column_number = 1 + table.columns().len() as i32;
+        let columns_row = vec![
+            Value::from(table_name),
+            Value::Int(column_number),
+            Value::from(column.name()),
+            Value::Int(column.bitfield()),
+        ];
+        let validation_row = make_validation_row(table_name, &column);
+        self.check_new_row(COLUMNS_TABLE_NAME, &columns_row)?;
+        self.check_new_row(VALIDATION_TABLE_NAME, &validation_row)?;
+        self.insert_rows(Insert::into(COLUMNS_TABLE_NAME).row(columns_row))?;
+        let columns_key = Expr::col("Table")
+            .eq(Expr::string(table_name))
+            .and(Expr::col("Number").eq(Expr::integer(column_number)));
+        let result = self.insert_rows(
+            Insert::into(VALIDATION_TABLE_NAME).row(validation_row),
+        );
+        if let Err(error) = result {
+            self.delete_rows(
+                Delete::from(COLUMNS_TABLE_NAME).with(columns_key),
+            )?;
+            return Err(error);
+        }
+        let column_name = column.name().to_string();
+        for row in rows.iter_mut() {
+            row.push(ValueRef::create(
+                fill_value.clone(),
+                &mut self.string_pool,
+            ));
+        }
+        let mut columns = table.columns().to_vec();
+        columns.push(column);
+        let table = Table::new(
+            table_name.to_string(),
+            columns,
+            table.long_string_refs(),
+        );
+        if let Err(error) = table.store_rows(self.comp.as_mut().unwrap(), rows)
+        {
+            // Put the schema back the way it was, so that it still matches
+            // the table's data.
+            self.delete_rows(
+                Delete::from(COLUMNS_TABLE_NAME).with(columns_key),
+            )?;
+            self.delete_rows(
+                Delete::from(VALIDATION_TABLE_NAME).with(
+                    Expr::col("Table").eq(Expr::string(table_name)).and(
+                        Expr::col("Column").eq(Expr::string(column_name)),
+                    ),
+                ),
+            )?;
+            return Err(error.into());
+        }
+        self.tables.insert(table_name.to_string(), table);
+        Ok(())
+    }
+
+    /// Checks that `values` could be inserted as a new row of the named
+    /// table, without inserting it, so that a change spanning several tables
+    /// can be rejected before any of them is modified.
+    fn check_new_row(&self, table_name: &str, values: &[Value]) -> Result<()> {
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table,
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        for (column, value) in table.columns().iter().zip(values) {
+            column.check_value(table_name, value, self.validate_on_insert)?;
+            if let Some(string) = value.as_str() {
+                self.string_pool.check_encodable(string)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes a column from an existing database table, discarding its
+    /// value from each row.  Returns an error without modifying the database
+    /// if the column doesn't exist, or if it's a primary key column or the
+    /// table's only column.
+    pub fn drop_column(
+        &mut self,
+        table_name: &str,
+        column_name: &str,
+    ) -> Result<()> {
+        if is_reserved_table_name(table_name) {
+            invalid_input!("Cannot alter special {:?} table", table_name);
+        }
+        let table = match find_table(&self.tables, table_name) {
+            Some(table) => table.clone(),
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        let table_name = table.name();
+        let index = match table.index_for_column_name(column_name) {
+            Some(index) => index,
+            None => not_found!(
+                "Table {:?} has no column named {:?}",
+                table_name,
+                column_name
+            ),
+        };
+        if table.columns()[index].is_primary_key() {
+            invalid_input!(
+                "Cannot drop primary key column {:?} of table {:?}",
+                column_name,
+                table_name
+            );
+        }
+        if table.columns().len() == 1 {
+            invalid_input!(
+                "Cannot drop the only column of table {:?}",
+                table_name
+            );
+        }
+        let mut rows = table.load_rows(self.comp.as_mut().unwrap())?;
+        let mut columns = table.columns().to_vec();
+        columns.remove(index);
+        // Rewrite the table's _Columns rows, since the later columns'
+        // numbers all shift down by one.
+        self.delete_rows(
+            Delete::from(COLUMNS_TABLE_NAME)
+                .with(Expr::col("Table").eq(Expr::string(table_name))),
+        )?;
+        self.insert_rows(
+            Insert::into(COLUMNS_TABLE_NAME).rows(
+                columns
+                    .iter()
+                    .enumerate()
+                    .map(|(index, column)| {
+                        vec![
+                            Value::from(table_name),
+                            Value::Int(1 + index as i32),
+                            Value::from(column.name()),
+                            Value::Int(column.bitfield()),
+                        ]
+                    })
+                    .collect(),
+            ),
+        )?;
+        self.delete_rows(
+            Delete::from(VALIDATION_TABLE_NAME).with(
+                Expr::col("Table").eq(Expr::string(table_name)).and(
+                    Expr::col("Column")
+                        .eq(Expr::string(table.columns()[index].name())),
+                ),
+            ),
+        )?;
+        for row in rows.iter_mut() {
+            row.remove(index).remove(&mut self.string_pool);
+        }
+        let table = Table::new(
+            table_name.to_string(),
+            columns,
+            table.long_string_refs(),
+        );
+        table.store_rows(self.comp.as_mut().unwrap(), rows)?;
+        self.tables.insert(table_name.to_string(), table);
+        Ok(())
+    }
+
+    /// Attempts to execute a delete query, and returns the number of rows
+    /// that were deleted.  Returns an error without modifying the database if
+    /// the query fails (e.g. due to the table not existing).
+    pub fn delete_rows(&mut self, query: Delete) -> Result<usize> {
         self.set_finisher();
-        query.exec(
+        Ok(query.exec(
             self.comp.as_mut().unwrap(),
             &mut self.string_pool,
             &self.tables,
-        )
+        )?)
     }
 
     /// Attempts to execute an insert query.  Returns an error without
     /// modifying the database if the query fails (e.g. due to values being
     /// invalid, or keys not being unique, or the table not existing).
-    pub fn insert_rows(&mut self, query: Insert) -> io::Result<()> {
+    ///
+    /// Each query reads the table and rewrites its stream once, no matter how
+    /// many rows it inserts, so adding many rows with a single query (see
+    /// [`Insert::rows`]) takes O(n) time overall, whereas executing a
+    /// separate query for each of n rows takes O(n²) time.
+    pub fn insert_rows(&mut self, query: Insert) -> Result<()> {
         self.set_finisher();
-        query.exec(
+        Ok(query.exec(
             self.comp.as_mut().unwrap(),
             &mut self.string_pool,
             &self.tables,
-        )
+            self.validate_on_insert,
+        )?)
     }
 
-    /// Attempts to execute an update query.  Returns an error without
-    /// modifying the database if the query fails (e.g. due to values being
-    /// invalid, or column names being incorrect, or the table not existing).
-    pub fn update_rows(&mut self, query: Update) -> io::Result<()> {
+    /// Attempts to execute an update query, and returns the number of rows
+    /// that matched the query's condition (and so were updated).  Returns an
+    /// error without modifying the database if the query fails (e.g. due to
+    /// values being invalid, or column names being incorrect, or the table not
+    /// existing).
+    pub fn update_rows(&mut self, query: Update) -> Result<usize> {
         self.set_finisher();
-        query.exec(
+        Ok(query.exec(
             self.comp.as_mut().unwrap(),
             &mut self.string_pool,
             &self.tables,
-        )
+            self.validate_on_insert,
+        )?)
+    }
+
+    /// Applies the row insertions, modifications, and deletions recorded in a
+    /// transform (.mst) file to this package.  By default, inserting a row
+    /// that already exists or modifying or deleting a row that doesn't exist
+    /// is an error; the `flags` can be used to skip such changes instead.
+    ///
+    /// Changes are applied in order, so if an error occurs partway through,
+    /// the changes before it will already have been made.  Transforms that
+    /// add or remove tables or columns are not yet supported.
+    pub fn apply_transform<R: Read + Seek>(
+        &mut self,
+        transform: R,
+        flags: TransformFlags,
+    ) -> Result<()> {
+        let transform = Transform::read(transform, &self.tables)?;
+        for (table_name, changes) in transform.into_changes() {
+            let table = self.tables[&table_name].clone();
+            let key_indices = table.primary_key_indices();
+            for change in changes {
+                match change {
+                    RowChange::Insert(values) => {
+                        let key: Vec<Value> = key_indices
+                            .iter()
+                            .map(|&index| values[index].clone())
+                            .collect();
+                        if self.get_row(&table_name, &key)?.is_some() {
+                            if flags.contains(
+                                TransformFlags::IGNORE_ADD_EXISTING_ROW,
+                            ) {
+                                continue;
+                            }
+                            already_exists!(
+                                "Transform adds row {} to table {:?}, which \
+                                 already has a row with that key",
+                                format_key(&key),
+                                table_name
+                            );
+                        }
+                        let query =
+                            Insert::into(table_name.as_str()).row(values);
+                        self.insert_rows(query)?;
+                    }
+                    RowChange::Update { key, values } => {
+                        let mut query = Update::table(table_name.as_str())
+                            .with(key_condition(&table, &key));
+                        for (column_name, value) in values {
+                            query = query.set(column_name, value);
+                        }
+                        if self.update_rows(query)? == 0
+                            && !flags.contains(
+                                TransformFlags::IGNORE_UPDATE_MISSING_ROW,
+                            )
+                        {
+                            not_found!(
+                                "Transform modifies row {} of table {:?}, \
+                                 which does not exist",
+                                format_key(&key),
+                                table_name
+                            );
+                        }
+                    }
+                    RowChange::Delete(key) => {
+                        let query = Delete::from(table_name.as_str())
+                            .with(key_condition(&table, &key));
+                        if self.delete_rows(query)? == 0
+                            && !flags.contains(
+                                TransformFlags::IGNORE_DELETE_MISSING_ROW,
+                            )
+                        {
+                            not_found!(
+                                "Transform deletes row {} of table {:?}, \
+                                 which does not exist",
+                                format_key(&key),
+                                table_name
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the file hash of the given file contents (see
+    /// [`compute_file_hash`](Package::compute_file_hash)) and stores it in the
+    /// `MsiFileHash` table for the given `File` table key, replacing any
+    /// existing hash for that file.  The `MsiFileHash` table is created if it
+    /// doesn't exist yet.
+    pub fn insert_file_hash(
+        &mut self,
+        file_key: &str,
+        data: &[u8],
+    ) -> Result<()> {
+        if !self.has_table(MSI_FILE_HASH_TABLE_NAME) {
+            self.create_table(
+                MSI_FILE_HASH_TABLE_NAME,
+                vec![
+                    Column::build("File_")
+                        .primary_key()
+                        .foreign_key("File", 1)
+                        .id_string(72),
+                    Column::build("Options").range(0, 0).int16(),
+                    Column::build("HashPart1").int32(),
+                    Column::build("HashPart2").int32(),
+                    Column::build("HashPart3").int32(),
+                    Column::build("HashPart4").int32(),
+                ],
+            )?;
+        }
+        let [part1, part2, part3, part4] =
+            Package::<F>::compute_file_hash(data);
+        let row = vec![
+            Value::from(file_key),
+            Value::Int(0),
+            Value::Int(part1),
+            Value::Int(part2),
+            Value::Int(part3),
+            Value::Int(part4),
+        ];
+        // Validate the row before deleting any existing hash for this file.
+        let table = self.get_table(MSI_FILE_HASH_TABLE_NAME).unwrap();
+        for (column, value) in table.columns().iter().zip(row.iter()) {
+            column.check_value(MSI_FILE_HASH_TABLE_NAME, value, true)?;
+        }
+        self.delete_rows(
+            Delete::from(MSI_FILE_HASH_TABLE_NAME)
+                .with(Expr::col("File_").eq(Expr::string(file_key))),
+        )?;
+        self.insert_rows(Insert::into(MSI_FILE_HASH_TABLE_NAME).row(row))
+    }
+
+    /// Imports a table from an IDT archive file, in the format produced by
+    /// `msidb -e` or msitools' `msiexport`.  The table is created from the
+    /// file's header if it doesn't exist yet; otherwise, the header's column
+    /// names must match those of the existing table.  The file's rows are
+    /// then inserted into the table.  A `_ForceCodepage` file sets the
+    /// database codepage instead.
+    ///
+    /// Binary columns are imported as they appear in the file (i.e. as the
+    /// name of the file holding the stream data); the streams themselves are
+    /// not imported.
+    pub fn import_idt<R: Read>(&mut self, reader: R) -> Result<()> {
+        let file = idt::parse(reader, self.database_codepage())?;
+        if let Some(codepage) = file.codepage {
+            return self.set_database_codepage(codepage);
+        }
+        if let Some(table) = find_table(&self.tables, &file.table_name) {
+            let expected: Vec<&str> =
+                table.columns().iter().map(Column::name).collect();
+            let actual: Vec<&str> =
+                file.columns.iter().map(Column::name).collect();
+            if expected != actual {
+                invalid_data!(
+                    "IDT columns {:?} do not match the columns of existing \
+                     table {:?} ({:?})",
+                    actual,
+                    file.table_name,
+                    expected
+                );
+            }
+        } else {
+            self.create_table(file.table_name.clone(), file.columns)?;
+        }
+        self.insert_rows(Insert::into(file.table_name).rows(file.rows))
     }
 
     /// Creates (or overwrites) a binary stream in the package.
+    ///
+    /// Data written to the returned writer goes directly into the underlying
+    /// compound file, a sector at a time; it is not held in memory until the
+    /// package is flushed, so streams of any size can be written without
+    /// staging them elsewhere first.
     pub fn write_stream(
         &mut self,
         stream_name: &str,
-    ) -> io::Result<StreamWriter<F>> {
+    ) -> Result<StreamWriter<F>> {
         if !streamname::is_valid(stream_name, false) {
             invalid_input!("{:?} is not a valid stream name", stream_name);
         }
         let encoded_name = streamname::encode(stream_name, false);
+        self.set_finisher();
         Ok(StreamWriter::new(self.comp_mut().create_stream(&encoded_name)?))
     }
 
-    /// Removes an existing binary stream from the package.
-    pub fn remove_stream(&mut self, stream_name: &str) -> io::Result<()> {
+    /// Creates (or overwrites) a binary stream in the package, filling it
+    /// with everything read from `reader`, and returns the number of bytes
+    /// written.  The data is copied in chunks rather than read into memory
+    /// all at once, so this is suitable for embedding large payloads such as
+    /// cabinet files.
+    pub fn write_stream_from<R: Read>(
+        &mut self,
+        stream_name: &str,
+        mut reader: R,
+    ) -> Result<u64> {
+        let mut writer = self.write_stream(stream_name)?;
+        let num_bytes = io::copy(&mut reader, &mut writer)?;
+        writer.flush()?;
+        Ok(num_bytes)
+    }
+
+    /// Copies a binary stream from another package into this one, without
+    /// reading the whole stream into memory.  Returns an error if the stream
+    /// doesn't exist in `source`, or if a stream with that name already exists
+    /// in this package.
+    pub fn copy_stream_from<G: Read + Seek>(
+        &mut self,
+        source: &mut Package<G>,
+        stream_name: &str,
+    ) -> Result<()> {
+        if self.has_stream(stream_name) {
+            already_exists!("Stream {:?} already exists", stream_name);
+        }
+        let mut reader = source.read_stream(stream_name)?;
+        let mut writer = self.write_stream(stream_name)?;
+        io::copy(&mut reader, &mut writer)?;
+        Ok(())
+    }
+
+    /// Removes a binary stream from the package, returning true if the
+    /// stream existed, or false if there was no such stream.  The streams
+    /// that hold the package's tables, string pool, and summary information
+    /// cannot be removed this way.
+    pub fn remove_stream(&mut self, stream_name: &str) -> Result<bool> {
         if !streamname::is_valid(stream_name, false) {
             invalid_input!("{:?} is not a valid stream name", stream_name);
         }
+        if is_internal_stream_name(stream_name) {
+            invalid_input!(
+                "Cannot remove internal stream {:?} of the package",
+                stream_name
+            );
+        }
         let encoded_name = streamname::encode(stream_name, false);
         if !self.comp().is_stream(&encoded_name) {
-            not_found!("Stream {:?} does not exist", stream_name);
+            return Ok(false);
+        }
+        self.set_finisher();
+        self.comp_mut().remove_stream(&encoded_name)?;
+        Ok(true)
+    }
+
+    /// Renames a binary stream in the package.  Any table rows that refer
+    /// to the stream by name (such as the `Name` column of the `Binary` or
+    /// `Icon` table) are not updated; that is left to the caller.
+    ///
+    /// Returns an error if `old_name` doesn't exist, if `new_name` already
+    /// exists, or if either name is invalid or belongs to one of the streams
+    /// that hold the package's tables, string pool, and summary information.
+    pub fn rename_stream(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<()> {
+        for stream_name in [old_name, new_name] {
+            if !streamname::is_valid(stream_name, false) {
+                invalid_input!("{:?} is not a valid stream name", stream_name);
+            }
+            if is_internal_stream_name(stream_name) {
+                invalid_input!(
+                    "Cannot rename internal stream {:?} of the package",
+                    stream_name
+                );
+            }
+        }
+        let old_encoded = streamname::encode(old_name, false);
+        let new_encoded = streamname::encode(new_name, false);
+        if !self.comp().is_stream(&old_encoded) {
+            not_found!("Stream {:?} does not exist", old_name);
+        }
+        if self.comp().exists(&new_encoded) {
+            already_exists!("Stream {:?} already exists", new_name);
+        }
+        // The compound file can't rename entries, so copy the data across a
+        // chunk at a time (each stream borrows the compound file, so only one
+        // can be open at once) and then remove the original.
+        self.set_finisher();
+        self.comp_mut().create_stream(&new_encoded)?;
+        let mut buffer = vec![0u8; RENAME_STREAM_CHUNK_SIZE];
+        let mut offset = 0;
+        loop {
+            let mut reader = self.comp_mut().open_stream(&old_encoded)?;
+            reader.seek(io::SeekFrom::Start(offset))?;
+            let num_bytes = reader.read(&mut buffer)?;
+            if num_bytes == 0 {
+                break;
+            }
+            let mut writer = self.comp_mut().open_stream(&new_encoded)?;
+            writer.seek(io::SeekFrom::Start(offset))?;
+            writer.write_all(&buffer[..num_bytes])?;
+            offset += num_bytes as u64;
         }
-        self.comp_mut().remove_stream(&encoded_name)
+        self.comp_mut().remove_stream(&old_encoded)?;
+        Ok(())
     }
 
     // TODO: pub fn add_digital_signature(&mut self, ...) -> io::Result<()>
@@ -780,7 +3086,8 @@ impl<F: Read + Write + Seek> Package<F> {
     /// Removes any existing digital signature from the package.  This can be
     /// useful if you need to modify a signed package (which will invalidate
     /// the signature).
-    pub fn remove_digital_signature(&mut self) -> io::Result<()> {
+    pub fn remove_digital_signature(&mut self) -> Result<()> {
+        self.set_finisher();
         if self.comp().is_stream(DIGITAL_SIGNATURE_STREAM_NAME) {
             self.comp_mut().remove_stream(DIGITAL_SIGNATURE_STREAM_NAME)?;
         }
@@ -792,11 +3099,67 @@ impl<F: Read + Write + Seek> Package<F> {
     }
 
     /// Flushes any buffered changes to the underlying writer.
-    pub fn flush(&mut self) -> io::Result<()> {
+    ///
+    /// Only data that has changed since the package was opened is written:
+    /// table streams are rewritten when their rows change, the summary
+    /// information and string pool are rewritten only if they were modified,
+    /// and other streams (such as `Binary` table data) are left untouched.
+    /// (A package created with [`CreateOptions::canonical_layout`] is instead
+    /// rewritten in full whenever anything has changed, which drops any
+    /// digital signature.)
+    pub fn flush(&mut self) -> Result<()> {
         if let Some(finisher) = self.finisher.take() {
-            finisher.finish(self)?;
+            if let Err(error) = finisher.finish(self) {
+                // Keep the changes pending, so that flushing can be retried.
+                self.finisher = Some(finisher);
+                return Err(error.into());
+            }
         }
-        self.comp_mut().flush()
+        Ok(self.comp_mut().flush()?)
+    }
+
+    /// Compacts the package in place, as if by writing a copy with
+    /// [`Package::compact_into`] back over the underlying writer, and returns
+    /// the size in bytes of the package before and after compacting.  Any
+    /// pending changes are flushed first, and, as with `compact_into`, any
+    /// digital signature is dropped.  If writing the compacted package fails
+    /// partway, the old contents are written back before the error is
+    /// returned.
+    ///
+    /// The underlying writer can't be truncated, so the bytes past the new
+    /// size are zeroed rather than removed; to reclaim that space on disk,
+    /// truncate the file to the returned size afterwards (e.g. with
+    /// [`File::set_len`](std::fs::File::set_len)).
+    pub fn compact(&mut self) -> Result<(u64, u64)> {
+        self.flush()?;
+        self.rewrite_in_place()
+    }
+
+    /// Rebuilds the underlying compound file from scratch, as
+    /// [`Package::compact_into`] does, and writes it back over the
+    /// underlying writer.  Returns the size of the compound file before and
+    /// after rebuilding it.
+    fn rewrite_in_place(&mut self) -> Result<(u64, u64)> {
+        let data = self
+            .compact_into(io::Cursor::new(Vec::new()))?
+            .into_inner()?
+            .into_inner();
+        // Make sure the new image opens before touching the underlying
+        // writer, and keep the tables and string pool read from it.
+        let mut package = Package::open(io::Cursor::new(data))?;
+        let data = package.comp.take().unwrap().into_inner().into_inner();
+        let mut inner = self.comp.take().unwrap().into_inner();
+        let result = overwrite_image(&mut inner, &data);
+        // Even if the overwrite failed, the old image has been written back,
+        // so the package can carry on with its old state.
+        self.comp = Some(cfb::CompoundFile::open(inner)?);
+        let old_len = result?;
+        self.string_pool = std::mem::replace(
+            &mut package.string_pool,
+            StringPool::new(CodePage::default()),
+        );
+        self.tables = std::mem::take(&mut package.tables);
+        Ok((old_len, data.len() as u64))
     }
 
     fn set_finisher(&mut self) {
@@ -807,6 +3170,70 @@ impl<F: Read + Write + Seek> Package<F> {
     }
 }
 
+impl Package<fs::File> {
+    /// Opens an existing MSI file at the given path for reading and writing.
+    /// (To open a read-only file, use [`msi::open`](crate::open) instead.)
+    pub fn open_path<P: AsRef<Path>>(path: P) -> Result<Package<fs::File>> {
+        let file = fs::OpenOptions::new().read(true).write(true).open(path)?;
+        Package::open(file)
+    }
+
+    /// Creates a new, empty package of the given type in a new file at the
+    /// given path.  Returns an error if a file already exists at that path.
+    pub fn create_path<P: AsRef<Path>>(
+        package_type: PackageType,
+        path: P,
+    ) -> Result<Package<fs::File>> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        Package::create(package_type, file)
+    }
+
+    /// Creates a new, empty package of the given type in a file at the given
+    /// path, replacing the file's contents if it already exists.
+    pub fn create_path_overwrite<P: AsRef<Path>>(
+        package_type: PackageType,
+        path: P,
+    ) -> Result<Package<fs::File>> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Package::create(package_type, file)
+    }
+}
+
+/// Replaces the contents of `inner` with `data`.  The writer can't be
+/// truncated, so whatever is left of a larger old image is zeroed; the
+/// compound file treats it as free sectors.  If writing fails partway, the
+/// old image is written back before the error is returned.  Returns the
+/// length of the old image.
+fn overwrite_image<F: Read + Write + Seek>(
+    inner: &mut F,
+    data: &[u8],
+) -> io::Result<u64> {
+    let mut old_data = Vec::new();
+    inner.rewind()?;
+    inner.read_to_end(&mut old_data)?;
+    let write_image = |inner: &mut F, data: &[u8]| -> io::Result<()> {
+        inner.rewind()?;
+        inner.write_all(data)?;
+        let leftover = old_data.len().saturating_sub(data.len()) as u64;
+        io::copy(&mut io::repeat(0).take(leftover), inner)?;
+        inner.flush()
+    };
+    if let Err(error) = write_image(inner, data) {
+        write_image(inner, &old_data)?;
+        return Err(error);
+    }
+    Ok(old_data.len() as u64)
+}
+
 impl<F> Drop for Package<F> {
     fn drop(&mut self) {
         if let Some(finisher) = self.finisher.take() {
@@ -819,7 +3246,8 @@ impl<F> Drop for Package<F> {
 
 /// An iterator over the database tables in a package.
 ///
-/// No guarantees are made about the order in which items are returned.
+/// Tables are returned in order by name (comparing bytewise), so the order is
+/// the same every time a given package is read.
 #[derive(Clone)]
 pub struct Tables<'a> {
     iter: btree_map::Values<'a, String, Rc<Table>>,
@@ -873,6 +3301,9 @@ impl<F: Read + Write + Seek> Finish<F> for FinishImpl {
             }
             package.string_pool.mark_unmodified();
         }
+        if package.canonical_layout {
+            package.rewrite_in_place()?;
+        }
         Ok(())
     }
 }
@@ -895,7 +3326,7 @@ mod tests {
         let mut package =
             Package::create(PackageType::Installer, cursor).expect("create");
         assert_eq!(package.database_codepage(), CodePage::Utf8);
-        package.set_database_codepage(CodePage::MacintoshRoman);
+        package.set_database_codepage(CodePage::MacintoshRoman).unwrap();
         assert_eq!(package.database_codepage(), CodePage::MacintoshRoman);
 
         let cursor = package.into_inner().expect("into_inner");
@@ -969,13 +3400,30 @@ mod tests {
                     .row(vec![Value::Int(3), Value::Int(17)]),
             )
             .expect("insert_rows");
-        package
+        let num_updated = package
             .update_rows(
                 Update::table("Mapping")
                     .set("Value", Value::Int(-5))
                     .with(Expr::col("Value").eq(Expr::integer(17))),
             )
             .unwrap();
+        assert_eq!(num_updated, 2);
+        let num_updated = package
+            .update_rows(
+                Update::table("Mapping")
+                    .set("Value", Value::Int(0))
+                    .with(Expr::col("Key").eq(Expr::integer(7))),
+            )
+            .unwrap();
+        assert_eq!(num_updated, 0);
+        let num_updated = package
+            .update_rows(
+                Update::table("Mapping")
+                    .set("Value", Value::Int(42))
+                    .with(Expr::col("Key").eq(Expr::integer(2))),
+            )
+            .unwrap();
+        assert_eq!(num_updated, 1);
 
         let cursor = package.into_inner().expect("into_inner");
         let mut package = Package::open(cursor).expect("open");
@@ -985,6 +3433,41 @@ mod tests {
             .collect();
         assert_eq!(values, vec![(1, -5), (2, 42), (3, -5)]);
     }
+
+    #[test]
+    fn drop_column_releases_strings() {
+        let cursor = Cursor::new(Vec::new());
+        let mut package =
+            Package::create(PackageType::Installer, cursor).expect("create");
+        let columns = vec![
+            Column::build("Key").primary_key().int16(),
+            Column::build("Note").nullable().string(0),
+        ];
+        package.create_table("Notes", columns).expect("create_table");
+        package
+            .insert_rows(
+                Insert::into("Notes")
+                    .row(vec![Value::Int(1), Value::from("Unique")])
+                    .row(vec![Value::Int(2), Value::from("Shared")])
+                    .row(vec![Value::Int(3), Value::from("Shared")]),
+            )
+            .expect("insert_rows");
+        package.drop_column("Notes", "Note").expect("drop_column");
+        let num_strings = package.string_pool.num_strings();
+        // The entries freed by dropping the column should be reused, rather
+        // than adding new entries to the string pool.
+        package
+            .add_column("Notes", Column::build("Extra").nullable().string(0))
+            .expect("add_column");
+        package
+            .insert_rows(
+                Insert::into("Notes")
+                    .row(vec![Value::Int(4), Value::from("Foo")])
+                    .row(vec![Value::Int(5), Value::from("Bar")]),
+            )
+            .expect("insert_rows");
+        assert_eq!(package.string_pool.num_strings(), num_strings);
+    }
 }
 
 // ========================================================================= //
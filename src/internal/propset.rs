@@ -35,6 +35,7 @@ impl PropertyFormatVersion {
 
 // ========================================================================= //
 
+#[derive(Clone, Copy)]
 pub enum OperatingSystem {
     Win16,
     Macintosh,
@@ -43,14 +44,23 @@ pub enum OperatingSystem {
 
 // ========================================================================= //
 
-#[derive(Debug, Eq, PartialEq)]
+/// The value of a property in a property set, such as a package's summary
+/// information.
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum PropertyValue {
+    /// No value (`VT_EMPTY`).
     Empty,
+    /// A null value (`VT_NULL`).
     Null,
+    /// A signed 8-bit integer (`VT_I1`).
     I1(i8),
+    /// A signed 16-bit integer (`VT_I2`).
     I2(i16),
+    /// A signed 32-bit integer (`VT_I4`).
     I4(i32),
+    /// A string, encoded in the property set's codepage (`VT_LPSTR`).
     LpStr(String),
+    /// A timestamp (`VT_FILETIME`).
     FileTime(Timestamp),
 }
 
@@ -76,7 +86,7 @@ impl PropertyValue {
                 if reader.read_u8()? != 0 {
                     invalid_data!("Property set string not null-terminated");
                 }
-                Ok(PropertyValue::LpStr(codepage.decode(&bytes)))
+                Ok(PropertyValue::LpStr(codepage.decode(&bytes).into_owned()))
             }
             64 => {
                 let timestamp = Timestamp::read_from(&mut reader)?;
@@ -138,9 +148,9 @@ impl PropertyValue {
         Ok(())
     }
 
-    /// Returns the number of bytes, including any padding bytes, that will be
-    /// written by the `write()` method.  Always returns a multiple of four.
-    fn size_including_padding(&self) -> u32 {
+    /// Returns the number of bytes that `write` will produce for this value
+    /// when strings are encoded with the given codepage.
+    fn size_including_padding(&self, codepage: CodePage) -> u32 {
         match self {
             PropertyValue::Empty => 4,
             PropertyValue::Null => 4,
@@ -148,7 +158,8 @@ impl PropertyValue {
             PropertyValue::I2(_) => 8,
             PropertyValue::I4(_) => 8,
             PropertyValue::LpStr(ref string) => {
-                ((12 + string.len() as u32) >> 2) << 2
+                let length = codepage.encode(string.as_str()).len() as u32;
+                ((12 + length) >> 2) << 2
             }
             PropertyValue::FileTime(_) => 12,
         }
@@ -179,6 +190,7 @@ impl PropertyValue {
 
 // ========================================================================= //
 
+#[derive(Clone)]
 pub struct PropertySet {
     os: OperatingSystem,
     os_version: u16,
@@ -339,7 +351,7 @@ impl PropertySet {
         let mut property_offsets: Vec<u32> = Vec::new();
         for value in self.properties.values() {
             property_offsets.push(section_size);
-            section_size += value.size_including_padding();
+            section_size += value.size_including_padding(self.codepage);
         }
         writer.write_u32::<LittleEndian>(section_size)?;
         writer.write_u32::<LittleEndian>(num_properties)?;
@@ -499,18 +511,20 @@ mod tests {
             PropertyValue::LpStr("".to_string()),
             PropertyValue::LpStr("foo".to_string()),
             PropertyValue::LpStr("foobar".to_string()),
+            PropertyValue::LpStr("Caf\u{e9} \u{a9} M\u{fc}ller".to_string()),
             PropertyValue::FileTime(sat_2017_mar_18_at_18_46_36_gmt),
         ];
-        let codepage = CodePage::Utf8;
-        for value in values {
-            let mut output = Vec::<u8>::new();
-            value.write(&mut output, codepage).unwrap();
-            let parsed =
-                PropertyValue::read(&output as &[u8], codepage).unwrap();
-            assert_eq!(parsed, *value);
-            let expected_size = value.size_including_padding();
-            assert_eq!(output.len() as u32, expected_size);
-            assert_eq!(expected_size % 4, 0);
+        for codepage in [CodePage::Utf8, CodePage::Windows1252] {
+            for value in values {
+                let mut output = Vec::<u8>::new();
+                value.write(&mut output, codepage).unwrap();
+                let parsed =
+                    PropertyValue::read(&output as &[u8], codepage).unwrap();
+                assert_eq!(parsed, *value);
+                let expected_size = value.size_including_padding(codepage);
+                assert_eq!(output.len() as u32, expected_size);
+                assert_eq!(expected_size % 4, 0);
+            }
         }
     }
 
@@ -1,6 +1,6 @@
 use crate::internal::expr::Expr;
 use crate::internal::stringpool::StringPool;
-use crate::internal::table::{Row, Rows, Table};
+use crate::internal::table::{find_table, Row, RowStream, Rows, Table};
 use crate::internal::value::{Value, ValueRef};
 use cfb;
 use std::collections::{BTreeMap, HashSet};
@@ -10,16 +10,59 @@ use std::rc::Rc;
 
 // ========================================================================= //
 
+/// An aggregate function that can be computed over the values in one column
+/// of a table, using [`Package::aggregate`](crate::Package::aggregate).
+/// Null values are always skipped.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Agg {
+    /// The number of non-null values in the column.
+    Count,
+    /// The smallest value in the column.  String columns are compared
+    /// lexicographically.
+    Min,
+    /// The largest value in the column.  String columns are compared
+    /// lexicographically.
+    Max,
+    /// The sum of the values in an integer column.
+    Sum,
+}
+
+impl fmt::Display for Agg {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        formatter.write_str(match *self {
+            Agg::Count => "COUNT",
+            Agg::Min => "MIN",
+            Agg::Max => "MAX",
+            Agg::Sum => "SUM",
+        })
+    }
+}
+
+// ========================================================================= //
+
 /// A database query to delete existing rows.
+///
+/// To guard against accidentally emptying a table, a query must either be
+/// restricted with [`Delete::with`] or explicitly opt in to deleting every
+/// row with [`Delete::all`]; otherwise, executing it is an error.
 pub struct Delete {
     table_name: String,
     condition: Option<Expr>,
+    all: bool,
 }
 
 impl Delete {
     /// Starts building a query that will delete rows from the specified table.
     pub fn from<S: Into<String>>(table_name: S) -> Delete {
-        Delete { table_name: table_name.into(), condition: None }
+        Delete { table_name: table_name.into(), condition: None, all: false }
+    }
+
+    /// Allows the query to delete every row of the table, if it has no
+    /// condition added with [`Delete::with`].
+    #[must_use]
+    pub fn all(mut self) -> Delete {
+        self.all = true;
+        self
     }
 
     /// Adds a restriction on which rows should be deleted by the query; only
@@ -37,23 +80,30 @@ impl Delete {
     }
 
     pub(crate) fn exec<F>(
-        self,
+        mut self,
         comp: &mut cfb::CompoundFile<F>,
         string_pool: &mut StringPool,
         tables: &BTreeMap<String, Rc<Table>>,
-    ) -> io::Result<()>
+    ) -> io::Result<usize>
     where
         F: Read + Write + Seek,
     {
-        let table = match tables.get(&self.table_name) {
+        let table = match find_table(tables, &self.table_name) {
             Some(table) => table,
             None => not_found!("Table {:?} does not exist", self.table_name),
         };
         // Validate the condition.
+        if self.condition.is_none() && !self.all {
+            invalid_input!(
+                "Refusing to delete every row of table {:?} without a \
+                 condition; use Delete::all() to do so deliberately",
+                self.table_name
+            );
+        }
         if let Some(ref expr) = self.condition {
             for column_name in expr.column_names() {
                 if !table.has_column(column_name) {
-                    invalid_input!(
+                    schema_mismatch!(
                         "Table {:?} has no column named {:?}",
                         self.table_name,
                         column_name
@@ -61,15 +111,13 @@ impl Delete {
                 }
             }
         }
+        if let Some(ref mut expr) = self.condition {
+            resolve_subqueries(expr, comp, string_pool, tables)?;
+        }
         // Read in the rows from the table.
-        let stream_name = table.stream_name();
-        let mut rows = if comp.exists(&stream_name) {
-            let stream = comp.open_stream(&stream_name)?;
-            table.read_rows(stream)?
-        } else {
-            Vec::new()
-        };
+        let mut rows = table.load_rows(comp)?;
         // Delete rows from the table.
+        let num_rows = rows.len();
         rows.retain(|value_refs| {
             let should_delete = match self.condition {
                 Some(ref expr) => {
@@ -92,10 +140,10 @@ impl Delete {
                 true
             }
         });
+        let num_deleted = num_rows - rows.len();
         // Write the table back out to the file.
-        let stream = comp.create_stream(&stream_name)?;
-        table.write_rows(stream, rows)?;
-        Ok(())
+        table.store_rows(comp, rows)?;
+        Ok(num_deleted)
     }
 }
 
@@ -113,16 +161,65 @@ impl fmt::Display for Delete {
 
 // ========================================================================= //
 
+/// A type that can be converted into the values of one table row, for use
+/// with [`Insert::rows_from`].
+///
+/// This is implemented for `Vec<Value>`, and for tuples of up to twelve
+/// elements that each convert into a [`Value`], so that the number of values
+/// in each row is checked at compile time.
+pub trait IntoRow {
+    /// Converts `self` into a row of values, one per column.
+    fn into_row(self) -> Vec<Value>;
+}
+
+impl IntoRow for Vec<Value> {
+    fn into_row(self) -> Vec<Value> {
+        self
+    }
+}
+
+macro_rules! tuple_into_row {
+    ($($name:ident),+) => {
+        impl<$($name: Into<Value>),+> IntoRow for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn into_row(self) -> Vec<Value> {
+                let ($($name,)+) = self;
+                vec![$($name.into()),+]
+            }
+        }
+    };
+}
+
+tuple_into_row!(A);
+tuple_into_row!(A, B);
+tuple_into_row!(A, B, C);
+tuple_into_row!(A, B, C, D);
+tuple_into_row!(A, B, C, D, E);
+tuple_into_row!(A, B, C, D, E, F);
+tuple_into_row!(A, B, C, D, E, F, G);
+tuple_into_row!(A, B, C, D, E, F, G, H);
+tuple_into_row!(A, B, C, D, E, F, G, H, I);
+tuple_into_row!(A, B, C, D, E, F, G, H, I, J);
+tuple_into_row!(A, B, C, D, E, F, G, H, I, J, K);
+tuple_into_row!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+// ========================================================================= //
+
 /// A database query to insert new rows.
 pub struct Insert {
     table_name: String,
     new_rows: Vec<Vec<Value>>,
+    replace: bool,
 }
 
 impl Insert {
     /// Starts building a query that will insert rows into the specified table.
     pub fn into<S: Into<String>>(table_name: S) -> Insert {
-        Insert { table_name: table_name.into(), new_rows: Vec::new() }
+        Insert {
+            table_name: table_name.into(),
+            new_rows: Vec::new(),
+            replace: false,
+        }
     }
 
     /// Adds a new row to be inserted into the table.
@@ -132,69 +229,108 @@ impl Insert {
         self
     }
 
-    /// Adds multiple new rows to be inserted into the table.
+    /// Makes the query replace any existing rows that have the same primary
+    /// key as a new row, rather than failing.  (This is like SQL's `INSERT OR
+    /// REPLACE`.)
+    #[must_use]
+    pub fn replace(mut self) -> Insert {
+        self.replace = true;
+        self
+    }
+
+    /// Adds multiple new rows to be inserted into the table.  Inserting many
+    /// rows with one query is much faster than using a separate query for
+    /// each row; see [`Package::insert_rows`](crate::Package::insert_rows).
     #[must_use]
     pub fn rows(mut self, mut rows: Vec<Vec<Value>>) -> Insert {
         self.new_rows.append(&mut rows);
         self
     }
 
+    /// Adds a new row to be inserted into the table for each item of the
+    /// iterator, converting each item with [`IntoRow`].  This allows
+    /// inserting rows from tuples, e.g. `("ProductName", "Example App")`.
+    #[must_use]
+    pub fn rows_from<I, R>(mut self, rows: I) -> Insert
+    where
+        I: IntoIterator<Item = R>,
+        R: IntoRow,
+    {
+        self.new_rows.extend(rows.into_iter().map(IntoRow::into_row));
+        self
+    }
+
     pub(crate) fn exec<F>(
-        self,
+        mut self,
         comp: &mut cfb::CompoundFile<F>,
         string_pool: &mut StringPool,
         tables: &BTreeMap<String, Rc<Table>>,
+        validate: bool,
     ) -> io::Result<()>
     where
         F: Read + Write + Seek,
     {
-        let table = match tables.get(&self.table_name) {
+        let table = match find_table(tables, &self.table_name) {
             Some(table) => table,
             None => not_found!("Table {:?} does not exist", self.table_name),
         };
+        // Fill in any omitted trailing columns that have default values.
+        for values in self.new_rows.iter_mut() {
+            for column in table.columns().iter().skip(values.len()) {
+                match column.default_value() {
+                    Some(value) => values.push(value.clone()),
+                    None => break,
+                }
+            }
+        }
         // Validate the new rows.
-        for values in &self.new_rows {
+        for (row_index, values) in self.new_rows.iter().enumerate() {
             if values.len() != table.columns().len() {
-                invalid_input!(
-                    "Table {:?} has {} columns, but a row with {} values was \
-                     provided",
+                schema_mismatch!(
+                    "Table {:?} has {} columns, but the row at index {} has \
+                     {} values",
                     self.table_name,
                     table.columns().len(),
+                    row_index,
                     values.len()
                 );
             }
             for (column, value) in table.columns().iter().zip(values.iter()) {
-                if !column.is_valid_value(value) {
-                    invalid_input!(
-                        "{} is not a valid value for column {:?}",
+                if !column.coltype().accepts_kind(value) {
+                    schema_mismatch!(
+                        "Cannot insert {} into {} column {:?} of table {:?} \
+                         (row at index {})",
                         value,
-                        column.name()
+                        column.coltype(),
+                        column.name(),
+                        self.table_name,
+                        row_index
                     );
                 }
+                column.check_value(&self.table_name, value, validate)?;
+                if let Some(string) = value.as_str() {
+                    string_pool.check_encodable(string)?;
+                }
                 // TODO: Validate foreign keys.
             }
         }
         // Read in the rows from the table.
-        let stream_name = table.stream_name();
         let key_indices = table.primary_key_indices();
         let mut rows_map = BTreeMap::<Vec<Value>, Vec<ValueRef>>::new();
-        if comp.exists(&stream_name) {
-            let stream = comp.open_stream(&stream_name)?;
-            for row in table.read_rows(stream)? {
-                let keys: Vec<Value> = key_indices
-                    .iter()
-                    .map(|&index| row[index].to_value(string_pool))
-                    .collect();
-                if rows_map.contains_key(&keys) {
-                    invalid_data!(
-                        "Malformed table {:?} contains multiple rows with \
-                         key {:?}",
-                        self.table_name,
-                        keys
-                    );
-                }
-                rows_map.insert(keys, row);
+        for row in table.load_rows(comp)? {
+            let keys: Vec<Value> = key_indices
+                .iter()
+                .map(|&index| row[index].to_value(string_pool))
+                .collect();
+            if rows_map.contains_key(&keys) {
+                invalid_data!(
+                    "Malformed table {:?} contains multiple rows with key \
+                     {:?}",
+                    self.table_name,
+                    keys
+                );
             }
+            rows_map.insert(keys, row);
         }
         // Check if any of the new rows already exist in the table (or conflict
         // with each other).
@@ -204,7 +340,7 @@ impl Insert {
                 .iter()
                 .map(|&index| values[index].clone())
                 .collect();
-            if rows_map.contains_key(&keys) {
+            if rows_map.contains_key(&keys) && !self.replace {
                 already_exists!(
                     "Table {:?} already contains a row with key {:?}",
                     self.table_name,
@@ -229,19 +365,26 @@ impl Insert {
                 .into_iter()
                 .map(|value| ValueRef::create(value, string_pool))
                 .collect();
-            rows_map.insert(keys, row);
+            if let Some(old_row) = rows_map.insert(keys, row) {
+                for value_ref in old_row {
+                    value_ref.remove(string_pool);
+                }
+            }
         }
         // Write the table back out to the file.
         let rows: Vec<Vec<ValueRef>> = rows_map.into_values().collect();
-        let stream = comp.create_stream(&stream_name)?;
-        table.write_rows(stream, rows)?;
+        table.store_rows(comp, rows)?;
         Ok(())
     }
 }
 
 impl fmt::Display for Insert {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        formatter.write_str("INSERT INTO ")?;
+        if self.replace {
+            formatter.write_str("INSERT OR REPLACE INTO ")?;
+        } else {
+            formatter.write_str("INSERT INTO ")?;
+        }
         formatter.write_str(&self.table_name)?;
         if !self.new_rows.is_empty() {
             formatter.write_str(" VALUES ")?;
@@ -289,22 +432,17 @@ impl Join {
     {
         match self {
             Join::Table(table_name) => {
-                let table = match tables.get(&table_name) {
+                let table = match find_table(tables, &table_name) {
                     Some(table) => table,
                     None => {
                         not_found!("Table {:?} does not exist", table_name)
                     }
                 };
-                let stream_name = table.stream_name();
-                let rows = if comp.exists(&stream_name) {
-                    let stream = comp.open_stream(&stream_name)?;
-                    table.read_rows(stream)?
-                } else {
-                    Vec::new()
-                };
+                let rows = table.load_rows(comp)?;
                 Ok(Rows::new(string_pool, table.clone(), rows))
             }
-            Join::Inner(select1, select2, condition) => {
+            Join::Inner(select1, select2, mut condition) => {
+                resolve_subqueries(&mut condition, comp, string_pool, tables)?;
                 let (table1, rows1) = select1
                     .exec(comp, string_pool, tables)?
                     .into_table_and_values();
@@ -345,7 +483,8 @@ impl Join {
                 }
                 Ok(Rows::new(string_pool, table, rows))
             }
-            Join::Left(select1, select2, condition) => {
+            Join::Left(select1, select2, mut condition) => {
+                resolve_subqueries(&mut condition, comp, string_pool, tables)?;
                 let (table1, rows1) = select1
                     .exec(comp, string_pool, tables)?
                     .into_table_and_values();
@@ -435,6 +574,7 @@ pub struct Select {
     from: Join,
     column_names: Vec<String>,
     condition: Option<Expr>,
+    distinct: bool,
 }
 
 impl Select {
@@ -444,6 +584,7 @@ impl Select {
             from: Join::Table(table_name.into()),
             column_names: vec![],
             condition: None,
+            distinct: false,
         }
     }
 
@@ -455,6 +596,7 @@ impl Select {
             from: Join::Inner(Box::new(self), Box::new(rhs), on),
             column_names: vec![],
             condition: None,
+            distinct: false,
         }
     }
 
@@ -465,6 +607,7 @@ impl Select {
             from: Join::Left(Box::new(self), Box::new(rhs), on),
             column_names: vec![],
             condition: None,
+            distinct: false,
         }
     }
 
@@ -482,6 +625,15 @@ impl Select {
         self
     }
 
+    /// Collapses duplicate rows in the query's results, so that each distinct
+    /// row (after limiting to the selected columns, if any) is returned only
+    /// once.  The first occurrence of each row is kept.
+    #[must_use]
+    pub fn distinct(mut self) -> Select {
+        self.distinct = true;
+        self
+    }
+
     /// Adds a restriction on which rows should be selected by the query; only
     /// rows that match the given boolean expression will be returned.  (This
     /// method would have been called `where()`, to better match SQL, but
@@ -506,37 +658,17 @@ impl Select {
         F: Read + Seek,
     {
         // Join the table(s) to be queried.
-        let rows = self.from.exec(comp, string_pool, tables)?;
+        let Select { from, column_names, mut condition, distinct } = self;
+        let rows = from.exec(comp, string_pool, tables)?;
         let (mut table, mut rows) = rows.into_table_and_values();
-        // Validate the selected column names.
-        let mut column_indices =
-            Vec::<usize>::with_capacity(self.column_names.len());
-        for column_name in &self.column_names {
-            match table.index_for_column_name(column_name.as_str()) {
-                Some(index) => column_indices.push(index),
-                None => {
-                    invalid_input!(
-                        "Table {:?} has no column named {:?}",
-                        table.name(),
-                        column_name
-                    );
-                }
-            }
-        }
-        // Validate the condition.
-        if let Some(ref expr) = self.condition {
-            for column_name in expr.column_names() {
-                if !table.has_column(column_name) {
-                    invalid_input!(
-                        "Table {:?} has no column named {:?}",
-                        table.name(),
-                        column_name
-                    );
-                }
-            }
+        // Validate the selected column names and the condition.
+        let column_indices =
+            Select::validate(&column_names, condition.as_ref(), &table)?;
+        if let Some(ref mut expr) = condition {
+            resolve_subqueries(expr, comp, string_pool, tables)?;
         }
         // Filter the rows to those matching the condition.
-        if let Some(condition) = self.condition {
+        if let Some(condition) = condition {
             rows.retain(|value_refs| {
                 let values: Vec<Value> = value_refs
                     .iter()
@@ -561,14 +693,132 @@ impl Select {
                     .collect();
             }
         }
+        // Remove duplicate rows, keeping the first occurrence of each.
+        if distinct {
+            let mut seen = HashSet::<Vec<Value>>::new();
+            rows.retain(|value_refs| {
+                seen.insert(
+                    value_refs
+                        .iter()
+                        .map(|value_ref| value_ref.to_value(string_pool))
+                        .collect(),
+                )
+            });
+        }
         Ok(Rows::new(string_pool, table, rows))
     }
 
+    /// Like `exec`, but reads rows from single-table queries lazily, as the
+    /// returned iterator advances.
+    pub(crate) fn stream<'a, F>(
+        mut self,
+        comp: &mut cfb::CompoundFile<F>,
+        string_pool: &'a StringPool,
+        tables: &BTreeMap<String, Rc<Table>>,
+    ) -> io::Result<RowStream<'a, F>>
+    where
+        F: Read + Seek,
+    {
+        // Removing duplicates requires seeing all the rows, so distinct
+        // queries can't be streamed.
+        if self.distinct {
+            let rows = self.exec(comp, string_pool, tables)?;
+            return Ok(RowStream::buffered(rows));
+        }
+        let table = match self.from {
+            Join::Table(ref table_name) => {
+                match find_table(tables, table_name) {
+                    Some(table) => table.clone(),
+                    None => {
+                        not_found!("Table {:?} does not exist", table_name)
+                    }
+                }
+            }
+            _ => {
+                let rows = self.exec(comp, string_pool, tables)?;
+                return Ok(RowStream::buffered(rows));
+            }
+        };
+        let column_indices = Select::validate(
+            &self.column_names,
+            self.condition.as_ref(),
+            &table,
+        )?;
+        if let Some(ref mut expr) = self.condition {
+            resolve_subqueries(expr, comp, string_pool, tables)?;
+        }
+        let stream_name = table.stream_name();
+        let (stream, num_rows) = if comp.exists(&stream_name) {
+            let stream = comp.open_stream(&stream_name)?;
+            let num_rows = table.num_rows(stream.len())?;
+            (Some(stream), num_rows)
+        } else {
+            (None, 0)
+        };
+        let output_table = if column_indices.is_empty() {
+            table.clone()
+        } else {
+            let columns = column_indices
+                .iter()
+                .map(|&index| table.columns()[index].clone())
+                .collect();
+            Table::new(String::new(), columns, table.long_string_refs())
+        };
+        Ok(RowStream::lazy(
+            string_pool,
+            stream,
+            table,
+            num_rows,
+            self.condition,
+            column_indices,
+            output_table,
+        ))
+    }
+
+    /// Checks that the selected column names and the condition only refer to
+    /// columns in the given table, and returns the indices of the selected
+    /// columns.
+    fn validate(
+        column_names: &[String],
+        condition: Option<&Expr>,
+        table: &Table,
+    ) -> io::Result<Vec<usize>> {
+        let mut column_indices =
+            Vec::<usize>::with_capacity(column_names.len());
+        for column_name in column_names {
+            match table.index_for_column_name(column_name.as_str()) {
+                Some(index) => column_indices.push(index),
+                None => {
+                    schema_mismatch!(
+                        "Table {:?} has no column named {:?}",
+                        table.name(),
+                        column_name
+                    );
+                }
+            }
+        }
+        if let Some(expr) = condition {
+            for column_name in expr.column_names() {
+                if !table.has_column(column_name) {
+                    schema_mismatch!(
+                        "Table {:?} has no column named {:?}",
+                        table.name(),
+                        column_name
+                    );
+                }
+            }
+        }
+        Ok(column_indices)
+    }
+
     fn format_for_join(
         &self,
         formatter: &mut fmt::Formatter,
     ) -> Result<(), fmt::Error> {
-        if self.column_names.is_empty() && self.condition.is_none() {
+        if self.column_names.is_empty()
+            && self.condition.is_none()
+            && !self.distinct
+        {
             if let Join::Table(ref name) = self.from {
                 return formatter.write_str(name.as_str());
             }
@@ -583,6 +833,9 @@ impl Select {
 impl fmt::Display for Select {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         formatter.write_str("SELECT ")?;
+        if self.distinct {
+            formatter.write_str("DISTINCT ")?;
+        }
         if self.column_names.is_empty() {
             formatter.write_str("*")?;
         } else {
@@ -611,7 +864,7 @@ impl fmt::Display for Select {
 /// A database query to update existing rows.
 pub struct Update {
     table_name: String,
-    updates: Vec<(String, Value)>,
+    updates: Vec<(String, Expr)>,
     condition: Option<Expr>,
 }
 
@@ -632,7 +885,24 @@ impl Update {
         column_name: S,
         value: Value,
     ) -> Update {
-        self.updates.push((column_name.into(), value));
+        self.updates.push((column_name.into(), Expr::literal(value)));
+        self
+    }
+
+    /// Adds a column to be set by the query to the result of evaluating an
+    /// expression against each matching row, such as
+    /// `Expr::col("Sequence") + Expr::integer(100)`.  All expressions see
+    /// the row's values from before the update.  If the result for any row
+    /// isn't a valid value for the column (for example, if it's out of range
+    /// for a 16-bit integer column), the query fails without updating any
+    /// rows.
+    #[must_use]
+    pub fn set_expr<S: Into<String>>(
+        mut self,
+        column_name: S,
+        expr: Expr,
+    ) -> Update {
+        self.updates.push((column_name.into(), expr));
         self
     }
 
@@ -651,34 +921,43 @@ impl Update {
     }
 
     pub(crate) fn exec<F>(
-        self,
+        mut self,
         comp: &mut cfb::CompoundFile<F>,
         string_pool: &mut StringPool,
         tables: &BTreeMap<String, Rc<Table>>,
-    ) -> io::Result<()>
+        validate: bool,
+    ) -> io::Result<usize>
     where
         F: Read + Write + Seek,
     {
-        let table = match tables.get(&self.table_name) {
+        let table = match find_table(tables, &self.table_name) {
             Some(table) => table,
             None => not_found!("Table {:?} does not exist", self.table_name),
         };
         // Validate the updates.
-        for (column_name, value) in &self.updates {
+        for (column_name, expr) in &self.updates {
             if !table.has_column(column_name.as_str()) {
-                invalid_input!(
+                schema_mismatch!(
                     "Table {:?} has no column named {:?}",
                     self.table_name,
                     column_name
                 );
             }
-            let column = table.get_column(column_name).unwrap();
-            if !column.is_valid_value(value) {
-                invalid_input!(
-                    "{} is not a valid value for column {:?}",
-                    value,
-                    column_name
-                );
+            if let Some(value) = expr.as_literal() {
+                let column = table.get_column(column_name).unwrap();
+                column.check_value(&self.table_name, value, validate)?;
+                if let Some(string) = value.as_str() {
+                    string_pool.check_encodable(string)?;
+                }
+            }
+            for name in expr.column_names() {
+                if !table.has_column(name) {
+                    schema_mismatch!(
+                        "Table {:?} has no column named {:?}",
+                        self.table_name,
+                        name
+                    );
+                }
             }
             // TODO: Validate foreign keys.
         }
@@ -686,7 +965,7 @@ impl Update {
         if let Some(ref expr) = self.condition {
             for column_name in expr.column_names() {
                 if !table.has_column(column_name) {
-                    invalid_input!(
+                    schema_mismatch!(
                         "Table {:?} has no column named {:?}",
                         self.table_name,
                         column_name
@@ -694,41 +973,70 @@ impl Update {
                 }
             }
         }
+        for (_, expr) in &mut self.updates {
+            resolve_subqueries(expr, comp, string_pool, tables)?;
+        }
+        if let Some(ref mut expr) = self.condition {
+            resolve_subqueries(expr, comp, string_pool, tables)?;
+        }
         // Read in the rows from the table.
-        let stream_name = table.stream_name();
-        let mut rows = if comp.exists(&stream_name) {
-            let stream = comp.open_stream(&stream_name)?;
-            table.read_rows(stream)?
-        } else {
-            Vec::new()
-        };
-        // Update the rows.
-        for value_refs in &mut rows {
-            let should_update = match self.condition {
-                Some(ref expr) => {
-                    let values: Vec<Value> = value_refs
-                        .iter()
-                        .map(|value_ref| value_ref.to_value(string_pool))
-                        .collect();
-                    let row = Row::new(table.clone(), values);
-                    expr.eval(&row).to_bool()
-                }
-                None => true,
+        let mut rows = table.load_rows(comp)?;
+        // Compute the new values for each matching row, so that if any of
+        // them are invalid, we can fail without having changed anything.
+        let needs_row = self.condition.is_some()
+            || self
+                .updates
+                .iter()
+                .any(|(_, expr)| expr.as_literal().is_none());
+        let mut changes = Vec::<(usize, usize, Value)>::new();
+        let mut num_updated = 0;
+        for (row_index, value_refs) in rows.iter().enumerate() {
+            let row = if needs_row {
+                let values: Vec<Value> = value_refs
+                    .iter()
+                    .map(|value_ref| value_ref.to_value(string_pool))
+                    .collect();
+                Some(Row::new(table.clone(), values))
+            } else {
+                None
+            };
+            let should_update = match (&self.condition, &row) {
+                (Some(expr), Some(row)) => expr.eval(row).to_bool(),
+                _ => true,
             };
             if should_update {
-                for (column_name, value) in &self.updates {
+                num_updated += 1;
+                for (column_name, expr) in &self.updates {
                     let index =
                         table.index_for_column_name(column_name).unwrap();
-                    let value_ref = &mut value_refs[index];
-                    value_ref.remove(string_pool);
-                    *value_ref = ValueRef::create(value.clone(), string_pool);
+                    let value = match expr.as_literal() {
+                        Some(value) => value.clone(),
+                        None => {
+                            let value = expr.eval(row.as_ref().unwrap());
+                            table.columns()[index].check_value(
+                                &self.table_name,
+                                &value,
+                                validate,
+                            )?;
+                            if let Some(string) = value.as_str() {
+                                string_pool.check_encodable(string)?;
+                            }
+                            value
+                        }
+                    };
+                    changes.push((row_index, index, value));
                 }
             }
         }
+        // Update the rows.
+        for (row_index, index, value) in changes {
+            let value_ref = &mut rows[row_index][index];
+            value_ref.remove(string_pool);
+            *value_ref = ValueRef::create(value, string_pool);
+        }
         // Write the table back out to the file.
-        let stream = comp.create_stream(&stream_name)?;
-        table.write_rows(stream, rows)?;
-        Ok(())
+        table.store_rows(comp, rows)?;
+        Ok(num_updated)
     }
 }
 
@@ -738,7 +1046,7 @@ impl fmt::Display for Update {
         formatter.write_str(&self.table_name)?;
         formatter.write_str(" SET ")?;
         let mut comma = false;
-        for (column_name, value) in &self.updates {
+        for (column_name, expr) in &self.updates {
             if comma {
                 formatter.write_str(", ")?;
             } else {
@@ -746,7 +1054,7 @@ impl fmt::Display for Update {
             }
             formatter.write_str(column_name)?;
             formatter.write_str(" = ")?;
-            value.fmt(formatter)?;
+            expr.fmt(formatter)?;
         }
         if let Some(ref expr) = self.condition {
             formatter.write_str(" WHERE ")?;
@@ -758,6 +1066,42 @@ impl fmt::Display for Update {
 
 // ========================================================================= //
 
+/// Loads the column values needed to evaluate any
+/// [`exists_in`](Expr::exists_in) or [`not_exists_in`](Expr::not_exists_in)
+/// subexpressions of the given expression.  Each referenced column is read
+/// once, no matter how many rows the expression is evaluated against.
+pub(crate) fn resolve_subqueries<F: Read + Seek>(
+    expr: &mut Expr,
+    comp: &mut cfb::CompoundFile<F>,
+    string_pool: &StringPool,
+    tables: &BTreeMap<String, Rc<Table>>,
+) -> io::Result<()> {
+    expr.resolve_subqueries(&mut |table_name, column_name| {
+        let table = match find_table(tables, table_name) {
+            Some(table) => table,
+            None => not_found!("Table {:?} does not exist", table_name),
+        };
+        let index = match table.index_for_column_name(column_name) {
+            Some(index) => index,
+            None => schema_mismatch!(
+                "Table {:?} has no column named {:?}",
+                table_name,
+                column_name
+            ),
+        };
+        let mut values = HashSet::new();
+        for row in table.load_rows(comp)? {
+            let value = row[index].to_value(string_pool);
+            if !value.is_null() {
+                values.insert(value);
+            }
+        }
+        Ok(values)
+    })
+}
+
+// ========================================================================= //
+
 #[cfg(test)]
 mod tests {
     use super::{Delete, Insert, Select, Update};
@@ -766,7 +1110,7 @@ mod tests {
 
     #[test]
     fn display_delete() {
-        let query = Delete::from("Foobar");
+        let query = Delete::from("Foobar").all();
         assert_eq!(format!("{query}"), "DELETE FROM Foobar".to_string());
 
         let query = Delete::from("Foobar")
@@ -801,6 +1145,12 @@ mod tests {
             "INSERT INTO Foobar VALUES (1, 2), (3, 4), (5, 6), (7, 8)"
                 .to_string()
         );
+
+        let query = Insert::into("Foobar").row(vec![Value::Int(1)]).replace();
+        assert_eq!(
+            format!("{query}"),
+            "INSERT OR REPLACE INTO Foobar VALUES (1)".to_string()
+        );
     }
 
     #[test]
@@ -844,6 +1194,12 @@ mod tests {
                     ON Foobar.Key = Quux.Quay"
                 .to_string()
         );
+
+        let query = Select::table("Foobar").columns(&["Foo"]).distinct();
+        assert_eq!(
+            format!("{query}"),
+            "SELECT DISTINCT Foo FROM Foobar".to_string()
+        );
     }
 
     #[test]
@@ -865,6 +1221,13 @@ mod tests {
                     WHERE Foo < 17"
                 .to_string()
         );
+
+        let query = Update::table("Foobar")
+            .set_expr("Foo", Expr::col("Foo") + Expr::integer(100));
+        assert_eq!(
+            format!("{query}"),
+            "UPDATE Foobar SET Foo = Foo + 100".to_string()
+        );
     }
 }
 
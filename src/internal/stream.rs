@@ -1,13 +1,14 @@
-use crate::internal::streamname::{
-    self, DIGITAL_SIGNATURE_STREAM_NAME, DOCUMENT_SUMMARY_INFO_STREAM_NAME,
-    MSI_DIGITAL_SIGNATURE_EX_STREAM_NAME, SUMMARY_INFO_STREAM_NAME,
-};
+use crate::internal::streamname;
 use cfb;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 
 // ========================================================================= //
 
 /// An IO reader for an embedded binary stream in a package.
+///
+/// Data is read from the underlying file on demand, so even very large
+/// streams (such as embedded cabinets) can be read without loading the whole
+/// stream into memory.
 pub struct StreamReader<F> {
     stream: cfb::Stream<F>,
 }
@@ -16,6 +17,18 @@ impl<F> StreamReader<F> {
     pub(crate) fn new(stream: cfb::Stream<F>) -> StreamReader<F> {
         StreamReader { stream }
     }
+
+    /// Returns the total length of the stream, in bytes.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.stream.len()
+    }
+
+    /// Returns true if the stream is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.stream.len() == 0
+    }
 }
 
 impl<F: Read + Seek> Read for StreamReader<F> {
@@ -80,12 +93,7 @@ impl<'a, F: 'a> Iterator for Streams<'a, F> {
     fn next(&mut self) -> Option<String> {
         loop {
             let entry = self.entries.next()?;
-            if !entry.is_stream()
-                || entry.name() == DIGITAL_SIGNATURE_STREAM_NAME
-                || entry.name() == MSI_DIGITAL_SIGNATURE_EX_STREAM_NAME
-                || entry.name() == SUMMARY_INFO_STREAM_NAME
-                || entry.name() == DOCUMENT_SUMMARY_INFO_STREAM_NAME
-            {
+            if !entry.is_stream() || streamname::is_metadata(entry.name()) {
                 continue;
             }
             let (name, is_table) = streamname::decode(entry.name());
@@ -38,6 +38,15 @@ pub fn decode(name: &str) -> (String, bool) {
     (output, is_table)
 }
 
+/// Returns true if the given (undecoded) stream name is one of the special
+/// streams that hold package metadata rather than binary data.
+pub fn is_metadata(name: &str) -> bool {
+    name == DIGITAL_SIGNATURE_STREAM_NAME
+        || name == MSI_DIGITAL_SIGNATURE_EX_STREAM_NAME
+        || name == SUMMARY_INFO_STREAM_NAME
+        || name == DOCUMENT_SUMMARY_INFO_STREAM_NAME
+}
+
 /// Encodes a stream name.
 pub fn encode(name: &str, is_table: bool) -> String {
     let mut output = String::new();
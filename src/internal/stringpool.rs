@@ -1,5 +1,6 @@
 use crate::internal::codepage::CodePage;
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::collections::{BTreeSet, HashMap};
 use std::io::{self, Read, Write};
 
 // ========================================================================= //
@@ -120,14 +121,21 @@ impl StringPoolBuilder {
         for (length, refcount) in self.lengths_and_refcounts {
             let mut buffer = vec![0u8; length as usize];
             reader.read_exact(&mut buffer)?;
-            strings.push((self.codepage.decode(&buffer), refcount));
+            strings
+                .push((self.codepage.decode(&buffer).into_owned(), refcount));
         }
-        Ok(StringPool {
+        let mut string_pool = StringPool {
             codepage: self.codepage,
             strings,
+            indices: HashMap::new(),
+            empty_indices: BTreeSet::new(),
+            has_duplicates: false,
             long_string_refs: self.long_string_refs,
+            lossy_encoding: false,
             is_modified: false,
-        })
+        };
+        string_pool.rebuild_indices();
+        Ok(string_pool)
     }
 }
 
@@ -137,7 +145,16 @@ impl StringPoolBuilder {
 pub struct StringPool {
     codepage: CodePage,
     strings: Vec<(String, u16)>,
+    /// For each string in the pool, the index of the first entry holding it.
+    indices: HashMap<String, usize>,
+    /// The indices of entries with a zero refcount, which can be reused.
+    empty_indices: BTreeSet<usize>,
+    /// True if some string may be held by more than one entry.
+    has_duplicates: bool,
     long_string_refs: bool,
+    /// If false, strings that can't be represented in the code page are
+    /// rejected instead of having replacement characters substituted.
+    lossy_encoding: bool,
     is_modified: bool,
 }
 
@@ -147,11 +164,30 @@ impl StringPool {
         StringPool {
             codepage,
             strings: Vec::new(),
+            indices: HashMap::new(),
+            empty_indices: BTreeSet::new(),
+            has_duplicates: false,
             long_string_refs: false,
+            lossy_encoding: false,
             is_modified: true,
         }
     }
 
+    fn rebuild_indices(&mut self) {
+        self.indices.clear();
+        self.empty_indices.clear();
+        self.has_duplicates = false;
+        for (index, (string, refcount)) in self.strings.iter().enumerate() {
+            if *refcount == 0 {
+                self.empty_indices.insert(index);
+            } else if self.indices.contains_key(string) {
+                self.has_duplicates = true;
+            } else {
+                self.indices.insert(string.clone(), index);
+            }
+        }
+    }
+
     /// Gets the code page used for serializing the string data.
     pub fn codepage(&self) -> CodePage {
         self.codepage
@@ -163,6 +199,54 @@ impl StringPool {
         self.codepage = codepage;
     }
 
+    /// Returns true if strings that can't be represented in the code page
+    /// are encoded with replacement characters rather than rejected.
+    pub fn lossy_encoding(&self) -> bool {
+        self.lossy_encoding
+    }
+
+    /// Sets whether strings that can't be represented in the code page are
+    /// encoded with replacement characters rather than rejected.
+    pub fn set_lossy_encoding(&mut self, lossy: bool) {
+        self.lossy_encoding = lossy;
+    }
+
+    /// Returns an error if the string can't be represented in the pool's
+    /// code page, unless lossy encoding is enabled.
+    pub fn check_encodable(&self, string: &str) -> io::Result<()> {
+        if !self.lossy_encoding && !self.codepage.can_encode(string) {
+            invalid_input!(
+                "String {:?} cannot be represented in code page {} ({})",
+                string,
+                self.codepage.id(),
+                self.codepage.name()
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the first string in the pool that cannot be represented in
+    /// the given code page, if any.
+    pub fn find_unencodable(&self, codepage: CodePage) -> Option<&str> {
+        self.strings
+            .iter()
+            .filter(|&&(_, refcount)| refcount > 0)
+            .map(|(string, _)| string.as_str())
+            .find(|string| !codepage.can_encode(string))
+    }
+
+    /// Returns an iterator over the references and strings of the entries in
+    /// the pool that are in use (that is, that have a nonzero refcount).
+    pub fn live_entries(&self) -> impl Iterator<Item = (StringRef, &str)> {
+        self.strings
+            .iter()
+            .enumerate()
+            .filter(|&(_, &(_, refcount))| refcount > 0)
+            .map(|(index, (string, _))| {
+                (StringRef((index + 1) as i32), string.as_str())
+            })
+    }
+
     /// Returns the number of strings in the string pool (including empty
     /// entries).
     #[allow(dead_code)]
@@ -209,35 +293,46 @@ impl StringPool {
     /// already in the pool, and returns the index of the string in the pool.
     pub fn incref(&mut self, string: String) -> StringRef {
         self.is_modified = true;
-        // TODO: change the internal representation of StringPool to make this
-        // more efficient.
-        for (index, &mut (ref mut st, ref mut refcount)) in
-            self.strings.iter_mut().enumerate()
-        {
-            if *refcount == 0 {
-                debug_assert_eq!(st, "");
-                *st = string;
-                *refcount = 1;
+        // Look for the string itself before reusing an empty entry, so that
+        // the pool never ends up with two entries for the same string (unless
+        // the existing entry's refcount is saturated).
+        if let Some(&index) = self.indices.get(&string) {
+            let refcount = &mut self.strings[index].1;
+            if *refcount < u16::MAX {
+                *refcount += 1;
                 return StringRef((index + 1) as i32);
             }
-            if *st == string && *refcount < u16::MAX {
-                *refcount += 1;
+            let duplicate = self.strings.iter().position(|(st, refcount)| {
+                *refcount > 0 && *refcount < u16::MAX && *st == string
+            });
+            if let Some(index) = duplicate {
+                self.strings[index].1 += 1;
                 return StringRef((index + 1) as i32);
             }
+            self.has_duplicates = true;
         }
-        if self.strings.len() >= u16::MAX as usize && !self.long_string_refs {
-            // TODO: If this happens, we need to rewrite all database tables
-            // from short to long string refs.
-            panic!(
-                "Too many strings; rewriting to long string refs is not \
-                    yet supported"
-            );
-        }
-        if self.strings.len() >= MAX_STRING_REF as usize {
-            panic!("Too many distinct strings in string pool");
-        }
-        self.strings.push((string, 1));
-        StringRef(self.strings.len() as i32)
+        let index = if let Some(index) = self.empty_indices.pop_first() {
+            self.strings[index] = (string.clone(), 1);
+            index
+        } else {
+            if self.strings.len() >= u16::MAX as usize
+                && !self.long_string_refs
+            {
+                // TODO: If this happens, we need to rewrite all database
+                // tables from short to long string refs.
+                panic!(
+                    "Too many strings; rewriting to long string refs is not \
+                     yet supported"
+                );
+            }
+            if self.strings.len() >= MAX_STRING_REF as usize {
+                panic!("Too many distinct strings in string pool");
+            }
+            self.strings.push((string.clone(), 1));
+            self.strings.len() - 1
+        };
+        self.indices.entry(string).or_insert(index);
+        StringRef((index + 1) as i32)
     }
 
     /// Decrements the refcount of a string in the pool.
@@ -257,13 +352,20 @@ impl StringPool {
         self.is_modified = true;
         *refcount -= 1;
         if *refcount == 0 {
-            string.clear();
+            let string = std::mem::take(string);
+            self.empty_indices.insert(index);
+            if self.indices.get(&string) == Some(&index) {
+                self.indices.remove(&string);
+                if self.has_duplicates {
+                    self.rebuild_indices();
+                }
+            }
         }
     }
 
     /// Writes to the `_StringPool` table.
     pub fn write_pool<W: Write>(&self, mut writer: W) -> io::Result<()> {
-        let mut codepage_id = self.codepage.id() as u32;
+        let mut codepage_id = u32::from(self.codepage.id());
         if self.long_string_refs {
             codepage_id |= LONG_STRING_REFS_BIT;
         }
@@ -392,6 +494,45 @@ mod tests {
         assert_eq!(&data_output as &[u8], b"FooQuux");
     }
 
+    #[test]
+    fn incref_does_not_duplicate_strings() {
+        let mut string_pool = StringPool::new(CodePage::Utf8);
+        assert_eq!(string_pool.incref("Foo".to_string()), StringRef(1));
+        assert_eq!(string_pool.incref("Bar".to_string()), StringRef(2));
+        string_pool.decref(StringRef(1));
+        // "Bar" is already in the pool, so the freed entry must not be reused
+        // for it.
+        assert_eq!(string_pool.incref("Bar".to_string()), StringRef(2));
+        assert_eq!(string_pool.refcount(StringRef(2)), 2);
+        assert_eq!(string_pool.incref("Baz".to_string()), StringRef(1));
+        assert_eq!(string_pool.num_strings(), 2);
+    }
+
+    #[test]
+    fn empty_string_round_trip() {
+        let mut string_pool = StringPool::new(CodePage::Windows1252);
+        assert_eq!(string_pool.incref(String::new()), StringRef(1));
+        assert_eq!(string_pool.incref("Foo".to_string()), StringRef(2));
+        let mut pool_output = Vec::<u8>::new();
+        string_pool.write_pool(&mut pool_output).expect("pool");
+        assert_eq!(
+            &pool_output as &[u8],
+            b"\xe4\x04\x00\x00\x00\x00\x01\x00\x00\x00\x00\x00\x03\x00\x01\x00"
+        );
+        let mut data_output = Vec::<u8>::new();
+        string_pool.write_data(&mut data_output).expect("data");
+        assert_eq!(&data_output as &[u8], b"Foo");
+
+        let builder =
+            StringPoolBuilder::read_from_pool(&pool_output as &[u8]).unwrap();
+        let string_pool =
+            builder.build_from_data(&data_output as &[u8]).unwrap();
+        assert_eq!(string_pool.num_strings(), 2);
+        assert_eq!(string_pool.get(StringRef(1)), "");
+        assert_eq!(string_pool.refcount(StringRef(1)), 1);
+        assert_eq!(string_pool.get(StringRef(2)), "Foo");
+    }
+
     #[test]
     fn long_string_refs() {
         let pool: &[u8] = b"\xe4\x04\x00\x80\x03\x00\x02\x00\x04\x00\x07\x00";
@@ -416,6 +557,25 @@ mod tests {
         assert_eq!(string_pool.refcount(StringRef(2)), 7);
     }
 
+    #[test]
+    fn repeated_string_refcounts() {
+        let pool: &[u8] = b"\xe9\xfd\x00\x00\x03\x00\x02\x00\x03\x00\x07\x00";
+        let data: &[u8] = b"FooFoo";
+        let builder = StringPoolBuilder::read_from_pool(pool).expect("pool");
+        let mut string_pool = builder.build_from_data(data).expect("data");
+        assert_eq!(string_pool.incref("Foo".to_string()), StringRef(1));
+        assert_eq!(string_pool.refcount(StringRef(1)), 3);
+        string_pool.decref(StringRef(1));
+        string_pool.decref(StringRef(1));
+        string_pool.decref(StringRef(1));
+        // Once the first entry is freed, the second entry for the same string
+        // is used instead.
+        assert_eq!(string_pool.incref("Foo".to_string()), StringRef(2));
+        assert_eq!(string_pool.refcount(StringRef(2)), 8);
+        assert_eq!(string_pool.incref("Bar".to_string()), StringRef(1));
+        assert_eq!(string_pool.num_strings(), 2);
+    }
+
     #[test]
     fn deserialize_string_over_64k() {
         // This byte array represents a serialized string pool with:
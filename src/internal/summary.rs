@@ -1,8 +1,12 @@
 use crate::internal::codepage::CodePage;
+use crate::internal::error::{Error, Result};
+use crate::internal::guid::Guid;
 use crate::internal::language::Language;
 use crate::internal::propset::{OperatingSystem, PropertySet, PropertyValue};
 use crate::internal::timestamp::Timestamp;
+use std::fmt;
 use std::io::{self, Read, Seek, Write};
+use std::str;
 use std::time::SystemTime;
 use uuid::Uuid;
 
@@ -36,14 +40,138 @@ const PROPERTY_CHARACTER_COUNT: u32 = 16;
 const PROPERTY_CREATING_APP: u32 = 18;
 const PROPERTY_DOC_SECURITY: u32 = 19;
 
+/// The "creating application" summary property given to packages created by
+/// this crate (see [`Package::create`](crate::Package::create)), unless a
+/// different one is provided.
+pub const DEFAULT_CREATING_APPLICATION: &str =
+    concat!("rust-msi ", env!("CARGO_PKG_VERSION"));
+
+// ========================================================================= //
+
+/// A hardware architecture that a package can target, as recorded in the
+/// "template" summary property.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Architecture {
+    /// 32-bit x86 (`"Intel"`).
+    Intel,
+    /// 64-bit x86 (`"x64"`).
+    X64,
+    /// Itanium (`"Intel64"`).
+    Intel64,
+    /// 32-bit ARM (`"Arm"`).
+    Arm,
+    /// 64-bit ARM (`"Arm64"`).
+    Arm64,
+}
+
+impl Architecture {
+    /// Returns the canonical MSI spelling of this architecture.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Architecture::Intel => "Intel",
+            Architecture::X64 => "x64",
+            Architecture::Intel64 => "Intel64",
+            Architecture::Arm => "Arm",
+            Architecture::Arm64 => "Arm64",
+        }
+    }
+}
+
+impl fmt::Display for Architecture {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        self.as_str().fmt(formatter)
+    }
+}
+
+impl str::FromStr for Architecture {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Architecture> {
+        for arch in [
+            Architecture::Intel,
+            Architecture::X64,
+            Architecture::Intel64,
+            Architecture::Arm,
+            Architecture::Arm64,
+        ] {
+            if string.eq_ignore_ascii_case(arch.as_str()) {
+                return Ok(arch);
+            }
+        }
+        invalid_input!("Invalid architecture: {:?}", string)
+    }
+}
+
+// ========================================================================= //
+
+/// The read/write restrictions recorded in a package's "Security" summary
+/// property.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Security {
+    /// No restrictions (stored as 0).
+    NoRestriction,
+    /// The package should be opened read-only, but this is not enforced
+    /// (stored as 2).
+    ReadOnlyRecommended,
+    /// The package must be opened read-only (stored as 4).
+    ReadOnlyEnforced,
+}
+
+impl Security {
+    /// Returns the security setting (if any) with the given property value.
+    #[must_use]
+    pub fn from_id(id: i32) -> Option<Security> {
+        match id {
+            0 => Some(Security::NoRestriction),
+            2 => Some(Security::ReadOnlyRecommended),
+            4 => Some(Security::ReadOnlyEnforced),
+            _ => None,
+        }
+    }
+
+    /// Returns the value used to store this setting in the "Security"
+    /// property.
+    #[must_use]
+    pub fn id(self) -> i32 {
+        match self {
+            Security::NoRestriction => 0,
+            Security::ReadOnlyRecommended => 2,
+            Security::ReadOnlyEnforced => 4,
+        }
+    }
+}
+
 // ========================================================================= //
 
 /// Summary information (e.g. title, author) about an MSI package.
+#[derive(Clone)]
 pub struct SummaryInfo {
     properties: PropertySet,
 }
 
 impl SummaryInfo {
+    /// Starts building a new `SummaryInfo`, for use with
+    /// `Package::create_with_summary_info`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use msi::{Architecture, Language};
+    /// let summary_info = msi::SummaryInfo::build()
+    ///     .languages(&[Language::from_tag("en-US")])
+    ///     .arch(Architecture::X64)
+    ///     .author("Jane Doe")
+    ///     .finish();
+    /// assert_eq!(summary_info.arch(), Some("x64"));
+    /// assert_eq!(summary_info.languages(), [Language::from_tag("en-US")]);
+    /// assert_eq!(summary_info.author(), Some("Jane Doe"));
+    /// ```
+    #[must_use]
+    pub fn build() -> SummaryInfoBuilder {
+        SummaryInfoBuilder::new()
+    }
+
     /// Creates an empty `SummaryInfo` with no properties set.
     pub(crate) fn new() -> SummaryInfo {
         let properties = PropertySet::new(OperatingSystem::Win32, 10, FMTID);
@@ -66,9 +194,25 @@ impl SummaryInfo {
 
     /// Gets the architecture string from the "template" property, if one is
     /// set. This indicates the hardware architecture that this package is
-    /// intended for (e.g. `"x64"`).
+    /// intended for (e.g. `"x64"`).  Recognized architectures are returned
+    /// with their canonical spelling; anything else is returned as-is.
     #[must_use]
     pub fn arch(&self) -> Option<&str> {
+        let arch = self.raw_arch()?;
+        match arch.parse::<Architecture>() {
+            Ok(arch) => Some(arch.as_str()),
+            Err(_) => Some(arch),
+        }
+    }
+
+    /// Gets the architecture from the "template" property, if one is set and
+    /// recognized.
+    #[must_use]
+    pub fn architecture(&self) -> Option<Architecture> {
+        self.raw_arch().and_then(|arch| arch.parse().ok())
+    }
+
+    fn raw_arch(&self) -> Option<&str> {
         match self.properties.get(PROPERTY_TEMPLATE) {
             Some(PropertyValue::LpStr(template)) => {
                 let arch =
@@ -83,8 +227,28 @@ impl SummaryInfo {
         }
     }
 
-    /// Sets the architecture string in the "template" property.
-    pub fn set_arch<S: Into<String>>(&mut self, arch: S) {
+    /// Sets the architecture string in the "template" property.  The string
+    /// must be one of the architectures recognized by `Architecture`
+    /// (case-insensitively), and is stored with its canonical spelling.
+    pub fn set_arch<S: AsRef<str>>(&mut self, arch: S) -> Result<()> {
+        let arch: Architecture = arch.as_ref().parse()?;
+        self.set_architecture(arch);
+        Ok(())
+    }
+
+    /// Sets the architecture in the "template" property.
+    pub fn set_architecture(&mut self, arch: Architecture) {
+        self.set_arch_string(arch.as_str());
+    }
+
+    /// Sets the architecture string in the "template" property without
+    /// checking that it names a recognized architecture.
+    #[deprecated(note = "use `set_arch` or `set_architecture` instead")]
+    pub fn set_arch_unchecked<S: Into<String>>(&mut self, arch: S) {
+        self.set_arch_string(arch);
+    }
+
+    pub(crate) fn set_arch_string<S: Into<String>>(&mut self, arch: S) {
         let langs = match self.properties.get(PROPERTY_TEMPLATE) {
             Some(PropertyValue::LpStr(template)) => {
                 let parts: Vec<&str> = template.splitn(2, ';').collect();
@@ -102,7 +266,28 @@ impl SummaryInfo {
 
     /// Clears the architecture string in the "template" property.
     pub fn clear_arch(&mut self) {
-        self.set_arch("");
+        self.set_arch_string("");
+    }
+
+    /// Gets the raw value of the "template" property, if one is set.  For an
+    /// installer or transform, this holds the target architecture and
+    /// languages (e.g. `"x64;1033"`); for a patch, it holds the product codes
+    /// of the products that the patch can be applied to.
+    #[must_use]
+    pub fn template(&self) -> Option<&str> {
+        match self.properties.get(PROPERTY_TEMPLATE) {
+            Some(PropertyValue::LpStr(template)) => Some(template.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Sets the raw value of the "template" property.
+    pub(crate) fn set_template_string<S: Into<String>>(
+        &mut self,
+        template: S,
+    ) {
+        self.properties
+            .set(PROPERTY_TEMPLATE, PropertyValue::LpStr(template.into()));
     }
 
     /// Gets the "author" property, if one is set.  This indicates the name of
@@ -405,8 +590,7 @@ impl SummaryInfo {
 
     /// Sets the "UUID" property.
     pub fn set_uuid(&mut self, uuid: Uuid) {
-        let mut string = format!("{{{}}}", uuid.hyphenated());
-        string.make_ascii_uppercase();
+        let string = Guid::from(uuid).to_string();
         self.properties.set(PROPERTY_UUID, PropertyValue::LpStr(string));
     }
 
@@ -415,6 +599,17 @@ impl SummaryInfo {
         self.properties.remove(PROPERTY_UUID);
     }
 
+    /// Sets the "UUID" property to an arbitrary string.  (For transforms,
+    /// this property holds the product codes and versions of the base and
+    /// target packages, rather than a single UUID.)
+    pub(crate) fn set_revision_number<S: Into<String>>(
+        &mut self,
+        revision: S,
+    ) {
+        self.properties
+            .set(PROPERTY_UUID, PropertyValue::LpStr(revision.into()));
+    }
+
     /// Gets the "Word Count" property, if one is set.
     #[must_use]
     pub fn word_count(&self) -> Option<i32> {
@@ -455,7 +650,20 @@ impl SummaryInfo {
         self.properties.remove(PROPERTY_PAGE_COUNT);
     }
 
-    /// Gets the "Security" property, if one is set.
+    /// Gets the "Security" property as a typed value, if one is set.  Returns
+    /// `None` if the property is unset or holds a value other than 0, 2, or 4;
+    /// use `doc_security` to get the raw value.
+    #[must_use]
+    pub fn security(&self) -> Option<Security> {
+        self.doc_security().and_then(Security::from_id)
+    }
+
+    /// Sets the "Security" property to the given typed value.
+    pub fn set_security(&mut self, security: Security) {
+        self.set_doc_security(security.id());
+    }
+
+    /// Gets the raw value of the "Security" property, if one is set.
     #[must_use]
     pub fn doc_security(&self) -> Option<i32> {
         match self.properties.get(PROPERTY_DOC_SECURITY) {
@@ -464,7 +672,7 @@ impl SummaryInfo {
         }
     }
 
-    /// Sets the "Security" property.
+    /// Sets the "Security" property to a raw value.
     pub fn set_doc_security(&mut self, doc_security: i32) {
         self.properties
             .set(PROPERTY_DOC_SECURITY, PropertyValue::I4(doc_security));
@@ -518,13 +726,186 @@ impl SummaryInfo {
     pub fn clear_last_saved_by(&mut self) {
         self.properties.remove(PROPERTY_LAST_SAVED_BY);
     }
+
+    /// Gets the raw value of the property with the given property ID, if
+    /// one is set.  This gives access to nonstandard (e.g. vendor-specific)
+    /// properties that `SummaryInfo` has no typed accessor for.
+    #[must_use]
+    pub fn raw_property(&self, pid: u32) -> Option<&PropertyValue> {
+        self.properties.get(pid)
+    }
+
+    /// Sets the raw value of the property with the given property ID.  This
+    /// bypasses the checks made by the typed setters, so the caller is
+    /// responsible for using the value type that readers of the property
+    /// expect.  Setting property ID 1 to an `I2` value changes the
+    /// [codepage](SummaryInfo::codepage).
+    pub fn set_raw_property(&mut self, pid: u32, value: PropertyValue) {
+        self.properties.set(pid, value);
+    }
+}
+
+// ========================================================================= //
+
+/// A factory for configuring the summary information of a new package.
+///
+/// The architecture and languages are both stored in the "template" property;
+/// the builder combines them when `finish` is called, so they may be set in
+/// either order.
+pub struct SummaryInfoBuilder {
+    summary_info: SummaryInfo,
+    arch: Option<Architecture>,
+    languages: Vec<Language>,
+}
+
+impl SummaryInfoBuilder {
+    fn new() -> SummaryInfoBuilder {
+        SummaryInfoBuilder {
+            summary_info: SummaryInfo::new(),
+            arch: None,
+            languages: Vec::new(),
+        }
+    }
+
+    /// Sets the architecture that the package is intended for.
+    #[must_use]
+    pub fn arch(mut self, arch: Architecture) -> SummaryInfoBuilder {
+        self.arch = Some(arch);
+        self
+    }
+
+    /// Sets the languages that the package supports.
+    #[must_use]
+    pub fn languages(mut self, languages: &[Language]) -> SummaryInfoBuilder {
+        self.languages = languages.to_vec();
+        self
+    }
+
+    /// Sets the "author" property.
+    #[must_use]
+    pub fn author<S: Into<String>>(mut self, author: S) -> SummaryInfoBuilder {
+        self.summary_info.set_author(author);
+        self
+    }
+
+    /// Sets the code page used for serializing the summary info.
+    #[must_use]
+    pub fn codepage(mut self, codepage: CodePage) -> SummaryInfoBuilder {
+        self.summary_info.set_codepage(codepage);
+        self
+    }
+
+    /// Sets the "comments" property.
+    #[must_use]
+    pub fn comments<S: Into<String>>(
+        mut self,
+        comments: S,
+    ) -> SummaryInfoBuilder {
+        self.summary_info.set_comments(comments);
+        self
+    }
+
+    /// Sets the "creating application" property.
+    #[must_use]
+    pub fn creating_application<S: Into<String>>(
+        mut self,
+        app_name: S,
+    ) -> SummaryInfoBuilder {
+        self.summary_info.set_creating_application(app_name);
+        self
+    }
+
+    /// Sets the "creation time" property.
+    #[must_use]
+    pub fn creation_time(
+        mut self,
+        timestamp: SystemTime,
+    ) -> SummaryInfoBuilder {
+        self.summary_info.set_creation_time(timestamp);
+        self
+    }
+
+    /// Sets the list of keywords.
+    #[must_use]
+    pub fn keywords(mut self, keywords: &[String]) -> SummaryInfoBuilder {
+        self.summary_info.set_keywords(keywords);
+        self
+    }
+
+    /// Sets the "Last Saved By" property.
+    #[must_use]
+    pub fn last_saved_by<S: Into<String>>(
+        mut self,
+        last_saved_by: S,
+    ) -> SummaryInfoBuilder {
+        self.summary_info.set_last_saved_by(last_saved_by);
+        self
+    }
+
+    /// Sets the "Page Count" property.
+    #[must_use]
+    pub fn page_count(mut self, page_count: i32) -> SummaryInfoBuilder {
+        self.summary_info.set_page_count(page_count);
+        self
+    }
+
+    /// Sets the "Security" property.
+    #[must_use]
+    pub fn security(mut self, security: Security) -> SummaryInfoBuilder {
+        self.summary_info.set_security(security);
+        self
+    }
+
+    /// Sets the "subject" property.
+    #[must_use]
+    pub fn subject<S: Into<String>>(
+        mut self,
+        subject: S,
+    ) -> SummaryInfoBuilder {
+        self.summary_info.set_subject(subject);
+        self
+    }
+
+    /// Sets the "title" property.  If no title is set, `Package::create`
+    /// will use the default title for the package type.
+    #[must_use]
+    pub fn title<S: Into<String>>(mut self, title: S) -> SummaryInfoBuilder {
+        self.summary_info.set_title(title);
+        self
+    }
+
+    /// Sets the "UUID" property.
+    #[must_use]
+    pub fn uuid(mut self, uuid: Uuid) -> SummaryInfoBuilder {
+        self.summary_info.set_uuid(uuid);
+        self
+    }
+
+    /// Sets the "Word Count" property.
+    #[must_use]
+    pub fn word_count(mut self, word_count: i32) -> SummaryInfoBuilder {
+        self.summary_info.set_word_count(word_count);
+        self
+    }
+
+    /// Builds the `SummaryInfo`.
+    #[must_use]
+    pub fn finish(mut self) -> SummaryInfo {
+        if let Some(arch) = self.arch {
+            self.summary_info.set_architecture(arch);
+        }
+        if !self.languages.is_empty() {
+            self.summary_info.set_languages(&self.languages);
+        }
+        self.summary_info
+    }
 }
 
 // ========================================================================= //
 
 #[cfg(test)]
 mod tests {
-    use super::SummaryInfo;
+    use super::{Architecture, Security, SummaryInfo};
     use crate::internal::{
         language::Language,
         summary::{
@@ -553,7 +934,7 @@ mod tests {
         summary_info
             .set_keywords(&["Test", "Package"].map(ToString::to_string));
         summary_info.set_comments("This app is the greatest!");
-        summary_info.set_arch("x64");
+        summary_info.set_arch("x64").unwrap();
         summary_info.set_languages(&languages);
         summary_info.set_last_saved_by("John Doe");
         summary_info.set_uuid(uuid);
@@ -637,7 +1018,7 @@ mod tests {
         summary_info.set_languages(&[Language::from_tag("en")]);
         assert_eq!(summary_info.arch(), None);
         assert_eq!(summary_info.languages(), vec![Language::from_tag("en")]);
-        summary_info.set_arch("Intel");
+        summary_info.set_arch("Intel").unwrap();
         assert_eq!(summary_info.arch(), Some("Intel"));
         assert_eq!(summary_info.languages(), vec![Language::from_tag("en")]);
 
@@ -645,13 +1026,74 @@ mod tests {
         let mut summary_info = SummaryInfo::new();
         assert_eq!(summary_info.languages(), vec![]);
         assert_eq!(summary_info.arch(), None);
-        summary_info.set_arch("Intel");
+        summary_info.set_arch("Intel").unwrap();
         assert_eq!(summary_info.languages(), vec![]);
         assert_eq!(summary_info.arch(), Some("Intel"));
         summary_info.set_languages(&[Language::from_tag("en")]);
         assert_eq!(summary_info.languages(), vec![Language::from_tag("en")]);
         assert_eq!(summary_info.arch(), Some("Intel"));
     }
+
+    #[test]
+    fn architecture_spellings() {
+        let spellings = [
+            ("Intel", Architecture::Intel),
+            ("x64", Architecture::X64),
+            ("Intel64", Architecture::Intel64),
+            ("Arm", Architecture::Arm),
+            ("Arm64", Architecture::Arm64),
+        ];
+        for (spelling, arch) in spellings {
+            assert_eq!(spelling.parse::<Architecture>().unwrap(), arch);
+            assert_eq!(arch.to_string(), spelling);
+            let upper = spelling.to_ascii_uppercase();
+            assert_eq!(upper.parse::<Architecture>().unwrap(), arch);
+
+            let mut summary_info = SummaryInfo::new();
+            summary_info.set_arch(spelling.to_ascii_lowercase()).unwrap();
+            assert_eq!(summary_info.arch(), Some(spelling));
+            assert_eq!(summary_info.architecture(), Some(arch));
+        }
+    }
+
+    #[test]
+    fn invalid_architecture() {
+        assert!("amd64".parse::<Architecture>().is_err());
+        let mut summary_info = SummaryInfo::new();
+        summary_info.set_arch("x64").unwrap();
+        let error = summary_info.set_arch("amd64").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "Invalid architecture: \"amd64\"");
+        assert_eq!(summary_info.arch(), Some("x64"));
+
+        #[allow(deprecated)]
+        summary_info.set_arch_unchecked("amd64");
+        assert_eq!(summary_info.arch(), Some("amd64"));
+        assert_eq!(summary_info.architecture(), None);
+    }
+
+    #[test]
+    fn security_round_trip() {
+        for security in [
+            Security::NoRestriction,
+            Security::ReadOnlyRecommended,
+            Security::ReadOnlyEnforced,
+        ] {
+            let mut summary_info = SummaryInfo::new();
+            summary_info.set_security(security);
+            let mut output = Vec::new();
+            summary_info.write(&mut output).unwrap();
+            let summary_info =
+                SummaryInfo::read(std::io::Cursor::new(output)).unwrap();
+            assert_eq!(summary_info.security(), Some(security));
+            assert_eq!(summary_info.doc_security(), Some(security.id()));
+        }
+
+        let mut summary_info = SummaryInfo::new();
+        assert_eq!(summary_info.security(), None);
+        summary_info.set_doc_security(3);
+        assert_eq!(summary_info.security(), None);
+    }
 }
 
 // ========================================================================= //
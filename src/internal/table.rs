@@ -1,8 +1,12 @@
 use crate::internal::category::Category;
 use crate::internal::column::Column;
+use crate::internal::expr::Expr;
 use crate::internal::streamname;
 use crate::internal::stringpool::StringPool;
-use crate::internal::value::{Value, ValueRef};
+use crate::internal::value::{BorrowedValue, Value, ValueRef};
+use cfb;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::ops::Index;
 use std::rc::Rc;
@@ -15,6 +19,10 @@ pub struct Table {
     name: String,
     columns: Vec<Column>,
     long_string_refs: bool,
+    // The most recently loaded rows of this table, so that repeated queries
+    // don't have to re-read and re-decode the table's stream.  This is
+    // cleared whenever the rows are rewritten.
+    cached_rows: RefCell<Option<Vec<Vec<ValueRef>>>>,
 }
 
 impl Table {
@@ -26,7 +34,12 @@ impl Table {
         columns: Vec<Column>,
         long_string_refs: bool,
     ) -> Rc<Table> {
-        Rc::new(Table { name, columns, long_string_refs })
+        Rc::new(Table {
+            name,
+            columns,
+            long_string_refs,
+            cached_rows: RefCell::new(None),
+        })
     }
 
     /// Returns the name of the table.
@@ -35,6 +48,16 @@ impl Table {
         &self.name
     }
 
+    /// Returns true if this is one of the system tables that Windows
+    /// Installer uses to store database metadata (such as `_Tables`,
+    /// `_Columns`, `_Validation`, or `_Streams`), rather than a table
+    /// holding package content.  Windows Installer reserves all table names
+    /// that begin with an underscore for this purpose.
+    #[must_use]
+    pub fn is_system(&self) -> bool {
+        self.name.starts_with('_')
+    }
+
     /// Returns the name of the CFB stream that holds this table's data.
     pub(crate) fn stream_name(&self) -> String {
         streamname::encode(&self.name, true)
@@ -55,13 +78,16 @@ impl Table {
         &self.columns
     }
 
-    /// Returns true if this table has a column with the given name.
+    /// Returns true if this table has a column with the given name.  Column
+    /// names are matched the same way as by [`Table::get_column`].
     #[must_use]
     pub fn has_column(&self, column_name: &str) -> bool {
         self.index_for_column_name(column_name).is_some()
     }
 
-    /// Returns the column with the given name, if any.
+    /// Returns the column with the given name, if any.  An exact match for
+    /// the name is preferred, but failing that, column names are compared
+    /// ASCII-case-insensitively.
     #[must_use]
     pub fn get_column(&self, column_name: &str) -> Option<&Column> {
         match self.index_for_column_name(column_name) {
@@ -70,6 +96,12 @@ impl Table {
         }
     }
 
+    /// Returns the table's primary key columns, in order.
+    #[must_use]
+    pub fn primary_key_columns(&self) -> Vec<&Column> {
+        self.columns.iter().filter(|column| column.is_primary_key()).collect()
+    }
+
     /// Returns the indices of table's primary key columns.
     #[must_use]
     pub fn primary_key_indices(&self) -> Vec<usize> {
@@ -86,34 +118,41 @@ impl Table {
             .collect()
     }
 
+    /// Returns the index of the column with the given name.  An exact match
+    /// is preferred, but failing that, column names are compared
+    /// ASCII-case-insensitively.
     pub(crate) fn index_for_column_name(
         &self,
         column_name: &str,
     ) -> Option<usize> {
-        for (index, column) in self.columns.iter().enumerate() {
-            if column.name() == column_name {
-                return Some(index);
-            }
-        }
-        None
+        self.columns
+            .iter()
+            .position(|column| column.name() == column_name)
+            .or_else(|| {
+                self.columns.iter().position(|column| {
+                    column.name().eq_ignore_ascii_case(column_name)
+                })
+            })
     }
 
-    /// Parses row data from the given data source and returns an interator
-    /// over the rows.
-    pub(crate) fn read_rows<R: Read + Seek>(
-        &self,
-        mut reader: R,
-    ) -> io::Result<Vec<Vec<ValueRef>>> {
-        let data_length = reader.seek(SeekFrom::End(0))?;
-        reader.rewind()?;
+    /// Returns the number of rows stored in a table data stream of the given
+    /// length, without reading any of the row data.
+    pub(crate) fn num_rows(&self, data_length: u64) -> io::Result<usize> {
         let row_size = self
             .columns
             .iter()
             .map(|col| col.coltype().width(self.long_string_refs))
             .sum::<u64>();
-        let num_columns = self.columns.len();
-        let num_rows =
-            if row_size > 0 { (data_length / row_size) as usize } else { 0 };
+        if row_size > 0 && !data_length.is_multiple_of(row_size) {
+            schema_mismatch!(
+                "Table {:?} has {} bytes of data, which is not a whole number \
+                 of rows of the {} bytes per row declared by its columns",
+                self.name,
+                data_length,
+                row_size
+            );
+        }
+        let num_rows = data_length.checked_div(row_size).unwrap_or(0) as usize;
         // The number of rows cannot exceed 65536, according to this FAQ:
         // http://www.installsite.org/pages/en/msifaq/a/1043.htm
         if num_rows > 65536 {
@@ -122,6 +161,55 @@ impl Table {
                 num_rows
             );
         }
+        Ok(num_rows)
+    }
+
+    /// Returns the rows of this table, reading them from the table's stream in
+    /// the given compound file unless they are already cached.
+    pub(crate) fn load_rows<F: Read + Seek>(
+        &self,
+        comp: &mut cfb::CompoundFile<F>,
+    ) -> io::Result<Vec<Vec<ValueRef>>> {
+        if let Some(ref rows) = *self.cached_rows.borrow() {
+            return Ok(rows.clone());
+        }
+        let stream_name = self.stream_name();
+        let rows = if comp.exists(&stream_name) {
+            self.read_rows(comp.open_stream(&stream_name)?)?
+        } else {
+            Vec::new()
+        };
+        *self.cached_rows.borrow_mut() = Some(rows.clone());
+        Ok(rows)
+    }
+
+    /// Replaces the rows in this table's stream in the given compound file,
+    /// and invalidates the cached rows.
+    pub(crate) fn store_rows<F: Read + Write + Seek>(
+        &self,
+        comp: &mut cfb::CompoundFile<F>,
+        rows: Vec<Vec<ValueRef>>,
+    ) -> io::Result<()> {
+        self.clear_cache();
+        let stream = comp.create_stream(self.stream_name())?;
+        self.write_rows(stream, rows)
+    }
+
+    /// Discards the cached rows (if any) for this table.
+    pub(crate) fn clear_cache(&self) {
+        *self.cached_rows.borrow_mut() = None;
+    }
+
+    /// Parses row data from the given data source and returns an interator
+    /// over the rows.
+    pub(crate) fn read_rows<R: Read + Seek>(
+        &self,
+        mut reader: R,
+    ) -> io::Result<Vec<Vec<ValueRef>>> {
+        let data_length = reader.seek(SeekFrom::End(0))?;
+        reader.rewind()?;
+        let num_columns = self.columns.len();
+        let num_rows = self.num_rows(data_length)?;
         let mut rows =
             vec![Vec::<ValueRef>::with_capacity(num_columns); num_rows];
         for column in &self.columns {
@@ -135,6 +223,29 @@ impl Table {
         Ok(rows)
     }
 
+    /// Reads a single row from table data with the given number of rows,
+    /// without reading the rest of the data.
+    pub(crate) fn read_row<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        num_rows: usize,
+        row_index: usize,
+    ) -> io::Result<Vec<ValueRef>> {
+        debug_assert!(row_index < num_rows);
+        let mut values = Vec::with_capacity(self.columns.len());
+        let mut column_start = 0;
+        for column in &self.columns {
+            let coltype = column.coltype();
+            let width = coltype.width(self.long_string_refs);
+            reader.seek(SeekFrom::Start(
+                column_start + width * (row_index as u64),
+            ))?;
+            values.push(coltype.read_value(reader, self.long_string_refs)?);
+            column_start += width * (num_rows as u64);
+        }
+        Ok(values)
+    }
+
     pub(crate) fn write_rows<W: Write>(
         &self,
         mut writer: W,
@@ -187,10 +298,36 @@ impl Row {
         self.table.columns()
     }
 
-    /// Returns true if this row has a column with the given name.
+    /// Returns true if this row has a column with the given name.  Column
+    /// names are matched case-insensitively, as in [`Row::get`].
     #[must_use]
     pub fn has_column(&self, column_name: &str) -> bool {
-        self.table.has_column(column_name)
+        self.index_for_column_name(column_name).is_some()
+    }
+
+    /// Returns the value of the column with the given name, or `None` if
+    /// there is no such column.  An exact match for the name is preferred,
+    /// but failing that, column names are compared ASCII-case-insensitively.
+    #[must_use]
+    pub fn get(&self, column_name: &str) -> Option<&Value> {
+        self.index_for_column_name(column_name)
+            .map(|index| &self.values[index])
+    }
+
+    /// Returns the values of this row's primary key columns, in column
+    /// order.  For a row produced by a join, this includes the key columns
+    /// of every joined table.
+    #[must_use]
+    pub fn primary_key(&self) -> Vec<Value> {
+        self.table
+            .primary_key_indices()
+            .into_iter()
+            .map(|index| self.values[index].clone())
+            .collect()
+    }
+
+    fn index_for_column_name(&self, column_name: &str) -> Option<usize> {
+        self.table.index_for_column_name(column_name)
     }
 }
 
@@ -218,13 +355,13 @@ impl Index<usize> for Row {
     }
 }
 
-/// Gets the value of the column with the given name.  Panics if
-/// `!self.has_column(column_name)`.
+/// Gets the value of the column with the given name (see [`Row::get`]).
+/// Panics if `!self.has_column(column_name)`.
 impl Index<&str> for Row {
     type Output = Value;
 
     fn index(&self, column_name: &str) -> &Value {
-        match self.table.index_for_column_name(column_name) {
+        match self.index_for_column_name(column_name) {
             Some(index) => &self.values[index],
             None => {
                 if self.table.name.is_empty() {
@@ -267,6 +404,91 @@ impl<'a> Rows<'a> {
         self.table.columns()
     }
 
+    /// Returns the values of the next row, borrowing strings directly from
+    /// the package's string pool rather than copying each one into a new
+    /// `Value`.  This advances the same position as `next()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use msi::{Column, Insert, Package, PackageType, Select, Value};
+    /// # use std::io::Cursor;
+    /// let cursor = Cursor::new(Vec::new());
+    /// let mut package = Package::create(PackageType::Installer, cursor)?;
+    /// package.create_table(
+    ///     "Property",
+    ///     vec![
+    ///         Column::build("Property").primary_key().id_string(72),
+    ///         Column::build("Value").text_string(0),
+    ///     ],
+    /// )?;
+    /// package.insert_rows(
+    ///     Insert::into("Property").row(vec!["Foo".into(), "Bar".into()]),
+    /// )?;
+    /// let mut rows = package.select_rows(Select::table("Property"))?;
+    /// let values = rows.next_borrowed().unwrap();
+    /// assert_eq!(values[1].as_str(), Some("Bar"));
+    /// assert!(rows.next_borrowed().is_none());
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn next_borrowed(&mut self) -> Option<Vec<BorrowedValue<'a>>> {
+        let string_pool = self.string_pool;
+        let values = self.rows.get(self.next_row_index)?;
+        let values = values
+            .iter()
+            .map(|value_ref| value_ref.to_borrowed(string_pool))
+            .collect();
+        self.next_row_index += 1;
+        Some(values)
+    }
+
+    /// Converts this into an iterator-like object that reuses a single
+    /// [`Row`] for every row, rather than allocating a new one each time.
+    /// String values are copied into the existing strings' buffers where
+    /// possible, so a full scan of a table allocates little beyond the
+    /// longest string in each column.
+    ///
+    /// Because each row overwrites the previous one, `ReusingRows` can't
+    /// implement `Iterator`; call [`ReusingRows::next`] in a `while let`
+    /// loop instead.  The returned `&Row` is only valid until the next call
+    /// to `next()` (which the borrow checker enforces); clone it to keep it
+    /// longer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use msi::{Column, Insert, Package, PackageType, Select, Value};
+    /// # use std::io::Cursor;
+    /// let cursor = Cursor::new(Vec::new());
+    /// let mut package = Package::create(PackageType::Installer, cursor)?;
+    /// package.create_table(
+    ///     "Property",
+    ///     vec![
+    ///         Column::build("Property").primary_key().id_string(72),
+    ///         Column::build("Value").text_string(0),
+    ///     ],
+    /// )?;
+    /// package.insert_rows(
+    ///     Insert::into("Property")
+    ///         .row(vec!["Bar".into(), "2".into()])
+    ///         .row(vec!["Foo".into(), "1".into()]),
+    /// )?;
+    /// let rows = package.select_rows(Select::table("Property"))?;
+    /// let mut rows = rows.into_reusing_iter();
+    /// let mut names = Vec::new();
+    /// while let Some(row) = rows.next() {
+    ///     names.push(row["Property"].as_str().unwrap().to_string());
+    /// }
+    /// assert_eq!(names, vec!["Bar", "Foo"]);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[must_use]
+    pub fn into_reusing_iter(self) -> ReusingRows<'a> {
+        let values = vec![Value::Null; self.table.columns().len()];
+        let row = Row::new(self.table.clone(), values);
+        ReusingRows { rows: self, row }
+    }
+
     pub(crate) fn into_table_and_values(
         self,
     ) -> (Rc<Table>, Vec<Vec<ValueRef>>) {
@@ -299,6 +521,244 @@ impl<'a> Iterator for Rows<'a> {
 
 impl<'a> ExactSizeIterator for Rows<'a> {}
 
+/// A lending iterator over the rows in a database table, which reuses one
+/// [`Row`] for every row.  See [`Rows::into_reusing_iter`].
+pub struct ReusingRows<'a> {
+    rows: Rows<'a>,
+    row: Row,
+}
+
+impl<'a> ReusingRows<'a> {
+    /// Returns the list of columns for these rows.
+    #[must_use]
+    pub fn columns(&self) -> &[Column] {
+        self.rows.columns()
+    }
+
+    /// Returns the number of rows remaining.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns true if there are no rows remaining.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Overwrites the reused row with the values of the next row, and
+    /// returns it, or returns `None` if there are no more rows.  The row is
+    /// only valid until the next call to this method.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Option<&Row> {
+        let string_pool = self.rows.string_pool;
+        let values = self.rows.rows.get(self.rows.next_row_index)?;
+        for (value, value_ref) in self.row.values.iter_mut().zip(values) {
+            match (value_ref.to_borrowed(string_pool), &mut *value) {
+                (BorrowedValue::Str(string), Value::Str(buffer)) => {
+                    buffer.clear();
+                    buffer.push_str(string);
+                }
+                (borrowed, value) => *value = borrowed.to_value(),
+            }
+        }
+        self.rows.next_row_index += 1;
+        Some(&self.row)
+    }
+}
+
+// ========================================================================= //
+
+/// An iterator over the rows selected by a query, which reads each row from
+/// the package only as the iterator advances.
+///
+/// Queries on a single table are evaluated lazily, so that only the current
+/// row (rather than the whole table) is held in memory.  Queries involving
+/// joins are evaluated up front, as with
+/// [`Package::select_rows`](crate::Package::select_rows).
+pub struct RowStream<'a, F> {
+    string_pool: &'a StringPool,
+    source: RowSource<'a, F>,
+}
+
+enum RowSource<'a, F> {
+    Buffered(Rows<'a>),
+    Lazy {
+        stream: Option<cfb::Stream<F>>,
+        table: Rc<Table>,
+        num_rows: usize,
+        next_row_index: usize,
+        condition: Option<Expr>,
+        condition_columns: Vec<bool>,
+        column_indices: Vec<usize>,
+        output_table: Rc<Table>,
+    },
+}
+
+impl<'a, F> RowStream<'a, F> {
+    pub(crate) fn buffered(rows: Rows<'a>) -> RowStream<'a, F> {
+        RowStream {
+            string_pool: rows.string_pool,
+            source: RowSource::Buffered(rows),
+        }
+    }
+
+    /// Creates a lazy row stream over the given table data stream.  If
+    /// `column_indices` is non-empty, only those columns are included in the
+    /// output rows (which use `output_table` as their table).
+    pub(crate) fn lazy(
+        string_pool: &'a StringPool,
+        stream: Option<cfb::Stream<F>>,
+        table: Rc<Table>,
+        num_rows: usize,
+        condition: Option<Expr>,
+        column_indices: Vec<usize>,
+        output_table: Rc<Table>,
+    ) -> RowStream<'a, F> {
+        let mut condition_columns = vec![false; table.columns().len()];
+        if let Some(ref expr) = condition {
+            for column_name in expr.column_names() {
+                if let Some(index) = table.index_for_column_name(column_name) {
+                    condition_columns[index] = true;
+                }
+            }
+        }
+        RowStream {
+            string_pool,
+            source: RowSource::Lazy {
+                stream,
+                table,
+                num_rows,
+                next_row_index: 0,
+                condition,
+                condition_columns,
+                column_indices,
+                output_table,
+            },
+        }
+    }
+
+    /// Returns the list of columns for these rows.
+    #[must_use]
+    pub fn columns(&self) -> &[Column] {
+        match self.source {
+            RowSource::Buffered(ref rows) => rows.columns(),
+            RowSource::Lazy { ref output_table, .. } => output_table.columns(),
+        }
+    }
+}
+
+impl<'a, F: Read + Seek> Iterator for RowStream<'a, F> {
+    type Item = io::Result<Row>;
+
+    fn next(&mut self) -> Option<io::Result<Row>> {
+        let string_pool = self.string_pool;
+        match self.source {
+            RowSource::Buffered(ref mut rows) => rows.next().map(Ok),
+            RowSource::Lazy {
+                ref mut stream,
+                ref table,
+                num_rows,
+                ref mut next_row_index,
+                ref condition,
+                ref condition_columns,
+                ref column_indices,
+                ref output_table,
+            } => {
+                let stream = stream.as_mut()?;
+                while *next_row_index < num_rows {
+                    let row_index = *next_row_index;
+                    *next_row_index += 1;
+                    let value_refs =
+                        match table.read_row(stream, num_rows, row_index) {
+                            Ok(value_refs) => value_refs,
+                            Err(error) => {
+                                *next_row_index = num_rows;
+                                return Some(Err(error));
+                            }
+                        };
+                    if let Some(ref expr) = *condition {
+                        // Only resolve the strings that the condition needs.
+                        let values: Vec<Value> = value_refs
+                            .iter()
+                            .zip(condition_columns.iter())
+                            .map(|(value_ref, &used)| {
+                                if used {
+                                    value_ref.to_value(string_pool)
+                                } else {
+                                    Value::Null
+                                }
+                            })
+                            .collect();
+                        if !expr
+                            .eval(&Row::new(table.clone(), values))
+                            .to_bool()
+                        {
+                            continue;
+                        }
+                    }
+                    let values: Vec<Value> = if column_indices.is_empty() {
+                        value_refs
+                            .iter()
+                            .map(|value_ref| value_ref.to_value(string_pool))
+                            .collect()
+                    } else {
+                        column_indices
+                            .iter()
+                            .map(|&index| {
+                                value_refs[index].to_value(string_pool)
+                            })
+                            .collect()
+                    };
+                    return Some(Ok(Row::new(output_table.clone(), values)));
+                }
+                None
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self.source {
+            RowSource::Buffered(ref rows) => rows.size_hint(),
+            RowSource::Lazy {
+                ref stream,
+                num_rows,
+                next_row_index,
+                ref condition,
+                ..
+            } => {
+                let remaining = if stream.is_some() {
+                    num_rows - next_row_index
+                } else {
+                    0
+                };
+                if condition.is_some() {
+                    (0, Some(remaining))
+                } else {
+                    (remaining, Some(remaining))
+                }
+            }
+        }
+    }
+}
+
+// ========================================================================= //
+
+/// Looks up a table by name.  An exact match is preferred, but failing that,
+/// table names are compared ASCII-case-insensitively.
+pub(crate) fn find_table<'a, T>(
+    tables: &'a BTreeMap<String, T>,
+    table_name: &str,
+) -> Option<&'a T> {
+    tables.get(table_name).or_else(|| {
+        tables
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(table_name))
+            .map(|(_, table)| table)
+    })
+}
+
 // ========================================================================= //
 
 #[cfg(test)]
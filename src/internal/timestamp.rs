@@ -4,27 +4,31 @@ use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 //===========================================================================//
 
-/// A Windows timestamp.  This is represented as the number of 100-nanosecond
-/// intervals since January 1, 1601 UTC.
+/// A Windows timestamp (`FILETIME`).  This is represented as the number of
+/// 100-nanosecond intervals since January 1, 1601 UTC, and is serialized as a
+/// little-endian 64-bit integer.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct Timestamp(u64);
 
 impl Timestamp {
-    /// Returns a timestamp representing the given system time.
+    /// Returns a timestamp representing the given system time.  Any precision
+    /// finer than 100 nanoseconds is truncated (towards the past).
+    #[must_use]
     pub fn from_system_time(system_time: SystemTime) -> Timestamp {
         Timestamp(timestamp_from_system_time(system_time))
     }
 
-    /// Returns the local system time that this timestamp represents.
+    /// Returns the system time that this timestamp represents.
+    #[must_use]
     pub fn to_system_time(self) -> SystemTime {
         system_time_from_timestamp(self.0)
     }
 
-    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Timestamp> {
+    pub(crate) fn read_from<R: Read>(reader: &mut R) -> io::Result<Timestamp> {
         Ok(Timestamp(reader.read_u64::<LittleEndian>()?))
     }
 
-    pub fn write_to<W: Write>(self, writer: &mut W) -> io::Result<()> {
+    pub(crate) fn write_to<W: Write>(self, writer: &mut W) -> io::Result<()> {
         writer.write_u64::<LittleEndian>(self.0)
     }
 }
@@ -34,7 +38,8 @@ impl Timestamp {
 /// The Windows timestamp value for the Unix epoch (Jan 1, 1970 UTC).
 const UNIX_EPOCH_TIMESTAMP: u64 = 116444736000000000;
 
-/// Converts a local `SystemTime` to a Windows timestamp value.
+/// Converts a `SystemTime` to a Windows timestamp value, rounding down to the
+/// nearest 100 nanoseconds.
 fn timestamp_from_system_time(system_time: SystemTime) -> u64 {
     match system_time.duration_since(UNIX_EPOCH) {
         Ok(duration) => {
@@ -42,13 +47,19 @@ fn timestamp_from_system_time(system_time: SystemTime) -> u64 {
             UNIX_EPOCH_TIMESTAMP.saturating_add(delta)
         }
         Err(err) => {
-            let delta = duration_to_timestamp_delta(err.duration());
+            // Round the distance before the epoch up, so that the timestamp
+            // is rounded down, just as it is for times after the epoch.
+            let duration = err.duration();
+            let mut delta = duration_to_timestamp_delta(duration);
+            if duration.subsec_nanos() % 100 != 0 {
+                delta = delta.saturating_add(1);
+            }
             UNIX_EPOCH_TIMESTAMP.saturating_sub(delta)
         }
     }
 }
 
-/// Converts a Windows timestamp value to a local `SystemTime`.
+/// Converts a Windows timestamp value to a `SystemTime`.
 fn system_time_from_timestamp(timestamp: u64) -> SystemTime {
     // The maximum range of SystemTime varies by system, and some systems
     // (e.g. 32-bit Linux) can't represent, say, a zero Windows timestamp.  So
@@ -91,7 +102,7 @@ fn timestamp_delta_to_duration(delta: u64) -> Duration {
 mod tests {
     use super::{
         duration_to_timestamp_delta, system_time_from_timestamp,
-        timestamp_delta_to_duration, timestamp_from_system_time,
+        timestamp_delta_to_duration, timestamp_from_system_time, Timestamp,
         UNIX_EPOCH_TIMESTAMP,
     };
     use std::time::{Duration, UNIX_EPOCH};
@@ -155,6 +166,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn sub_tick_precision_rounds_down() {
+        let after = UNIX_EPOCH + Duration::new(1, 150);
+        assert_eq!(
+            timestamp_from_system_time(after),
+            UNIX_EPOCH_TIMESTAMP + 10_000_001
+        );
+        let before = UNIX_EPOCH - Duration::new(1, 150);
+        assert_eq!(
+            timestamp_from_system_time(before),
+            UNIX_EPOCH_TIMESTAMP - 10_000_002
+        );
+        let before = UNIX_EPOCH - Duration::new(1, 200);
+        assert_eq!(
+            timestamp_from_system_time(before),
+            UNIX_EPOCH_TIMESTAMP - 10_000_002
+        );
+    }
+
+    #[test]
+    fn serialized_layout() {
+        let mut output = Vec::new();
+        Timestamp(131343363961234567).write_to(&mut output).unwrap();
+        assert_eq!(output, 131343363961234567u64.to_le_bytes());
+        let timestamp = Timestamp::read_from(&mut &output[..]).unwrap();
+        assert_eq!(timestamp, Timestamp(131343363961234567));
+        assert_eq!(
+            timestamp.to_system_time(),
+            UNIX_EPOCH + Duration::new(1489862796, 123_456_700)
+        );
+    }
+
     #[test]
     fn extreme_timestamps() {
         // If the system we're on can't represent these timestamps in a
@@ -0,0 +1,535 @@
+use crate::internal::column::Column;
+use crate::internal::diff::{keyed_rows, same_columns};
+use crate::internal::error::Result;
+use crate::internal::package::{
+    Package, PackageType, COLUMNS_TABLE_NAME, STRING_DATA_TABLE_NAME,
+    STRING_POOL_TABLE_NAME, TABLES_TABLE_NAME,
+};
+use crate::internal::streamname::{self, SUMMARY_INFO_STREAM_NAME};
+use crate::internal::stringpool::{StringPool, StringPoolBuilder};
+use crate::internal::summary::SummaryInfo;
+use crate::internal::table::{find_table, Table};
+use crate::internal::value::{Value, ValueRef};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use cfb;
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Read, Seek, Write};
+use std::ops::BitOr;
+use std::rc::Rc;
+
+// ========================================================================= //
+
+// The first bit of a row's mask in a transform table stream indicates that
+// the row is being inserted, in which case the high byte of the mask holds
+// the number of column values that follow.  Otherwise, each bit of the mask
+// indicates that the corresponding (non-key) column is being modified; a mask
+// of zero indicates that the row is being deleted.
+const MASK_INSERT: u16 = 0x1;
+const MAX_MASK_COLUMNS: usize = 16;
+
+// ========================================================================= //
+
+/// One change to a table row, as recorded in a [`Transform`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RowChange {
+    /// Adds a new row, with the given values for all columns.
+    Insert(Vec<Value>),
+    /// Changes the values of some of the non-key columns of the row with the
+    /// given primary key.
+    Update {
+        /// The values of the row's primary key columns.
+        key: Vec<Value>,
+        /// The names and new values of the columns being changed.
+        values: Vec<(String, Value)>,
+    },
+    /// Deletes the row with the given primary key.
+    Delete(Vec<Value>),
+}
+
+// ========================================================================= //
+
+/// Flags controlling which errors are ignored when applying a transform to a
+/// package with [`Package::apply_transform`](crate::Package::apply_transform).
+/// Flags can be combined with the `|` operator.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TransformFlags(u32);
+
+impl TransformFlags {
+    /// No errors are ignored.
+    pub const NONE: TransformFlags = TransformFlags(0);
+    /// Skip inserting rows whose primary key already exists in the table.
+    pub const IGNORE_ADD_EXISTING_ROW: TransformFlags = TransformFlags(0x1);
+    /// Skip deleting rows that don't exist in the table.
+    pub const IGNORE_DELETE_MISSING_ROW: TransformFlags = TransformFlags(0x2);
+    /// Skip modifying rows that don't exist in the table.
+    pub const IGNORE_UPDATE_MISSING_ROW: TransformFlags = TransformFlags(0x10);
+
+    /// Returns the flags as a bitmask, using the same values as the error
+    /// conditions in the character count property of a transform's summary
+    /// information.
+    #[must_use]
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Returns true if all of the given flags are set.
+    #[must_use]
+    pub fn contains(self, other: TransformFlags) -> bool {
+        (self.0 & other.0) == other.0
+    }
+}
+
+impl BitOr for TransformFlags {
+    type Output = TransformFlags;
+
+    fn bitor(self, rhs: TransformFlags) -> TransformFlags {
+        TransformFlags(self.0 | rhs.0)
+    }
+}
+
+// ========================================================================= //
+
+struct TableChanges {
+    columns: Vec<Column>,
+    changes: Vec<RowChange>,
+}
+
+/// A set of changes to the rows of a package's tables, which can be stored
+/// as a transform (.mst) file and later applied to a package.
+///
+/// Currently, transforms can only change rows of tables that exist with the
+/// same columns in both the base and target packages; adding or removing
+/// tables or columns, and the contents of binary streams, are not yet
+/// supported.
+pub struct Transform {
+    summary_info: SummaryInfo,
+    tables: BTreeMap<String, TableChanges>,
+}
+
+impl Transform {
+    /// Creates a transform containing the row insertions, modifications, and
+    /// deletions that turn the `base` package into the `target` package.
+    /// Returns an error if the two packages' tables have different schemas.
+    pub fn generate<F, G>(
+        base: &mut Package<F>,
+        target: &mut Package<G>,
+    ) -> Result<Transform>
+    where
+        F: Read + Seek,
+        G: Read + Seek,
+    {
+        let base_tables: BTreeMap<String, Table> = schema(base);
+        let target_tables: BTreeMap<String, Table> = schema(target);
+        for table_name in base_tables.keys().chain(target_tables.keys()) {
+            if find_table(&base_tables, table_name).is_none()
+                || find_table(&target_tables, table_name).is_none()
+            {
+                invalid_input!(
+                    "Table {:?} only exists in one of the packages; adding \
+                     or removing tables is not supported in transforms",
+                    table_name
+                );
+            }
+        }
+        let mut tables = BTreeMap::new();
+        for (table_name, table) in base_tables.iter() {
+            let target_table = find_table(&target_tables, table_name).unwrap();
+            if !same_columns(table, target_table) {
+                invalid_input!(
+                    "Table {:?} has different columns in the two packages; \
+                     changing columns is not supported in transforms",
+                    table_name
+                );
+            }
+            let base_rows = keyed_rows(base, table)?;
+            let target_rows = keyed_rows(target, table)?;
+            let changes = diff_rows(table, &base_rows, &target_rows);
+            if !changes.is_empty() {
+                let columns = table.columns().to_vec();
+                tables.insert(
+                    table_name.clone(),
+                    TableChanges { columns, changes },
+                );
+            }
+        }
+        let summary_info = transform_summary_info(base, target)?;
+        Ok(Transform { summary_info, tables })
+    }
+
+    /// Returns the summary information for the transform.  This records the
+    /// product codes and versions of the base and target packages.
+    #[must_use]
+    pub fn summary_info(&self) -> &SummaryInfo {
+        &self.summary_info
+    }
+
+    /// Returns the names of the tables changed by this transform, in
+    /// alphabetical order.
+    #[must_use]
+    pub fn changed_tables(&self) -> Vec<&str> {
+        self.tables.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the changes this transform makes to the named table, in order
+    /// by primary key.
+    #[must_use]
+    pub fn changes(&self, table_name: &str) -> &[RowChange] {
+        match find_table(&self.tables, table_name) {
+            Some(table) => &table.changes,
+            None => &[],
+        }
+    }
+
+    /// Reads a transform (.mst) file, using the given table definitions (from
+    /// the package that the transform will be applied to) to decode its rows.
+    pub(crate) fn read<R: Read + Seek>(
+        reader: R,
+        tables: &BTreeMap<String, Rc<Table>>,
+    ) -> io::Result<Transform> {
+        let mut comp = cfb::CompoundFile::open(reader)?;
+        if *comp.root_entry().clsid() != PackageType::Transform.clsid() {
+            invalid_data!("Not a transform (.mst) file");
+        }
+        let summary_info = if comp.exists(SUMMARY_INFO_STREAM_NAME) {
+            SummaryInfo::read(comp.open_stream(SUMMARY_INFO_STREAM_NAME)?)?
+        } else {
+            SummaryInfo::new()
+        };
+        let string_pool = {
+            let name = streamname::encode(STRING_POOL_TABLE_NAME, true);
+            let builder =
+                StringPoolBuilder::read_from_pool(comp.open_stream(name)?)?;
+            let name = streamname::encode(STRING_DATA_TABLE_NAME, true);
+            builder.build_from_data(comp.open_stream(name)?)?
+        };
+        let long_string_refs = string_pool.long_string_refs();
+        let mut table_names = Vec::new();
+        for entry in comp.read_root_storage() {
+            let (name, is_table) = streamname::decode(entry.name());
+            if !is_table
+                || name == STRING_POOL_TABLE_NAME
+                || name == STRING_DATA_TABLE_NAME
+            {
+                continue;
+            }
+            if name == TABLES_TABLE_NAME || name == COLUMNS_TABLE_NAME {
+                invalid_data!(
+                    "Transforms that add or remove tables or columns are not \
+                     supported"
+                );
+            }
+            table_names.push(name);
+        }
+        let mut changed_tables = BTreeMap::new();
+        for table_name in table_names {
+            let table = match find_table(tables, &table_name) {
+                Some(table) => table,
+                None => invalid_data!(
+                    "Transform changes table {:?}, which does not exist in \
+                     the package",
+                    table_name
+                ),
+            };
+            let columns = table.columns();
+            let mut data = Vec::new();
+            comp.open_stream(streamname::encode(&table_name, true))?
+                .read_to_end(&mut data)?;
+            let data_len = data.len() as u64;
+            let mut cursor = Cursor::new(data);
+            let read_value = |cursor: &mut Cursor<Vec<u8>>, index: usize| {
+                read_transform_value(
+                    cursor,
+                    &columns[index],
+                    long_string_refs,
+                    &string_pool,
+                )
+            };
+            let mut changes = Vec::new();
+            while cursor.position() < data_len {
+                let mask = cursor.read_u16::<LittleEndian>()?;
+                if (mask & MASK_INSERT) != 0 {
+                    let num_values = (mask >> 8) as usize;
+                    if num_values > columns.len() {
+                        invalid_data!(
+                            "Transform inserts a row with {} values into \
+                             table {:?}, which has only {} columns",
+                            num_values,
+                            table_name,
+                            columns.len()
+                        );
+                    }
+                    let mut values = vec![Value::Null; columns.len()];
+                    for (index, value) in
+                        values.iter_mut().enumerate().take(num_values)
+                    {
+                        *value = read_value(&mut cursor, index)?;
+                    }
+                    changes.push(RowChange::Insert(values));
+                    continue;
+                }
+                let mut key = Vec::new();
+                let mut values = Vec::new();
+                for (index, column) in columns.iter().enumerate() {
+                    if column.is_primary_key() {
+                        key.push(read_value(&mut cursor, index)?);
+                    } else if index < MAX_MASK_COLUMNS
+                        && (mask & (1 << index)) != 0
+                    {
+                        let value = read_value(&mut cursor, index)?;
+                        values.push((column.name().to_string(), value));
+                    }
+                }
+                if mask == 0 {
+                    changes.push(RowChange::Delete(key));
+                } else {
+                    changes.push(RowChange::Update { key, values });
+                }
+            }
+            let columns = columns.to_vec();
+            // Key the changes by the package's spelling of the table name,
+            // which may differ in case from the transform's.
+            changed_tables.insert(
+                table.name().to_string(),
+                TableChanges { columns, changes },
+            );
+        }
+        Ok(Transform { summary_info, tables: changed_tables })
+    }
+
+    /// Consumes the transform, returning the changes for each table.
+    pub(crate) fn into_changes(self) -> Vec<(String, Vec<RowChange>)> {
+        self.tables
+            .into_iter()
+            .map(|(table_name, table)| (table_name, table.changes))
+            .collect()
+    }
+
+    /// Writes the transform to the given writer, in the format of a
+    /// transform (.mst) file.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        let codepage = self.summary_info.codepage();
+        let mut string_pool = StringPool::new(codepage);
+        let mut table_rows = Vec::with_capacity(self.tables.len());
+        for (table_name, table) in self.tables.iter() {
+            let mut rows = Vec::with_capacity(table.changes.len());
+            for change in table.changes.iter() {
+                rows.push(encode_change(
+                    table_name,
+                    &table.columns,
+                    change,
+                    &mut string_pool,
+                )?);
+            }
+            table_rows.push((table_name, &table.columns, rows));
+        }
+        let long_string_refs = string_pool.long_string_refs();
+
+        let mut comp = cfb::CompoundFile::create(Cursor::new(Vec::new()))?;
+        comp.set_storage_clsid("/", PackageType::Transform.clsid())?;
+        self.summary_info
+            .write(comp.create_stream(SUMMARY_INFO_STREAM_NAME)?)?;
+        for (table_name, columns, rows) in table_rows {
+            let name = streamname::encode(table_name, true);
+            let mut stream = comp.create_stream(name)?;
+            for (mask, values) in rows {
+                stream.write_u16::<LittleEndian>(mask)?;
+                for (index, value_ref) in values {
+                    columns[index].coltype().write_value(
+                        &mut stream,
+                        value_ref,
+                        long_string_refs,
+                    )?;
+                }
+            }
+        }
+        let name = streamname::encode(STRING_POOL_TABLE_NAME, true);
+        string_pool.write_pool(comp.create_stream(name)?)?;
+        let name = streamname::encode(STRING_DATA_TABLE_NAME, true);
+        string_pool.write_data(comp.create_stream(name)?)?;
+        comp.flush()?;
+        Ok(writer.write_all(comp.into_inner().get_ref())?)
+    }
+}
+
+// ========================================================================= //
+
+fn read_transform_value<R: Read>(
+    reader: &mut R,
+    column: &Column,
+    long_string_refs: bool,
+    string_pool: &StringPool,
+) -> io::Result<Value> {
+    let value_ref = column.coltype().read_value(reader, long_string_refs)?;
+    Ok(value_ref.to_value(string_pool))
+}
+
+/// Returns copies of the definitions of the package's user tables.
+fn schema<F>(package: &Package<F>) -> BTreeMap<String, Table> {
+    package
+        .tables()
+        .filter(|table| {
+            table.name() != "_Tables" && table.name() != "_Columns"
+        })
+        .map(|table| (table.name().to_string(), table.clone()))
+        .collect()
+}
+
+fn diff_rows(
+    table: &Table,
+    base_rows: &BTreeMap<Vec<Value>, Vec<Value>>,
+    target_rows: &BTreeMap<Vec<Value>, Vec<Value>>,
+) -> Vec<RowChange> {
+    let mut changes = Vec::new();
+    let mut keys: Vec<&Vec<Value>> =
+        base_rows.keys().chain(target_rows.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    for key in keys {
+        match (base_rows.get(key), target_rows.get(key)) {
+            (Some(_), None) => changes.push(RowChange::Delete(key.clone())),
+            (None, Some(values)) => {
+                changes.push(RowChange::Insert(values.clone()))
+            }
+            (Some(base_values), Some(target_values)) => {
+                let values: Vec<(String, Value)> = table
+                    .columns()
+                    .iter()
+                    .zip(base_values.iter().zip(target_values.iter()))
+                    .filter(|(_, (base_value, target_value))| {
+                        base_value != target_value
+                    })
+                    .map(|(column, (_, target_value))| {
+                        (column.name().to_string(), target_value.clone())
+                    })
+                    .collect();
+                if !values.is_empty() {
+                    changes
+                        .push(RowChange::Update { key: key.clone(), values });
+                }
+            }
+            (None, None) => unreachable!(),
+        }
+    }
+    changes
+}
+
+/// Encodes a change as a row mask and the (column index, value) pairs to be
+/// written after it.
+fn encode_change(
+    table_name: &str,
+    columns: &[Column],
+    change: &RowChange,
+    string_pool: &mut StringPool,
+) -> io::Result<(u16, Vec<(usize, ValueRef)>)> {
+    let key_indices: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .filter(|(_, column)| column.is_primary_key())
+        .map(|(index, _)| index)
+        .collect();
+    let mut encode = |index: usize, value: &Value| {
+        (index, ValueRef::create(value.clone(), string_pool))
+    };
+    match *change {
+        RowChange::Insert(ref values) => {
+            let mask = ((values.len() as u16) << 8) | MASK_INSERT;
+            let values = values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| encode(index, value))
+                .collect();
+            Ok((mask, values))
+        }
+        RowChange::Update { ref key, ref values } => {
+            let mut new_values = vec![None; columns.len()];
+            for (column_name, value) in values.iter() {
+                let index = match columns
+                    .iter()
+                    .position(|column| column.name() == column_name)
+                {
+                    Some(index) => index,
+                    None => schema_mismatch!(
+                        "Table {:?} has no column named {:?}",
+                        table_name,
+                        column_name
+                    ),
+                };
+                if index >= MAX_MASK_COLUMNS {
+                    invalid_input!(
+                        "Cannot modify column {:?} of table {:?} in a \
+                         transform; only the first {} columns can be modified",
+                        column_name,
+                        table_name,
+                        MAX_MASK_COLUMNS
+                    );
+                }
+                new_values[index] = Some(value);
+            }
+            let mut mask = 0u16;
+            let mut encoded = Vec::new();
+            let mut key_values = key.iter();
+            for (index, new_value) in new_values.into_iter().enumerate() {
+                if key_indices.contains(&index) {
+                    encoded.push(encode(index, key_values.next().unwrap()));
+                } else if let Some(value) = new_value {
+                    mask |= 1 << index;
+                    encoded.push(encode(index, value));
+                }
+            }
+            Ok((mask, encoded))
+        }
+        RowChange::Delete(ref key) => {
+            let values = key_indices
+                .iter()
+                .zip(key.iter())
+                .map(|(&index, value)| encode(index, value))
+                .collect();
+            Ok((0, values))
+        }
+    }
+}
+
+fn transform_summary_info<F, G>(
+    base: &mut Package<F>,
+    target: &mut Package<G>,
+) -> io::Result<SummaryInfo>
+where
+    F: Read + Seek,
+    G: Read + Seek,
+{
+    let base_summary = base.summary_info();
+    let mut summary_info = SummaryInfo::new();
+    summary_info.set_codepage(base_summary.codepage());
+    summary_info.set_title(PackageType::Transform.default_title());
+    if let Some(arch) = base_summary.arch() {
+        summary_info.set_arch_string(arch);
+    }
+    summary_info.set_languages(&base_summary.languages());
+    if let Some(page_count) = base_summary.page_count() {
+        summary_info.set_page_count(page_count);
+    }
+    // The revision number of a transform identifies the base and target
+    // products, as "{base code}version;{target code}version;{upgrade code}".
+    let revision = format!(
+        "{}{};{}{};{}",
+        property(base, "ProductCode")?,
+        property(base, "ProductVersion")?,
+        property(target, "ProductCode")?,
+        property(target, "ProductVersion")?,
+        property(base, "UpgradeCode")?
+    );
+    summary_info.set_revision_number(revision);
+    summary_info.set_character_count(0);
+    Ok(summary_info)
+}
+
+/// Returns the value of a property from the package's `Property` table, or
+/// the empty string if it isn't set.
+fn property<F: Read + Seek>(
+    package: &mut Package<F>,
+    name: &str,
+) -> io::Result<String> {
+    Ok(package.property(name)?.unwrap_or_default())
+}
+
+// ========================================================================= //
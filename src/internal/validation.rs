@@ -0,0 +1,403 @@
+use crate::internal::category::Category;
+use crate::internal::column::{Column, ColumnType};
+use crate::internal::table::Row;
+use crate::internal::value::Value;
+use std::fmt;
+
+// ========================================================================= //
+
+/// A problem found when checking a package's rows against the constraints
+/// recorded in its `_Validation` table, as returned by
+/// `Package::validate_against_validation_table`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ValidationError {
+    table: String,
+    key: Vec<Value>,
+    column: String,
+    reason: String,
+}
+
+impl ValidationError {
+    pub(crate) fn new(
+        table: &str,
+        key: Vec<Value>,
+        column: &str,
+        reason: String,
+    ) -> ValidationError {
+        ValidationError {
+            table: table.to_string(),
+            key,
+            column: column.to_string(),
+            reason,
+        }
+    }
+
+    /// Returns the name of the table containing the invalid row.
+    #[must_use]
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Returns the values of the invalid row's primary key columns.
+    #[must_use]
+    pub fn key(&self) -> &[Value] {
+        &self.key
+    }
+
+    /// Returns the name of the column containing the invalid value.
+    #[must_use]
+    pub fn column(&self) -> &str {
+        &self.column
+    }
+
+    /// Returns a description of why the value is invalid.
+    #[must_use]
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(formatter, "{}.{} [", self.table, self.column)?;
+        for (index, value) in self.key.iter().enumerate() {
+            if index > 0 {
+                formatter.write_str(", ")?;
+            }
+            value.fmt(formatter)?;
+        }
+        write!(formatter, "]: {}", self.reason)
+    }
+}
+
+// ========================================================================= //
+
+/// The constraints on one column, as given by its row in the `_Validation`
+/// table.
+pub(crate) struct ColumnRule {
+    nullable: bool,
+    range: Option<(i32, i32)>,
+    key_tables: Vec<String>,
+    key_column: Option<usize>,
+    category: Option<Category>,
+    set: Vec<String>,
+}
+
+impl ColumnRule {
+    /// Parses a row of the `_Validation` table.
+    pub(crate) fn from_row(row: &Row) -> ColumnRule {
+        let range = match (row[3].as_int(), row[4].as_int()) {
+            (Some(min), Some(max)) => Some((min, max)),
+            _ => None,
+        };
+        let key_tables = match row[5].as_str() {
+            Some(tables) => tables.split(';').map(str::to_string).collect(),
+            None => Vec::new(),
+        };
+        let key_column = match row[6].as_int() {
+            Some(column) if column > 0 => Some(column as usize - 1),
+            _ => None,
+        };
+        let set = match row[8].as_str() {
+            Some(values) => values.split(';').map(str::to_string).collect(),
+            None => Vec::new(),
+        };
+        ColumnRule {
+            nullable: row[2].as_str() == Some("Y"),
+            range,
+            key_tables,
+            key_column,
+            category: row[7].as_str().and_then(|name| name.parse().ok()),
+            set,
+        }
+    }
+
+    /// Returns the names of the tables (if any) that values in this column
+    /// must refer to, along with the index of the referenced column.
+    pub(crate) fn foreign_key(&self) -> Option<(&[String], usize)> {
+        match self.key_column {
+            Some(index) if !self.key_tables.is_empty() => {
+                Some((&self.key_tables, index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns a description of why the given value violates this rule for
+    /// the given column, if it does.  Foreign keys are not checked.
+    pub(crate) fn check(
+        &self,
+        column: &Column,
+        value: &Value,
+    ) -> Option<String> {
+        match *value {
+            Value::Null => {
+                if !self.nullable {
+                    return Some("Null value in non-nullable column".into());
+                }
+            }
+            Value::Int(number) => {
+                if let ColumnType::Str(_) = column.coltype() {
+                    return Some(format!("{number} is not a string"));
+                }
+                if let Some((min, max)) = self.range {
+                    if number < min || number > max {
+                        return Some(format!(
+                            "{number} is outside the allowed range of {min} \
+                             to {max}"
+                        ));
+                    }
+                }
+            }
+            Value::Str(ref string) => {
+                let max_len = match column.coltype() {
+                    ColumnType::Str(max_len) => max_len,
+                    ColumnType::Int16 | ColumnType::Int32 => {
+                        return Some(format!("{value} is not an integer"));
+                    }
+                };
+                if max_len > 0 && string.chars().count() > max_len {
+                    return Some(format!(
+                        "{value} is longer than {max_len} characters"
+                    ));
+                }
+                if let Some(category) = self.category {
+                    if !category.validate(string) {
+                        return Some(format!(
+                            "{value} is not a valid {category} value"
+                        ));
+                    }
+                }
+                if !self.set.is_empty() && !self.set.contains(string) {
+                    return Some(format!(
+                        "{value} is not one of the allowed values"
+                    ));
+                }
+            }
+        }
+        None
+    }
+}
+
+// ========================================================================= //
+
+/// A reference from a column of one table (the child) to a column of another
+/// table (the parent), for use with
+/// `Package::check_referential_integrity`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ForeignKey {
+    child_table: String,
+    child_column: String,
+    parent_table: String,
+    parent_column: String,
+}
+
+impl ForeignKey {
+    /// Creates a foreign key requiring that every non-null value in the child
+    /// column appears somewhere in the parent column.
+    pub fn new<S, T, U, V>(
+        child_table: S,
+        child_column: T,
+        parent_table: U,
+        parent_column: V,
+    ) -> ForeignKey
+    where
+        S: Into<String>,
+        T: Into<String>,
+        U: Into<String>,
+        V: Into<String>,
+    {
+        ForeignKey {
+            child_table: child_table.into(),
+            child_column: child_column.into(),
+            parent_table: parent_table.into(),
+            parent_column: parent_column.into(),
+        }
+    }
+
+    /// Returns the name of the table containing the references.
+    #[must_use]
+    pub fn child_table(&self) -> &str {
+        &self.child_table
+    }
+
+    /// Returns the name of the column containing the references.
+    #[must_use]
+    pub fn child_column(&self) -> &str {
+        &self.child_column
+    }
+
+    /// Returns the name of the table being referred to.
+    #[must_use]
+    pub fn parent_table(&self) -> &str {
+        &self.parent_table
+    }
+
+    /// Returns the name of the column being referred to.
+    #[must_use]
+    pub fn parent_column(&self) -> &str {
+        &self.parent_column
+    }
+}
+
+impl fmt::Display for ForeignKey {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(
+            formatter,
+            "{}.{} -> {}.{}",
+            self.child_table,
+            self.child_column,
+            self.parent_table,
+            self.parent_column
+        )
+    }
+}
+
+// ========================================================================= //
+
+/// A child row whose value for a foreign key doesn't match any row of the
+/// parent table, as returned by `Package::check_referential_integrity`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntegrityViolation {
+    foreign_key: ForeignKey,
+    key: Vec<Value>,
+    value: Value,
+}
+
+impl IntegrityViolation {
+    pub(crate) fn new(
+        foreign_key: ForeignKey,
+        key: Vec<Value>,
+        value: Value,
+    ) -> IntegrityViolation {
+        IntegrityViolation { foreign_key, key, value }
+    }
+
+    /// Returns the foreign key that was violated.
+    #[must_use]
+    pub fn foreign_key(&self) -> &ForeignKey {
+        &self.foreign_key
+    }
+
+    /// Returns the values of the orphaned child row's primary key columns.
+    #[must_use]
+    pub fn key(&self) -> &[Value] {
+        &self.key
+    }
+
+    /// Returns the child row's value that has no match in the parent table.
+    #[must_use]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+impl fmt::Display for IntegrityViolation {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(formatter, "{} [", self.foreign_key.child_table)?;
+        for (index, value) in self.key.iter().enumerate() {
+            if index > 0 {
+                formatter.write_str(", ")?;
+            }
+            value.fmt(formatter)?;
+        }
+        write!(
+            formatter,
+            "]: {} refers to missing {}.{}",
+            self.value,
+            self.foreign_key.parent_table,
+            self.foreign_key.parent_column
+        )
+    }
+}
+
+// ========================================================================= //
+
+/// Pairs of standard actions `(action, dependency)` where the Windows
+/// Installer documentation requires `action` to be sequenced after
+/// `dependency` whenever both appear in the same sequence table.
+pub(crate) const SEQUENCE_DEPENDENCIES: &[(&str, &str)] = &[
+    ("LaunchConditions", "AppSearch"),
+    ("MigrateFeatureStates", "FindRelatedProducts"),
+    ("FileCost", "CostInitialize"),
+    ("CostFinalize", "CostInitialize"),
+    ("CostFinalize", "FileCost"),
+    ("InstallValidate", "CostFinalize"),
+    ("InstallInitialize", "InstallValidate"),
+    ("RemoveExistingProducts", "InstallValidate"),
+    ("ProcessComponents", "InstallInitialize"),
+    ("UnpublishFeatures", "InstallInitialize"),
+    ("StopServices", "InstallInitialize"),
+    ("DeleteServices", "StopServices"),
+    ("RemoveRegistryValues", "InstallInitialize"),
+    ("RemoveShortcuts", "InstallInitialize"),
+    ("RemoveFiles", "InstallInitialize"),
+    ("RemoveFolders", "RemoveFiles"),
+    ("CreateFolders", "InstallInitialize"),
+    ("MoveFiles", "InstallInitialize"),
+    ("InstallFiles", "InstallInitialize"),
+    ("InstallFiles", "RemoveFiles"),
+    ("InstallFiles", "MoveFiles"),
+    ("DuplicateFiles", "InstallFiles"),
+    ("WriteRegistryValues", "InstallInitialize"),
+    ("WriteRegistryValues", "RemoveRegistryValues"),
+    ("CreateShortcuts", "InstallInitialize"),
+    ("CreateShortcuts", "RemoveShortcuts"),
+    ("InstallServices", "InstallInitialize"),
+    ("StartServices", "InstallServices"),
+    ("RegisterProduct", "InstallInitialize"),
+    ("PublishFeatures", "InstallInitialize"),
+    ("PublishProduct", "PublishFeatures"),
+    ("InstallFinalize", "InstallInitialize"),
+];
+
+/// A problem found in a sequence table (such as `InstallExecuteSequence`),
+/// as returned by `Package::verify_sequence_table`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SequenceIssue {
+    /// Two or more actions have the same sequence number, so their relative
+    /// order is undefined.
+    DuplicateSequence {
+        /// The shared sequence number.
+        sequence: i32,
+        /// The names of the actions sharing it, in alphabetical order.
+        actions: Vec<String>,
+    },
+    /// A standard action is sequenced before another standard action that
+    /// it is documented to depend on.
+    OutOfOrder {
+        /// The name of the misplaced action.
+        action: String,
+        /// The misplaced action's sequence number.
+        sequence: i32,
+        /// The name of the action that must come first.
+        dependency: String,
+        /// The sequence number of the action that must come first.
+        dependency_sequence: i32,
+    },
+}
+
+impl fmt::Display for SequenceIssue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            SequenceIssue::DuplicateSequence { sequence, actions } => write!(
+                formatter,
+                "Actions {} share sequence number {}",
+                actions.join(", "),
+                sequence
+            ),
+            SequenceIssue::OutOfOrder {
+                action,
+                sequence,
+                dependency,
+                dependency_sequence,
+            } => write!(
+                formatter,
+                "{} ({}) must come after {} ({})",
+                action, sequence, dependency, dependency_sequence
+            ),
+        }
+    }
+}
+
+// ========================================================================= //
@@ -1,12 +1,25 @@
+use crate::internal::directory::DefaultDir;
+use crate::internal::guid::Guid;
 use crate::internal::language::Language;
 use crate::internal::stringpool::{StringPool, StringRef};
-use std::convert::From;
+use crate::internal::version::Version;
+use std::convert::{From, TryFrom};
 use std::fmt;
+use std::num::TryFromIntError;
 use uuid::Uuid;
 
 // ========================================================================= //
 
 /// A value from one cell in a database table row.
+///
+/// In string columns, an empty string (`Value::from("")`) and `Value::Null`
+/// are distinct: a null value refers to no string at all, while an empty
+/// string is stored as a zero-length entry in the package's string pool.
+/// Each reads back as it was written.
+///
+/// With the `serde` feature enabled, values can be serialized and
+/// deserialized; `Null` maps to a unit/none value (e.g. JSON `null`), `Int`
+/// to an integer, and `Str` to a string.
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Value {
     /// A null value.
@@ -19,6 +32,13 @@ pub enum Value {
 
 impl Value {
     /// Returns true if this is a null value.
+    ///
+    /// ```
+    /// # use msi::Value;
+    /// assert!(Value::Null.is_null());
+    /// assert!(!Value::Int(0).is_null());
+    /// assert!(!Value::from("").is_null());
+    /// ```
     #[must_use]
     pub fn is_null(&self) -> bool {
         matches!(*self, Value::Null)
@@ -30,7 +50,34 @@ impl Value {
         matches!(*self, Value::Int(_))
     }
 
-    /// Extracts the integer value if it is an integer.
+    /// Extracts the integer value if it is an integer.  Returns `None` for
+    /// null and string values.
+    ///
+    /// ```
+    /// # use msi::{Column, Insert, Package, PackageType, Select, Value};
+    /// # let cursor = std::io::Cursor::new(Vec::new());
+    /// # let mut package =
+    /// #     Package::create(PackageType::Installer, cursor).unwrap();
+    /// # let columns = vec![
+    /// #     Column::build("File").primary_key().id_string(72),
+    /// #     Column::build("FileName").string(255),
+    /// #     Column::build("FileSize").int32(),
+    /// # ];
+    /// # package.create_table("File", columns).unwrap();
+    /// # let query = Insert::into("File").row(vec![
+    /// #     Value::from("readme"),
+    /// #     Value::from("README.TXT"),
+    /// #     Value::Int(1024),
+    /// # ]);
+    /// # package.insert_rows(query).unwrap();
+    /// let mut total_size = 0;
+    /// for row in package.select_rows(Select::table("File")).unwrap() {
+    ///     if let Some(n) = row[2].as_int() {
+    ///         total_size += n;
+    ///     }
+    /// }
+    /// assert_eq!(total_size, 1024);
+    /// ```
     #[must_use]
     pub fn as_int(&self) -> Option<i32> {
         match *self {
@@ -46,7 +93,15 @@ impl Value {
         matches!(*self, Value::Str(_))
     }
 
-    /// Extracts the string value if it is a string.
+    /// Extracts the string value if it is a string.  Returns `None` for null
+    /// and integer values.
+    ///
+    /// ```
+    /// # use msi::Value;
+    /// assert_eq!(Value::from("foo").as_str(), Some("foo"));
+    /// assert_eq!(Value::Int(42).as_str(), None);
+    /// assert_eq!(Value::Null.as_str(), None);
+    /// ```
     #[must_use]
     pub fn as_str(&self) -> Option<&str> {
         match *self {
@@ -65,6 +120,22 @@ impl Value {
         }
     }
 
+    /// Returns an object that formats the value as plain text, for
+    /// exporters: `Null` is written as an empty string, integers as their
+    /// number, and strings as they are, without quoting or escaping.
+    #[must_use]
+    pub fn display_raw(&self) -> impl fmt::Display + '_ {
+        RawDisplay(self)
+    }
+
+    /// Returns an object that formats the value as a CSV field, like
+    /// [`Value::display_raw`], but with strings quoted as per RFC 4180 if
+    /// they contain commas, quotes, or line breaks.
+    #[must_use]
+    pub fn display_for_csv(&self) -> impl fmt::Display + '_ {
+        CsvDisplay(self)
+    }
+
     /// Coerces the `Value` to a boolean.  Returns false for null, zero, and
     /// empty string; returns true for all other values.
     pub(crate) fn to_bool(&self) -> bool {
@@ -76,6 +147,9 @@ impl Value {
     }
 }
 
+/// Formats the value for debugging and error messages: `Null` is written as
+/// `NULL`, and strings are quoted and escaped.  See also
+/// [`Value::display_raw`] and [`Value::display_for_csv`].
 impl fmt::Display for Value {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match *self {
@@ -86,12 +160,50 @@ impl fmt::Display for Value {
     }
 }
 
+struct RawDisplay<'a>(&'a Value);
+
+impl fmt::Display for RawDisplay<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self.0 {
+            Value::Null => "".fmt(formatter),
+            Value::Int(number) => number.fmt(formatter),
+            Value::Str(ref string) => string.fmt(formatter),
+        }
+    }
+}
+
+struct CsvDisplay<'a>(&'a Value);
+
+impl fmt::Display for CsvDisplay<'_> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self.0 {
+            Value::Str(ref string) => csv_field(string).fmt(formatter),
+            ref value => value.display_raw().fmt(formatter),
+        }
+    }
+}
+
+/// Quotes a field for a CSV file, if necessary, as per RFC 4180.
+pub(crate) fn csv_field(string: &str) -> String {
+    if string.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", string.replace('"', "\"\""))
+    } else {
+        string.to_string()
+    }
+}
+
 impl From<bool> for Value {
     fn from(boolean: bool) -> Value {
         Value::from_bool(boolean)
     }
 }
 
+impl From<u8> for Value {
+    fn from(integer: u8) -> Value {
+        Value::Int(integer as i32)
+    }
+}
+
 impl From<i16> for Value {
     fn from(integer: i16) -> Value {
         Value::Int(integer as i32)
@@ -110,6 +222,16 @@ impl From<i32> for Value {
     }
 }
 
+/// Converts a 64-bit integer into an integer value, failing if it is
+/// outside the range of `i32` (the widest integer type a column can hold).
+impl TryFrom<i64> for Value {
+    type Error = TryFromIntError;
+
+    fn try_from(integer: i64) -> Result<Value, TryFromIntError> {
+        Ok(Value::Int(i32::try_from(integer)?))
+    }
+}
+
 impl<'a> From<&'a str> for Value {
     fn from(string: &'a str) -> Value {
         Value::Str(string.to_string())
@@ -144,9 +266,199 @@ impl<'a> From<&'a [Language]> for Value {
 /// column with the `Guid` category.
 impl From<Uuid> for Value {
     fn from(uuid: Uuid) -> Value {
-        let mut string = format!("{{{}}}", uuid.hyphenated());
-        string.make_ascii_uppercase();
-        Value::Str(string)
+        Value::from(Guid::from(uuid))
+    }
+}
+
+/// Returns a string value containing the given directory spec, suitable for
+/// storing in a column with the `DefaultDir` category.
+impl From<DefaultDir> for Value {
+    fn from(dir: DefaultDir) -> Value {
+        Value::Str(dir.to_string())
+    }
+}
+
+/// Returns a string value containing the given version number, suitable for
+/// storing in a column with the `Version` category.
+impl From<Version> for Value {
+    fn from(version: Version) -> Value {
+        Value::Str(version.to_string())
+    }
+}
+
+/// Returns a string value containing the given GUID, suitable for storing in
+/// a column with the `Guid` category.
+impl From<Guid> for Value {
+    fn from(guid: Guid) -> Value {
+        Value::Str(guid.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match *self {
+            Value::Null => serializer.serialize_none(),
+            Value::Int(number) => serializer.serialize_i32(number),
+            Value::Str(ref string) => serializer.serialize_str(string),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Value {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct ValueVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("null, a 32-bit integer, or a string")
+    }
+
+    fn visit_unit<E: serde::de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: serde::de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        serde::Deserialize::deserialize(deserializer)
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, number: i64) -> Result<Value, E> {
+        match i32::try_from(number) {
+            Ok(number) => Ok(Value::Int(number)),
+            Err(_) => Err(E::invalid_value(
+                serde::de::Unexpected::Signed(number),
+                &self,
+            )),
+        }
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, number: u64) -> Result<Value, E> {
+        match i32::try_from(number) {
+            Ok(number) => Ok(Value::Int(number)),
+            Err(_) => Err(E::invalid_value(
+                serde::de::Unexpected::Unsigned(number),
+                &self,
+            )),
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, string: &str) -> Result<Value, E> {
+        Ok(Value::Str(string.to_string()))
+    }
+
+    fn visit_string<E: serde::de::Error>(
+        self,
+        string: String,
+    ) -> Result<Value, E> {
+        Ok(Value::Str(string))
+    }
+}
+
+// ========================================================================= //
+
+/// A value from one cell in a database table row, borrowing its string (if
+/// any) rather than owning it.
+///
+/// Borrowed values are returned by `Rows::next_borrowed`, which avoids
+/// allocating a new `String` for each string cell when scanning a table.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BorrowedValue<'a> {
+    /// A null value.
+    Null,
+    /// An integer value.
+    Int(i32),
+    /// A string value.
+    Str(&'a str),
+}
+
+impl<'a> BorrowedValue<'a> {
+    /// Returns true if this is a null value.
+    #[must_use]
+    pub fn is_null(&self) -> bool {
+        matches!(*self, BorrowedValue::Null)
+    }
+
+    /// Extracts the integer value if it is an integer.  Returns `None` for
+    /// null and string values.
+    #[must_use]
+    pub fn as_int(&self) -> Option<i32> {
+        match *self {
+            BorrowedValue::Int(number) => Some(number),
+            BorrowedValue::Null | BorrowedValue::Str(_) => None,
+        }
+    }
+
+    /// Extracts the string value if it is a string.  Returns `None` for null
+    /// and integer values.
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'a str> {
+        match *self {
+            BorrowedValue::Str(string) => Some(string),
+            BorrowedValue::Null | BorrowedValue::Int(_) => None,
+        }
+    }
+
+    /// Copies this into an owned `Value`.
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match *self {
+            BorrowedValue::Null => Value::Null,
+            BorrowedValue::Int(number) => Value::Int(number),
+            BorrowedValue::Str(string) => Value::Str(string.to_string()),
+        }
+    }
+}
+
+impl<'a> fmt::Display for BorrowedValue<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            BorrowedValue::Null => "NULL".fmt(formatter),
+            BorrowedValue::Int(number) => number.fmt(formatter),
+            BorrowedValue::Str(string) => format!("{string:?}").fmt(formatter),
+        }
+    }
+}
+
+impl<'a> From<&'a Value> for BorrowedValue<'a> {
+    fn from(value: &'a Value) -> BorrowedValue<'a> {
+        match *value {
+            Value::Null => BorrowedValue::Null,
+            Value::Int(number) => BorrowedValue::Int(number),
+            Value::Str(ref string) => BorrowedValue::Str(string.as_str()),
+        }
+    }
+}
+
+impl<'a> PartialEq<Value> for BorrowedValue<'a> {
+    fn eq(&self, other: &Value) -> bool {
+        *self == BorrowedValue::from(other)
+    }
+}
+
+impl<'a> PartialEq<BorrowedValue<'a>> for Value {
+    fn eq(&self, other: &BorrowedValue<'a>) -> bool {
+        BorrowedValue::from(self) == *other
     }
 }
 
@@ -182,6 +494,18 @@ impl ValueRef {
         }
     }
 
+    /// Dereferences the `ValueRef` into a `BorrowedValue`, without copying
+    /// the string (if any) out of the string pool.
+    pub fn to_borrowed(self, string_pool: &StringPool) -> BorrowedValue<'_> {
+        match self {
+            ValueRef::Null => BorrowedValue::Null,
+            ValueRef::Int(number) => BorrowedValue::Int(number),
+            ValueRef::Str(string_ref) => {
+                BorrowedValue::Str(string_pool.get(string_ref))
+            }
+        }
+    }
+
     /// Dereferences the `ValueRef` into a `Value`.
     pub fn to_value(self, string_pool: &StringPool) -> Value {
         match self {
@@ -202,6 +526,7 @@ mod tests {
     use crate::internal::codepage::CodePage;
     use crate::internal::language::Language;
     use crate::internal::stringpool::StringPool;
+    use std::convert::TryFrom;
     use uuid::Uuid;
 
     #[test]
@@ -222,12 +547,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn display_modes() {
+        let null = Value::Null;
+        assert_eq!(null.to_string(), "NULL");
+        assert_eq!(null.display_raw().to_string(), "");
+        assert_eq!(null.display_for_csv().to_string(), "");
+
+        let int = Value::Int(-137);
+        assert_eq!(int.to_string(), "-137");
+        assert_eq!(int.display_raw().to_string(), "-137");
+        assert_eq!(int.display_for_csv().to_string(), "-137");
+
+        let plain = Value::from("Example App");
+        assert_eq!(plain.to_string(), "\"Example App\"");
+        assert_eq!(plain.display_raw().to_string(), "Example App");
+        assert_eq!(plain.display_for_csv().to_string(), "Example App");
+
+        let special = Value::from("Say \"hi\", world");
+        assert_eq!(special.to_string(), "\"Say \\\"hi\\\", world\"");
+        assert_eq!(special.display_raw().to_string(), "Say \"hi\", world");
+        assert_eq!(
+            special.display_for_csv().to_string(),
+            "\"Say \"\"hi\"\", world\""
+        );
+        assert_eq!(format!("[{:>4}]", int.display_raw()), "[-137]");
+    }
+
     #[test]
     fn value_from() {
         assert_eq!(Value::from(false), Value::Int(0));
         assert_eq!(Value::from(true), Value::Int(1));
         assert_eq!(Value::from(-47i16), Value::Int(-47i32));
+        assert_eq!(Value::from(255u8), Value::Int(255i32));
         assert_eq!(Value::from(47u16), Value::Int(47i32));
+        assert_eq!(Value::from(u16::MAX), Value::Int(65535));
+        assert_eq!(Value::try_from(-47i64), Ok(Value::Int(-47i32)));
+        assert_eq!(Value::try_from(i32::MIN as i64), Ok(Value::Int(i32::MIN)));
+        assert!(Value::try_from(i32::MAX as i64 + 1).is_err());
+        assert!(Value::try_from(i64::MIN).is_err());
         assert_eq!(Value::from("foobar"), Value::Str("foobar".to_string()));
         assert_eq!(
             Value::from("foobar".to_string()),
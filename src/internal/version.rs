@@ -0,0 +1,209 @@
+use crate::internal::error::{Error, Result};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str;
+
+// ========================================================================= //
+
+const MAX_NUM_PARTS: usize = 4;
+
+// ========================================================================= //
+
+/// A version number, in the format used by columns with the `Version`
+/// category (such as `File.Version` or `Upgrade.VersionMin`): up to four
+/// period-separated numbers, each at most 65535.
+///
+/// Versions compare numerically, part by part, with missing trailing parts
+/// treated as zero, so `1.2` is equal to `1.2.0.0`.  Formatting a version
+/// reproduces the number of parts it was created with.
+///
+/// `Version` values can be passed to `Value::from()` to produce a column
+/// value that can be stored in a column with the `Version` category.
+///
+/// # Examples
+///
+/// ```
+/// let min: msi::Version = "1.0.0.0".parse().unwrap();
+/// let max = msi::Version::parse("1.0.1").unwrap();
+/// assert!(min < max);
+/// assert_eq!(max.to_string(), "1.0.1");
+/// let short = msi::Version::parse("1.2").unwrap();
+/// assert_eq!(short, msi::Version::new(1, 2, 0, 0));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Version {
+    parts: [u16; MAX_NUM_PARTS],
+    num_parts: usize,
+}
+
+impl Version {
+    /// Creates a four-part version number.
+    #[must_use]
+    pub fn new(major: u16, minor: u16, build: u16, revision: u16) -> Version {
+        Version {
+            parts: [major, minor, build, revision],
+            num_parts: MAX_NUM_PARTS,
+        }
+    }
+
+    /// Parses a version string.  Returns an error if the string has more than
+    /// four parts, or if any part isn't a number from 0 to 65535.
+    pub fn parse(string: &str) -> Result<Version> {
+        string.parse()
+    }
+
+    /// Returns the first part of the version number.
+    #[must_use]
+    pub fn major(&self) -> u16 {
+        self.parts[0]
+    }
+
+    /// Returns the second part of the version number (zero if omitted).
+    #[must_use]
+    pub fn minor(&self) -> u16 {
+        self.parts[1]
+    }
+
+    /// Returns the third part of the version number (zero if omitted).
+    #[must_use]
+    pub fn build(&self) -> u16 {
+        self.parts[2]
+    }
+
+    /// Returns the fourth part of the version number (zero if omitted).
+    #[must_use]
+    pub fn revision(&self) -> u16 {
+        self.parts[3]
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Version) -> bool {
+        self.parts == other.parts
+    }
+}
+
+impl Eq for Version {}
+
+impl Hash for Version {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.parts.hash(state);
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Version) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Version) -> Ordering {
+        self.parts.cmp(&other.parts)
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (index, part) in self.parts[..self.num_parts].iter().enumerate() {
+            if index > 0 {
+                formatter.write_str(".")?;
+            }
+            part.fmt(formatter)?;
+        }
+        Ok(())
+    }
+}
+
+impl str::FromStr for Version {
+    type Err = Error;
+
+    fn from_str(string: &str) -> Result<Version> {
+        let mut parts = [0u16; MAX_NUM_PARTS];
+        let mut num_parts = 0;
+        for part in string.split('.') {
+            if num_parts >= MAX_NUM_PARTS {
+                invalid_input!(
+                    "Invalid version {:?} (more than {} parts)",
+                    string,
+                    MAX_NUM_PARTS
+                );
+            }
+            if part.is_empty() || !part.bytes().all(|b| b.is_ascii_digit()) {
+                invalid_input!("Invalid version: {:?}", string);
+            }
+            parts[num_parts] = match part.parse::<u16>() {
+                Ok(number) => number,
+                Err(_) => invalid_input!(
+                    "Invalid version {:?} ({} is greater than 65535)",
+                    string,
+                    part
+                ),
+            };
+            num_parts += 1;
+        }
+        Ok(Version { parts, num_parts })
+    }
+}
+
+// ========================================================================= //
+
+#[cfg(test)]
+mod tests {
+    use super::Version;
+    use std::collections::HashSet;
+
+    #[test]
+    fn parse_and_format() {
+        for string in ["1", "1.2", "1.2.3", "1.2.3.4", "65535.0.0.65535"] {
+            assert_eq!(Version::parse(string).unwrap().to_string(), string);
+        }
+        let version = Version::parse("10.20.30").unwrap();
+        assert_eq!(
+            (
+                version.major(),
+                version.minor(),
+                version.build(),
+                version.revision()
+            ),
+            (10, 20, 30, 0)
+        );
+    }
+
+    #[test]
+    fn invalid_versions() {
+        for string in ["", ".", "1.", ".1", "1..2", "a.b", "1.-2", "+1", " 1"]
+        {
+            let error = Version::parse(string).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("Invalid version: {string:?}")
+            );
+        }
+        assert_eq!(
+            Version::parse("1.2.3.4.5").unwrap_err().to_string(),
+            "Invalid version \"1.2.3.4.5\" (more than 4 parts)"
+        );
+        assert_eq!(
+            Version::parse("1.65536").unwrap_err().to_string(),
+            "Invalid version \"1.65536\" (65536 is greater than 65535)"
+        );
+    }
+
+    #[test]
+    fn compare_versions() {
+        let parse = |string| Version::parse(string).unwrap();
+        assert!(parse("1.0.0.0") < parse("1.0.1.0"));
+        assert!(parse("1.9") < parse("1.10"));
+        assert!(parse("2") > parse("1.65535.65535.65535"));
+        assert!(parse("1.2.0.1") > parse("1.2"));
+        assert_eq!(parse("1.2"), parse("1.2.0.0"));
+        assert_eq!(parse("1.2"), Version::new(1, 2, 0, 0));
+        let set: HashSet<Version> =
+            ["1.2", "1.2.0", "1.2.0.0"].into_iter().map(parse).collect();
+        assert_eq!(set.len(), 1);
+    }
+}
+
+// ========================================================================= //
@@ -6,41 +6,82 @@
 //! An MSI file consists of a relational database stored within a [Compound
 //! File Binary](https://en.wikipedia.org/wiki/Compound_File_Binary_Format)
 //! file.
+//!
+//! # Cargo features
+//!
+//! * `serde`: Implements `Serialize` and `Deserialize` for [`Value`], so that
+//!   row data can be stored as (for example) JSON, and adds
+//!   `Package::to_json` for dumping a whole package as JSON.
+//! * `rayon`: Adds `Package::extract_all_streams_parallel`, which extracts a
+//!   package's binary streams to files concurrently.
 
 #![warn(missing_docs)]
 
 extern crate byteorder;
 extern crate cfb;
 extern crate encoding_rs;
+extern crate md5;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 extern crate uuid;
 
 mod internal;
 
+pub use crate::internal::diff;
+pub use crate::internal::filename;
+
+pub use crate::internal::attributes::{
+    ComponentAttributes, FeatureAttributes, RemoveFileInstallMode,
+    ServiceControlEvent,
+};
+pub use crate::internal::cabinet::{CabinetFile, CabinetReader};
 pub use crate::internal::category::Category;
 pub use crate::internal::codepage::CodePage;
 pub use crate::internal::column::{Column, ColumnBuilder, ColumnType};
+pub use crate::internal::condition::Condition;
+pub use crate::internal::directory::DefaultDir;
+pub use crate::internal::error::{Error, Result};
 pub use crate::internal::expr::Expr;
+pub use crate::internal::feature::FeatureNode;
+pub use crate::internal::guid::Guid;
 pub use crate::internal::language::Language;
-pub use crate::internal::package::{Package, PackageType, Tables};
-pub use crate::internal::query::{Delete, Insert, Select, Update};
+pub use crate::internal::package::{
+    CreateOptions, OpenOptions, Package, PackageType, SectorSize, Tables,
+};
+pub use crate::internal::propset::PropertyValue;
+pub use crate::internal::query::{
+    Agg, Delete, Insert, IntoRow, Select, Update,
+};
 pub use crate::internal::stream::{StreamReader, StreamWriter, Streams};
-pub use crate::internal::summary::SummaryInfo;
-pub use crate::internal::table::{Row, Rows, Table};
-pub use crate::internal::value::Value;
+pub use crate::internal::summary::{
+    Architecture, Security, SummaryInfo, SummaryInfoBuilder,
+    DEFAULT_CREATING_APPLICATION,
+};
+pub use crate::internal::table::{ReusingRows, Row, RowStream, Rows, Table};
+pub use crate::internal::timestamp::Timestamp;
+pub use crate::internal::transform::{RowChange, Transform, TransformFlags};
+pub use crate::internal::validation::{
+    ForeignKey, IntegrityViolation, SequenceIssue, ValidationError,
+};
+pub use crate::internal::value::{BorrowedValue, Value};
+pub use crate::internal::version::Version;
 use std::fs;
-use std::io;
 use std::path::Path;
 
 // ========================================================================= //
 
 /// Opens an existing MSI file at the given path in read-only mode.
-pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Package<fs::File>> {
+pub fn open<P: AsRef<Path>>(path: P) -> Result<Package<fs::File>> {
     Package::open(fs::File::open(path)?)
 }
 
 /// Opens an existing MSI file at the given path in read-write mode.
-pub fn open_rw<P: AsRef<Path>>(path: P) -> io::Result<Package<fs::File>> {
-    Package::open(fs::OpenOptions::new().read(true).write(true).open(path)?)
+pub fn open_rw<P: AsRef<Path>>(path: P) -> Result<Package<fs::File>> {
+    Package::open_path(path)
 }
 
 // ========================================================================= //
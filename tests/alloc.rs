@@ -0,0 +1,132 @@
+use msi::{Column, Insert, Package, PackageType, Select, Value};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io::Cursor;
+
+// ========================================================================= //
+
+/// Wraps the system allocator to count the allocations made by each thread,
+/// so that tests running in parallel don't disturb each other's counts.
+struct CountingAllocator;
+
+thread_local! {
+    static NUM_ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        NUM_ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        NUM_ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations made by the current thread while
+/// running the given function.
+fn count_allocations<T>(function: impl FnOnce() -> T) -> (T, usize) {
+    let before = NUM_ALLOCATIONS.with(Cell::get);
+    let result = function();
+    let after = NUM_ALLOCATIONS.with(Cell::get);
+    (result, after - before)
+}
+
+// ========================================================================= //
+
+const NUM_FILES: usize = 5_000;
+
+fn make_file_package() -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("File").primary_key().id_string(72),
+        Column::build("Component_").id_string(72),
+        Column::build("FileName").text_string(255),
+        Column::build("FileSize").int32(),
+        Column::build("Version").nullable().string(72),
+    ];
+    package.create_table("File", columns).unwrap();
+    let rows = (0..NUM_FILES)
+        .map(|index| {
+            vec![
+                Value::from(format!("File{index:05}")),
+                Value::from(format!("Component{:04}", index / 10)),
+                Value::from(format!("file{index:05}.txt")),
+                Value::Int(index as i32),
+                Value::from("1.0.0.0"),
+            ]
+        })
+        .collect();
+    package.insert_rows(Insert::into("File").rows(rows)).unwrap();
+    package
+}
+
+/// Scans the whole `File` table, both with the ordinary owning iterator and
+/// with a reusing iterator, and checks that the reusing iterator doesn't
+/// allocate for each row.
+#[test]
+fn full_table_scan_with_reusing_rows() {
+    let mut package = make_file_package();
+
+    let rows = package.select_rows(Select::table("File")).unwrap();
+    let (total_size, owning) = count_allocations(|| {
+        let mut total_size = 0;
+        for row in rows {
+            total_size += row["FileSize"].as_int().unwrap() as i64;
+        }
+        total_size
+    });
+
+    let rows = package.select_rows(Select::table("File")).unwrap();
+    let (reused_total_size, reusing) = count_allocations(|| {
+        let mut rows = rows.into_reusing_iter();
+        let mut total_size = 0;
+        let mut last_name = String::new();
+        while let Some(row) = rows.next() {
+            total_size += row["FileSize"].as_int().unwrap() as i64;
+            last_name.clear();
+            last_name.push_str(row["FileName"].as_str().unwrap());
+        }
+        assert_eq!(last_name, format!("file{:05}.txt", NUM_FILES - 1));
+        total_size
+    });
+
+    assert_eq!(reused_total_size, total_size);
+    assert!(owning >= NUM_FILES * 4);
+    assert!(reusing < NUM_FILES / 100);
+}
+
+/// Scans the whole `File` table with `next_borrowed`, and checks that it
+/// allocates only the `Vec` holding each row's values, and no strings.
+#[test]
+fn full_table_scan_with_borrowed_values() {
+    let mut package = make_file_package();
+    let mut rows = package.select_rows(Select::table("File")).unwrap();
+    let (total_len, borrowed) = count_allocations(|| {
+        let mut total_len = 0;
+        while let Some(values) = rows.next_borrowed() {
+            total_len += values[2].as_str().unwrap().len();
+        }
+        total_len
+    });
+    assert_eq!(total_len, NUM_FILES * "file00000.txt".len());
+    assert!(borrowed >= NUM_FILES);
+    assert!(borrowed < NUM_FILES + NUM_FILES / 100);
+}
+
+// ========================================================================= //
@@ -0,0 +1,118 @@
+#[macro_use]
+mod testutil;
+
+use msi::{CodePage, Insert, Package, Select, Update, Value};
+use std::io::{ErrorKind, Read, Seek};
+
+// ========================================================================= //
+
+fn read_properties<F>(package: &mut Package<F>) -> Vec<(String, String)>
+where
+    F: Read + Seek,
+{
+    package
+        .select_rows(Select::table("Property"))
+        .unwrap()
+        .map(|row| {
+            (
+                row[0].as_str().unwrap().to_string(),
+                row[1].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+// ========================================================================= //
+
+#[test]
+fn change_codepage_reencodes_strings() {
+    let mut package = testutil::package_with_properties(&[
+        ("Manufacturer", "Société Générale"),
+        ("ProductName", "Café Manager"),
+    ]);
+    assert_eq!(package.database_codepage(), CodePage::Utf8);
+    package.set_database_codepage(CodePage::Windows1252).unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(package.database_codepage(), CodePage::Windows1252);
+    assert_eq!(
+        read_properties(&mut package),
+        vec![
+            ("Manufacturer".to_string(), "Société Générale".to_string()),
+            ("ProductName".to_string(), "Café Manager".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn change_codepage_to_one_that_cannot_represent_strings() {
+    let mut package =
+        testutil::package_with_properties(&[("ProductName", "Café")]);
+    assert_error!(
+        package.set_database_codepage(CodePage::UsAscii),
+        ErrorKind::InvalidInput,
+        "String \"Café\" cannot be represented in code page 20127 (US-ASCII)"
+    );
+    assert_eq!(package.database_codepage(), CodePage::Utf8);
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(
+        read_properties(&mut package),
+        vec![("ProductName".to_string(), "Café".to_string())]
+    );
+}
+
+#[test]
+fn insert_unrepresentable_string() {
+    let mut package = testutil::package_with_properties(&[]);
+    package.set_database_codepage(CodePage::Windows1252).unwrap();
+    assert!(!package.lossy_encoding());
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductName"), Value::from("Café")])
+        .row(vec![Value::from("Comments"), Value::from("Party \u{1f389}")]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "String \"Party \u{1f389}\" cannot be represented in code page 1252 \
+         (Windows Latin 1)"
+    );
+    assert_eq!(package.count_rows("Property", None).unwrap(), 0);
+
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductName"), Value::from("Café")]);
+    package.insert_rows(query).unwrap();
+    let query = Update::table("Property")
+        .set("Value", Value::from("Caf\u{e9} \u{2615}"));
+    assert_error!(
+        package.update_rows(query),
+        ErrorKind::InvalidInput,
+        "String \"Caf\u{e9} \u{2615}\" cannot be represented in code page \
+         1252 (Windows Latin 1)"
+    );
+    assert_eq!(
+        read_properties(&mut package),
+        vec![("ProductName".to_string(), "Café".to_string())]
+    );
+}
+
+#[test]
+fn insert_unrepresentable_string_with_lossy_encoding() {
+    let mut package = testutil::package_with_properties(&[]);
+    package.set_database_codepage(CodePage::Windows1252).unwrap();
+    package.set_lossy_encoding(true);
+    assert!(package.lossy_encoding());
+    let query = Insert::into("Property")
+        .row(vec![Value::from("Comments"), Value::from("Party \u{1f389}")]);
+    package.insert_rows(query).unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(
+        read_properties(&mut package),
+        vec![("Comments".to_string(), "Party ?".to_string())]
+    );
+}
+
+// ========================================================================= //
@@ -0,0 +1,202 @@
+use msi::{
+    Column, Delete, Expr, Insert, Package, PackageType, Select, Update, Value,
+};
+use std::io::{Cursor, Read, Write};
+
+// ========================================================================= //
+
+const NUM_ROWS: i32 = 2_000;
+
+/// Builds a package with a large table and a large binary stream, then
+/// deletes most of the table's rows and removes the stream, leaving the
+/// package full of freed sectors and unused strings.
+fn make_bloated_package() -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.summary_info_mut().set_author("Jane Doe");
+    let columns = vec![
+        Column::build("Id").primary_key().int32(),
+        Column::build("Name").nullable().string(64),
+    ];
+    package.create_table("Things", columns).unwrap();
+    let rows = (0..NUM_ROWS)
+        .map(|id| {
+            vec![Value::Int(id), Value::Str(format!("thing number {id:05}"))]
+        })
+        .collect();
+    package.insert_rows(Insert::into("Things").rows(rows)).unwrap();
+    package
+        .write_stream("Junk")
+        .unwrap()
+        .write_all(&vec![0xAB; 100_000])
+        .unwrap();
+    package.write_stream("Keep").unwrap().write_all(b"kept").unwrap();
+    package.flush().unwrap();
+    let query =
+        Delete::from("Things").with(Expr::col("Id").ge(Expr::integer(10)));
+    package.delete_rows(query).unwrap();
+    assert!(package.remove_stream("Junk").unwrap());
+    package.flush().unwrap();
+    package
+}
+
+// ========================================================================= //
+
+#[test]
+fn compact_package_with_deleted_rows() {
+    let mut package = make_bloated_package();
+    let compacted = package.compact_into(Cursor::new(Vec::new())).unwrap();
+    let before = package.into_inner().unwrap().into_inner().len();
+    let after = compacted.into_inner().unwrap().into_inner().len();
+    assert!(after < before / 2);
+
+    let mut package = Package::open(Cursor::new(
+        make_bloated_package()
+            .compact_into(Cursor::new(Vec::new()))
+            .unwrap()
+            .into_inner()
+            .unwrap()
+            .into_inner(),
+    ))
+    .unwrap();
+    assert_eq!(package.package_type(), PackageType::Installer);
+    assert_eq!(package.summary_info().author(), Some("Jane Doe"));
+    let rows = package.select_rows(Select::table("Things")).unwrap();
+    let names: Vec<String> =
+        rows.map(|row| row["Name"].as_str().unwrap().to_string()).collect();
+    assert_eq!(names.len(), 10);
+    assert_eq!(names[9], "thing number 00009");
+    assert!(!package.has_stream("Junk"));
+    let mut data = Vec::new();
+    package.read_stream("Keep").unwrap().read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"kept");
+    assert!(package.validate_against_validation_table().unwrap().is_empty());
+    let query = Expr::col("Table").eq(Expr::string("Things"));
+    assert_eq!(package.count_rows("_Validation", Some(query)).unwrap(), 2);
+}
+
+#[test]
+fn compact_package_in_place() {
+    let mut package = make_bloated_package();
+    let (before, after) = package.compact().unwrap();
+    assert!(after < before / 2);
+    assert!(package.unreferenced_strings().unwrap().is_empty());
+    let count = package.count_rows("Things", None).unwrap();
+    assert_eq!(count, 10);
+
+    // The writer can't be truncated, so the leftover bytes are still there.
+    let mut data = package.into_inner().unwrap().into_inner();
+    assert_eq!(data.len() as u64, before);
+    data.truncate(after as usize);
+    let mut package = Package::open(Cursor::new(data)).unwrap();
+    assert_eq!(package.summary_info().author(), Some("Jane Doe"));
+    let rows = package.select_rows(Select::table("Things")).unwrap();
+    assert_eq!(rows.len(), 10);
+    assert!(!package.has_stream("Junk"));
+    let mut data = Vec::new();
+    package.read_stream("Keep").unwrap().read_to_end(&mut data).unwrap();
+    assert_eq!(data, b"kept");
+}
+
+#[test]
+fn compact_package_with_substorages() {
+    let cursor = Cursor::new(Vec::new());
+    let package = Package::create(PackageType::Patch, cursor).unwrap();
+    let cursor = package.into_inner().unwrap();
+    let mut comp = cfb::CompoundFile::open(cursor).unwrap();
+    comp.create_storage("/RTM.1").unwrap();
+    comp.create_stream("/RTM.1/Data").unwrap().write_all(b"mst").unwrap();
+    comp.flush().unwrap();
+    let cursor = comp.into_inner();
+
+    let mut package = Package::open(cursor).unwrap();
+    let cursor = package
+        .compact_into(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(package.package_type(), PackageType::Patch);
+    assert_eq!(package.patch_transforms(), vec!["RTM.1".to_string()]);
+    let mut data = Vec::new();
+    package
+        .read_substorage_stream("RTM.1", "Data")
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, b"mst");
+}
+
+#[test]
+fn compact_signed_package() {
+    let cursor = Cursor::new(Vec::new());
+    let package = Package::create(PackageType::Installer, cursor).unwrap();
+    let cursor = package.into_inner().unwrap();
+    let mut comp = cfb::CompoundFile::open(cursor).unwrap();
+    comp.create_stream("\u{5}DigitalSignature")
+        .unwrap()
+        .write_all(b"foo")
+        .unwrap();
+    comp.create_stream("\u{5}MsiDigitalSignatureEx")
+        .unwrap()
+        .write_all(b"bar")
+        .unwrap();
+    let cursor = comp.into_inner();
+
+    let mut package = Package::open(cursor).unwrap();
+    assert!(package.has_digital_signature());
+    let cursor = package
+        .compact_into(Cursor::new(Vec::new()))
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    let comp = cfb::CompoundFile::open(cursor).unwrap();
+    assert!(!comp.exists("\u{5}DigitalSignature"));
+    assert!(!comp.exists("\u{5}MsiDigitalSignatureEx"));
+}
+
+#[test]
+fn unreferenced_strings_after_drop_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Id").primary_key().int32(),
+        Column::build("Name").nullable().string(64),
+    ];
+    package.create_table("Things", columns.clone()).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Things")
+                .row(vec![Value::Int(1), Value::from("alpha")])
+                .row(vec![Value::Int(2), Value::from("beta")]),
+        )
+        .unwrap();
+    assert!(package.unreferenced_strings().unwrap().is_empty());
+
+    // Updating a cell releases the string it held, so nothing is left over.
+    let query = Update::table("Things")
+        .set("Name", Value::from("gamma"))
+        .with(Expr::col("Id").eq(Expr::integer(1)));
+    package.update_rows(query).unwrap();
+    assert!(package.unreferenced_strings().unwrap().is_empty());
+
+    // Dropping a table doesn't release the strings used by its rows.
+    package.create_table("Scratch", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Scratch")
+                .row(vec![Value::Int(1), Value::from("scratch")])
+                .row(vec![Value::Int(2), Value::from("beta")]),
+        )
+        .unwrap();
+    package.drop_table("Scratch").unwrap();
+    assert_eq!(
+        package.unreferenced_strings().unwrap(),
+        vec!["scratch".to_string()]
+    );
+
+    let mut package = package.compact_into(Cursor::new(Vec::new())).unwrap();
+    assert!(package.unreferenced_strings().unwrap().is_empty());
+}
+
+// ========================================================================= //
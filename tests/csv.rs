@@ -0,0 +1,68 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Column, Insert, Package, PackageType, Value};
+use std::io::{Cursor, ErrorKind};
+
+// ========================================================================= //
+
+#[test]
+fn export_property_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").nullable().text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductName"), Value::from("Example, Inc.")])
+        .row(vec![Value::from("ProductVersion"), Value::from("1.2.3")])
+        .row(vec![Value::from("Quote"), Value::from("say \"hi\"")])
+        .row(vec![Value::from("Multi"), Value::from("line 1\nline 2")])
+        .row(vec![Value::from("Unset"), Value::Null]);
+    package.insert_rows(query).unwrap();
+
+    let mut output = Vec::new();
+    package.export_table_csv("Property", &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Property,Value\r\n\
+         Multi,\"line 1\nline 2\"\r\n\
+         ProductName,\"Example, Inc.\"\r\n\
+         ProductVersion,1.2.3\r\n\
+         Quote,\"say \"\"hi\"\"\"\r\n\
+         Unset,\r\n"
+    );
+}
+
+#[test]
+fn export_binary_and_int_columns() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Name").primary_key().id_string(72),
+        Column::build("Size").nullable().int32(),
+        Column::build("Data").nullable().binary(),
+    ];
+    package.create_table("Binary", columns).unwrap();
+    let query = Insert::into("Binary")
+        .row(vec![Value::from("Icon"), Value::Int(1024), Value::from("Icon")])
+        .row(vec![Value::from("Empty"), Value::Null, Value::Null]);
+    package.insert_rows(query).unwrap();
+
+    let mut output = Vec::new();
+    package.export_table_csv("Binary", &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "Name,Size,Data\r\nEmpty,,\r\nIcon,1024,<stream:Binary.Icon>\r\n"
+    );
+
+    assert_error!(
+        package.export_table_csv("Foobar", Vec::new()),
+        ErrorKind::NotFound,
+        "Table \"Foobar\" does not exist"
+    );
+}
+
+// ========================================================================= //
@@ -55,9 +55,12 @@ fn delete_one_row() {
         .row(vec![Value::Int(3), Value::from("Three")]);
     package.insert_rows(query).unwrap();
 
+    let query =
+        Delete::from("Foobar").with(Expr::col("Foo").eq(Expr::integer(4)));
+    assert_eq!(package.delete_rows(query).unwrap(), 0);
     let query =
         Delete::from("Foobar").with(Expr::col("Foo").eq(Expr::integer(2)));
-    package.delete_rows(query).unwrap();
+    assert_eq!(package.delete_rows(query).unwrap(), 1);
 
     let cursor = package.into_inner().unwrap();
     let mut package = Package::open(cursor).unwrap();
@@ -85,7 +88,7 @@ fn delete_multiple_rows() {
 
     let query =
         Delete::from("Mapping").with(Expr::col("Value").eq(Expr::integer(17)));
-    package.delete_rows(query).unwrap();
+    assert_eq!(package.delete_rows(query).unwrap(), 2);
 
     let cursor = package.into_inner().unwrap();
     let mut package = Package::open(cursor).unwrap();
@@ -112,7 +115,15 @@ fn delete_all_rows() {
         .row(vec![Value::Int(3), Value::from("Three")]);
     package.insert_rows(query).unwrap();
 
-    package.delete_rows(Delete::from("Foobar")).unwrap();
+    assert_error!(
+        package.delete_rows(Delete::from("Foobar")),
+        ErrorKind::InvalidInput,
+        "Refusing to delete every row of table \"Foobar\" without a \
+         condition; use Delete::all() to do so deliberately"
+    );
+    assert_eq!(package.select_rows(Select::table("Foobar")).unwrap().len(), 3);
+    assert_eq!(package.delete_rows(Delete::from("Foobar").all()).unwrap(), 3);
+    assert_eq!(package.delete_rows(Delete::from("Foobar").all()).unwrap(), 0);
 
     let cursor = package.into_inner().unwrap();
     let mut package = Package::open(cursor).unwrap();
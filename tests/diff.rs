@@ -0,0 +1,83 @@
+#[macro_use]
+mod testutil;
+
+use msi::diff::table_diff;
+use msi::{Column, Package, PackageType, Value};
+use std::io::{Cursor, ErrorKind};
+
+// ========================================================================= //
+
+// ========================================================================= //
+
+#[test]
+fn diff_property_tables() {
+    let mut base = testutil::package_with_properties(&[
+        ("ProductName", "Example App"),
+        ("ProductVersion", "1.0.0"),
+        ("Obsolete", "Yes"),
+    ]);
+    let mut target = testutil::package_with_properties(&[
+        ("ProductName", "Example App"),
+        ("ProductVersion", "1.1.0"),
+        ("Manufacturer", "Example Corp"),
+    ]);
+    let diff = table_diff(&mut base, &mut target, "Property").unwrap();
+    assert!(!diff.is_empty());
+    assert_eq!(
+        diff.added(),
+        &[vec![Value::from("Manufacturer"), Value::from("Example Corp")]]
+    );
+    assert_eq!(
+        diff.removed(),
+        &[vec![Value::from("Obsolete"), Value::from("Yes")]]
+    );
+    assert_eq!(diff.modified().len(), 1);
+    let row = &diff.modified()[0];
+    assert_eq!(row.key(), &[Value::from("ProductVersion")]);
+    assert_eq!(row.changes().len(), 1);
+    assert_eq!(row.changes()[0].column(), "Value");
+    assert_eq!(row.changes()[0].old_value(), &Value::from("1.0.0"));
+    assert_eq!(row.changes()[0].new_value(), &Value::from("1.1.0"));
+
+    let mut copy = testutil::package_with_properties(&[
+        ("ProductVersion", "1.0.0"),
+        ("Obsolete", "Yes"),
+        ("ProductName", "Example App"),
+    ]);
+    assert!(table_diff(&mut base, &mut copy, "Property").unwrap().is_empty());
+}
+
+#[test]
+fn diff_table_in_one_package() {
+    let mut base =
+        testutil::package_with_properties(&[("ProductName", "Example")]);
+    let cursor = Cursor::new(Vec::new());
+    let mut target = Package::create(PackageType::Installer, cursor).unwrap();
+    let diff = table_diff(&mut base, &mut target, "Property").unwrap();
+    assert_eq!(diff.removed().len(), 1);
+    assert!(diff.added().is_empty());
+    let diff = table_diff(&mut target, &mut base, "Property").unwrap();
+    assert_eq!(diff.added().len(), 1);
+    assert!(diff.removed().is_empty());
+    assert_error!(
+        table_diff(&mut base, &mut target, "Feature"),
+        ErrorKind::NotFound,
+        "Table \"Feature\" does not exist in either package"
+    );
+}
+
+#[test]
+fn diff_tables_with_different_columns() {
+    let mut base = testutil::package_with_properties(&[]);
+    let cursor = Cursor::new(Vec::new());
+    let mut target = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![Column::build("Property").primary_key().id_string(72)];
+    target.create_table("Property", columns).unwrap();
+    assert_error!(
+        table_diff(&mut base, &mut target, "Property"),
+        ErrorKind::InvalidInput,
+        "Table \"Property\" has different columns in the two packages"
+    );
+}
+
+// ========================================================================= //
@@ -0,0 +1,176 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Column, Insert, Package, PackageType, Value};
+use std::io::{Cursor, ErrorKind};
+
+// ========================================================================= //
+
+fn create_directory_table(
+    directories: &[(&str, Option<&str>, &str)],
+) -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Directory").primary_key().id_string(72),
+        Column::build("Directory_Parent").nullable().id_string(72),
+        Column::build("DefaultDir").localizable().text_string(255),
+    ];
+    package.create_table("Directory", columns).unwrap();
+    let rows = directories
+        .iter()
+        .map(|&(key, parent, default_dir)| {
+            vec![
+                Value::from(key),
+                parent.map_or(Value::Null, Value::from),
+                Value::from(default_dir),
+            ]
+        })
+        .collect();
+    package.insert_rows(Insert::into("Directory").rows(rows)).unwrap();
+    package
+}
+
+// ========================================================================= //
+
+#[test]
+fn resolve_three_level_directory_tree() {
+    let mut package = create_directory_table(&[
+        ("TARGETDIR", None, "SourceDir"),
+        ("ProgramFilesFolder", Some("TARGETDIR"), "."),
+        ("CompanyDir", Some("ProgramFilesFolder"), "MyCo|MyCompany"),
+        ("INSTALLDIR", Some("CompanyDir"), "MyApp"),
+        ("BinDir", Some("INSTALLDIR"), "bin:srcbin"),
+        ("SameDir", Some("INSTALLDIR"), "."),
+        ("DataDir", Some("TARGETDIR"), "Data"),
+    ]);
+    let paths = package.resolve_directories().unwrap();
+    let expected = [
+        ("TARGETDIR", "TARGETDIR"),
+        ("ProgramFilesFolder", "ProgramFilesFolder"),
+        ("CompanyDir", "ProgramFilesFolder/MyCompany"),
+        ("INSTALLDIR", "ProgramFilesFolder/MyCompany/MyApp"),
+        ("BinDir", "ProgramFilesFolder/MyCompany/MyApp/bin"),
+        ("SameDir", "ProgramFilesFolder/MyCompany/MyApp"),
+        ("DataDir", "TARGETDIR/Data"),
+    ];
+    assert_eq!(paths.len(), expected.len());
+    for (key, path) in expected {
+        assert_eq!(paths[key], path, "{key:?}");
+    }
+}
+
+#[test]
+fn resolve_directories_without_directory_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert!(package.resolve_directories().unwrap().is_empty());
+}
+
+#[test]
+fn resolve_directories_with_missing_parent() {
+    let mut package = create_directory_table(&[
+        ("TARGETDIR", None, "SourceDir"),
+        ("INSTALLDIR", Some("NoSuchDir"), "MyApp"),
+    ]);
+    assert_error!(
+        package.resolve_directories(),
+        ErrorKind::InvalidData,
+        "Directory \"INSTALLDIR\" has nonexistent parent \"NoSuchDir\""
+    );
+}
+
+#[test]
+fn resolve_directories_with_cycle() {
+    let mut package = create_directory_table(&[
+        ("FirstDir", Some("SecondDir"), "First"),
+        ("SecondDir", Some("FirstDir"), "Second"),
+    ]);
+    let error = package.resolve_directories().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    assert!(error.to_string().ends_with("is its own ancestor"));
+}
+
+#[test]
+fn file_install_paths() {
+    let mut package = create_directory_table(&[
+        ("TARGETDIR", None, "SourceDir"),
+        ("ProgramFilesFolder", Some("TARGETDIR"), "."),
+        ("INSTALLDIR", Some("ProgramFilesFolder"), "MyApp|My App"),
+    ]);
+    let columns = vec![
+        Column::build("Component").primary_key().id_string(72),
+        Column::build("ComponentId").nullable().string(38),
+        Column::build("Directory_").id_string(72),
+        Column::build("Attributes").int16(),
+        Column::build("Condition").nullable().text_string(255),
+        Column::build("KeyPath").nullable().id_string(72),
+    ];
+    package.create_table("Component", columns).unwrap();
+    let query = Insert::into("Component").row(vec![
+        Value::from("MainComponent"),
+        Value::Null,
+        Value::from("INSTALLDIR"),
+        Value::from(0),
+        Value::Null,
+        Value::from("AppExe"),
+    ]);
+    package.insert_rows(query).unwrap();
+    let columns = vec![
+        Column::build("File").primary_key().id_string(72),
+        Column::build("Component_").id_string(72),
+        Column::build("FileName").localizable().text_string(255),
+        Column::build("FileSize").int32(),
+    ];
+    package.create_table("File", columns).unwrap();
+    let query = Insert::into("File")
+        .row(vec![
+            Value::from("AppExe"),
+            Value::from("MainComponent"),
+            Value::from("APP.EXE"),
+            Value::from(1024),
+        ])
+        .row(vec![
+            Value::from("ReadMe"),
+            Value::from("MainComponent"),
+            Value::from("README~1.TXT|Read Me.txt"),
+            Value::from(256),
+        ]);
+    package.insert_rows(query).unwrap();
+
+    assert_eq!(
+        package.file_install_paths().unwrap(),
+        vec![
+            (
+                "AppExe".to_string(),
+                "ProgramFilesFolder/My App/APP.EXE".to_string()
+            ),
+            (
+                "ReadMe".to_string(),
+                "ProgramFilesFolder/My App/Read Me.txt".to_string()
+            ),
+        ]
+    );
+
+    let query = Insert::into("File").row(vec![
+        Value::from("Orphan"),
+        Value::from("NoSuchComponent"),
+        Value::from("ORPHAN.DLL"),
+        Value::from(0),
+    ]);
+    package.insert_rows(query).unwrap();
+    assert_error!(
+        package.file_install_paths(),
+        ErrorKind::InvalidData,
+        "File \"Orphan\" refers to nonexistent component \"NoSuchComponent\""
+    );
+}
+
+#[test]
+fn file_install_paths_without_file_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert!(package.file_install_paths().unwrap().is_empty());
+}
+
+// ========================================================================= //
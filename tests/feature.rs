@@ -0,0 +1,114 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Column, Insert, Package, PackageType, Value};
+use std::io::{Cursor, ErrorKind};
+
+// ========================================================================= //
+
+fn create_feature_table(
+    features: &[(&str, Option<&str>, &str)],
+) -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Feature").primary_key().id_string(38),
+        Column::build("Feature_Parent").nullable().id_string(38),
+        Column::build("Title").nullable().localizable().text_string(64),
+        Column::build("Level").int16(),
+    ];
+    package.create_table("Feature", columns).unwrap();
+    let rows = features
+        .iter()
+        .map(|&(feature, parent, title)| {
+            vec![
+                Value::from(feature),
+                parent.map_or(Value::Null, Value::from),
+                Value::from(title),
+                Value::from(1),
+            ]
+        })
+        .collect();
+    package.insert_rows(Insert::into("Feature").rows(rows)).unwrap();
+    package
+}
+
+// ========================================================================= //
+
+#[test]
+fn feature_tree_and_components() {
+    let mut package = create_feature_table(&[
+        ("Complete", None, "Complete Install"),
+        ("Docs", Some("Complete"), "Documentation"),
+    ]);
+    let columns = vec![
+        Column::build("Feature_").primary_key().id_string(38),
+        Column::build("Component_").primary_key().id_string(72),
+    ];
+    package.create_table("FeatureComponents", columns).unwrap();
+    let query = Insert::into("FeatureComponents")
+        .row(vec![Value::from("Complete"), Value::from("MainExe")])
+        .row(vec![Value::from("Complete"), Value::from("Helper")])
+        .row(vec![Value::from("Docs"), Value::from("Manual")]);
+    package.insert_rows(query).unwrap();
+
+    let tree = package.feature_tree().unwrap();
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree[0].name(), "Complete");
+    assert_eq!(tree[0].title(), Some("Complete Install"));
+    assert_eq!(tree[0].level(), 1);
+    assert_eq!(tree[0].children().len(), 1);
+    let docs = &tree[0].children()[0];
+    assert_eq!(docs.name(), "Docs");
+    assert_eq!(docs.title(), Some("Documentation"));
+    assert!(docs.children().is_empty());
+
+    assert_eq!(
+        package.components_for_feature("Complete").unwrap(),
+        vec!["Helper".to_string(), "MainExe".to_string()]
+    );
+    assert_eq!(
+        package.components_for_feature("Docs").unwrap(),
+        vec!["Manual".to_string()]
+    );
+    assert_error!(
+        package.components_for_feature("Extras"),
+        ErrorKind::NotFound,
+        "Feature \"Extras\" does not exist"
+    );
+}
+
+#[test]
+fn feature_tree_with_missing_parent() {
+    let mut package = create_feature_table(&[
+        ("Complete", None, "Complete Install"),
+        ("Docs", Some("NoSuchFeature"), "Documentation"),
+    ]);
+    assert_error!(
+        package.feature_tree(),
+        ErrorKind::InvalidData,
+        "Feature \"Docs\" has nonexistent parent \"NoSuchFeature\""
+    );
+}
+
+#[test]
+fn feature_tree_with_cycle() {
+    let mut package = create_feature_table(&[
+        ("First", Some("Second"), "First"),
+        ("Second", Some("First"), "Second"),
+    ]);
+    assert_error!(
+        package.feature_tree(),
+        ErrorKind::InvalidData,
+        "Feature \"First\" is its own ancestor"
+    );
+}
+
+#[test]
+fn feature_tree_without_feature_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert!(package.feature_tree().unwrap().is_empty());
+}
+
+// ========================================================================= //
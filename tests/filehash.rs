@@ -0,0 +1,62 @@
+use msi::{Package, PackageType, Select, Value};
+use std::fs::File;
+use std::io::Cursor;
+
+// ========================================================================= //
+
+const FOX: &[u8] = b"The quick brown fox jumps over the lazy dog";
+
+#[test]
+fn compute_file_hash() {
+    // MD5("") = d41d8cd98f00b204e9800998ecf8427e
+    assert_eq!(
+        Package::<File>::compute_file_hash(b""),
+        [-645128748, 78774415, -1744207639, 2118318316]
+    );
+    // MD5(FOX) = 9e107d9d372bb6826bd81d3542a419d6
+    assert_eq!(
+        Package::<File>::compute_file_hash(FOX),
+        [-1652748130, -2101990601, 891148395, -702962622]
+    );
+}
+
+#[test]
+fn insert_file_hash() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert!(!package.has_table("MsiFileHash"));
+    package.insert_file_hash("fox.txt", b"").unwrap();
+    package.insert_file_hash("fox.txt", FOX).unwrap();
+    package.insert_file_hash("empty.txt", b"").unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let rows: Vec<Vec<Value>> = package
+        .select_rows(Select::table("MsiFileHash"))
+        .unwrap()
+        .map(|row| (0..row.len()).map(|index| row[index].clone()).collect())
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            vec![
+                Value::from("empty.txt"),
+                Value::Int(0),
+                Value::Int(-645128748),
+                Value::Int(78774415),
+                Value::Int(-1744207639),
+                Value::Int(2118318316),
+            ],
+            vec![
+                Value::from("fox.txt"),
+                Value::Int(0),
+                Value::Int(-1652748130),
+                Value::Int(-2101990601),
+                Value::Int(891148395),
+                Value::Int(-702962622),
+            ],
+        ]
+    );
+}
+
+// ========================================================================= //
@@ -0,0 +1,533 @@
+use msi::{
+    CodePage, Column, CreateOptions, Delete, Expr, Insert, Package,
+    PackageType, Select, Update, Value,
+};
+use std::cell::Cell;
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
+
+// ========================================================================= //
+
+const OLD_PRODUCT_CODE: &str = "{2A5B3C4D-1111-2222-3333-444455556666}";
+const NEW_PRODUCT_CODE: &str = "{9F8E7D6C-AAAA-BBBB-CCCC-DDDDEEEEFFFF}";
+
+/// Builds a package the way many existing installers are built: with a
+/// Windows-1252 codepage, and with non-ASCII text in the summary information.
+fn make_base_package() -> Cursor<Vec<u8>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.set_database_codepage(CodePage::Windows1252).unwrap();
+    let summary_info = package.summary_info_mut();
+    summary_info.set_codepage(CodePage::Windows1252);
+    summary_info.set_subject("Café Manager");
+    summary_info.set_author("Jürgen Müller");
+    summary_info.set_comments("© 2017 Société Générale");
+    summary_info.set_arch("Intel").unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").localizable().text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![
+                    Value::from("ProductCode"),
+                    Value::from(OLD_PRODUCT_CODE),
+                ])
+                .row(vec![
+                    Value::from("ProductName"),
+                    Value::from("Café Manager"),
+                ])
+                .row(vec![Value::from("UpgradeCode"), Value::from("Unused")]),
+        )
+        .unwrap();
+    package.into_inner().unwrap()
+}
+
+fn schema(package: &Package<Cursor<Vec<u8>>>) -> BTreeMap<String, String> {
+    package
+        .tables()
+        .map(|table| {
+            let columns: Vec<String> = table
+                .columns()
+                .iter()
+                .map(|column| {
+                    format!("{}:{}", column.name(), column.coltype())
+                })
+                .collect();
+            (table.name().to_string(), columns.join(","))
+        })
+        .collect()
+}
+
+fn properties(
+    package: &mut Package<Cursor<Vec<u8>>>,
+) -> BTreeMap<String, String> {
+    package
+        .select_rows(Select::table("Property"))
+        .unwrap()
+        .map(|row| {
+            (
+                row[0].as_str().unwrap().to_string(),
+                row[1].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn edit_existing_package_and_flush() {
+    let cursor = make_base_package();
+    let mut package = Package::open(cursor).unwrap();
+    let original_schema = schema(&package);
+
+    // Replacing the product code frees a string pool entry; setting a value
+    // that's already in the pool afterwards must not reuse that entry.
+    package
+        .update_rows(
+            Update::table("Property")
+                .set("Value", Value::from(NEW_PRODUCT_CODE))
+                .with(Expr::col("Property").eq(Expr::string("ProductCode"))),
+        )
+        .unwrap();
+    package
+        .update_rows(
+            Update::table("Property")
+                .set("Value", Value::from("Café Manager"))
+                .with(Expr::col("Property").eq(Expr::string("UpgradeCode"))),
+        )
+        .unwrap();
+    package.summary_info_mut().set_title("Société Installer");
+    package.flush().unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(schema(&package), original_schema);
+    assert_eq!(package.database_codepage(), CodePage::Windows1252);
+    let summary_info = package.summary_info();
+    assert_eq!(summary_info.codepage(), CodePage::Windows1252);
+    assert_eq!(summary_info.author(), Some("Jürgen Müller"));
+    assert_eq!(summary_info.comments(), Some("© 2017 Société Générale"));
+    assert_eq!(summary_info.title(), Some("Société Installer"));
+    assert_eq!(summary_info.subject(), Some("Café Manager"));
+    assert_eq!(summary_info.arch(), Some("Intel"));
+    let values = properties(&mut package);
+    assert_eq!(values.len(), 3);
+    assert_eq!(values["ProductCode"], NEW_PRODUCT_CODE);
+    assert_eq!(values["ProductName"], "Café Manager");
+    assert_eq!(values["UpgradeCode"], "Café Manager");
+    let query = Select::table("Property")
+        .with(Expr::col("Value").eq(Expr::string("Café Manager")));
+    assert_eq!(package.select_rows(query).unwrap().len(), 2);
+
+    // Editing and flushing a second time should also round-trip cleanly.
+    package
+        .update_rows(
+            Update::table("Property")
+                .set("Value", Value::from(OLD_PRODUCT_CODE))
+                .with(Expr::col("Property").eq(Expr::string("ProductCode"))),
+        )
+        .unwrap();
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(schema(&package), original_schema);
+    assert_eq!(properties(&mut package)["ProductCode"], OLD_PRODUCT_CODE);
+    assert_eq!(package.summary_info().author(), Some("Jürgen Müller"));
+}
+
+fn build_package() -> Vec<u8> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.summary_info_mut().set_author("Jane Doe");
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    let columns = vec![
+        Column::build("Name").primary_key().id_string(72),
+        Column::build("Data").binary(),
+    ];
+    package.create_table("Binary", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("ProductName"), Value::from("Foo")])
+                .row(vec![Value::from("ProductCode"), Value::from("{}")]),
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("Binary")
+                .row(vec![Value::from("Logo"), Value::from("Binary.Logo")]),
+        )
+        .unwrap();
+    package
+        .write_stream("Binary.Logo")
+        .unwrap()
+        .write_all(&[1; 5000])
+        .unwrap();
+    package.into_inner().unwrap().into_inner()
+}
+
+#[test]
+fn identical_builds_produce_identical_bytes() {
+    let first = build_package();
+    let second = build_package();
+    assert_eq!(first.len(), second.len());
+    assert!(first == second, "package output is not reproducible");
+}
+
+fn property_columns() -> Vec<Column> {
+    vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ]
+}
+
+fn binary_columns() -> Vec<Column> {
+    vec![
+        Column::build("Name").primary_key().id_string(72),
+        Column::build("Data").binary(),
+    ]
+}
+
+/// Builds a package with the same contents as `build_package`, but with the
+/// tables, rows, and streams written in a different order, some rows
+/// inserted and then deleted, and an extra flush partway through.
+fn build_package_in_other_order(options: CreateOptions) -> Vec<u8> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package =
+        Package::create_with_options(PackageType::Installer, options, cursor)
+            .unwrap();
+    package
+        .write_stream("Binary.Logo")
+        .unwrap()
+        .write_all(&[1; 5000])
+        .unwrap();
+    package.create_table("Binary", binary_columns()).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Binary")
+                .row(vec![Value::from("Logo"), Value::from("Binary.Logo")]),
+        )
+        .unwrap();
+    package.flush().unwrap();
+    package.create_table("Property", property_columns()).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("Temporary"), Value::from("Gone")])
+                .row(vec![Value::from("ProductCode"), Value::from("{}")])
+                .row(vec![Value::from("ProductName"), Value::from("Foo")]),
+        )
+        .unwrap();
+    let query = Delete::from("Property")
+        .with(Expr::col("Property").eq(Expr::string("Temporary")));
+    package.delete_rows(query).unwrap();
+    package.summary_info_mut().set_author("Jane Doe");
+    package.into_inner().unwrap().into_inner()
+}
+
+fn build_canonical_package() -> Vec<u8> {
+    let options = CreateOptions::new().canonical_layout(true);
+    let cursor = Cursor::new(Vec::new());
+    let mut package =
+        Package::create_with_options(PackageType::Installer, options, cursor)
+            .unwrap();
+    package.summary_info_mut().set_author("Jane Doe");
+    package.create_table("Property", property_columns()).unwrap();
+    package.create_table("Binary", binary_columns()).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("ProductName"), Value::from("Foo")])
+                .row(vec![Value::from("ProductCode"), Value::from("{}")]),
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("Binary")
+                .row(vec![Value::from("Logo"), Value::from("Binary.Logo")]),
+        )
+        .unwrap();
+    package
+        .write_stream("Binary.Logo")
+        .unwrap()
+        .write_all(&[1; 5000])
+        .unwrap();
+    package.into_inner().unwrap().into_inner()
+}
+
+#[test]
+fn canonical_layout_ignores_build_order() {
+    assert_ne!(
+        build_package_in_other_order(CreateOptions::new()),
+        build_package()
+    );
+    let canonical = build_canonical_package();
+    let options = CreateOptions::new().canonical_layout(true);
+    assert!(
+        build_package_in_other_order(options) == canonical,
+        "canonical package output depends on build order"
+    );
+
+    let mut package = Package::open(Cursor::new(canonical)).unwrap();
+    assert_eq!(package.summary_info().author(), Some("Jane Doe"));
+    let rows = package.select_rows(Select::table("Property")).unwrap();
+    let keys: Vec<String> =
+        rows.map(|row| row[0].as_str().unwrap().to_string()).collect();
+    assert_eq!(keys, vec!["ProductCode", "ProductName"]);
+    let mut data = Vec::new();
+    package
+        .read_stream("Binary.Logo")
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, vec![1; 5000]);
+}
+
+/// A writer that, once armed, fails the next large write partway through.
+struct FlakyWriter {
+    inner: Cursor<Vec<u8>>,
+    fail_next_large_write: Rc<Cell<bool>>,
+}
+
+impl Read for FlakyWriter {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for FlakyWriter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl Write for FlakyWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.fail_next_large_write.get() && buf.len() > 8192 {
+            self.fail_next_large_write.set(false);
+            self.inner.write_all(&buf[..4096])?;
+            return Err(io::Error::other("disk full"));
+        }
+        self.inner.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+fn canonical_rewrite_survives_failed_write() {
+    let fail_next_large_write = Rc::new(Cell::new(false));
+    let writer = FlakyWriter {
+        inner: Cursor::new(Vec::new()),
+        fail_next_large_write: fail_next_large_write.clone(),
+    };
+    let options = CreateOptions::new().canonical_layout(true);
+    let mut package =
+        Package::create_with_options(PackageType::Installer, options, writer)
+            .unwrap();
+    package.create_table("Property", property_columns()).unwrap();
+    package
+        .write_stream("Binary.Logo")
+        .unwrap()
+        .write_all(&[1; 5000])
+        .unwrap();
+    package.flush().unwrap();
+
+    let insert = |package: &mut Package<FlakyWriter>, name: &str| {
+        let row = vec![Value::from(name), Value::from("1")];
+        package.insert_rows(Insert::into("Property").row(row)).unwrap();
+    };
+    insert(&mut package, "Foo");
+    fail_next_large_write.set(true);
+    assert_eq!(package.flush().unwrap_err().to_string(), "disk full");
+    assert!(!fail_next_large_write.get());
+
+    // The package keeps working, and flushing again writes everything.
+    insert(&mut package, "Bar");
+    package.flush().unwrap();
+    let cursor = package.into_inner().unwrap().inner;
+    let mut package = Package::open(cursor).unwrap();
+    let rows = package.select_rows(Select::table("Property")).unwrap();
+    let keys: Vec<String> =
+        rows.map(|row| row[0].as_str().unwrap().to_string()).collect();
+    assert_eq!(keys, vec!["Bar", "Foo"]);
+    let mut data = Vec::new();
+    package
+        .read_stream("Binary.Logo")
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, vec![1; 5000]);
+}
+
+const STRING_POOL_STREAM_NAME: &str =
+    "\u{4840}\u{3f3f}\u{4577}\u{446c}\u{3e6a}\u{44b2}\u{482f}";
+const STRING_DATA_STREAM_NAME: &str =
+    "\u{4840}\u{3f3f}\u{4577}\u{446c}\u{3b6a}\u{45e4}\u{4824}";
+
+fn string_pool_streams(bytes: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
+    let mut comp = cfb::CompoundFile::open(Cursor::new(bytes)).unwrap();
+    let mut pool = Vec::new();
+    comp.open_stream(STRING_POOL_STREAM_NAME)
+        .unwrap()
+        .read_to_end(&mut pool)
+        .unwrap();
+    let mut data = Vec::new();
+    comp.open_stream(STRING_DATA_STREAM_NAME)
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    (pool, data)
+}
+
+fn build_property_package(batched: bool) -> Vec<u8> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    let rows: Vec<Vec<Value>> = ["Zeta", "Alpha", "Mu", "Beta"]
+        .iter()
+        .map(|name| vec![Value::from(*name), Value::from(name.to_lowercase())])
+        .collect();
+    if batched {
+        package.insert_rows(Insert::into("Property").rows(rows)).unwrap();
+    } else {
+        for row in rows {
+            package.insert_rows(Insert::into("Property").row(row)).unwrap();
+        }
+    }
+    package
+        .delete_rows(
+            Delete::from("Property")
+                .with(Expr::col("Property").eq(Expr::string("Mu"))),
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("Omega"), Value::from("omega")]),
+        )
+        .unwrap();
+    package.into_inner().unwrap().into_inner()
+}
+
+#[test]
+fn string_pool_order_is_deterministic() {
+    let first = string_pool_streams(build_property_package(false));
+    let second = string_pool_streams(build_property_package(false));
+    assert_eq!(first, second);
+    // The pool only depends on the order in which strings are added, not on
+    // how the rows were grouped into queries.
+    let batched = string_pool_streams(build_property_package(true));
+    assert_eq!(first, batched);
+    // New strings are appended in insertion order, and freed entries are
+    // reused lowest-first.
+    let (_, data) = first;
+    let data = String::from_utf8(data).unwrap();
+    assert!(data.contains("ZetazetaAlphaalpha"));
+    assert!(data.contains("OmegaomegaBetabeta"));
+}
+
+#[test]
+fn flush_leaves_unmodified_streams_in_place() {
+    let data: Vec<u8> = (0..1_000_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 24) as u8)
+        .collect();
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Name").primary_key().id_string(72),
+        Column::build("Data").binary(),
+    ];
+    package.create_table("Binary", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Binary")
+                .row(vec![Value::from("Big"), Value::from("Binary.Big")]),
+        )
+        .unwrap();
+    package.write_stream("Binary.Big").unwrap().write_all(&data).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("ProductName"), Value::from("Old")]),
+        )
+        .unwrap();
+    let before = package.into_inner().unwrap().into_inner();
+    let needle = &data[..4096];
+    let offset = before.windows(needle.len()).position(|w| w == needle);
+    assert!(offset.is_some());
+
+    let mut package = Package::open(Cursor::new(before)).unwrap();
+    package
+        .update_rows(
+            Update::table("Property")
+                .set("Value", Value::from("New"))
+                .with(Expr::col("Property").eq(Expr::string("ProductName"))),
+        )
+        .unwrap();
+    package.flush().unwrap();
+    let after = package.into_inner().unwrap().into_inner();
+    assert_eq!(
+        after.windows(needle.len()).position(|w| w == needle),
+        offset,
+        "binary stream was moved by flush"
+    );
+
+    let mut package = Package::open(Cursor::new(after)).unwrap();
+    let mut stream_data = Vec::new();
+    package
+        .read_stream("Binary.Big")
+        .unwrap()
+        .read_to_end(&mut stream_data)
+        .unwrap();
+    assert!(stream_data == data);
+    let rows = package.select_rows(Select::table("Property")).unwrap();
+    let values: Vec<String> =
+        rows.map(|row| row["Value"].as_str().unwrap().to_string()).collect();
+    assert_eq!(values, vec!["New".to_string()]);
+}
+
+#[test]
+fn into_inner_flushes_pending_changes() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("ProductName"), Value::from("Foo")]),
+        )
+        .unwrap();
+    package.summary_info_mut().set_subject("Foo");
+    // No explicit flush; into_inner takes care of it.
+    let bytes: Vec<u8> = package.into_inner().unwrap().into_inner();
+
+    let mut package = Package::open(Cursor::new(bytes)).unwrap();
+    assert_eq!(package.summary_info().subject(), Some("Foo"));
+    let expected =
+        BTreeMap::from([("ProductName".to_string(), "Foo".to_string())]);
+    assert_eq!(properties(&mut package), expected);
+}
+
+// ========================================================================= //
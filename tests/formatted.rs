@@ -0,0 +1,43 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Package, PackageType};
+use std::io::Cursor;
+
+// ========================================================================= //
+
+#[test]
+fn expand_product_properties() {
+    let mut package = testutil::package_with_properties(&[
+        ("ProductName", "Example App"),
+        ("ProductVersion", "1.2.3"),
+        ("Manufacturer", "Example Corp"),
+    ]);
+
+    assert_eq!(
+        package.expand_formatted("[ProductName] [ProductVersion]").unwrap(),
+        "Example App 1.2.3"
+    );
+    assert_eq!(
+        package.expand_formatted("[Manufacturer][\\\\][ProductName]").unwrap(),
+        "Example Corp\\Example App"
+    );
+    assert_eq!(
+        package.expand_formatted("[NoSuchProperty]|[%TEMP]").unwrap(),
+        "|[%TEMP]"
+    );
+}
+
+#[test]
+fn expand_without_property_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert_eq!(
+        package
+            .expand_formatted("[\\[]ProductName[\\]]=[ProductName]")
+            .unwrap(),
+        "[ProductName]="
+    );
+}
+
+// ========================================================================= //
@@ -0,0 +1,158 @@
+#[macro_use]
+mod testutil;
+
+use msi::{
+    Category, CodePage, Column, ColumnType, Insert, Package, PackageType,
+    Select, Value,
+};
+use std::io::{Cursor, ErrorKind};
+
+// ========================================================================= //
+
+const PROPERTY_IDT: &str = "Property\tValue\r\n\
+                            s72\tl0\r\n\
+                            Property\tProperty\r\n\
+                            Manufacturer\tExample Corp\r\n\
+                            ProductName\tExample\x15App\r\n\
+                            ProductVersion\t1.2.3\r\n";
+
+fn table_rows(package: &mut Package<Cursor<Vec<u8>>>) -> Vec<Vec<Value>> {
+    package
+        .select_rows(Select::table("Property"))
+        .unwrap()
+        .map(|row| (0..row.len()).map(|index| row[index].clone()).collect())
+        .collect()
+}
+
+#[test]
+fn import_property_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.import_idt(PROPERTY_IDT.as_bytes()).unwrap();
+
+    let cursor = Cursor::new(Vec::new());
+    let mut expected =
+        Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().string(72),
+        Column::build("Value").localizable().string(0),
+    ];
+    expected.create_table("Property", columns).unwrap();
+    let query = Insert::into("Property")
+        .row(vec![Value::from("Manufacturer"), Value::from("Example Corp")])
+        .row(vec![Value::from("ProductName"), Value::from("Example\tApp")])
+        .row(vec![Value::from("ProductVersion"), Value::from("1.2.3")]);
+    expected.insert_rows(query).unwrap();
+
+    {
+        let actual = package.get_table("Property").unwrap();
+        let expected = expected.get_table("Property").unwrap();
+        assert_eq!(actual.columns().len(), expected.columns().len());
+        for (actual, expected) in
+            actual.columns().iter().zip(expected.columns())
+        {
+            assert_eq!(actual.name(), expected.name());
+            assert_eq!(actual.coltype(), expected.coltype());
+            assert_eq!(actual.is_nullable(), expected.is_nullable());
+            assert_eq!(actual.is_localizable(), expected.is_localizable());
+            assert_eq!(actual.is_primary_key(), expected.is_primary_key());
+        }
+        assert_eq!(actual.columns()[1].coltype(), ColumnType::Str(0));
+    }
+    assert_eq!(table_rows(&mut package), table_rows(&mut expected));
+
+    // Importing into an existing table adds rows to it.
+    let more = "Property\tValue\r\ns72\tl0\r\nProperty\tProperty\r\n\
+                UpgradeCode\t{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}\r\n";
+    package.import_idt(more.as_bytes()).unwrap();
+    assert_eq!(table_rows(&mut package).len(), 4);
+}
+
+#[test]
+fn import_force_codepage() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.import_idt("\r\n\r\n1252\t_ForceCodepage\r\n".as_bytes()).unwrap();
+    assert_eq!(package.database_codepage(), CodePage::Windows1252);
+}
+
+#[test]
+fn import_mismatched_columns() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.import_idt(PROPERTY_IDT.as_bytes()).unwrap();
+    let idt = "Property\tText\r\ns72\tl0\r\nProperty\tProperty\r\n";
+    assert_error!(
+        package.import_idt(idt.as_bytes()),
+        ErrorKind::InvalidData,
+        "IDT columns [\"Property\", \"Text\"] do not match the columns of \
+         existing table \"Property\" ([\"Property\", \"Value\"])"
+    );
+}
+
+const ADMIN_EXECUTE_SEQUENCE_IDT: &str = "Action\tCondition\tSequence\r\n\
+                                          s72\tS255\tI2\r\n\
+                                          AdminExecuteSequence\tAction\r\n\
+                                          CostFinalize\t\t1000\r\n\
+                                          CostInitialize\t\t800\r\n\
+                                          FileCost\t\t900\r\n\
+                                          InstallAdminPackage\t\t3900\r\n\
+                                          InstallFiles\t\t4000\r\n\
+                                          InstallFinalize\t\t6600\r\n\
+                                          InstallInitialize\t\t1500\r\n\
+                                          InstallValidate\t\t1400\r\n";
+
+#[test]
+fn export_admin_execute_sequence() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Action").primary_key().id_string(72),
+        Column::build("Condition")
+            .nullable()
+            .category(Category::Condition)
+            .string(255),
+        Column::build("Sequence").nullable().range(-4, 32767).int16(),
+    ];
+    package.create_table("AdminExecuteSequence", columns).unwrap();
+    let mut query = Insert::into("AdminExecuteSequence");
+    for &(action, sequence) in &[
+        ("CostInitialize", 800),
+        ("FileCost", 900),
+        ("CostFinalize", 1000),
+        ("InstallValidate", 1400),
+        ("InstallInitialize", 1500),
+        ("InstallAdminPackage", 3900),
+        ("InstallFiles", 4000),
+        ("InstallFinalize", 6600),
+    ] {
+        query = query.row(vec![
+            Value::from(action),
+            Value::Null,
+            Value::Int(sequence),
+        ]);
+    }
+    package.insert_rows(query).unwrap();
+
+    let mut output = Vec::new();
+    package.export_idt("AdminExecuteSequence", &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), ADMIN_EXECUTE_SEQUENCE_IDT);
+
+    assert_error!(
+        package.export_idt("Foobar", Vec::new()),
+        ErrorKind::NotFound,
+        "Table \"Foobar\" does not exist"
+    );
+}
+
+#[test]
+fn export_then_import_round_trip() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.import_idt(PROPERTY_IDT.as_bytes()).unwrap();
+    let mut output = Vec::new();
+    package.export_idt("Property", &mut output).unwrap();
+    assert_eq!(String::from_utf8(output).unwrap(), PROPERTY_IDT);
+}
+
+// ========================================================================= //
@@ -0,0 +1,395 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Column, Insert, Package, PackageType, Select, Value};
+use std::cell::Cell;
+use std::io::{self, Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::rc::Rc;
+
+// ========================================================================= //
+
+fn property_rows(
+    package: &mut Package<Cursor<Vec<u8>>>,
+) -> Vec<(String, String)> {
+    package
+        .select_rows(Select::table("Property"))
+        .unwrap()
+        .map(|row| {
+            (
+                row[0].as_str().unwrap().to_string(),
+                row[1].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn insert_duplicate_key() {
+    let mut package = testutil::package_with_properties(&[]);
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductVersion"), Value::from("0.0.0")]);
+    package.insert_rows(query).unwrap();
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductVersion"), Value::from("1.2.3")]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::AlreadyExists,
+        "Table \"Property\" already contains a row with key \
+         [Str(\"ProductVersion\")]"
+    );
+}
+
+#[test]
+fn insert_or_replace() {
+    let mut package = testutil::package_with_properties(&[]);
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductName"), Value::from("Widget")])
+        .row(vec![Value::from("ProductVersion"), Value::from("0.0.0")]);
+    package.insert_rows(query).unwrap();
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductVersion"), Value::from("1.2.3")])
+        .row(vec![Value::from("Manufacturer"), Value::from("Acme")])
+        .replace();
+    package.insert_rows(query).unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(
+        property_rows(&mut package),
+        vec![
+            ("Manufacturer".to_string(), "Acme".to_string()),
+            ("ProductName".to_string(), "Widget".to_string()),
+            ("ProductVersion".to_string(), "1.2.3".to_string()),
+        ]
+    );
+
+    // Rows within a single query must still have distinct keys.
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductVersion"), Value::from("1.0")])
+        .row(vec![Value::from("ProductVersion"), Value::from("2.0")])
+        .replace();
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "Cannot insert multiple rows with key [Str(\"ProductVersion\")]"
+    );
+}
+
+#[test]
+fn insert_rows_from_tuples() {
+    let mut package = testutil::package_with_properties(&[]);
+    let properties = vec![
+        ("Manufacturer", "Example Corp"),
+        ("ProductName", "Example App"),
+        ("ProductVersion", "1.2.3"),
+    ];
+    let query = Insert::into("Property").rows_from(properties);
+    package.insert_rows(query).unwrap();
+    assert_eq!(
+        property_rows(&mut package),
+        vec![
+            ("Manufacturer".to_string(), "Example Corp".to_string()),
+            ("ProductName".to_string(), "Example App".to_string()),
+            ("ProductVersion".to_string(), "1.2.3".to_string()),
+        ]
+    );
+
+    let query = Insert::into("Property").rows_from(vec![("ProductCode",)]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "Table \"Property\" has 2 columns, but the row at index 0 has 1 \
+         values"
+    );
+}
+
+#[test]
+fn insert_row_with_wrong_arity() {
+    let mut package = testutil::package_with_properties(&[]);
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductName"), Value::from("Widget")])
+        .row(vec![Value::from("ProductVersion")]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "Table \"Property\" has 2 columns, but the row at index 1 has 1 \
+         values"
+    );
+    let query = Insert::into("Property").row(vec![
+        Value::from("ProductName"),
+        Value::from("Widget"),
+        Value::from("Extra"),
+    ]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "Table \"Property\" has 2 columns, but the row at index 0 has 3 \
+         values"
+    );
+    assert_eq!(package.count_rows("Property", None).unwrap(), 0);
+}
+
+#[test]
+fn insert_value_of_wrong_type() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Number").int16(),
+    ];
+    package.create_table("Numbers", columns).unwrap();
+    let query = Insert::into("Numbers")
+        .row(vec![Value::from("One"), Value::Int(1)])
+        .row(vec![Value::from("Two"), Value::from("2")]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "Cannot insert \"2\" into SMALLINT column \"Number\" of table \
+         \"Numbers\" (row at index 1)"
+    );
+    let query =
+        Insert::into("Numbers").row(vec![Value::Int(3), Value::Int(3)]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "Cannot insert 3 into VARCHAR(72) column \"Key\" of table \
+         \"Numbers\" (row at index 0)"
+    );
+    assert_eq!(package.count_rows("Numbers", None).unwrap(), 0);
+}
+
+#[test]
+fn insert_string_longer_than_64k() {
+    let long_value: String =
+        (0..100_000).map(|i| char::from(b'a' + (i % 26) as u8)).collect();
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Registry").primary_key().id_string(72),
+        Column::build("Value").nullable().text_string(0),
+    ];
+    package.create_table("Registry", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Registry")
+                .row(vec![Value::from("Short1"), Value::from("before")])
+                .row(vec![
+                    Value::from("Long"),
+                    Value::from(long_value.clone()),
+                ])
+                .row(vec![Value::from("Short2"), Value::from("after")]),
+        )
+        .unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let rows: Vec<(String, String)> = package
+        .select_rows(Select::table("Registry"))
+        .unwrap()
+        .map(|row| {
+            let key = row[0].as_str().unwrap().to_string();
+            (key, row[1].as_str().unwrap().to_string())
+        })
+        .collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[0], ("Long".to_string(), long_value));
+    assert_eq!(rows[1], ("Short1".to_string(), "before".to_string()));
+    assert_eq!(rows[2], ("Short2".to_string(), "after".to_string()));
+}
+
+#[test]
+fn int16_null_and_extreme_values_are_distinct() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Number").nullable().int16(),
+    ];
+    package.create_table("Numbers", columns).unwrap();
+    let values = [
+        ("A", Value::Null),
+        ("B", Value::from(0)),
+        ("C", Value::from(i16::MIN as i32 + 1)),
+        ("D", Value::from(i16::MAX as i32)),
+    ];
+    let mut insert = Insert::into("Numbers");
+    for (key, value) in values.iter() {
+        insert = insert.row(vec![Value::from(*key), value.clone()]);
+    }
+    package.insert_rows(insert).unwrap();
+    // The minimum i16 value is reserved for encoding null, so it can't be
+    // stored.
+    assert_error!(
+        package.insert_rows(
+            Insert::into("Numbers")
+                .row(vec![Value::from("E"), Value::from(i16::MIN as i32)]),
+        ),
+        ErrorKind::InvalidInput,
+        "-32768 is not a valid value for column \"Number\" of table \
+         \"Numbers\""
+    );
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let rows: Vec<Value> = package
+        .select_rows(Select::table("Numbers"))
+        .unwrap()
+        .map(|row| row["Number"].clone())
+        .collect();
+    let expected: Vec<Value> =
+        values.iter().map(|(_, value)| value.clone()).collect();
+    assert_eq!(rows, expected);
+}
+
+#[test]
+fn empty_string_is_distinct_from_null() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Text").nullable().text_string(0),
+    ];
+    package.create_table("Strings", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Strings")
+                .row(vec![Value::from("Empty"), Value::from("")])
+                .row(vec![Value::from("Null"), Value::Null]),
+        )
+        .unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let rows: Vec<(String, Value)> = package
+        .select_rows(Select::table("Strings"))
+        .unwrap()
+        .map(|row| (row[0].as_str().unwrap().to_string(), row[1].clone()))
+        .collect();
+    assert_eq!(rows[0], ("Empty".to_string(), Value::from("")));
+    assert!(!rows[0].1.is_null());
+    assert_eq!(rows[1], ("Null".to_string(), Value::Null));
+    assert!(rows[1].1.is_null());
+}
+
+fn file_columns() -> Vec<Column> {
+    vec![
+        Column::build("File").primary_key().id_string(72),
+        Column::build("Component_").id_string(72),
+        Column::build("FileName").text_string(255),
+        Column::build("FileSize").int32(),
+        Column::build("Version").nullable().string(72),
+    ]
+}
+
+fn create_file_table() -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.create_table("File", file_columns()).unwrap();
+    package
+}
+
+fn file_row(index: usize) -> Vec<Value> {
+    vec![
+        Value::from(format!("File{index}")),
+        Value::from(format!("Component{}", index / 10)),
+        Value::from(format!("file{index}.txt")),
+        Value::from(index as i32),
+        if index.is_multiple_of(3) {
+            Value::from("1.0.0.0")
+        } else {
+            Value::Null
+        },
+    ]
+}
+
+fn file_rows(package: &mut Package<Cursor<Vec<u8>>>) -> Vec<Vec<Value>> {
+    package
+        .select_rows(Select::table("File"))
+        .unwrap()
+        .map(|row| (0..row.len()).map(|index| row[index].clone()).collect())
+        .collect()
+}
+
+#[test]
+fn batch_insert_matches_row_by_row_insert() {
+    const NUM_ROWS: usize = 500;
+    let mut batched = create_file_table();
+    batched
+        .insert_rows(
+            Insert::into("File").rows((0..NUM_ROWS).map(file_row).collect()),
+        )
+        .unwrap();
+    let mut one_by_one = create_file_table();
+    for index in 0..NUM_ROWS {
+        one_by_one
+            .insert_rows(Insert::into("File").row(file_row(index)))
+            .unwrap();
+    }
+
+    let batched = batched.into_inner().unwrap();
+    let one_by_one = one_by_one.into_inner().unwrap();
+    let mut batched = Package::open(batched).unwrap();
+    let mut one_by_one = Package::open(one_by_one).unwrap();
+    let rows = file_rows(&mut batched);
+    assert_eq!(rows.len(), NUM_ROWS);
+    assert_eq!(rows, file_rows(&mut one_by_one));
+}
+
+/// A writer that adds up the number of bytes written to it.
+struct CountingWriter {
+    inner: Cursor<Vec<u8>>,
+    num_bytes_written: Rc<Cell<usize>>,
+}
+
+impl Read for CountingWriter {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for CountingWriter {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let num_bytes = self.inner.write(buf)?;
+        self.num_bytes_written.set(self.num_bytes_written.get() + num_bytes);
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[test]
+fn batch_insert_writes_table_stream_once() {
+    const NUM_ROWS: usize = 10_000;
+    // Each row takes 12 bytes: four two-byte string references and a
+    // four-byte integer.
+    const TABLE_SIZE: usize = NUM_ROWS * 12;
+    let num_bytes_written = Rc::new(Cell::new(0));
+    let writer = CountingWriter {
+        inner: Cursor::new(Vec::new()),
+        num_bytes_written: num_bytes_written.clone(),
+    };
+    let mut package = Package::create(PackageType::Installer, writer).unwrap();
+    package.create_table("File", file_columns()).unwrap();
+    package.flush().unwrap();
+
+    let before = num_bytes_written.get();
+    let rows = (0..NUM_ROWS).map(file_row).collect();
+    package.insert_rows(Insert::into("File").rows(rows)).unwrap();
+    let written = num_bytes_written.get() - before;
+    // Besides the table data itself, the compound file rewrites a sector of
+    // its allocation table for each new sector of the stream.  Inserting the
+    // rows one at a time would instead write about NUM_ROWS / 2 times as
+    // much.
+    assert!(written >= TABLE_SIZE);
+    assert!(written < 3 * TABLE_SIZE);
+}
+
+// ========================================================================= //
@@ -0,0 +1,289 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Column, Insert, Package, PackageType, Select, Value};
+use std::io::{Cursor, ErrorKind, Read, Write};
+
+// ========================================================================= //
+
+const MODULE_GUID: &str = "0A3C2E1F_5B4D_4C6E_8F7A_9B0C1D2E3F40";
+
+fn directory_columns() -> Vec<Column> {
+    vec![
+        Column::build("Directory").primary_key().id_string(72),
+        Column::build("Directory_Parent").nullable().id_string(72),
+        Column::build("DefaultDir").localizable().text_string(255),
+    ]
+}
+
+fn component_columns() -> Vec<Column> {
+    vec![
+        Column::build("Component").primary_key().id_string(72),
+        Column::build("ComponentId").nullable().string(38),
+        Column::build("Directory_").id_string(72),
+        Column::build("Attributes").int16(),
+        Column::build("Condition").nullable().string(255),
+        Column::build("KeyPath").nullable().id_string(72),
+    ]
+}
+
+fn file_columns() -> Vec<Column> {
+    vec![
+        Column::build("File").primary_key().id_string(72),
+        Column::build("Component_").id_string(72),
+        Column::build("FileName").localizable().text_string(255),
+        Column::build("FileSize").int32(),
+        Column::build("Version").nullable().string(72),
+        Column::build("Language").nullable().string(20),
+        Column::build("Attributes").nullable().int16(),
+        Column::build("Sequence").int16(),
+    ]
+}
+
+/// Builds a minimal merge module with one component holding one file, in a
+/// subdirectory of the module's root directory.  The component and file keys
+/// already carry the module's GUID, as merge module authoring tools produce;
+/// the subdirectory's key does not.
+fn make_merge_module() -> Vec<u8> {
+    let cursor = Cursor::new(Vec::new());
+    let mut module = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("ModuleID").primary_key().id_string(72),
+        Column::build("Language").primary_key().int16(),
+        Column::build("Version").string(32),
+    ];
+    module.create_table("ModuleSignature", columns).unwrap();
+    module
+        .insert_rows(Insert::into("ModuleSignature").row(vec![
+            Value::Str(format!("Runtime.{MODULE_GUID}")),
+            Value::Int(0),
+            Value::from("1.0.0"),
+        ]))
+        .unwrap();
+    module.create_table("Directory", directory_columns()).unwrap();
+    module
+        .insert_rows(
+            Insert::into("Directory")
+                .row(vec![
+                    Value::from("TARGETDIR"),
+                    Value::Null,
+                    Value::from("SourceDir"),
+                ])
+                .row(vec![
+                    Value::from("RuntimeDir"),
+                    Value::from("TARGETDIR"),
+                    Value::from("Runtime"),
+                ]),
+        )
+        .unwrap();
+    module.create_table("Component", component_columns()).unwrap();
+    module
+        .insert_rows(Insert::into("Component").row(vec![
+            Value::Str(format!("RuntimeDll.{MODULE_GUID}")),
+            Value::from("{7C1E0B5A-3D2F-4A6B-9C8D-1E2F3A4B5C6D}"),
+            Value::from("RuntimeDir"),
+            Value::Int(0),
+            Value::Null,
+            Value::Str(format!("runtime.dll.{MODULE_GUID}")),
+        ]))
+        .unwrap();
+    module.create_table("File", file_columns()).unwrap();
+    module
+        .insert_rows(Insert::into("File").row(vec![
+            Value::Str(format!("runtime.dll.{MODULE_GUID}")),
+            Value::Str(format!("RuntimeDll.{MODULE_GUID}")),
+            Value::from("runtime.dll"),
+            Value::Int(1024),
+            Value::Null,
+            Value::Null,
+            Value::Null,
+            Value::Int(1),
+        ]))
+        .unwrap();
+    module
+        .write_stream("MergeModule.CABinet")
+        .unwrap()
+        .write_all(b"MSCF")
+        .unwrap();
+    module.into_inner().unwrap().into_inner()
+}
+
+fn make_installer() -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Feature").primary_key().id_string(38),
+        Column::build("Feature_Parent").nullable().id_string(38),
+        Column::build("Title").nullable().localizable().text_string(64),
+        Column::build("Level").int16(),
+    ];
+    package.create_table("Feature", columns).unwrap();
+    package
+        .insert_rows(Insert::into("Feature").row(vec![
+            Value::from("Complete"),
+            Value::Null,
+            Value::from("Complete"),
+            Value::Int(1),
+        ]))
+        .unwrap();
+    package.create_table("Directory", directory_columns()).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Directory")
+                .row(vec![
+                    Value::from("TARGETDIR"),
+                    Value::Null,
+                    Value::from("SourceDir"),
+                ])
+                .row(vec![
+                    Value::from("INSTALLDIR"),
+                    Value::from("TARGETDIR"),
+                    Value::from("MyApp"),
+                ]),
+        )
+        .unwrap();
+    package.create_table("Component", component_columns()).unwrap();
+    package
+        .insert_rows(Insert::into("Component").row(vec![
+            Value::from("MainExe"),
+            Value::from("{2B3C4D5E-6F7A-4B8C-9D0E-1F2A3B4C5D6E}"),
+            Value::from("INSTALLDIR"),
+            Value::Int(0),
+            Value::Null,
+            Value::from("app.exe"),
+        ]))
+        .unwrap();
+    package.create_table("File", file_columns()).unwrap();
+    package
+        .insert_rows(Insert::into("File").row(vec![
+            Value::from("app.exe"),
+            Value::from("MainExe"),
+            Value::from("app.exe"),
+            Value::Int(4096),
+            Value::Null,
+            Value::Null,
+            Value::Null,
+            Value::Int(3),
+        ]))
+        .unwrap();
+    package
+}
+
+// ========================================================================= //
+
+#[test]
+fn merge_module_into_feature() {
+    let runtime_dll = format!("RuntimeDll.{MODULE_GUID}");
+    let runtime_file = format!("runtime.dll.{MODULE_GUID}");
+    let mut package = make_installer();
+    package
+        .merge_module(
+            Cursor::new(make_merge_module()),
+            "Complete",
+            "INSTALLDIR",
+        )
+        .unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(
+        package.components_for_feature("Complete").unwrap(),
+        vec![runtime_dll.clone()]
+    );
+    let row = package
+        .get_row("Component", &[Value::Str(runtime_dll)])
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        row["Directory_"],
+        Value::Str(format!("RuntimeDir.{MODULE_GUID}"))
+    );
+    assert_eq!(row["KeyPath"], Value::Str(runtime_file.clone()));
+    let row = package
+        .get_row("File", &[Value::Str(runtime_file.clone())])
+        .unwrap()
+        .unwrap();
+    assert_eq!(row["Sequence"], Value::Int(4));
+    assert!(package
+        .get_row(
+            "Directory",
+            &[Value::Str(format!("TARGETDIR.{MODULE_GUID}"))]
+        )
+        .unwrap()
+        .is_none());
+    let query = Select::table("Directory").columns(&["Directory"]);
+    assert_eq!(package.select_rows(query).unwrap().count(), 3);
+    let paths: Vec<(String, String)> = package
+        .file_install_paths()
+        .unwrap()
+        .into_iter()
+        .filter(|(file, _)| *file == runtime_file)
+        .collect();
+    assert_eq!(
+        paths,
+        vec![(
+            runtime_file,
+            "TARGETDIR/MyApp/Runtime/runtime.dll".to_string()
+        )]
+    );
+    let mut data = Vec::new();
+    package
+        .read_stream("MergeModule.CABinet")
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, b"MSCF");
+}
+
+#[test]
+fn merge_module_twice() {
+    let mut package = make_installer();
+    package
+        .merge_module(
+            Cursor::new(make_merge_module()),
+            "Complete",
+            "INSTALLDIR",
+        )
+        .unwrap();
+    let message = format!(
+        "Table \"Directory\" already contains a row with key \
+         [Str(\"RuntimeDir.{MODULE_GUID}\")]"
+    );
+    assert_error!(
+        package.merge_module(
+            Cursor::new(make_merge_module()),
+            "Complete",
+            "INSTALLDIR"
+        ),
+        ErrorKind::AlreadyExists,
+        message.as_str()
+    );
+}
+
+#[test]
+fn merge_module_into_nonexistent_feature() {
+    let mut package = make_installer();
+    assert_error!(
+        package.merge_module(
+            Cursor::new(make_merge_module()),
+            "Extras",
+            "INSTALLDIR"
+        ),
+        ErrorKind::NotFound,
+        "Feature \"Extras\" does not exist"
+    );
+    assert_eq!(package.count_rows("Component", None).unwrap(), 1);
+}
+
+#[test]
+fn merge_package_that_is_not_a_module() {
+    let mut package = make_installer();
+    let other = make_installer().into_inner().unwrap();
+    assert_error!(
+        package.merge_module(other, "Complete", "INSTALLDIR"),
+        ErrorKind::InvalidInput,
+        "Package is not a merge module (it has no ModuleSignature table)"
+    );
+}
+
+// ========================================================================= //
@@ -0,0 +1,200 @@
+#[macro_use]
+mod testutil;
+
+use msi::{
+    Column, Error, Insert, Package, PackageType, SectorSize, Select, Value,
+};
+use std::fs;
+use std::io::{Cursor, ErrorKind, Read, Write};
+
+// ========================================================================= //
+
+const COLUMNS_STREAM_NAME: &str = "\u{4840}\u{3b3f}\u{43f2}\u{4438}\u{45b1}";
+const DRIFT_STREAM_NAME: &str = "\u{4840}\u{454d}\u{426c}\u{4837}";
+
+// ========================================================================= //
+
+#[test]
+fn open_zero_byte_file() {
+    assert_error!(
+        Package::open(Cursor::new(Vec::new())),
+        ErrorKind::InvalidData,
+        "Not a valid MSI package: Invalid CFB file (0 bytes is too small)"
+    );
+}
+
+#[test]
+fn open_non_cfb_file() {
+    let data = vec![b'x'; 4096];
+    match Package::open(Cursor::new(data)) {
+        Err(Error::Corrupt(message)) => {
+            assert!(message.starts_with("Not a valid MSI package: "));
+        }
+        Err(error) => panic!("Unexpected error: {error:?}"),
+        Ok(_) => panic!("Expected an error"),
+    }
+}
+
+#[test]
+fn open_package_missing_columns_stream() {
+    let cursor = Cursor::new(Vec::new());
+    let package = Package::create(PackageType::Installer, cursor).unwrap();
+    let cursor = package.into_inner().unwrap();
+    let mut comp = cfb::CompoundFile::open(cursor).unwrap();
+    comp.remove_stream(COLUMNS_STREAM_NAME).unwrap();
+    let cursor = comp.into_inner();
+    match Package::open(cursor) {
+        Err(Error::Corrupt(message)) => {
+            assert_eq!(
+                message,
+                "Package is corrupt: missing the _Columns stream"
+            );
+        }
+        Err(error) => panic!("Unexpected error: {error:?}"),
+        Ok(_) => panic!("Expected an error"),
+    }
+}
+
+#[test]
+fn create_package_with_each_sector_size() {
+    for sector_size in [SectorSize::Bytes512, SectorSize::Bytes4096] {
+        let cursor = Cursor::new(Vec::new());
+        let mut package = Package::create_with_sector_size(
+            PackageType::Installer,
+            cursor,
+            sector_size,
+        )
+        .unwrap();
+        assert_eq!(package.sector_size(), sector_size);
+        package.set_product_name("Example App").unwrap();
+
+        let cursor = package.into_inner().unwrap();
+        assert_eq!(cursor.get_ref().len() % sector_size.bytes(), 0);
+        let mut package = Package::open(cursor).unwrap();
+        assert_eq!(package.sector_size(), sector_size);
+        assert_eq!(package.package_type(), PackageType::Installer);
+        assert_eq!(
+            package.product_name().unwrap().as_deref(),
+            Some("Example App")
+        );
+    }
+
+    let cursor = Cursor::new(Vec::new());
+    let package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert_eq!(package.sector_size(), SectorSize::Bytes4096);
+}
+
+/// Builds a package with a three-row `Drift` table (4 bytes per row), then
+/// resizes the table's stream by `delta` bytes, as a third-party tool that
+/// disagreed with `_Columns` about the table's schema might.
+fn make_drifted_package(delta: isize) -> Cursor<Vec<u8>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Number").int16(),
+    ];
+    package.create_table("Drift", columns).unwrap();
+    let query = Insert::into("Drift")
+        .row(vec![Value::from("A"), Value::Int(1)])
+        .row(vec![Value::from("B"), Value::Int(2)])
+        .row(vec![Value::from("C"), Value::Int(3)]);
+    package.insert_rows(query).unwrap();
+    let cursor = package.into_inner().unwrap();
+    let mut comp = cfb::CompoundFile::open(cursor).unwrap();
+    let mut data = Vec::new();
+    comp.open_stream(DRIFT_STREAM_NAME)
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data.len(), 12);
+    data.resize((12 + delta) as usize, 0);
+    comp.create_stream(DRIFT_STREAM_NAME).unwrap().write_all(&data).unwrap();
+    comp.into_inner()
+}
+
+#[test]
+fn select_rows_from_drifted_table() {
+    for (delta, length) in [(2, 14), (-2, 10)] {
+        let cursor = make_drifted_package(delta);
+        let mut package = Package::open(cursor).unwrap();
+        let expected = format!(
+            "Table \"Drift\" has {length} bytes of data, which is not a \
+             whole number of rows of the 4 bytes per row declared by its \
+             columns"
+        );
+        match package.select_rows(Select::table("Drift")) {
+            Err(Error::SchemaMismatch(message)) => {
+                assert_eq!(message, expected);
+            }
+            Err(error) => panic!("Unexpected error: {error:?}"),
+            Ok(_) => panic!("Expected an error"),
+        }
+        match package.stream_rows(Select::table("Drift")) {
+            Err(Error::SchemaMismatch(message)) => {
+                assert_eq!(message, expected);
+            }
+            Err(error) => panic!("Unexpected error: {error:?}"),
+            Ok(_) => panic!("Expected an error"),
+        }
+    }
+}
+
+#[test]
+fn create_and_reopen_by_path() {
+    let dir = testutil::scratch_dir("create_and_reopen_by_path");
+    let path = dir.join("test.msi");
+    {
+        let mut package =
+            Package::create_path(PackageType::Installer, &path).unwrap();
+        let columns = vec![
+            Column::build("Id").primary_key().int16(),
+            Column::build("Name").string(16),
+        ];
+        package.create_table("Things", columns).unwrap();
+        package
+            .insert_rows(
+                Insert::into("Things")
+                    .row(vec![Value::Int(1), Value::from("One")]),
+            )
+            .unwrap();
+        package
+            .write_stream("Padding")
+            .unwrap()
+            .write_all(&[0; 100_000])
+            .unwrap();
+    }
+    match Package::create_path(PackageType::Installer, &path) {
+        Err(error) => assert_eq!(error.kind(), ErrorKind::AlreadyExists),
+        Ok(_) => panic!("Expected an error"),
+    }
+
+    {
+        let mut package = Package::open_path(&path).unwrap();
+        package
+            .insert_rows(
+                Insert::into("Things")
+                    .row(vec![Value::Int(2), Value::from("Two")]),
+            )
+            .unwrap();
+    }
+    let mut package = msi::open(&path).unwrap();
+    let rows = package.select_rows(Select::table("Things")).unwrap();
+    let names: Vec<String> =
+        rows.map(|row| row["Name"].as_str().unwrap().to_string()).collect();
+    assert_eq!(names, vec!["One".to_string(), "Two".to_string()]);
+    drop(package);
+
+    // Overwriting truncates the old file rather than leaving its contents
+    // behind the new package.
+    let old_len = fs::metadata(&path).unwrap().len();
+    Package::create_path_overwrite(PackageType::Installer, &path).unwrap();
+    assert!(fs::metadata(&path).unwrap().len() < old_len);
+    let package = Package::open_path(&path).unwrap();
+    assert!(!package.has_table("Things"));
+    assert!(!package.has_stream("Padding"));
+    drop(package);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// ========================================================================= //
@@ -0,0 +1,93 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Package, PackageType};
+use std::io::{Cursor, ErrorKind, Read, Write};
+use uuid::Uuid;
+
+// ========================================================================= //
+
+const PRODUCT_CODE_1: &str = "2A5B3C4D-1111-2222-3333-444455556666";
+const PRODUCT_CODE_2: &str = "9F8E7D6C-AAAA-BBBB-CCCC-DDDDEEEEFFFF";
+
+#[test]
+fn open_patch_package() {
+    let product_codes = [
+        Uuid::parse_str(PRODUCT_CODE_1).unwrap(),
+        Uuid::parse_str(PRODUCT_CODE_2).unwrap(),
+    ];
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Patch, cursor).unwrap();
+    package.set_patch_target_product_codes(&product_codes).unwrap();
+    let cursor = package.into_inner().unwrap();
+
+    // Add the transform substorages that a real patch would contain.
+    let mut comp = cfb::CompoundFile::open(cursor).unwrap();
+    for name in ["/RTM.1", "/#RTM.1"] {
+        comp.create_storage(name).unwrap();
+        let stream_path = format!("{name}/Data");
+        comp.create_stream(stream_path).unwrap().write_all(b"mst").unwrap();
+    }
+    comp.flush().unwrap();
+    let cursor = comp.into_inner();
+
+    let package = Package::open(cursor).unwrap();
+    assert_eq!(package.package_type(), PackageType::Patch);
+    assert_eq!(package.patch_target_product_codes(), product_codes.to_vec());
+    let mut transforms = package.patch_transforms();
+    transforms.sort();
+    assert_eq!(transforms, vec!["#RTM.1".to_string(), "RTM.1".to_string()]);
+}
+
+#[test]
+fn read_substorage_streams() {
+    let cursor = Cursor::new(Vec::new());
+    let package = Package::create(PackageType::Patch, cursor).unwrap();
+    let cursor = package.into_inner().unwrap();
+    let mut comp = cfb::CompoundFile::open(cursor).unwrap();
+    comp.create_storage("/RTM.1").unwrap();
+    comp.create_stream("/RTM.1/Data").unwrap().write_all(b"mst").unwrap();
+    comp.create_storage("/RTM.1/Nested").unwrap();
+    comp.flush().unwrap();
+    let cursor = comp.into_inner();
+
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(package.substorages().collect::<Vec<_>>(), vec!["RTM.1"]);
+    let mut data = Vec::new();
+    package
+        .read_substorage_stream("RTM.1", "Data")
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, b"mst");
+    assert_error!(
+        package.read_substorage_stream("RTM.2", "Data"),
+        ErrorKind::NotFound,
+        "Substorage \"RTM.2\" does not exist"
+    );
+    assert_error!(
+        package.read_substorage_stream("RTM.1", "Nested"),
+        ErrorKind::NotFound,
+        "Stream \"Nested\" does not exist in substorage \"RTM.1\""
+    );
+    assert_error!(
+        package.read_substorage_stream("RTM.1", "Nested/Data"),
+        ErrorKind::InvalidInput,
+        "\"Nested/Data\" is not a valid storage entry name"
+    );
+}
+
+#[test]
+fn installer_has_no_patch_metadata() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert!(package.patch_target_product_codes().is_empty());
+    assert!(package.patch_transforms().is_empty());
+    assert_error!(
+        package.set_patch_target_product_codes(&[Uuid::nil()]),
+        ErrorKind::InvalidInput,
+        "Cannot set target product codes for Installer package"
+    );
+}
+
+// ========================================================================= //
@@ -0,0 +1,96 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Package, PackageType};
+use std::io::Cursor;
+
+// ========================================================================= //
+
+const PRODUCT_CODE: &str = "{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}";
+const UPGRADE_CODE: &str = "{9D8E7F6A-5B4C-4D3E-8F2A-1B0C9D8E7F6A}";
+
+// ========================================================================= //
+
+#[test]
+fn read_product_properties() {
+    let package = testutil::package_with_properties(&[
+        ("ProductCode", PRODUCT_CODE),
+        ("ProductVersion", "1.2.3"),
+        ("ProductName", "Example App"),
+        ("Manufacturer", "Example Corp"),
+    ]);
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(package.product_code().unwrap().as_deref(), Some(PRODUCT_CODE));
+    assert_eq!(package.product_version().unwrap().as_deref(), Some("1.2.3"));
+    assert_eq!(
+        package.product_name().unwrap().as_deref(),
+        Some("Example App")
+    );
+    assert_eq!(
+        package.manufacturer().unwrap().as_deref(),
+        Some("Example Corp")
+    );
+    assert_eq!(package.upgrade_code().unwrap(), None);
+    assert_eq!(package.property("NoSuchProperty").unwrap(), None);
+}
+
+#[test]
+fn read_properties_without_property_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert_eq!(package.product_code().unwrap(), None);
+    assert_eq!(package.manufacturer().unwrap(), None);
+}
+
+#[test]
+fn set_product_properties() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.set_product_name("Example App").unwrap();
+    package.set_product_version("1.0.0").unwrap();
+    package.set_upgrade_code(UPGRADE_CODE).unwrap();
+    assert!(package.has_table("Property"));
+    package.set_product_version("2.0.0").unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(
+        package.product_name().unwrap().as_deref(),
+        Some("Example App")
+    );
+    assert_eq!(package.product_version().unwrap().as_deref(), Some("2.0.0"));
+    assert_eq!(package.upgrade_code().unwrap().as_deref(), Some(UPGRADE_CODE));
+    assert_eq!(package.count_rows("Property", None).unwrap(), 3);
+}
+
+#[test]
+fn validate_required_properties() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.set_product_code(PRODUCT_CODE).unwrap();
+    package.set_property("ProductLanguage", "1033").unwrap();
+    package.set_product_name("Example App").unwrap();
+    package.set_product_version("1.0.0").unwrap();
+    package.set_upgrade_code("{*}").unwrap();
+    assert_eq!(
+        package.validate_required_properties().unwrap(),
+        vec![
+            "Required property Manufacturer is missing".to_string(),
+            "Property UpgradeCode is \"{*}\", which is not a braced \
+             uppercase GUID"
+                .to_string(),
+        ]
+    );
+
+    package.set_manufacturer("Example Corp").unwrap();
+    package.set_upgrade_code(UPGRADE_CODE).unwrap();
+    assert!(package.validate_required_properties().unwrap().is_empty());
+
+    package.set_product_code(&PRODUCT_CODE.to_lowercase()).unwrap();
+    package.set_property("ProductLanguage", "English").unwrap();
+    assert_eq!(package.validate_required_properties().unwrap().len(), 2);
+}
+
+// ========================================================================= //
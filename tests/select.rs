@@ -1,8 +1,13 @@
 #[macro_use]
 mod testutil;
 
-use msi::{Column, Expr, Insert, Package, PackageType, Select, Value};
-use std::io::{Cursor, ErrorKind};
+use msi::{
+    Agg, BorrowedValue, Category, Column, Delete, Error, Expr, Insert,
+    Package, PackageType, Select, Update, Value,
+};
+use std::cell::Cell;
+use std::io::{self, Cursor, ErrorKind, Read, Seek, SeekFrom};
+use std::rc::Rc;
 
 //===========================================================================//
 
@@ -39,6 +44,34 @@ fn nonexistent_column() {
     );
 }
 
+#[test]
+fn match_on_error_variants() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    match package.select_rows(Select::table("Foobar")) {
+        Err(Error::NotFound(message)) => {
+            assert_eq!(message, "Table \"Foobar\" does not exist");
+        }
+        Err(error) => panic!("Unexpected error: {error:?}"),
+        Ok(_) => panic!("Expected an error"),
+    }
+
+    let columns = vec![Column::build("Foo").primary_key().int32()];
+    package.create_table("Foobar", columns).unwrap();
+    let query = Select::table("Foobar").columns(&["Baz"]);
+    match package.select_rows(query) {
+        Err(Error::SchemaMismatch(_)) => {}
+        Err(error) => panic!("Unexpected error: {error:?}"),
+        Ok(_) => panic!("Expected an error"),
+    }
+
+    // Errors keep their variant when passed through an io::Error.
+    let error = package.drop_table("Missing").unwrap_err();
+    let io_error = io::Error::from(error);
+    assert_eq!(io_error.kind(), ErrorKind::NotFound);
+    assert!(matches!(Error::from(io_error), Error::NotFound(_)));
+}
+
 #[test]
 fn select_rows() {
     let cursor = Cursor::new(Vec::new());
@@ -204,3 +237,669 @@ fn nested_inner_join() {
 }
 
 //===========================================================================//
+
+#[test]
+fn count_rows() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Foo").primary_key().int16(),
+        Column::build("Bar").string(16),
+        Column::build("Baz").nullable().int32(),
+    ];
+    package.create_table("Quux", columns).unwrap();
+    assert_eq!(package.count_rows("Quux", None).unwrap(), 0);
+    let mut query = Insert::into("Quux");
+    for index in 0..100 {
+        let baz = if index % 3 == 0 { Value::Null } else { Value::Int(index) };
+        query = query.row(vec![
+            Value::Int(index),
+            Value::Str(format!("row{}", index % 7)),
+            baz,
+        ]);
+    }
+    package.insert_rows(query).unwrap();
+
+    let total = package.select_rows(Select::table("Quux")).unwrap().len();
+    assert_eq!(total, 100);
+    assert_eq!(package.count_rows("Quux", None).unwrap(), total);
+
+    let conditions: Vec<fn() -> Expr> = vec![
+        || Expr::col("Baz").eq(Expr::null()),
+        || Expr::col("Bar").eq(Expr::string("row3")),
+        || {
+            Expr::col("Foo")
+                .gt(Expr::integer(50))
+                .and(Expr::col("Bar").ne(Expr::string("row0")))
+        },
+    ];
+    for condition in conditions {
+        let expected = package
+            .select_rows(Select::table("Quux").with(condition()))
+            .unwrap()
+            .len();
+        assert!(expected > 0 && expected < total);
+        assert_eq!(
+            package.count_rows("Quux", Some(condition())).unwrap(),
+            expected
+        );
+    }
+
+    assert_error!(
+        package.count_rows("Foobar", None),
+        ErrorKind::NotFound,
+        "Table \"Foobar\" does not exist"
+    );
+    assert_error!(
+        package.count_rows("Quux", Some(Expr::col("Xyzzy").eq(Expr::null()))),
+        ErrorKind::InvalidInput,
+        "Table \"Quux\" has no column named \"Xyzzy\""
+    );
+}
+
+#[test]
+fn stream_rows() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().int32(),
+        Column::build("Name").string(16),
+        Column::build("Flags").nullable().int16(),
+    ];
+    package.create_table("Big", columns).unwrap();
+    let mut query = Insert::into("Big");
+    for index in 0..20000 {
+        let flags = if index % 3 == 0 { Value::Null } else { Value::Int(7) };
+        query = query.row(vec![
+            Value::Int(index),
+            Value::Str(format!("name{}", index % 100)),
+            flags,
+        ]);
+    }
+    package.insert_rows(query).unwrap();
+
+    let rows = package.stream_rows(Select::table("Big")).unwrap();
+    assert_eq!(rows.size_hint(), (20000, Some(20000)));
+    let mut count = 0;
+    for (index, row) in rows.enumerate() {
+        let row = row.unwrap();
+        assert_eq!(row["Key"], Value::Int(index as i32));
+        assert_eq!(row["Name"], Value::Str(format!("name{}", index % 100)));
+        count += 1;
+    }
+    assert_eq!(count, 20000);
+
+    let condition = || {
+        Expr::col("Name")
+            .eq(Expr::string("name42"))
+            .and(Expr::col("Flags").ne(Expr::null()))
+    };
+    let expected: Vec<Vec<Value>> = package
+        .select_rows(Select::table("Big").columns(&["Key"]).with(condition()))
+        .unwrap()
+        .map(|row| vec![row[0].clone()])
+        .collect();
+    assert!(!expected.is_empty() && expected.len() < 200);
+    let stream = package
+        .stream_rows(Select::table("Big").columns(&["Key"]).with(condition()))
+        .unwrap();
+    assert_eq!(stream.columns().len(), 1);
+    let actual: Vec<Vec<Value>> =
+        stream.map(|row| vec![row.unwrap()[0].clone()]).collect();
+    assert_eq!(actual, expected);
+
+    assert_error!(
+        package.stream_rows(Select::table("Foobar")),
+        ErrorKind::NotFound,
+        "Table \"Foobar\" does not exist"
+    );
+    assert_error!(
+        package.stream_rows(Select::table("Big").columns(&["Xyzzy"])),
+        ErrorKind::InvalidInput,
+        "Table \"Big\" has no column named \"Xyzzy\""
+    );
+}
+
+#[test]
+fn stream_rows_with_join() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package
+        .create_table("Foo", vec![Column::build("Id").primary_key().int16()])
+        .unwrap();
+    package
+        .create_table(
+            "Bar",
+            vec![
+                Column::build("Id").primary_key().int16(),
+                Column::build("Foo_").int16(),
+            ],
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("Foo")
+                .row(vec![Value::Int(1)])
+                .row(vec![Value::Int(2)]),
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("Bar").row(vec![Value::Int(5), Value::Int(2)]),
+        )
+        .unwrap();
+    let query = Select::table("Foo")
+        .inner_join(
+            Select::table("Bar"),
+            Expr::col("Foo.Id").eq(Expr::col("Bar.Foo_")),
+        )
+        .columns(&["Foo.Id", "Bar.Id"]);
+    let rows: Vec<(i32, i32)> = package
+        .stream_rows(query)
+        .unwrap()
+        .map(|row| {
+            let row = row.unwrap();
+            (row[0].as_int().unwrap(), row[1].as_int().unwrap())
+        })
+        .collect();
+    assert_eq!(rows, vec![(2, 5)]);
+}
+
+#[test]
+fn get_row_by_key() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    let columns = vec![
+        Column::build("Dialog").primary_key().id_string(72),
+        Column::build("Control").primary_key().id_string(50),
+        Column::build("Order").int16(),
+    ];
+    package.create_table("ControlEvent", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("ProductName"), Value::from("Widget")])
+                .row(vec![Value::from("Manufacturer"), Value::from("Acme")]),
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("ControlEvent")
+                .row(vec![Value::from("Main"), Value::from("Ok"), 1.into()])
+                .row(vec![Value::from("Main"), Value::from("Back"), 2.into()])
+                .row(vec![Value::from("Exit"), Value::from("Ok"), 3.into()]),
+        )
+        .unwrap();
+
+    let row = package
+        .get_row("Property", &[Value::from("ProductName")])
+        .unwrap()
+        .unwrap();
+    assert_eq!(row["Value"], Value::from("Widget"));
+    assert!(package
+        .get_row("Property", &[Value::from("ProductVersion")])
+        .unwrap()
+        .is_none());
+    let row = package
+        .get_row("ControlEvent", &[Value::from("Exit"), Value::from("Ok")])
+        .unwrap()
+        .unwrap();
+    assert_eq!(row["Order"], Value::Int(3));
+
+    assert_error!(
+        package.get_row("ControlEvent", &[Value::from("Exit")]),
+        ErrorKind::InvalidInput,
+        "Table \"ControlEvent\" has 2 primary key columns, but a key with 1 \
+         values was provided"
+    );
+    assert_error!(
+        package.get_row("Missing", &[]),
+        ErrorKind::NotFound,
+        "Table \"Missing\" does not exist"
+    );
+}
+
+#[test]
+fn select_distinct() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Component").primary_key().id_string(72),
+        Column::build("Directory_").id_string(72),
+        Column::build("Attributes").int16(),
+    ];
+    package.create_table("Component", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Component")
+                .row(vec!["App".into(), "INSTALLDIR".into(), 0.into()])
+                .row(vec!["Docs".into(), "DocsDir".into(), 0.into()])
+                .row(vec!["Help".into(), "DocsDir".into(), 4.into()])
+                .row(vec!["Lib".into(), "INSTALLDIR".into(), 0.into()])
+                .row(vec!["Shortcut".into(), "MenuDir".into(), 4.into()]),
+        )
+        .unwrap();
+
+    let query = Select::table("Component").columns(&["Directory_"]).distinct();
+    let directories: Vec<String> = package
+        .select_rows(query)
+        .unwrap()
+        .map(|row| row[0].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(directories, vec!["INSTALLDIR", "DocsDir", "MenuDir"]);
+
+    // Only the selected columns are compared.
+    let query = Select::table("Component")
+        .columns(&["Directory_", "Attributes"])
+        .distinct();
+    assert_eq!(package.select_rows(query).unwrap().len(), 4);
+    let query = Select::table("Component").columns(&["Attributes"]).distinct();
+    let mut stream = package.stream_rows(query).unwrap();
+    assert_eq!(stream.next().unwrap().unwrap()[0], Value::Int(0));
+    assert_eq!(stream.next().unwrap().unwrap()[0], Value::Int(4));
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn aggregate_column() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Action").primary_key().id_string(72),
+        Column::build("Condition")
+            .nullable()
+            .category(Category::Condition)
+            .string(255),
+        Column::build("Sequence").nullable().range(-4, 32767).int16(),
+    ];
+    package.create_table("InstallExecuteSequence", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("InstallExecuteSequence")
+                .row(vec!["CostInitialize".into(), Value::Null, 800.into()])
+                .row(vec!["FileCost".into(), Value::Null, 900.into()])
+                .row(vec!["InstallFiles".into(), Value::Null, 4000.into()])
+                .row(vec!["InstallFinalize".into(), Value::Null, 6600.into()])
+                .row(vec!["Unused".into(), Value::Null, Value::Null]),
+        )
+        .unwrap();
+    let columns = vec![
+        Column::build("File").primary_key().id_string(72),
+        Column::build("FileName").text_string(255),
+        Column::build("FileSize").range(0, 0x7fffffff).int32(),
+    ];
+    package.create_table("File", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("File")
+                .row(vec!["App".into(), "app.exe".into(), 150_000.into()])
+                .row(vec!["Lib".into(), "lib.dll".into(), 72_000.into()])
+                .row(vec!["Readme".into(), "readme.txt".into(), 1_234.into()]),
+        )
+        .unwrap();
+
+    let table = "InstallExecuteSequence";
+    assert_eq!(
+        package.aggregate(table, "Sequence", Agg::Max).unwrap(),
+        Value::Int(6600)
+    );
+    assert_eq!(
+        package.aggregate(table, "Sequence", Agg::Min).unwrap(),
+        Value::Int(800)
+    );
+    assert_eq!(
+        package.aggregate(table, "Sequence", Agg::Count).unwrap(),
+        Value::Int(4)
+    );
+    assert_eq!(
+        package.aggregate(table, "Condition", Agg::Count).unwrap(),
+        Value::Int(0)
+    );
+    assert_eq!(
+        package.aggregate(table, "Condition", Agg::Max).unwrap(),
+        Value::Null
+    );
+    assert_eq!(
+        package.aggregate("File", "FileSize", Agg::Sum).unwrap(),
+        Value::Int(223_234)
+    );
+    assert_eq!(
+        package.aggregate("File", "FileName", Agg::Min).unwrap(),
+        Value::from("app.exe")
+    );
+    assert_eq!(
+        package.aggregate("File", "FileName", Agg::Max).unwrap(),
+        Value::from("readme.txt")
+    );
+
+    assert_error!(
+        package.aggregate("File", "FileName", Agg::Sum),
+        ErrorKind::InvalidInput,
+        "Cannot compute SUM of non-integer column \"FileName\""
+    );
+    assert_error!(
+        package.aggregate("File", "Size", Agg::Max),
+        ErrorKind::InvalidInput,
+        "Table \"File\" has no column named \"Size\""
+    );
+    assert_error!(
+        package.aggregate("Component", "Directory_", Agg::Count),
+        ErrorKind::NotFound,
+        "Table \"Component\" does not exist"
+    );
+}
+
+fn make_property_package() -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("ProductName"), Value::from("Widget")]),
+        )
+        .unwrap();
+    package
+}
+
+#[test]
+fn row_lookup_by_column_name() {
+    let mut package = make_property_package();
+    let row = package.select_rows(Select::table("Property")).unwrap().next();
+    let row = row.unwrap();
+    assert_eq!(row["Value"], Value::from("Widget"));
+    assert_eq!(row["value"], Value::from("Widget"));
+    assert_eq!(row.get("Property"), Some(&Value::from("ProductName")));
+    assert_eq!(row.get("PROPERTY"), Some(&Value::from("ProductName")));
+    assert!(row.has_column("vAlUe"));
+    assert_eq!(row.get("Component_"), None);
+    assert!(!row.has_column("Component_"));
+}
+
+#[test]
+#[should_panic(expected = "Table \"Property\" has no column named \"Foo\"")]
+fn row_index_by_missing_column_name() {
+    let mut package = make_property_package();
+    let row = package.select_rows(Select::table("Property")).unwrap().next();
+    let _ = &row.unwrap()["Foo"];
+}
+
+#[test]
+fn borrowed_row_values() {
+    let mut package = make_property_package();
+    let mut rows = package.select_rows(Select::table("Property")).unwrap();
+    let values = rows.next_borrowed().unwrap();
+    assert_eq!(
+        values,
+        vec![BorrowedValue::Str("ProductName"), BorrowedValue::Str("Widget")]
+    );
+    assert_eq!(values[1], Value::from("Widget"));
+    assert_eq!(values[1].to_value(), Value::from("Widget"));
+    assert!(rows.next_borrowed().is_none());
+    assert!(rows.next().is_none());
+    assert_eq!(values[1].to_string(), "\"Widget\"");
+}
+
+/// A reader that counts how many times it has been read from.
+struct CountingReader {
+    inner: Cursor<Vec<u8>>,
+    num_reads: Rc<Cell<usize>>,
+    num_bytes_read: Rc<Cell<usize>>,
+}
+
+impl Read for CountingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.num_reads.set(self.num_reads.get() + 1);
+        let num_bytes = self.inner.read(buf)?;
+        self.num_bytes_read.set(self.num_bytes_read.get() + num_bytes);
+        Ok(num_bytes)
+    }
+}
+
+impl Seek for CountingReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn repeated_selects_reuse_decoded_rows() {
+    let data = make_property_package().into_inner().unwrap().into_inner();
+    let num_reads = Rc::new(Cell::new(0));
+    let reader = CountingReader {
+        inner: Cursor::new(data),
+        num_reads: num_reads.clone(),
+        num_bytes_read: Rc::new(Cell::new(0)),
+    };
+    let mut package = Package::open(reader).unwrap();
+
+    let before = num_reads.get();
+    let rows: Vec<_> =
+        package.select_rows(Select::table("Property")).unwrap().collect();
+    assert_eq!(rows.len(), 1);
+    let after_first = num_reads.get();
+    assert!(after_first > before);
+    for _ in 0..9 {
+        let rows: Vec<_> =
+            package.select_rows(Select::table("Property")).unwrap().collect();
+        assert_eq!(rows[0]["Value"], Value::from("Widget"));
+    }
+    assert_eq!(num_reads.get(), after_first);
+
+    package.clear_cache();
+    package.select_rows(Select::table("Property")).unwrap();
+    assert!(num_reads.get() > after_first);
+}
+
+/// Opens a package with a 50,000-row table, whose data is 400,000 bytes,
+/// through a reader that adds up the number of bytes read into
+/// `num_bytes_read`.
+fn open_big_package(
+    num_bytes_read: Rc<Cell<usize>>,
+) -> Package<CountingReader> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().int32(),
+        Column::build("Value").int32(),
+    ];
+    package.create_table("Big", columns).unwrap();
+    let rows = (0..50_000).map(|index| vec![Value::Int(index); 2]).collect();
+    package.insert_rows(Insert::into("Big").rows(rows)).unwrap();
+    let data = package.into_inner().unwrap().into_inner();
+    let reader = CountingReader {
+        inner: Cursor::new(data),
+        num_reads: Rc::new(Cell::new(0)),
+        num_bytes_read,
+    };
+    Package::open(reader).unwrap()
+}
+
+#[test]
+fn stream_rows_reads_table_lazily() {
+    let num_bytes_read = Rc::new(Cell::new(0));
+    let mut package = open_big_package(num_bytes_read.clone());
+    // Taking the first few rows should only read the parts of the table's
+    // data stream holding them.
+    let before = num_bytes_read.get();
+    let mut rows = package.stream_rows(Select::table("Big")).unwrap();
+    for index in 0..10 {
+        let row = rows.next().unwrap().unwrap();
+        assert_eq!(row["Value"], Value::Int(index));
+    }
+    assert!(num_bytes_read.get() - before < 200_000);
+}
+
+#[test]
+fn count_rows_without_condition_reads_no_table_data() {
+    let num_bytes_read = Rc::new(Cell::new(0));
+    let mut package = open_big_package(num_bytes_read.clone());
+    let before = num_bytes_read.get();
+    assert_eq!(package.count_rows("Big", None).unwrap(), 50_000);
+    assert_eq!(num_bytes_read.get(), before);
+    let rows = package.select_rows(Select::table("Big")).unwrap();
+    assert_eq!(rows.count(), 50_000);
+    assert!(num_bytes_read.get() - before >= 400_000);
+}
+
+#[test]
+fn mutations_invalidate_decoded_rows() {
+    let mut package = make_property_package();
+    let rows = package.select_rows(Select::table("Property")).unwrap();
+    assert_eq!(rows.count(), 1);
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("Manufacturer"), Value::from("Acme")]),
+        )
+        .unwrap();
+    let rows = package.select_rows(Select::table("Property")).unwrap();
+    assert_eq!(rows.count(), 2);
+    package
+        .update_rows(
+            Update::table("Property")
+                .set("Value", Value::from("Gadget"))
+                .with(Expr::col("Property").eq(Expr::string("ProductName"))),
+        )
+        .unwrap();
+    let row =
+        package.get_row("Property", &[Value::from("ProductName")]).unwrap();
+    assert_eq!(row.unwrap()["Value"], Value::from("Gadget"));
+    package
+        .delete_rows(
+            Delete::from("Property")
+                .with(Expr::col("Property").eq(Expr::string("Manufacturer"))),
+        )
+        .unwrap();
+    let rows = package.select_rows(Select::table("Property")).unwrap();
+    assert_eq!(rows.count(), 1);
+    package
+        .add_column(
+            "Property",
+            Column::build("Note").nullable().text_string(0),
+        )
+        .unwrap();
+    let rows: Vec<_> =
+        package.select_rows(Select::table("Property")).unwrap().collect();
+    assert_eq!(rows[0].len(), 3);
+    package.drop_table("Property").unwrap();
+    assert_error!(
+        package.select_rows(Select::table("Property")),
+        ErrorKind::NotFound,
+        "Table \"Property\" does not exist"
+    );
+}
+
+#[test]
+fn query_package_opened_from_read_only_buffer() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("ProductName"), Value::from("Foo")]),
+        )
+        .unwrap();
+    package.summary_info_mut().set_author("Jane Doe");
+    let bytes = package.into_inner().unwrap().into_inner();
+
+    // A `Cursor<&[u8]>` can be read and seeked, but not written.
+    let mut package = Package::open(Cursor::new(bytes.as_slice())).unwrap();
+    assert_eq!(package.summary_info().author(), Some("Jane Doe"));
+    assert!(package.has_table("Property"));
+    let rows: Vec<_> =
+        package.select_rows(Select::table("Property")).unwrap().collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["Value"], Value::from("Foo"));
+    assert_eq!(package.streams().count(), 0);
+}
+
+fn make_component_package() -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Component").primary_key().id_string(72),
+        Column::build("Directory_").id_string(72),
+    ];
+    package.create_table("Component", columns).unwrap();
+    let columns = vec![
+        Column::build("Feature_").primary_key().id_string(38),
+        Column::build("Component_").primary_key().id_string(72),
+    ];
+    package.create_table("FeatureComponents", columns).unwrap();
+    let query = Insert::into("Component")
+        .row(vec![Value::from("MainExe"), Value::from("INSTALLDIR")])
+        .row(vec![Value::from("Helper"), Value::from("INSTALLDIR")])
+        .row(vec![Value::from("Orphan"), Value::from("INSTALLDIR")])
+        .row(vec![Value::from("Manual"), Value::from("DOCSDIR")]);
+    package.insert_rows(query).unwrap();
+    let query = Insert::into("FeatureComponents")
+        .row(vec![Value::from("Complete"), Value::from("MainExe")])
+        .row(vec![Value::from("Complete"), Value::from("Helper")])
+        .row(vec![Value::from("Docs"), Value::from("Manual")])
+        .row(vec![Value::from("Docs"), Value::from("MainExe")]);
+    package.insert_rows(query).unwrap();
+    package
+}
+
+#[test]
+fn select_rows_not_in_other_table() {
+    let mut package = make_component_package();
+    let query = Select::table("Component").columns(&["Component"]).with(
+        Expr::col("Component")
+            .not_exists_in("FeatureComponents", "Component_"),
+    );
+    let orphans: Vec<Value> = package
+        .select_rows(query)
+        .unwrap()
+        .map(|row| row[0].clone())
+        .collect();
+    assert_eq!(orphans, vec![Value::from("Orphan")]);
+
+    let condition = Expr::col("Component")
+        .exists_in("FeatureComponents", "Component_")
+        .and(Expr::col("Directory_").eq(Expr::string("INSTALLDIR")));
+    assert_eq!(package.count_rows("Component", Some(condition)).unwrap(), 2);
+
+    let query = Delete::from("Component").with(
+        Expr::col("Component")
+            .not_exists_in("FeatureComponents", "Component_"),
+    );
+    assert_eq!(package.delete_rows(query).unwrap(), 1);
+    assert_eq!(package.count_rows("Component", None).unwrap(), 3);
+}
+
+#[test]
+fn select_rows_in_nonexistent_table() {
+    let mut package = make_component_package();
+    let query = Select::table("Component")
+        .with(Expr::col("Component").exists_in("Foobar", "Component_"));
+    assert_error!(
+        package.select_rows(query),
+        ErrorKind::NotFound,
+        "Table \"Foobar\" does not exist"
+    );
+    let query = Select::table("Component")
+        .with(Expr::col("Component").exists_in("FeatureComponents", "Foobar"));
+    assert_error!(
+        package.select_rows(query),
+        ErrorKind::InvalidInput,
+        "Table \"FeatureComponents\" has no column named \"Foobar\""
+    );
+}
+
+//===========================================================================//
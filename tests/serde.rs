@@ -0,0 +1,99 @@
+#![cfg(feature = "serde")]
+
+use msi::{Column, Insert, Package, PackageType, Select, Value};
+use std::io::{Cursor, Write};
+
+// ========================================================================= //
+
+#[test]
+fn value_json_round_trip() {
+    let values = vec![
+        Value::Null,
+        Value::Int(0),
+        Value::Int(-2147483647),
+        Value::Int(i32::MAX),
+        Value::from(""),
+        Value::from("Hello, \"world\"!"),
+        Value::from("42"),
+    ];
+    let json = serde_json::to_string(&values).unwrap();
+    assert_eq!(
+        json,
+        "[null,0,-2147483647,2147483647,\"\",\"Hello, \\\"world\\\"!\",\"42\"]"
+    );
+    let decoded: Vec<Value> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, values);
+}
+
+#[test]
+fn value_json_out_of_range() {
+    assert!(serde_json::from_str::<Value>("2147483648").is_err());
+    assert!(serde_json::from_str::<Value>("1.5").is_err());
+    assert!(serde_json::from_str::<Value>("true").is_err());
+}
+
+#[test]
+fn selected_row_round_trip() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Number").nullable().int32(),
+        Column::build("Text").nullable().text_string(0),
+    ];
+    package.create_table("Table", columns).unwrap();
+    let query = Insert::into("Table")
+        .row(vec![Value::from("A"), Value::Int(7), Value::Null])
+        .row(vec![Value::from("B"), Value::Null, Value::from("text")]);
+    package.insert_rows(query).unwrap();
+
+    let rows: Vec<Vec<Value>> = package
+        .select_rows(Select::table("Table"))
+        .unwrap()
+        .map(|row| (0..row.len()).map(|index| row[index].clone()).collect())
+        .collect();
+    let json = serde_json::to_string(&rows).unwrap();
+    assert_eq!(json, "[[\"A\",7,null],[\"B\",null,\"text\"]]");
+    let decoded: Vec<Vec<Value>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, rows);
+}
+
+#[test]
+fn package_to_json() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.summary_info_mut().set_author("Jane Doe".to_string());
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    let query = Insert::into("Property")
+        .row(vec![Value::from("ProductName"), Value::from("Example")])
+        .row(vec![Value::from("ProductVersion"), Value::from("1.2.3")]);
+    package.insert_rows(query).unwrap();
+    package.write_stream("Icon.Data").unwrap().write_all(&[0; 100]).unwrap();
+
+    let json = package.to_json().unwrap();
+    let property = &json["tables"]["Property"];
+    assert_eq!(
+        property["rows"],
+        serde_json::json!([
+            ["ProductName", "Example"],
+            ["ProductVersion", "1.2.3"],
+        ])
+    );
+    assert_eq!(property["columns"][0]["name"], "Property");
+    assert_eq!(property["columns"][0]["type"], "VARCHAR(72)");
+    assert_eq!(property["columns"][0]["primary_key"], true);
+    assert_eq!(property["columns"][1]["category"], "Text");
+    assert!(json["tables"]["_Validation"].is_object());
+    assert_eq!(
+        json["streams"],
+        serde_json::json!([{ "name": "Icon.Data", "length": 100 }])
+    );
+    assert_eq!(json["summary_info"]["author"], "Jane Doe");
+    assert_eq!(json["summary_info"]["title"], "Installation Database");
+}
+
+// ========================================================================= //
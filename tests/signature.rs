@@ -15,6 +15,7 @@ fn remove_signature_from_unsigned_package() -> io::Result<()> {
     // effect.
     package.remove_digital_signature()?;
     assert!(!package.has_digital_signature());
+    assert_eq!(package.digital_signature()?, None);
     Ok(())
 }
 
@@ -25,14 +26,15 @@ fn remove_signature_from_signed_package() -> io::Result<()> {
     let package = Package::create(PackageType::Installer, cursor)?;
     let cursor = package.into_inner()?;
     let mut comp = CompoundFile::open(cursor)?;
-    comp.create_stream("\u{5}DigitalSignature")?.write(b"foo")?;
-    comp.create_stream("\u{5}MsiDigitalSignatureEx")?.write(b"bar")?;
+    comp.create_stream("\u{5}DigitalSignature")?.write_all(b"foo")?;
+    comp.create_stream("\u{5}MsiDigitalSignatureEx")?.write_all(b"bar")?;
 
     // Open the package again.  It should now have a signature.  However, the
     // signature data should not show up in the list of MSI streams.
     let cursor = comp.into_inner();
     let mut package = Package::open(cursor)?;
     assert!(package.has_digital_signature());
+    assert_eq!(package.digital_signature()?, Some(b"foo".to_vec()));
     assert_eq!(
         package.streams().collect::<Vec<String>>(),
         Vec::<String>::new()
@@ -41,6 +43,7 @@ fn remove_signature_from_signed_package() -> io::Result<()> {
     // Remove the signature.
     package.remove_digital_signature()?;
     assert!(!package.has_digital_signature());
+    assert_eq!(package.digital_signature()?, None);
 
     // Check that the signature data really is gone from the underlying CFB
     // file.
@@ -1,8 +1,9 @@
 #[macro_use]
 mod testutil;
 
-use msi::{Package, PackageType};
-use std::io::{Cursor, ErrorKind, Read, Write};
+use msi::{Column, Insert, Package, PackageType, Value};
+use std::fs;
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
 
 // ========================================================================= //
 
@@ -36,10 +37,11 @@ fn nonexistent_stream_name() {
         ErrorKind::NotFound,
         "Stream \"Foo\" does not exist"
     );
+    assert!(!package.remove_stream("Baz").unwrap());
     assert_error!(
-        package.remove_stream("Baz"),
+        package.stream_len("Quux"),
         ErrorKind::NotFound,
-        "Stream \"Baz\" does not exist"
+        "Stream \"Quux\" does not exist"
     );
 }
 
@@ -72,8 +74,9 @@ fn create_and_remove_stream() {
     package.read_stream("Hello").unwrap().read_to_end(&mut data).unwrap();
     assert_eq!(data.as_slice(), b"Hi there!");
 
-    package.remove_stream("Hello").unwrap();
+    assert!(package.remove_stream("Hello").unwrap());
     assert!(!package.has_stream("Hello"));
+    assert!(!package.remove_stream("Hello").unwrap());
     assert_eq!(
         package.streams().collect::<Vec<String>>(),
         Vec::<String>::new()
@@ -88,4 +91,379 @@ fn create_and_remove_stream() {
     );
 }
 
+#[test]
+fn has_stream_checks_existence_without_opening() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Name").primary_key().id_string(72),
+        Column::build("Data").binary(),
+    ];
+    package.create_table("Binary", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Binary")
+                .row(vec![Value::from("Data"), Value::from("Data")]),
+        )
+        .unwrap();
+    package.write_stream("Binary.Data").unwrap().write_all(b"data").unwrap();
+    assert!(package.has_stream("Binary.Data"));
+    assert!(!package.has_stream("Binary.Missing"));
+    assert!(!package.has_stream(""));
+    // The stream holding the Binary table's rows is not a binary stream.
+    assert!(!package.has_stream("\u{4840}Binary"));
+    assert_error!(
+        package.read_stream("\u{4840}Binary"),
+        ErrorKind::InvalidInput,
+        "\"\u{4840}Binary\" is not a valid stream name"
+    );
+}
+
+#[test]
+fn write_stream_from_reader() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let payload: Vec<u8> =
+        (0..2 * 1024 * 1024).map(|index| (index % 251) as u8).collect();
+    let num_bytes = package
+        .write_stream_from("Payload", Cursor::new(payload.clone()))
+        .unwrap();
+    assert_eq!(num_bytes, payload.len() as u64);
+    assert_eq!(package.stream_len("Payload").unwrap(), num_bytes);
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let mut data = Vec::<u8>::new();
+    package.read_stream("Payload").unwrap().read_to_end(&mut data).unwrap();
+    assert!(data == payload);
+
+    assert_error!(
+        package.write_stream_from("\u{4840}Foo", Cursor::new(b"data")),
+        ErrorKind::InvalidInput,
+        "\"\u{4840}Foo\" is not a valid stream name"
+    );
+}
+
+#[test]
+fn cannot_remove_internal_streams() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    for name in ["_Tables", "_Columns", "_StringData", "_StringPool"] {
+        assert_error!(
+            package.remove_stream(name),
+            ErrorKind::InvalidInput,
+            format!("Cannot remove internal stream {name:?} of the package")
+        );
+    }
+    assert_error!(
+        package.remove_stream("\u{5}SummaryInformation"),
+        ErrorKind::InvalidInput,
+        "Cannot remove internal stream \"\\u{5}SummaryInformation\" of the \
+         package"
+    );
+
+    let cursor = package.into_inner().unwrap();
+    let package = Package::open(cursor).unwrap();
+    assert!(package.has_table("_Validation"));
+    assert_eq!(package.summary_info().title(), Some("Installation Database"));
+}
+
+#[test]
+fn rename_stream() {
+    let data: Vec<u8> =
+        (0..200_000).map(|index: u32| (index % 251) as u8).collect();
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Name").primary_key().id_string(72),
+        Column::build("Data").binary(),
+    ];
+    package.create_table("Binary", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Binary")
+                .row(vec![Value::from("OldIcon"), Value::from("OldIcon")]),
+        )
+        .unwrap();
+    package.write_stream("Binary.OldIcon").unwrap().write_all(&data).unwrap();
+    package.write_stream("Binary.Other").unwrap().write_all(b"x").unwrap();
+
+    package.rename_stream("Binary.OldIcon", "Binary.NewIcon").unwrap();
+    assert!(!package.has_stream("Binary.OldIcon"));
+    assert_error!(
+        package.rename_stream("Binary.OldIcon", "Binary.Icon"),
+        ErrorKind::NotFound,
+        "Stream \"Binary.OldIcon\" does not exist"
+    );
+    assert_error!(
+        package.rename_stream("Binary.NewIcon", "Binary.Other"),
+        ErrorKind::AlreadyExists,
+        "Stream \"Binary.Other\" already exists"
+    );
+    assert_error!(
+        package.rename_stream("Binary.NewIcon", "_StringPool"),
+        ErrorKind::InvalidInput,
+        "Cannot rename internal stream \"_StringPool\" of the package"
+    );
+    assert_error!(
+        package.rename_stream("_Tables", "Binary.Tables"),
+        ErrorKind::InvalidInput,
+        "Cannot rename internal stream \"_Tables\" of the package"
+    );
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let mut stream_names: Vec<String> = package.streams().collect();
+    stream_names.sort();
+    assert_eq!(stream_names, vec!["Binary.NewIcon", "Binary.Other"]);
+    let mut renamed = Vec::new();
+    package
+        .read_stream("Binary.NewIcon")
+        .unwrap()
+        .read_to_end(&mut renamed)
+        .unwrap();
+    assert_eq!(renamed, data);
+    assert_eq!(package.count_rows("Binary", None).unwrap(), 1);
+}
+
+#[test]
+fn list_streams_with_lengths() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Name").primary_key().id_string(72),
+        Column::build("Data").nullable().binary(),
+    ];
+    package.create_table("Binary", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Binary")
+                .row(vec![Value::from("Logo"), Value::from("Binary.Logo")]),
+        )
+        .unwrap();
+    package
+        .write_stream("Binary.Logo")
+        .unwrap()
+        .write_all(&[7; 1500])
+        .unwrap();
+    package.write_stream("Icon.App").unwrap().write_all(&[9; 42]).unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let package = Package::open(cursor).unwrap();
+    let mut names: Vec<String> = package.streams().collect();
+    names.sort();
+    // The streams holding the tables (including the string pool) and the
+    // summary information are not included.
+    assert_eq!(names, vec!["Binary.Logo".to_string(), "Icon.App".to_string()]);
+    assert_eq!(package.stream_len("Binary.Logo").unwrap(), 1500);
+    assert_eq!(package.stream_len("Icon.App").unwrap(), 42);
+}
+
+#[test]
+fn read_large_stream_in_chunks() {
+    let data: Vec<u8> =
+        (0..(1 << 20)).map(|index: u32| (index % 251) as u8).collect();
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.write_stream("Big").unwrap().write_all(&data).unwrap();
+    package.write_stream("Empty").unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert!(package.read_stream("Empty").unwrap().is_empty());
+    let mut reader = package.read_stream("Big").unwrap();
+    assert_eq!(reader.len(), 1 << 20);
+    assert!(!reader.is_empty());
+    let mut chunk = vec![0u8; 64 * 1024];
+    for expected in data.chunks(chunk.len()) {
+        reader.read_exact(&mut chunk).unwrap();
+        assert_eq!(chunk.as_slice(), expected);
+    }
+    assert_eq!(reader.read(&mut chunk).unwrap(), 0);
+
+    // Seeking doesn't require reading the data in between.
+    reader.seek(SeekFrom::Start(1000)).unwrap();
+    let mut byte = [0u8];
+    reader.read_exact(&mut byte).unwrap();
+    assert_eq!(byte[0], data[1000]);
+}
+
+#[test]
+fn copy_stream_between_packages() {
+    let data: Vec<u8> =
+        (0..100_000).map(|index: u32| (index % 253) as u8).collect();
+    let cursor = Cursor::new(Vec::new());
+    let mut source = Package::create(PackageType::Installer, cursor).unwrap();
+    source.write_stream("Icon.Data").unwrap().write_all(&data).unwrap();
+    let cursor = Cursor::new(Vec::new());
+    let mut dest = Package::create(PackageType::Installer, cursor).unwrap();
+
+    dest.copy_stream_from(&mut source, "Icon.Data").unwrap();
+    assert_error!(
+        dest.copy_stream_from(&mut source, "Icon.Data"),
+        ErrorKind::AlreadyExists,
+        "Stream \"Icon.Data\" already exists"
+    );
+    assert_error!(
+        dest.copy_stream_from(&mut source, "Missing"),
+        ErrorKind::NotFound,
+        "Stream \"Missing\" does not exist"
+    );
+
+    let cursor = dest.into_inner().unwrap();
+    let mut dest = Package::open(cursor).unwrap();
+    let mut copied = Vec::new();
+    dest.read_stream("Icon.Data").unwrap().read_to_end(&mut copied).unwrap();
+    assert_eq!(
+        Package::<Cursor<Vec<u8>>>::compute_file_hash(&copied),
+        Package::<Cursor<Vec<u8>>>::compute_file_hash(&data)
+    );
+    assert_eq!(copied, data);
+}
+
+/// Builds a minimal, uncompressed cabinet file holding the given files.
+fn make_cabinet(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let files_offset = 36 + 8;
+    let names_len: usize = files.iter().map(|(name, _)| name.len() + 1).sum();
+    let data_offset = files_offset + 16 * files.len() + names_len;
+    let data: Vec<u8> =
+        files.iter().flat_map(|(_, data)| data.iter().copied()).collect();
+    let total_len = data_offset + 8 + data.len();
+    let mut cab = Vec::new();
+    cab.extend_from_slice(b"MSCF");
+    cab.extend_from_slice(&0u32.to_le_bytes());
+    cab.extend_from_slice(&(total_len as u32).to_le_bytes());
+    cab.extend_from_slice(&0u32.to_le_bytes());
+    cab.extend_from_slice(&(files_offset as u32).to_le_bytes());
+    cab.extend_from_slice(&0u32.to_le_bytes());
+    cab.extend_from_slice(&[3, 1]);
+    cab.extend_from_slice(&1u16.to_le_bytes());
+    cab.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    cab.extend_from_slice(&[0; 6]);
+    // CFFOLDER:
+    cab.extend_from_slice(&(data_offset as u32).to_le_bytes());
+    cab.extend_from_slice(&1u16.to_le_bytes());
+    cab.extend_from_slice(&0u16.to_le_bytes());
+    // CFFILE entries:
+    let mut offset = 0u32;
+    for (name, data) in files {
+        cab.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        cab.extend_from_slice(&offset.to_le_bytes());
+        cab.extend_from_slice(&[0; 8]);
+        cab.extend_from_slice(name.as_bytes());
+        cab.push(0);
+        offset += data.len() as u32;
+    }
+    // CFDATA:
+    cab.extend_from_slice(&0u32.to_le_bytes());
+    cab.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    cab.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    cab.extend_from_slice(&data);
+    assert_eq!(cab.len(), total_len);
+    cab
+}
+
+#[test]
+fn list_embedded_cabinet_files() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("DiskId").primary_key().range(1, 32767).int16(),
+        Column::build("LastSequence").range(0, 32767).int16(),
+        Column::build("Cabinet").nullable().string(255),
+    ];
+    package.create_table("Media", columns).unwrap();
+    package
+        .insert_rows(Insert::into("Media").row(vec![
+            Value::Int(1),
+            Value::Int(2),
+            Value::from("#Data1"),
+        ]))
+        .unwrap();
+    let cab = make_cabinet(&[
+        ("readme.txt", b"Hello, world!\n"),
+        ("app.exe", &[0u8; 300]),
+    ]);
+    package.write_stream("Data1").unwrap().write_all(&cab).unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    for name in ["#Data1", "Data1"] {
+        let cabinet = package.open_embedded_cabinet(name).unwrap();
+        let files: Vec<(String, u32)> = cabinet
+            .files()
+            .map(|file| (file.name().to_string(), file.size()))
+            .collect();
+        assert_eq!(
+            files,
+            vec![("readme.txt".to_string(), 14), ("app.exe".to_string(), 300)]
+        );
+    }
+    assert_error!(
+        package.open_embedded_cabinet("#Data2"),
+        ErrorKind::NotFound,
+        "No Media table row has an embedded cabinet named \"Data2\""
+    );
+}
+
+fn make_package_with_two_streams() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+    let logo: Vec<u8> =
+        (0..5000).map(|index: u32| (index % 241) as u8).collect();
+    let icon = vec![9u8; 42];
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.write_stream("Binary.Logo").unwrap().write_all(&logo).unwrap();
+    package.write_stream("Icon.App").unwrap().write_all(&icon).unwrap();
+    let cursor = package.into_inner().unwrap();
+    (cursor.into_inner(), logo, icon)
+}
+
+#[test]
+fn extract_all_streams() {
+    let (bytes, logo, icon) = make_package_with_two_streams();
+    let mut package = Package::open(Cursor::new(bytes)).unwrap();
+    let dir = testutil::scratch_dir("extract_all_streams");
+    let paths = package.extract_all_streams(&dir).unwrap();
+    // Only the binary streams are extracted, not the summary information or
+    // the tables.
+    assert_eq!(paths, vec![dir.join("Binary.Logo"), dir.join("Icon.App")]);
+    assert_eq!(fs::read(&paths[0]).unwrap(), logo);
+    assert_eq!(fs::read(&paths[1]).unwrap(), icon);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn extract_stream_with_unsafe_file_name() {
+    let dir = testutil::scratch_dir("extract_stream_with_unsafe_file_name");
+    for name in ["..", "a<b", "what?", "a|b", "*"] {
+        let cursor = Cursor::new(Vec::new());
+        let mut package =
+            Package::create(PackageType::Installer, cursor).unwrap();
+        package.write_stream("Icon.App").unwrap().write_all(&[9; 42]).unwrap();
+        package.write_stream(name).unwrap().write_all(&[1; 8]).unwrap();
+        assert_error!(
+            package.extract_all_streams(&dir),
+            ErrorKind::InvalidData,
+            format!("Stream {name:?} cannot be extracted to a file")
+        );
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    }
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn extract_all_streams_in_parallel() {
+    let (bytes, logo, icon) = make_package_with_two_streams();
+    let package = Package::open(Cursor::new(bytes.clone())).unwrap();
+    let dir = testutil::scratch_dir("extract_all_streams_in_parallel");
+    let paths = package
+        .extract_all_streams_parallel(&dir, || Ok(Cursor::new(bytes.clone())))
+        .unwrap();
+    assert_eq!(paths, vec![dir.join("Binary.Logo"), dir.join("Icon.App")]);
+    assert_eq!(fs::read(&paths[0]).unwrap(), logo);
+    assert_eq!(fs::read(&paths[1]).unwrap(), icon);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
 // ========================================================================= //
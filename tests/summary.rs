@@ -1,10 +1,50 @@
-use msi::{CodePage, Package, PackageType};
-use std::io::{Cursor, Write};
+#[macro_use]
+mod testutil;
+
+use msi::{
+    Architecture, CodePage, Language, OpenOptions, Package, PackageType,
+    PropertyValue, Security, SummaryInfo, Timestamp,
+    DEFAULT_CREATING_APPLICATION,
+};
+use std::io::{Cursor, ErrorKind, Write};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use uuid::Uuid;
 
 // ========================================================================= //
 
+#[test]
+fn default_creating_application() {
+    let cursor = Cursor::new(Vec::new());
+    let package = Package::create(PackageType::Installer, cursor).unwrap();
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let creating_app = package.summary_info().creating_application();
+    assert_eq!(creating_app, Some(DEFAULT_CREATING_APPLICATION));
+    assert!(DEFAULT_CREATING_APPLICATION.starts_with("rust-msi "));
+
+    package.summary_info_mut().set_creating_application("msitools 0.106");
+    let cursor = package.into_inner().unwrap();
+    let package = Package::open(cursor).unwrap();
+    assert_eq!(
+        package.summary_info().creating_application(),
+        Some("msitools 0.106")
+    );
+
+    let summary_info =
+        SummaryInfo::build().creating_application("WiX Toolset").finish();
+    let cursor = Cursor::new(Vec::new());
+    let package = Package::create_with_summary_info(
+        PackageType::Installer,
+        summary_info,
+        cursor,
+    )
+    .unwrap();
+    assert_eq!(
+        package.summary_info().creating_application(),
+        Some("WiX Toolset")
+    );
+}
+
 #[test]
 fn set_summary_info_properties() {
     let sat_2017_mar_18_at_18_46_36_gmt =
@@ -40,6 +80,30 @@ fn set_summary_info_properties() {
     assert_eq!(summary_info.uuid(), Some(uuid));
 }
 
+#[test]
+fn set_last_printed_and_character_count() {
+    let last_printed = UNIX_EPOCH + Duration::from_secs(1489862796);
+
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert_eq!(package.summary_info().last_printed(), None);
+    assert_eq!(package.summary_info().character_count(), None);
+    package.summary_info_mut().set_last_printed(last_printed);
+    package.summary_info_mut().set_character_count(12345);
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(package.summary_info().last_printed(), Some(last_printed));
+    assert_eq!(package.summary_info().character_count(), Some(12345));
+    package.summary_info_mut().clear_last_printed();
+    package.summary_info_mut().clear_character_count();
+
+    let cursor = package.into_inner().unwrap();
+    let package = Package::open(cursor).unwrap();
+    assert_eq!(package.summary_info().last_printed(), None);
+    assert_eq!(package.summary_info().character_count(), None);
+}
+
 #[test]
 fn set_summary_info_codepage() {
     let cursor = Cursor::new(Vec::new());
@@ -72,6 +136,33 @@ fn dropping_package_persists_changes() {
     assert_eq!(package.summary_info().comments(), Some("Hello, world!"));
 }
 
+#[test]
+fn timestamps_round_trip_exactly() {
+    // Timestamps have 100-nanosecond precision.
+    let after_epoch = UNIX_EPOCH + Duration::new(1489862796, 123_456_700);
+    let before_epoch = UNIX_EPOCH - Duration::new(14182980, 987_654_300);
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.summary_info_mut().set_creation_time(after_epoch);
+    package.summary_info_mut().set_last_save_time(before_epoch);
+    package.summary_info_mut().set_last_printed(UNIX_EPOCH);
+
+    let mut cursor = package.into_inner().unwrap();
+    // The creation time should appear in the file as a VT_FILETIME value: the
+    // number of 100ns ticks since 1601, as a little-endian u64.
+    let mut filetime = vec![0x40, 0, 0, 0];
+    filetime.extend_from_slice(&131343363961234567u64.to_le_bytes());
+    let bytes = cursor.get_ref();
+    assert!(bytes.windows(filetime.len()).any(|w| w == &filetime[..]));
+
+    cursor.set_position(0);
+    let package = Package::open(cursor).unwrap();
+    let summary_info = package.summary_info();
+    assert_eq!(summary_info.creation_time(), Some(after_epoch));
+    assert_eq!(summary_info.last_saved_time(), Some(before_epoch));
+    assert_eq!(summary_info.last_printed(), Some(UNIX_EPOCH));
+}
+
 #[test]
 fn set_creation_time_to_now() {
     let timestamp = SystemTime::now();
@@ -86,4 +177,190 @@ fn set_creation_time_to_now() {
     assert!(package.summary_info().creation_time().unwrap() > timestamp);
 }
 
+#[test]
+fn package_type_matches_summary_template() {
+    let expected = [
+        (PackageType::Installer, ";"),
+        (PackageType::Transform, ";"),
+        (PackageType::Patch, ""),
+    ];
+    for (package_type, template) in expected {
+        let cursor = Cursor::new(Vec::new());
+        let package = Package::create(package_type, cursor).unwrap();
+        assert_eq!(package.summary_info().template(), Some(template));
+        let cursor = package.into_inner().unwrap();
+        let package = Package::open(cursor).unwrap();
+        assert_eq!(package.package_type(), package_type);
+        assert_eq!(package.summary_info().template(), Some(template));
+        package.check_summary_template().unwrap();
+    }
+
+    // A patch's template lists the products it applies to.
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Patch, cursor).unwrap();
+    let product_code =
+        Uuid::parse_str("3e2a2c26-6a8c-4e55-8f53-4a7ad9c4b3d1").unwrap();
+    package.set_patch_target_product_codes(&[product_code]).unwrap();
+    assert_eq!(
+        package.summary_info().template(),
+        Some("{3E2A2C26-6A8C-4E55-8F53-4A7AD9C4B3D1}")
+    );
+    package.check_summary_template().unwrap();
+
+    // A template that disagrees with the CLSID is an error when opening the
+    // package, unless that check is turned off.
+    let cursor = package.into_inner().unwrap();
+    let mut comp = cfb::CompoundFile::open(cursor).unwrap();
+    comp.set_storage_clsid("/", PackageType::Installer.clsid()).unwrap();
+    let mut cursor = comp.into_inner();
+    let message = "Summary information template \
+                   \"{3E2A2C26-6A8C-4E55-8F53-4A7AD9C4B3D1}\" doesn't \
+                   match Installer package";
+    assert_error!(Package::open(&mut cursor), ErrorKind::InvalidData, message);
+    let options = OpenOptions::new().check_summary_template(false);
+    let package = Package::open_with_options(cursor, options).unwrap();
+    assert_eq!(package.package_type(), PackageType::Installer);
+    assert_error!(
+        package.check_summary_template(),
+        ErrorKind::InvalidData,
+        message
+    );
+}
+
+#[test]
+fn create_package_with_built_summary_info() {
+    let languages = [Language::from_tag("en-US"), Language::from_tag("fr-FR")];
+    let summary_info = SummaryInfo::build()
+        .languages(&languages)
+        .arch(Architecture::Intel)
+        .codepage(CodePage::Windows1252)
+        .author("Jane Doe")
+        .subject("My Great App")
+        .security(Security::ReadOnlyRecommended)
+        .finish();
+    let cursor = Cursor::new(Vec::new());
+    let package = Package::create_with_summary_info(
+        PackageType::Installer,
+        summary_info,
+        cursor,
+    )
+    .unwrap();
+    assert_eq!(package.database_codepage(), CodePage::Windows1252);
+
+    let cursor = package.into_inner().unwrap();
+    let package = Package::open(cursor).unwrap();
+    let summary_info = package.summary_info();
+    assert_eq!(summary_info.arch(), Some("Intel"));
+    assert_eq!(summary_info.languages(), languages);
+    assert_eq!(summary_info.codepage(), CodePage::Windows1252);
+    assert_eq!(summary_info.author(), Some("Jane Doe"));
+    assert_eq!(summary_info.subject(), Some("My Great App"));
+    assert_eq!(summary_info.title(), Some("Installation Database"));
+    assert_eq!(summary_info.security(), Some(Security::ReadOnlyRecommended));
+}
+
+#[test]
+fn create_package_with_mismatched_summary_title() {
+    let summary_info = SummaryInfo::build().title("Patch").finish();
+    let cursor = Cursor::new(Vec::new());
+    assert_error!(
+        Package::create_with_summary_info(
+            PackageType::Installer,
+            summary_info,
+            cursor,
+        ),
+        ErrorKind::InvalidInput,
+        "Cannot create Installer package with summary information title \
+         \"Patch\""
+    );
+}
+
+#[test]
+fn root_storage_has_standard_clsid() {
+    let expected = [
+        (PackageType::Installer, "000c1084-0000-0000-c000-000000000046"),
+        (PackageType::Patch, "000c1086-0000-0000-c000-000000000046"),
+        (PackageType::Transform, "000c1082-0000-0000-c000-000000000046"),
+    ];
+    for (package_type, clsid) in expected {
+        assert_eq!(package_type.clsid().hyphenated().to_string(), clsid);
+        let cursor = Cursor::new(Vec::new());
+        let package = Package::create(package_type, cursor).unwrap();
+        assert_eq!(package.root_clsid(), package_type.clsid());
+        let cursor = package.into_inner().unwrap();
+        let comp = cfb::CompoundFile::open(cursor).unwrap();
+        assert_eq!(comp.root_entry().clsid().hyphenated().to_string(), clsid);
+        let package = Package::open(comp.into_inner()).unwrap();
+        assert_eq!(package.root_clsid(), package_type.clsid());
+    }
+}
+
+#[test]
+fn set_root_clsid() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    package.set_root_clsid(PackageType::Patch.clsid()).unwrap();
+    package.summary_info_mut().set_title("Patch");
+    package.set_patch_target_product_codes(&[Uuid::nil()]).unwrap();
+    assert_eq!(package.package_type(), PackageType::Patch);
+    assert_eq!(package.root_clsid(), PackageType::Patch.clsid());
+    let cursor = package.into_inner().unwrap();
+    let comp = cfb::CompoundFile::open(cursor).unwrap();
+    assert_eq!(*comp.root_entry().clsid(), PackageType::Patch.clsid());
+    let mut package = Package::open(comp.into_inner()).unwrap();
+    assert_eq!(package.package_type(), PackageType::Patch);
+    assert_eq!(package.root_clsid(), PackageType::Patch.clsid());
+
+    let clsid =
+        Uuid::parse_str("01234567-89ab-cdef-0123-456789abcdef").unwrap();
+    assert_error!(
+        package.set_root_clsid(clsid),
+        ErrorKind::InvalidInput,
+        "Unrecognized package CLSID (01234567-89ab-cdef-0123-456789abcdef)"
+    );
+    assert_eq!(package.package_type(), PackageType::Patch);
+}
+
+#[test]
+fn set_raw_summary_info_properties() {
+    const PID_VENDOR_NOTE: u32 = 0x1000;
+    const PID_VENDOR_BUILD: u32 = 0x1001;
+    let build_time = UNIX_EPOCH + Duration::from_secs(1489862796);
+
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    {
+        let summary_info = package.summary_info_mut();
+        summary_info.set_author("Jane Doe");
+        summary_info.set_raw_property(
+            PID_VENDOR_NOTE,
+            PropertyValue::LpStr("Built by the nightly job".to_string()),
+        );
+        summary_info.set_raw_property(
+            PID_VENDOR_BUILD,
+            PropertyValue::FileTime(Timestamp::from_system_time(build_time)),
+        );
+    }
+
+    let cursor = package.into_inner().unwrap();
+    let package = Package::open(cursor).unwrap();
+    let summary_info = package.summary_info();
+    assert_eq!(summary_info.author(), Some("Jane Doe"));
+    assert_eq!(
+        summary_info.raw_property(4),
+        Some(&PropertyValue::LpStr("Jane Doe".to_string()))
+    );
+    assert_eq!(
+        summary_info.raw_property(PID_VENDOR_NOTE),
+        Some(&PropertyValue::LpStr("Built by the nightly job".to_string()))
+    );
+    match summary_info.raw_property(PID_VENDOR_BUILD) {
+        Some(&PropertyValue::FileTime(timestamp)) => {
+            assert_eq!(timestamp.to_system_time(), build_time);
+        }
+        other => panic!("Unexpected value: {other:?}"),
+    }
+    assert_eq!(summary_info.raw_property(0x1002), None);
+}
+
 // ========================================================================= //
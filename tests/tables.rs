@@ -2,7 +2,8 @@
 mod testutil;
 
 use msi::{
-    Column, ColumnType, Expr, Insert, Package, PackageType, Select, Value,
+    Category, Column, ColumnType, Delete, Expr, Insert, Package, PackageType,
+    Row, Select, Update, Value,
 };
 use std::io::{Cursor, ErrorKind};
 
@@ -94,6 +95,78 @@ fn create_table_with_duplicate_column_names() {
     assert!(!package.has_table("FooBar"));
 }
 
+#[test]
+fn create_table_with_invalid_default() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Foo").primary_key().int32(),
+        Column::build("Bar").default(Value::Null).int16(),
+    ];
+    assert_error!(
+        package.create_table("FooBar", columns),
+        ErrorKind::InvalidInput,
+        "NULL is not a valid default value for column \"Bar\""
+    );
+    let columns = vec![
+        Column::build("Foo").primary_key().int32(),
+        Column::build("Bar").default(Value::from("1abc")).id_string(10),
+    ];
+    assert_error!(
+        package.create_table("FooBar", columns),
+        ErrorKind::InvalidInput,
+        "\"1abc\" is not a valid default value for column \"Bar\""
+    );
+    assert!(!package.has_table("FooBar"));
+}
+
+#[test]
+fn insert_row_with_defaults() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Value").nullable().string(0),
+        Column::build("Count").default(Value::Int(1)).int16(),
+        Column::build("Comment").nullable().default(Value::Null).string(0),
+    ];
+    package.create_table("Things", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Things")
+                .row(vec![Value::from("A"), Value::from("a")])
+                .row(vec![Value::from("B"), Value::Null, Value::Int(5)]),
+        )
+        .unwrap();
+    assert_error!(
+        package
+            .insert_rows(Insert::into("Things").row(vec![Value::from("C")])),
+        ErrorKind::InvalidInput,
+        "Table \"Things\" has 4 columns, but the row at index 0 has 1 \
+         values"
+    );
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let rows: Vec<Vec<Value>> = package
+        .select_rows(Select::table("Things"))
+        .unwrap()
+        .map(|row| (0..row.len()).map(|index| row[index].clone()).collect())
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            vec![
+                Value::from("A"),
+                Value::from("a"),
+                Value::Int(1),
+                Value::Null
+            ],
+            vec![Value::from("B"), Value::Null, Value::Int(5), Value::Null],
+        ]
+    );
+}
+
 #[test]
 fn create_valid_table() {
     let cursor = Cursor::new(Vec::new());
@@ -127,6 +200,37 @@ fn create_valid_table() {
     assert!(column.is_nullable());
 }
 
+#[test]
+fn column_metadata_survives_reopen() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Feature").primary_key().id_string(38),
+        Column::build("Title").nullable().localizable().formatted_string(64),
+        Column::build("Level").int16(),
+    ];
+    package.create_table("Feature", columns).unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let package = Package::open(cursor).unwrap();
+    let table = package.get_table("Feature").unwrap();
+    let column = table.get_column("Title").unwrap();
+    assert!(column.is_localizable());
+    assert!(column.is_nullable());
+    assert_eq!(column.category(), Some(Category::Formatted));
+    assert_eq!(column.max_len(), Some(64));
+
+    let column = table.get_column("Feature").unwrap();
+    assert!(!column.is_localizable());
+    assert!(!column.is_nullable());
+    assert_eq!(column.category(), Some(Category::Identifier));
+    assert_eq!(column.max_len(), Some(38));
+
+    let column = table.get_column("Level").unwrap();
+    assert_eq!(column.category(), None);
+    assert_eq!(column.max_len(), None);
+}
+
 #[test]
 fn drop_table_with_invalid_name() {
     let cursor = Cursor::new(Vec::new());
@@ -257,4 +361,402 @@ fn drop_table_with_rows() {
     assert_eq!(package.select_rows(query).unwrap().len(), 0);
 }
 
+#[test]
+fn add_column_to_table_with_rows() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec![Value::from("ProductName"), Value::from("Foo")])
+                .row(vec![Value::from("ProductVersion"), Value::from("1.0")]),
+        )
+        .unwrap();
+    assert_error!(
+        package.add_column("Property", Column::build("Count").int16()),
+        ErrorKind::InvalidInput,
+        "Cannot add non-nullable column \"Count\" with no default value to \
+         non-empty table \"Property\""
+    );
+    assert_error!(
+        package.add_column("Property", Column::build("Value").int16()),
+        ErrorKind::AlreadyExists,
+        "Table \"Property\" already has a column named \"Value\""
+    );
+    assert_error!(
+        package.add_column(
+            "Property",
+            Column::build("Extra").primary_key().int16()
+        ),
+        ErrorKind::InvalidInput,
+        "Cannot add primary key column \"Extra\" to existing table \
+         \"Property\""
+    );
+    // A column whose _Validation row can't be stored is rejected up front.
+    let values: Vec<String> = (0..100).map(|n| format!("v{n}")).collect();
+    let values: Vec<&str> = values.iter().map(String::as_str).collect();
+    let column =
+        Column::build("Choice").nullable().enum_values(&values).string(8);
+    assert_error!(
+        package.add_column("Property", column),
+        ErrorKind::InvalidInput,
+        format!(
+            "{:?} is not a valid value for column \"Set\" of table \
+             \"_Validation\"",
+            values.join(";")
+        )
+    );
+    // If the _Validation row conflicts with a stale one, the _Columns row
+    // that was already added is removed again.
+    let mut stale_row = vec![Value::Null; 10];
+    stale_row[0] = Value::from("Property");
+    stale_row[1] = Value::from("Comment");
+    stale_row[2] = Value::from("Y");
+    package.insert_rows(Insert::into("_Validation").row(stale_row)).unwrap();
+    let column = Column::build("Comment").nullable().text_string(0);
+    assert_error!(
+        package.add_column("Property", column),
+        ErrorKind::AlreadyExists,
+        "Table \"_Validation\" already contains a row with key \
+         [Str(\"Property\"), Str(\"Comment\")]"
+    );
+    let query = Expr::col("Table").eq(Expr::string("Property"));
+    assert_eq!(package.count_rows("_Columns", Some(query)).unwrap(), 2);
+    let query = Delete::from("_Validation")
+        .with(Expr::col("Column").eq(Expr::string("Comment")));
+    package.delete_rows(query).unwrap();
+
+    let column = Column::build("Comment").nullable().text_string(0);
+    package.add_column("Property", column).unwrap();
+    let column = Column::build("Count").default(Value::Int(3)).int16();
+    package.add_column("Property", column).unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let table = package.get_table("Property").unwrap();
+    let names: Vec<&str> =
+        table.columns().iter().map(|column| column.name()).collect();
+    assert_eq!(names, vec!["Property", "Value", "Comment", "Count"]);
+    assert!(table.get_column("Comment").unwrap().is_nullable());
+    let rows: Vec<Vec<Value>> = package
+        .select_rows(Select::table("Property"))
+        .unwrap()
+        .map(|row| (0..row.len()).map(|index| row[index].clone()).collect())
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            vec![
+                Value::from("ProductName"),
+                Value::from("Foo"),
+                Value::Null,
+                Value::Int(3)
+            ],
+            vec![
+                Value::from("ProductVersion"),
+                Value::from("1.0"),
+                Value::Null,
+                Value::Int(3)
+            ],
+        ]
+    );
+    let query = Select::table("_Validation")
+        .columns(&["Column", "Nullable"])
+        .with(Expr::col("Table").eq(Expr::string("Property")));
+    assert_eq!(package.select_rows(query).unwrap().len(), 4);
+}
+
+#[test]
+fn drop_column_from_table_with_rows() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Note").nullable().text_string(0),
+        Column::build("Value").text_string(0),
+        Column::build("Count").nullable().int32(),
+    ];
+    package.create_table("Things", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Things")
+                .row(vec![
+                    Value::from("A"),
+                    Value::from("first"),
+                    Value::from("alpha"),
+                    Value::Int(1),
+                ])
+                .row(vec![
+                    Value::from("B"),
+                    Value::Null,
+                    Value::from("beta"),
+                    Value::Int(2),
+                ]),
+        )
+        .unwrap();
+    assert_error!(
+        package.drop_column("Things", "Key"),
+        ErrorKind::InvalidInput,
+        "Cannot drop primary key column \"Key\" of table \"Things\""
+    );
+    assert_error!(
+        package.drop_column("Things", "Missing"),
+        ErrorKind::NotFound,
+        "Table \"Things\" has no column named \"Missing\""
+    );
+    package.drop_column("Things", "Note").unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let table = package.get_table("Things").unwrap();
+    let names: Vec<&str> =
+        table.columns().iter().map(|column| column.name()).collect();
+    assert_eq!(names, vec!["Key", "Value", "Count"]);
+    let rows: Vec<Vec<Value>> = package
+        .select_rows(Select::table("Things"))
+        .unwrap()
+        .map(|row| (0..row.len()).map(|index| row[index].clone()).collect())
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            vec![Value::from("A"), Value::from("alpha"), Value::Int(1)],
+            vec![Value::from("B"), Value::from("beta"), Value::Int(2)],
+        ]
+    );
+    let query = Select::table("_Validation")
+        .with(Expr::col("Table").eq(Expr::string("Things")));
+    assert_eq!(package.select_rows(query).unwrap().len(), 3);
+}
+
+#[test]
+fn table_and_column_names_are_case_insensitive() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Note").nullable().text_string(0),
+        Column::build("Value").nullable().int32(),
+    ];
+    package.create_table("Things", columns).unwrap();
+    assert!(package.has_table("things"));
+    let table = package.get_table("THINGS").unwrap();
+    assert_eq!(table.name(), "Things");
+    assert!(table.has_column("value"));
+    assert_eq!(table.get_column("NOTE").unwrap().name(), "Note");
+
+    package
+        .insert_rows(
+            Insert::into("things")
+                .row(vec![Value::from("A"), Value::Null, Value::Int(1)])
+                .row(vec![Value::from("B"), Value::Null, Value::Int(2)]),
+        )
+        .unwrap();
+    package
+        .update_rows(
+            Update::table("THINGS")
+                .set("value", Value::Int(20))
+                .with(Expr::col("key").eq(Expr::string("B"))),
+        )
+        .unwrap();
+    let query =
+        Select::table("tHiNgS").with(Expr::col("VALUE").gt(Expr::integer(10)));
+    let rows: Vec<Row> = package.select_rows(query).unwrap().collect();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["KEY"], Value::from("B"));
+    assert_eq!(rows[0]["Value"], Value::Int(20));
+
+    package.drop_column("things", "note").unwrap();
+    let query = Select::table("_Validation")
+        .with(Expr::col("Table").eq(Expr::string("Things")));
+    assert_eq!(package.select_rows(query).unwrap().len(), 2);
+    package.drop_table("THINGS").unwrap();
+    assert!(!package.has_table("Things"));
+    let query = Select::table("_Columns")
+        .with(Expr::col("Table").eq(Expr::string("Things")));
+    assert_eq!(package.select_rows(query).unwrap().len(), 0);
+}
+
+#[test]
+fn create_table_with_name_differing_only_in_case() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![Column::build("Key").primary_key().int16()];
+    package.create_table("Foo", columns).unwrap();
+    let columns = vec![Column::build("Id").primary_key().int16()];
+    assert_error!(
+        package.create_table("foo", columns.clone()),
+        ErrorKind::AlreadyExists,
+        "Table \"foo\" already exists (as \"Foo\")"
+    );
+    assert_error!(
+        package.create_table("Foo", columns),
+        ErrorKind::AlreadyExists,
+        "Table \"Foo\" already exists"
+    );
+    assert_eq!(package.get_table("foo").unwrap().columns()[0].name(), "Key");
+}
+
+#[test]
+fn primary_key_columns() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("UpgradeCode").primary_key().string(38),
+        Column::build("VersionMin").primary_key().nullable().string(20),
+        Column::build("VersionMax").primary_key().nullable().string(20),
+        Column::build("Language").primary_key().nullable().string(255),
+        Column::build("Attributes").primary_key().int32(),
+        Column::build("Remove").nullable().string(255),
+        Column::build("ActionProperty").string(72),
+    ];
+    package.create_table("Upgrade", columns).unwrap();
+    let query = Insert::into("Upgrade").row(vec![
+        Value::from("{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}"),
+        Value::from("1.0.0"),
+        Value::Null,
+        Value::from("1033"),
+        Value::from(256),
+        Value::Null,
+        Value::from("OLDERVERSIONFOUND"),
+    ]);
+    package.insert_rows(query).unwrap();
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let row = package.select_rows(Select::table("Upgrade")).unwrap().next();
+    assert_eq!(
+        row.unwrap().primary_key(),
+        vec![
+            Value::from("{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}"),
+            Value::from("1.0.0"),
+            Value::Null,
+            Value::from("1033"),
+            Value::from(256),
+        ]
+    );
+    let table = package.get_table("Upgrade").unwrap();
+    let key_names: Vec<&str> = table
+        .primary_key_columns()
+        .into_iter()
+        .map(|column| column.name())
+        .collect();
+    assert_eq!(
+        key_names,
+        vec![
+            "UpgradeCode",
+            "VersionMin",
+            "VersionMax",
+            "Language",
+            "Attributes"
+        ]
+    );
+    assert!(!table.get_column("Remove").unwrap().is_primary_key());
+}
+
+#[test]
+fn enumerate_table_schemas() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    let columns = vec![
+        Column::build("Feature").primary_key().id_string(38),
+        Column::build("Feature_Parent").nullable().id_string(38),
+        Column::build("Title").nullable().localizable().text_string(64),
+        Column::build("Description").nullable().localizable().text_string(255),
+        Column::build("Display").nullable().int16(),
+        Column::build("Level").int16(),
+        Column::build("Directory_").nullable().id_string(72),
+        Column::build("Attributes").int16(),
+    ];
+    package.create_table("Feature", columns).unwrap();
+
+    let names: Vec<String> =
+        package.tables().map(|table| table.name().to_string()).collect();
+    assert_eq!(
+        names,
+        vec!["Feature", "Property", "_Columns", "_Tables", "_Validation"]
+    );
+    let user_tables: Vec<&str> =
+        package.user_tables().map(|table| table.name()).collect();
+    assert_eq!(user_tables, vec!["Feature", "Property"]);
+    assert!(package.get_table("_Tables").unwrap().is_system());
+    assert!(!package.get_table("Property").unwrap().is_system());
+    let feature = package.tables().find(|t| t.name() == "Feature").unwrap();
+    assert_eq!(feature.columns().len(), 8);
+    assert_eq!(feature.columns()[5].name(), "Level");
+    assert!(feature.has_column("Directory_"));
+    assert!(!feature.has_column("Component_"));
+
+    // The order is the same after reopening the package.
+    let cursor = package.into_inner().unwrap();
+    let package = Package::open(cursor).unwrap();
+    let reopened: Vec<String> =
+        package.tables().map(|table| table.name().to_string()).collect();
+    assert_eq!(reopened, names);
+    assert_eq!(package.get_table("Feature").unwrap().columns().len(), 8);
+}
+
+#[test]
+fn clone_schema_into_empty_package() {
+    let cursor = Cursor::new(Vec::new());
+    let mut template =
+        Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").localizable().text_string(0),
+    ];
+    template.create_table("Property", columns).unwrap();
+    let columns = vec![
+        Column::build("File").primary_key().id_string(72),
+        Column::build("Component_").id_string(72),
+        Column::build("FileName").localizable().text_string(255),
+        Column::build("FileSize").range(0, 0x7fff_ffff).int32(),
+        Column::build("Sequence").range(1, 32767).int16(),
+    ];
+    template.create_table("File", columns).unwrap();
+    template.set_product_name("Template").unwrap();
+
+    let cursor = Cursor::new(Vec::new());
+    let mut dest = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").localizable().text_string(0),
+    ];
+    dest.create_table("Property", columns).unwrap();
+    template.clone_schema_into(&mut dest).unwrap();
+
+    let cursor = template.into_inner().unwrap();
+    let mut template = Package::open(cursor).unwrap();
+    let cursor = dest.into_inner().unwrap();
+    let mut dest = Package::open(cursor).unwrap();
+    for table_name in ["_Columns", "_Validation"] {
+        let template_rows: Vec<Row> =
+            template.select_rows(Select::table(table_name)).unwrap().collect();
+        let dest_rows: Vec<Row> =
+            dest.select_rows(Select::table(table_name)).unwrap().collect();
+        assert_eq!(dest_rows.len(), template_rows.len());
+        for (dest_row, template_row) in dest_rows.iter().zip(&template_rows) {
+            for index in 0..template_row.len() {
+                assert_eq!(dest_row[index], template_row[index]);
+            }
+        }
+    }
+    assert_eq!(dest.count_rows("Property", None).unwrap(), 0);
+    let file = dest.get_table("File").unwrap();
+    assert_eq!(
+        file.get_column("Sequence").unwrap().value_range(),
+        Some((1, 32767))
+    );
+}
+
 // ========================================================================= //
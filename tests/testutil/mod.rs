@@ -1,5 +1,11 @@
+use msi::{Column, Insert, Package, PackageType, Value};
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+
 // ========================================================================= //
 
+#[allow(unused_macros)]
 macro_rules! assert_error {
     ($e:expr, $k:expr, $d:expr) => {
         let kind = $k;
@@ -31,3 +37,41 @@ macro_rules! assert_error {
 }
 
 // ========================================================================= //
+
+/// Creates a new installer package with a standard `Property` table holding
+/// the given name/value pairs.
+#[allow(dead_code)]
+pub fn package_with_properties(
+    properties: &[(&str, &str)],
+) -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").localizable().text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    let rows = properties
+        .iter()
+        .map(|&(name, value)| vec![Value::from(name), Value::from(value)])
+        .collect();
+    package.insert_rows(Insert::into("Property").rows(rows)).unwrap();
+    package
+}
+
+// ========================================================================= //
+
+/// Creates an empty scratch directory for a test to write files into.
+#[allow(dead_code)]
+pub fn scratch_dir(test_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "msi-test-{}-{}",
+        test_name,
+        std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// ========================================================================= //
@@ -0,0 +1,253 @@
+#[macro_use]
+mod testutil;
+
+use msi::{
+    Column, Delete, Expr, Insert, Package, PackageType, RowChange, Select,
+    Transform, TransformFlags, Update, Value,
+};
+use std::io::{Cursor, ErrorKind};
+
+// ========================================================================= //
+
+fn make_package(product_name: &str) -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Property").primary_key().id_string(72),
+        Column::build("Value").text_string(0),
+    ];
+    package.create_table("Property", columns).unwrap();
+    let columns = vec![
+        Column::build("Action").primary_key().id_string(72),
+        Column::build("Condition").nullable().text_string(255),
+        Column::build("Sequence").nullable().int16(),
+    ];
+    package.create_table("InstallExecuteSequence", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Property")
+                .row(vec!["ProductCode".into(), "{1234}".into()])
+                .row(vec!["ProductName".into(), product_name.into()])
+                .row(vec!["ProductVersion".into(), "1.0.0".into()])
+                .row(vec!["UpgradeCode".into(), "{5678}".into()]),
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("InstallExecuteSequence")
+                .row(vec!["CostInitialize".into(), Value::Null, 800.into()])
+                .row(vec!["InstallFinalize".into(), Value::Null, 6600.into()]),
+        )
+        .unwrap();
+    package
+}
+
+#[test]
+fn generate_transform_for_changed_property() {
+    let mut base = make_package("Widget");
+    let mut target = make_package("Widget Pro");
+    let transform = Transform::generate(&mut base, &mut target).unwrap();
+    assert_eq!(transform.changed_tables(), vec!["Property"]);
+    assert_eq!(
+        transform.changes("Property"),
+        &[RowChange::Update {
+            key: vec![Value::from("ProductName")],
+            values: vec![("Value".to_string(), Value::from("Widget Pro"))],
+        }]
+    );
+    assert_eq!(transform.changes("PROPERTY"), transform.changes("Property"));
+    assert!(transform.changes("InstallExecuteSequence").is_empty());
+    assert_eq!(transform.summary_info().title(), Some("Transform"));
+
+    let mut output = Vec::new();
+    transform.write(&mut output).unwrap();
+    let comp = cfb::CompoundFile::open(Cursor::new(output)).unwrap();
+    assert_eq!(
+        comp.root_entry().clsid().hyphenated().to_string(),
+        "000c1082-0000-0000-c000-000000000046"
+    );
+    let mut names: Vec<String> = comp
+        .read_root_storage()
+        .map(|entry| entry.name().to_string())
+        .filter(|name| !name.starts_with('\u{5}'))
+        .collect();
+    names.sort();
+    // Only the Property table has changes, so it's the only table stream
+    // besides the string pool.
+    assert_eq!(names.len(), 3);
+}
+
+#[test]
+fn generate_transform_for_sequence_edits() {
+    let mut base = make_package("Widget");
+    let mut target = make_package("Widget");
+    target
+        .update_rows(
+            Update::table("InstallExecuteSequence")
+                .set("Sequence", Value::Int(6500))
+                .with(Expr::col("Action").eq(Expr::string("InstallFinalize"))),
+        )
+        .unwrap();
+    target
+        .insert_rows(Insert::into("InstallExecuteSequence").row(vec![
+            "FileCost".into(),
+            Value::Null,
+            900.into(),
+        ]))
+        .unwrap();
+    target
+        .delete_rows(
+            Delete::from("InstallExecuteSequence")
+                .with(Expr::col("Action").eq(Expr::string("CostInitialize"))),
+        )
+        .unwrap();
+    let transform = Transform::generate(&mut base, &mut target).unwrap();
+    assert_eq!(transform.changed_tables(), vec!["InstallExecuteSequence"]);
+    assert_eq!(
+        transform.changes("InstallExecuteSequence"),
+        &[
+            RowChange::Delete(vec![Value::from("CostInitialize")]),
+            RowChange::Insert(vec![
+                Value::from("FileCost"),
+                Value::Null,
+                Value::Int(900)
+            ]),
+            RowChange::Update {
+                key: vec![Value::from("InstallFinalize")],
+                values: vec![("Sequence".to_string(), Value::Int(6500))],
+            },
+        ]
+    );
+}
+
+fn property_value(
+    package: &mut Package<Cursor<Vec<u8>>>,
+    name: &str,
+) -> Option<Value> {
+    let row = package.get_row("Property", &[Value::from(name)]).unwrap();
+    row.map(|row| row["Value"].clone())
+}
+
+fn sequence_actions(package: &mut Package<Cursor<Vec<u8>>>) -> Vec<String> {
+    package
+        .select_rows(Select::table("InstallExecuteSequence"))
+        .unwrap()
+        .map(|row| row["Action"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn apply_generated_transform() {
+    let mut base = make_package("Widget");
+    let mut target = make_package("Widget Pro");
+    target
+        .insert_rows(Insert::into("InstallExecuteSequence").row(vec![
+            "FileCost".into(),
+            Value::Null,
+            900.into(),
+        ]))
+        .unwrap();
+    target
+        .delete_rows(
+            Delete::from("Property")
+                .with(Expr::col("Property").eq(Expr::string("UpgradeCode"))),
+        )
+        .unwrap();
+    let transform = Transform::generate(&mut base, &mut target).unwrap();
+    let mut mst = Vec::new();
+    transform.write(&mut mst).unwrap();
+
+    base.apply_transform(Cursor::new(&mst), TransformFlags::NONE).unwrap();
+    assert_eq!(
+        property_value(&mut base, "ProductName"),
+        Some(Value::from("Widget Pro"))
+    );
+    assert_eq!(property_value(&mut base, "UpgradeCode"), None);
+    assert_eq!(
+        sequence_actions(&mut base),
+        vec!["CostInitialize", "FileCost", "InstallFinalize"]
+    );
+    let cursor = base.into_inner().unwrap();
+    let mut base = Package::open(cursor).unwrap();
+    assert_eq!(
+        property_value(&mut base, "ProductName"),
+        Some(Value::from("Widget Pro"))
+    );
+
+    // Applying the same transform a second time fails, unless the errors
+    // from rows that were already added or deleted are suppressed.
+    assert_error!(
+        base.apply_transform(Cursor::new(&mst), TransformFlags::NONE),
+        ErrorKind::AlreadyExists,
+        "Transform adds row [\"FileCost\"] to table \
+         \"InstallExecuteSequence\", which already has a row with that key"
+    );
+    assert_error!(
+        base.apply_transform(
+            Cursor::new(&mst),
+            TransformFlags::IGNORE_ADD_EXISTING_ROW
+        ),
+        ErrorKind::NotFound,
+        "Transform deletes row [\"UpgradeCode\"] of table \"Property\", \
+         which does not exist"
+    );
+    let flags = TransformFlags::IGNORE_ADD_EXISTING_ROW
+        | TransformFlags::IGNORE_DELETE_MISSING_ROW;
+    assert!(flags.contains(TransformFlags::IGNORE_ADD_EXISTING_ROW));
+    assert!(!flags.contains(TransformFlags::IGNORE_UPDATE_MISSING_ROW));
+    base.apply_transform(Cursor::new(&mst), flags).unwrap();
+    assert_eq!(
+        sequence_actions(&mut base),
+        vec!["CostInitialize", "FileCost", "InstallFinalize"]
+    );
+}
+
+#[test]
+fn apply_transform_with_missing_rows() {
+    let mut base = make_package("Widget");
+    let mut target = make_package("Widget Pro");
+    let transform = Transform::generate(&mut base, &mut target).unwrap();
+    let mut mst = Vec::new();
+    transform.write(&mut mst).unwrap();
+
+    let mut other = make_package("Gadget");
+    other
+        .delete_rows(
+            Delete::from("Property")
+                .with(Expr::col("Property").eq(Expr::string("ProductName"))),
+        )
+        .unwrap();
+    assert_error!(
+        other.apply_transform(Cursor::new(&mst), TransformFlags::NONE),
+        ErrorKind::NotFound,
+        "Transform modifies row [\"ProductName\"] of table \"Property\", \
+         which does not exist"
+    );
+    other
+        .apply_transform(
+            Cursor::new(&mst),
+            TransformFlags::IGNORE_UPDATE_MISSING_ROW,
+        )
+        .unwrap();
+    assert_eq!(property_value(&mut other, "ProductName"), None);
+
+    let cursor = Cursor::new(Vec::new());
+    let mut empty = Package::create(PackageType::Installer, cursor).unwrap();
+    assert_error!(
+        empty.apply_transform(Cursor::new(&mst), TransformFlags::NONE),
+        ErrorKind::InvalidData,
+        "Transform changes table \"Property\", which does not exist in the \
+         package"
+    );
+    let not_a_transform = other.into_inner().unwrap().into_inner();
+    assert_error!(
+        empty.apply_transform(
+            Cursor::new(not_a_transform),
+            TransformFlags::NONE
+        ),
+        ErrorKind::InvalidData,
+        "Not a transform (.mst) file"
+    );
+}
+
+// ========================================================================= //
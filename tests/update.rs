@@ -0,0 +1,135 @@
+#[macro_use]
+mod testutil;
+
+use msi::{Column, Expr, Insert, Package, PackageType, Select, Update, Value};
+use std::io::{Cursor, ErrorKind};
+
+// ========================================================================= //
+
+fn make_sequence_table() -> Package<Cursor<Vec<u8>>> {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Action").primary_key().id_string(72),
+        Column::build("Condition").nullable().formatted_string(255),
+        Column::build("Sequence").nullable().int16(),
+    ];
+    package.create_table("InstallExecuteSequence", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("InstallExecuteSequence")
+                .row(vec![
+                    Value::from("CostInitialize"),
+                    Value::Null,
+                    800.into(),
+                ])
+                .row(vec![Value::from("FileCost"), Value::Null, 900.into()])
+                .row(vec![
+                    Value::from("CostFinalize"),
+                    Value::Null,
+                    1000.into(),
+                ])
+                .row(vec![Value::from("Custom"), Value::Null, Value::Null]),
+        )
+        .unwrap();
+    package
+}
+
+fn sequences(package: &mut Package<Cursor<Vec<u8>>>) -> Vec<(String, Value)> {
+    package
+        .select_rows(Select::table("InstallExecuteSequence"))
+        .unwrap()
+        .map(|row| {
+            (
+                row["Action"].as_str().unwrap().to_string(),
+                row["Sequence"].clone(),
+            )
+        })
+        .collect()
+}
+
+// ========================================================================= //
+
+#[test]
+fn update_with_expression_over_column_values() {
+    let mut package = make_sequence_table();
+    let query = Update::table("InstallExecuteSequence")
+        .set_expr("Sequence", Expr::col("Sequence") + Expr::integer(50));
+    assert_eq!(package.update_rows(query).unwrap(), 4);
+
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    assert_eq!(
+        sequences(&mut package),
+        vec![
+            ("CostFinalize".to_string(), Value::Int(1050)),
+            ("CostInitialize".to_string(), Value::Int(850)),
+            ("Custom".to_string(), Value::Null),
+            ("FileCost".to_string(), Value::Int(950)),
+        ]
+    );
+}
+
+#[test]
+fn update_expressions_see_values_from_before_update() {
+    let mut package = make_sequence_table();
+    let query = Update::table("InstallExecuteSequence")
+        .set_expr("Sequence", Expr::col("Sequence") * Expr::integer(2))
+        .set_expr("Condition", Expr::col("Action") + Expr::string("_Cond"))
+        .with(Expr::col("Sequence").ge(Expr::integer(900)));
+    assert_eq!(package.update_rows(query).unwrap(), 2);
+    let rows: Vec<(Value, Value)> = package
+        .select_rows(
+            Select::table("InstallExecuteSequence")
+                .columns(&["Condition", "Sequence"]),
+        )
+        .unwrap()
+        .map(|row| (row[0].clone(), row[1].clone()))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            (Value::from("CostFinalize_Cond"), Value::Int(2000)),
+            (Value::Null, Value::Int(800)),
+            (Value::Null, Value::Null),
+            (Value::from("FileCost_Cond"), Value::Int(1800)),
+        ]
+    );
+}
+
+#[test]
+fn update_with_out_of_range_expression_result() {
+    let mut package = make_sequence_table();
+    let query = Update::table("InstallExecuteSequence")
+        .set_expr("Sequence", Expr::col("Sequence") * Expr::integer(40));
+    assert_error!(
+        package.update_rows(query),
+        ErrorKind::InvalidInput,
+        "40000 is not a valid value for column \"Sequence\" of table \
+         \"InstallExecuteSequence\""
+    );
+    // No rows should have been changed, even those that stayed in range.
+    assert_eq!(
+        sequences(&mut package),
+        vec![
+            ("CostFinalize".to_string(), Value::Int(1000)),
+            ("CostInitialize".to_string(), Value::Int(800)),
+            ("Custom".to_string(), Value::Null),
+            ("FileCost".to_string(), Value::Int(900)),
+        ]
+    );
+}
+
+#[test]
+fn update_with_expression_over_nonexistent_column() {
+    let mut package = make_sequence_table();
+    let query = Update::table("InstallExecuteSequence")
+        .set_expr("Sequence", Expr::col("Missing") + Expr::integer(1));
+    assert_error!(
+        package.update_rows(query),
+        ErrorKind::InvalidInput,
+        "Table \"InstallExecuteSequence\" has no column named \"Missing\""
+    );
+}
+
+// ========================================================================= //
@@ -1,7 +1,10 @@
 #[macro_use]
 mod testutil;
 
-use msi::{Category, Column, Insert, Package, PackageType, Value};
+use msi::{
+    Category, Column, Expr, ForeignKey, Insert, Package, PackageType,
+    SequenceIssue, Update, Value,
+};
 use std::io::{Cursor, ErrorKind};
 
 // ========================================================================= //
@@ -25,13 +28,13 @@ fn int_column_value_range() {
     assert_error!(
         package.insert_rows(query),
         ErrorKind::InvalidInput,
-        "-7 is not a valid value for column \"Number\""
+        "-7 is not a valid value for column \"Number\" of table \"Numbers\""
     );
     let query = Insert::into("Numbers").row(vec![Value::Int(101)]);
     assert_error!(
         package.insert_rows(query),
         ErrorKind::InvalidInput,
-        "101 is not a valid value for column \"Number\""
+        "101 is not a valid value for column \"Number\" of table \"Numbers\""
     );
     let query = Insert::into("Numbers").row(vec![Value::Int(100)]);
     package.insert_rows(query).unwrap();
@@ -58,7 +61,8 @@ fn string_column_category() {
     assert_error!(
         package.insert_rows(query),
         ErrorKind::InvalidInput,
-        "\"$99\" is not a valid value for column \"Property\""
+        "\"$99\" is not a valid Property value for column \"Property\" of \
+         table \"Properties\""
     );
     let query = Insert::into("Properties").row(vec![Value::from("%Foo")]);
     package.insert_rows(query).unwrap();
@@ -99,10 +103,327 @@ fn string_column_enum_values() {
     assert_error!(
         package.insert_rows(query),
         ErrorKind::InvalidInput,
-        "\"Sit\" is not a valid value for column \"Day\""
+        "\"Sit\" is not one of the allowed values for column \"Day\" of \
+         table \"Days\""
     );
     let query = Insert::into("Days").row(vec![Value::from("Sat")]);
     package.insert_rows(query).unwrap();
 }
 
+#[test]
+fn bad_guid_rejected() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Component").primary_key().id_string(72),
+        Column::build("ComponentId")
+            .nullable()
+            .category(Category::Guid)
+            .string(38),
+    ];
+    package.create_table("Component", columns).unwrap();
+    let query = Insert::into("Component").row(vec![
+        Value::from("Main"),
+        Value::from("{34ab5c53-9b30-4e14-aef0-2c1c7ba826c0}"),
+    ]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "\"{34ab5c53-9b30-4e14-aef0-2c1c7ba826c0}\" is not a valid GUID \
+         value for column \"ComponentId\" of table \"Component\""
+    );
+    let query = Insert::into("Component").row(vec![
+        Value::from("Main"),
+        Value::from("{34AB5C53-9B30-4E14-AEF0-2C1C7BA826C0}"),
+    ]);
+    package.insert_rows(query).unwrap();
+    let query = Update::table("Component")
+        .set("ComponentId", Value::from("not-a-guid"));
+    assert_error!(
+        package.update_rows(query),
+        ErrorKind::InvalidInput,
+        "\"not-a-guid\" is not a valid GUID value for column \
+         \"ComponentId\" of table \"Component\""
+    );
+}
+
+#[test]
+fn too_long_short_filename_rejected() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("File").primary_key().id_string(72),
+        Column::build("FileName").category(Category::Filename).string(255),
+    ];
+    package.create_table("File", columns).unwrap();
+    let query = Insert::into("File").row(vec![
+        Value::from("readme"),
+        Value::from("READMEFILE.TXT|readme file.txt"),
+    ]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "\"READMEFILE.TXT|readme file.txt\" is not a valid Filename value \
+         for column \"FileName\" of table \"File\""
+    );
+    let query = Insert::into("File").row(vec![
+        Value::from("readme"),
+        Value::from("README~1.TXT|readme file.txt"),
+    ]);
+    package.insert_rows(query).unwrap();
+}
+
+#[test]
+fn validation_can_be_disabled() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    assert!(package.validate_on_insert());
+    let columns = vec![
+        Column::build("Key").primary_key().id_string(72),
+        Column::build("Guid").category(Category::Guid).string(38),
+        Column::build("Number").range(0, 100).int16(),
+    ];
+    package.create_table("Table", columns).unwrap();
+    package.set_validate_on_insert(false);
+    assert!(!package.validate_on_insert());
+    let query = Insert::into("Table").row(vec![
+        Value::from("Foo"),
+        Value::from("not-a-guid"),
+        Value::Int(7),
+    ]);
+    package.insert_rows(query).unwrap();
+    // Type and range checks still apply even with validation disabled.
+    let query = Insert::into("Table").row(vec![
+        Value::from("Bar"),
+        Value::from("not-a-guid"),
+        Value::Int(101),
+    ]);
+    assert_error!(
+        package.insert_rows(query),
+        ErrorKind::InvalidInput,
+        "101 is not a valid value for column \"Number\" of table \"Table\""
+    );
+}
+
+#[test]
+fn validate_existing_rows() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Action").primary_key().id_string(72),
+        Column::build("Sequence").nullable().range(-4, 32767).int16(),
+    ];
+    package.create_table("InstallExecuteSequence", columns).unwrap();
+    let columns = vec![
+        Column::build("Component").primary_key().id_string(72),
+        Column::build("ComponentId").category(Category::Guid).string(38),
+    ];
+    package.create_table("Component", columns).unwrap();
+    let columns = vec![
+        Column::build("File").primary_key().id_string(72),
+        Column::build("Component_").foreign_key("Component", 1).id_string(72),
+    ];
+    package.create_table("File", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("InstallExecuteSequence")
+                .row(vec!["CostInitialize".into(), 800.into()])
+                .row(vec!["InstallFinalize".into(), 6600.into()])
+                .row(vec!["Unsequenced".into(), Value::Null]),
+        )
+        .unwrap();
+    package
+        .insert_rows(Insert::into("Component").row(vec![
+            "App".into(),
+            "{2A5B3C4D-1111-2222-3333-444455556666}".into(),
+        ]))
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("File")
+                .row(vec!["app.exe".into(), "App".into()])
+                .row(vec!["lib.dll".into(), "Lib".into()]),
+        )
+        .unwrap();
+    let errors = package.validate_against_validation_table().unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].table(), "File");
+
+    // Tighten the allowed range of sequence numbers after the fact, so that
+    // an existing row falls outside of it.
+    package
+        .update_rows(
+            Update::table("_Validation")
+                .set("MaxValue", Value::Int(5000))
+                .with(
+                    Expr::col("Table")
+                        .eq(Expr::string("InstallExecuteSequence"))
+                        .and(Expr::col("Column").eq(Expr::string("Sequence"))),
+                ),
+        )
+        .unwrap();
+    let cursor = package.into_inner().unwrap();
+    let mut package = Package::open(cursor).unwrap();
+    let errors = package.validate_against_validation_table().unwrap();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].table(), "File");
+    assert_eq!(errors[0].key(), &[Value::from("lib.dll")]);
+    assert_eq!(errors[0].column(), "Component_");
+    assert_eq!(
+        errors[0].reason(),
+        "\"Lib\" does not match any row of key table Component"
+    );
+    assert_eq!(errors[1].table(), "InstallExecuteSequence");
+    assert_eq!(errors[1].key(), &[Value::from("InstallFinalize")]);
+    assert_eq!(errors[1].column(), "Sequence");
+    assert_eq!(
+        errors[1].to_string(),
+        "InstallExecuteSequence.Sequence [\"InstallFinalize\"]: 6600 is \
+         outside the allowed range of -4 to 5000"
+    );
+}
+
+#[test]
+fn check_referential_integrity() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Feature").primary_key().id_string(38),
+        Column::build("Title").nullable().text_string(64),
+    ];
+    package.create_table("Feature", columns).unwrap();
+    let columns = vec![
+        Column::build("Component").primary_key().id_string(72),
+        Column::build("Directory_").id_string(72),
+    ];
+    package.create_table("Component", columns).unwrap();
+    let columns = vec![
+        Column::build("Feature_").primary_key().id_string(38),
+        Column::build("Component_").primary_key().id_string(72),
+    ];
+    package.create_table("FeatureComponents", columns).unwrap();
+    package
+        .insert_rows(
+            Insert::into("Feature")
+                .row(vec!["Complete".into(), "Everything".into()]),
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("Component")
+                .row(vec!["App".into(), "INSTALLDIR".into()])
+                .row(vec!["Docs".into(), "INSTALLDIR".into()]),
+        )
+        .unwrap();
+    package
+        .insert_rows(
+            Insert::into("FeatureComponents")
+                .row(vec!["Complete".into(), "App".into()])
+                .row(vec!["Complete".into(), "Docs".into()])
+                .row(vec!["Complete".into(), "Help".into()]),
+        )
+        .unwrap();
+
+    let rules = [
+        ForeignKey::new("FeatureComponents", "Feature_", "Feature", "Feature"),
+        ForeignKey::new(
+            "FeatureComponents",
+            "Component_",
+            "Component",
+            "Component",
+        ),
+    ];
+    let violations = package.check_referential_integrity(&rules).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].foreign_key(), &rules[1]);
+    assert_eq!(
+        violations[0].key(),
+        &[Value::from("Complete"), Value::from("Help")]
+    );
+    assert_eq!(violations[0].value(), &Value::from("Help"));
+    assert_eq!(
+        violations[0].to_string(),
+        "FeatureComponents [\"Complete\", \"Help\"]: \"Help\" refers to \
+         missing Component.Component"
+    );
+
+    let rules =
+        [ForeignKey::new("File", "Component_", "Component", "Component")];
+    assert_error!(
+        package.check_referential_integrity(&rules),
+        ErrorKind::NotFound,
+        "Table \"File\" does not exist"
+    );
+    let rules =
+        [ForeignKey::new("Component", "Feature", "Feature", "Feature")];
+    assert_error!(
+        package.check_referential_integrity(&rules),
+        ErrorKind::InvalidInput,
+        "Table \"Component\" has no column named \"Feature\""
+    );
+}
+
+#[test]
+fn verify_sequence_table() {
+    let cursor = Cursor::new(Vec::new());
+    let mut package = Package::create(PackageType::Installer, cursor).unwrap();
+    let columns = vec![
+        Column::build("Action").primary_key().id_string(72),
+        Column::build("Condition").nullable().text_string(255),
+        Column::build("Sequence").nullable().int16(),
+    ];
+    package.create_table("InstallExecuteSequence", columns).unwrap();
+    let mut query = Insert::into("InstallExecuteSequence");
+    for (action, sequence) in [
+        ("CostInitialize", Value::Int(800)),
+        ("FileCost", Value::Int(900)),
+        ("CostFinalize", Value::Int(1000)),
+        ("InstallValidate", Value::Int(1400)),
+        ("InstallInitialize", Value::Int(1500)),
+        ("InstallFiles", Value::Int(1450)),
+        ("ValidateProductID", Value::Int(800)),
+        ("InstallFinalize", Value::Int(6600)),
+        ("SetupCompleteSuccess", Value::Int(-1)),
+        ("ScheduleReboot", Value::Null),
+    ] {
+        query = query.row(vec![Value::from(action), Value::Null, sequence]);
+    }
+    package.insert_rows(query).unwrap();
+
+    let issues =
+        package.verify_sequence_table("InstallExecuteSequence").unwrap();
+    assert_eq!(
+        issues,
+        vec![
+            SequenceIssue::DuplicateSequence {
+                sequence: 800,
+                actions: vec![
+                    "CostInitialize".to_string(),
+                    "ValidateProductID".to_string(),
+                ],
+            },
+            SequenceIssue::OutOfOrder {
+                action: "InstallFiles".to_string(),
+                sequence: 1450,
+                dependency: "InstallInitialize".to_string(),
+                dependency_sequence: 1500,
+            },
+        ]
+    );
+    assert_eq!(
+        issues[0].to_string(),
+        "Actions CostInitialize, ValidateProductID share sequence number 800"
+    );
+    assert_eq!(
+        issues[1].to_string(),
+        "InstallFiles (1450) must come after InstallInitialize (1500)"
+    );
+
+    assert_error!(
+        package.verify_sequence_table("AdminExecuteSequence"),
+        ErrorKind::NotFound,
+        "Table \"AdminExecuteSequence\" does not exist"
+    );
+}
+
 // ========================================================================= //